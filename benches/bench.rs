@@ -78,3 +78,36 @@ fn bench_parse_der_seq(b: &mut Bencher) {
         }
     });
 }
+
+#[bench]
+fn bench_parse_ber_long_printablestring(b: &mut Bencher) {
+    let mut bytes = vec![0x13, 0x83, 0x01, 0x00, 0x00];
+    bytes.extend(b"Some-State ".repeat(65536 / 11 + 1).iter().take(65536));
+    b.iter(|| {
+        let res = der_parser::ber::parse_ber(&bytes);
+        assert!(res.is_ok());
+    });
+}
+
+#[bench]
+fn bench_parse_ber_long_ia5string(b: &mut Bencher) {
+    let mut bytes = vec![0x16, 0x83, 0x01, 0x00, 0x00];
+    bytes.extend(std::iter::repeat(b'a').take(65536));
+    b.iter(|| {
+        let res = der_parser::ber::parse_ber(&bytes);
+        assert!(res.is_ok());
+    });
+}
+
+#[bench]
+fn bench_parse_ber_nested_indefinite(b: &mut Bencher) {
+    // deeply nested indefinite-length sequences: locating the end-of-content octets requires
+    // reading all the nested headers
+    let depth = 40;
+    let mut bytes = [0x30, 0x80].repeat(depth);
+    bytes.extend([0x00, 0x00].repeat(depth));
+    b.iter(|| {
+        let res = der_parser::ber::parse_ber(&bytes);
+        assert!(res.is_ok());
+    });
+}
@@ -0,0 +1,77 @@
+/*
+ * C API for der-parser
+ *
+ * Link with libder_parser_capi (built by `cargo build --release` in the `capi` directory).
+ *
+ * Trees returned by der_parse() own a copy of the data, and must be released with der_free().
+ * Children and byte contents are borrowed from the tree, and are valid until the tree is
+ * released. All functions accept NULL handles, and return an error value (NULL, 0 or -1).
+ */
+
+#ifndef DER_PARSER_H
+#define DER_PARSER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque handle on a parsed object */
+typedef struct DerNode DerNode;
+
+/* Header of an object (same layout as der_parser::ber::HeaderSnapshot) */
+typedef struct DerHeader {
+    uint32_t tag;
+    uint8_t class_;      /* 0: universal, 1: application, 2: context-specific, 3: private */
+    uint8_t constructed; /* 1 if constructed, else 0 */
+    uint8_t indefinite;  /* 1 if the length is indefinite, else 0 */
+    uint8_t reserved;
+    uint64_t length;     /* length of the content (0 if indefinite) */
+} DerHeader;
+
+/* Parse a DER object. Returns NULL on error. If `consumed` is not NULL, the number of bytes read
+ * is stored there. */
+DerNode *der_parse(const uint8_t *data, size_t len, size_t *consumed);
+
+/* Release a tree returned by der_parse() (not a child) */
+void der_free(DerNode *node);
+
+/* Class of the object (0: universal, 1: application, 2: context-specific, 3: private), or -1 */
+int der_node_class(const DerNode *node);
+
+/* Tag number of the object, or -1 */
+int64_t der_node_tag(const DerNode *node);
+
+/* 1 if the object is constructed, 0 if it is primitive, or -1 */
+int der_node_is_constructed(const DerNode *node);
+
+/* Copy the header of the object to `header`. Returns 0 on success, -1 on error. */
+int der_node_header(const DerNode *node, DerHeader *header);
+
+/* Number of children (elements of a SEQUENCE or SET) */
+size_t der_node_num_children(const DerNode *node);
+
+/* Child at index `idx` (borrowed from `node`), or NULL */
+const DerNode *der_node_child(const DerNode *node, size_t idx);
+
+/* Content of a primitive object, or NULL. The length is stored in `len`.
+ * Objects with a non-universal class are not decoded: their raw content is returned, and can be
+ * parsed using der_parse(). */
+const uint8_t *der_node_content(const DerNode *node, size_t *len);
+
+/* Read an INTEGER or ENUMERATED value. Returns 0 on success, -1 on error. */
+int der_node_as_u64(const DerNode *node, uint64_t *value);
+
+/* Value of an OID in dotted notation, to be released with der_string_free(), or NULL */
+char *der_node_oid_string(const DerNode *node);
+
+/* Release a string returned by this library */
+void der_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DER_PARSER_H */
@@ -0,0 +1,291 @@
+//! C API for `der-parser`
+//!
+//! This crate exposes the DER parser to C/C++ programs. It builds a shared and a static library
+//! (`libder_parser_capi`), to be used with the header `include/der_parser.h`.
+//!
+//! Parsed objects are returned as opaque `DerNode` handles. A tree returned by [`der_parse`] owns
+//! a copy of the data, and must be released using [`der_free`]. Children and byte contents are
+//! borrowed from the tree, and are valid until the tree is released.
+//!
+//! All functions accept `NULL` handles, and return an error value (`NULL`, 0 or -1) in this case.
+
+#![deny(missing_debug_implementations, unreachable_pub)]
+#![warn(rust_2018_idioms)]
+
+use der_parser::ber::{BerObjectContentOwned, BerObjectOwned, HeaderSnapshot};
+use der_parser::der::parse_der;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// Opaque handle on a parsed object
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct DerNode(BerObjectOwned);
+
+impl DerNode {
+    fn children(&self) -> &[BerObjectOwned] {
+        match &self.0.content {
+            BerObjectContentOwned::Sequence(v) | BerObjectContentOwned::Set(v) => v,
+            BerObjectContentOwned::Tagged(_, _, o) => std::slice::from_ref(o.as_ref()),
+            BerObjectContentOwned::Optional(Some(o)) => std::slice::from_ref(o.as_ref()),
+            _ => &[],
+        }
+    }
+}
+
+/// Convert a pointer to a reference
+///
+/// # Safety
+///
+/// `node` must be NULL, or a handle returned by this library which has not been freed.
+unsafe fn node_ref<'a>(node: *const DerNode) -> Option<&'a DerNode> {
+    node.as_ref()
+}
+
+/// Parse a DER object from the `len` bytes at `data`
+///
+/// Returns a new tree, or `NULL` if the data could not be parsed. If `consumed` is not `NULL`,
+/// the number of bytes read is stored there.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. `consumed` must be `NULL` or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn der_parse(
+    data: *const u8,
+    len: usize,
+    consumed: *mut usize,
+) -> *mut DerNode {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    let input = std::slice::from_raw_parts(data, len);
+    match parse_der(input) {
+        Ok((rem, obj)) => {
+            if !consumed.is_null() {
+                *consumed = len - rem.len();
+            }
+            Box::into_raw(Box::new(DerNode(obj.to_owned_object())))
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a tree returned by [`der_parse`]
+///
+/// # Safety
+///
+/// `node` must be `NULL`, or a tree returned by `der_parse` (not a child), which has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn der_free(node: *mut DerNode) {
+    if !node.is_null() {
+        drop(Box::from_raw(node));
+    }
+}
+
+/// Return the class of the object (0: universal, 1: application, 2: context-specific, 3: private),
+/// or -1
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_class(node: *const DerNode) -> c_int {
+    node_ref(node).map_or(-1, |n| n.0.header.class() as c_int)
+}
+
+/// Return the tag number of the object, or -1
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_tag(node: *const DerNode) -> i64 {
+    node_ref(node).map_or(-1, |n| i64::from(n.0.header.tag().0))
+}
+
+/// Return 1 if the object is constructed, 0 if it is primitive, or -1
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_is_constructed(node: *const DerNode) -> c_int {
+    node_ref(node).map_or(-1, |n| c_int::from(n.0.header.is_constructed()))
+}
+
+/// Copy the header of the object to `header`
+///
+/// Returns 0 on success, or -1 on error.
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle. `header` must be `NULL` or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_header(
+    node: *const DerNode,
+    header: *mut HeaderSnapshot,
+) -> c_int {
+    match node_ref(node) {
+        Some(n) if !header.is_null() => {
+            *header = HeaderSnapshot::from(&n.0.header);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Return the number of children of the object (elements of a SEQUENCE or SET, or the object
+/// inside an explicit tag)
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_num_children(node: *const DerNode) -> usize {
+    node_ref(node).map_or(0, |n| n.children().len())
+}
+
+/// Return the child at index `idx`, or `NULL`
+///
+/// The child is borrowed from `node`, and must not be freed.
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_child(node: *const DerNode, idx: usize) -> *const DerNode {
+    match node_ref(node).and_then(|n| n.children().get(idx)) {
+        // DerNode is a transparent wrapper
+        Some(child) => child as *const BerObjectOwned as *const DerNode,
+        None => ptr::null(),
+    }
+}
+
+/// Return the content of a primitive object (integers, strings, unknown objects, etc.)
+///
+/// Objects with a non-universal class are not decoded: their raw content is returned, and can
+/// be parsed using `der_parse` (for ex. for explicit tags). For BIT STRING objects, the unused
+/// bits octet is not included.
+///
+/// Returns `NULL` if the object has no byte content, otherwise the length is stored in `len`. The
+/// content is borrowed from `node`.
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle. `len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_content(node: *const DerNode, len: *mut usize) -> *const u8 {
+    let slice = match node_ref(node).and_then(|n| n.0.as_ber().as_slice().ok()) {
+        Some(slice) => slice,
+        None => return ptr::null(),
+    };
+    if !len.is_null() {
+        *len = slice.len();
+    }
+    slice.as_ptr()
+}
+
+/// Read the value of an INTEGER or ENUMERATED object as an unsigned integer
+///
+/// Returns 0 on success, or -1 if the object is not an integer or the value does not fit.
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle. `value` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_as_u64(node: *const DerNode, value: *mut u64) -> c_int {
+    match node_ref(node).map(|n| n.0.as_ber().as_u64()) {
+        Some(Ok(v)) if !value.is_null() => {
+            *value = v;
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Return the value of an OID object in dotted notation, or `NULL`
+///
+/// The string must be released using [`der_string_free`].
+///
+/// # Safety
+///
+/// `node` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn der_node_oid_string(node: *const DerNode) -> *mut c_char {
+    let s = node_ref(node).and_then(|n| n.0.as_ber().as_oid().ok().map(|o| o.to_id_string()));
+    match s.and_then(|s| CString::new(s).ok()) {
+        Some(s) => s.into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `s` must be `NULL`, or a string returned by this library which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn der_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_capi() {
+        // SEQUENCE { INTEGER 259, OID 1.2.3, [0] { OCTET STRING "ab" } }, trailing byte
+        let bytes = [
+            0x30, 0x0e, 0x02, 0x02, 0x01, 0x03, 0x06, 0x02, 0x2a, 0x03, 0xa0, 0x04, 0x04, 0x02,
+            0x61, 0x62, 0xff,
+        ];
+        unsafe {
+            let mut consumed = 0;
+            let root = der_parse(bytes.as_ptr(), bytes.len(), &mut consumed);
+            assert!(!root.is_null());
+            assert_eq!(consumed, 16);
+            assert_eq!(der_node_tag(root), 16);
+            assert_eq!(der_node_is_constructed(root), 1);
+            assert_eq!(der_node_num_children(root), 3);
+            let mut header = HeaderSnapshot {
+                tag: 0,
+                class: 0,
+                constructed: 0,
+                indefinite: 0,
+                reserved: 0,
+                length: 0,
+            };
+            assert_eq!(der_node_header(root, &mut header), 0);
+            assert_eq!((header.tag, header.constructed, header.length), (16, 1, 14));
+
+            let mut value = 0;
+            assert_eq!(der_node_as_u64(der_node_child(root, 0), &mut value), 0);
+            assert_eq!(value, 259);
+
+            let oid = der_node_oid_string(der_node_child(root, 1));
+            assert_eq!(CStr::from_ptr(oid).to_str(), Ok("1.2.3"));
+            der_string_free(oid);
+
+            let tagged = der_node_child(root, 2);
+            assert_eq!(der_node_class(tagged), 2);
+            let mut len = 0;
+            let content = der_node_content(tagged, &mut len);
+            let inner = der_parse(content, len, ptr::null_mut());
+            let content = der_node_content(inner, &mut len);
+            assert_eq!(std::slice::from_raw_parts(content, len), b"ab");
+            der_free(inner);
+
+            assert!(der_node_child(root, 3).is_null());
+            der_free(root);
+
+            assert!(der_parse(bytes.as_ptr(), 3, ptr::null_mut()).is_null());
+        }
+    }
+}
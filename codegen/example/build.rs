@@ -0,0 +1,7 @@
+use std::env;
+
+fn main() {
+    let out = env::var("OUT_DIR").unwrap();
+    der_parser_codegen::compile("src/example.asn1", format!("{}/example.rs", out))
+        .expect("ASN.1 compilation failed");
+}
@@ -0,0 +1,7 @@
+//! Example of parsers generated by `der-parser-codegen`, from `src/example.asn1`
+
+pub mod example {
+    // the generated code is not formatted, and is not written for readability
+    #![allow(clippy::all)]
+    include!(concat!(env!("OUT_DIR"), "/example.rs"));
+}
@@ -0,0 +1,73 @@
+use der_parser::der::{DerObject, DerObjectContent};
+use der_parser::oid;
+use der_parser_codegen_example::example::*;
+use hex_literal::hex;
+
+fn record<'a>() -> Record<'a> {
+    Record {
+        version: Some(DerObject::from_int_slice(&[1])),
+        algorithm: AlgorithmIdentifier {
+            algorithm: oid!(1.2.840 .113549 .1 .1 .11),
+            parameters: Some(DerObject::from_obj(DerObjectContent::Null)),
+        },
+        names: vec![
+            GeneralName::DNSName("example.com"),
+            GeneralName::Directory(vec![vec![AttributeTypeAndValue {
+                r#type: oid!(2.5.4 .3),
+                value: "test",
+            }]]),
+        ],
+        flags: RecordFlags {
+            critical: None,
+            label: "label",
+        },
+        data: None,
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let bytes = encode_record(&record())
+        .to_vec()
+        .expect("serialization failed");
+    let (rem, parsed) = parse_record(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(parsed.version.as_ref().map(|v| v.as_u32()), Some(Ok(1)));
+    assert_eq!(parsed.algorithm.algorithm, oid!(1.2.840 .113549 .1 .1 .11));
+    assert!(parsed.algorithm.parameters.is_some());
+    assert_eq!(parsed.names, record().names);
+    assert_eq!(parsed.flags, record().flags);
+    assert_eq!(parsed.data, None);
+    let again = encode_record(&parsed)
+        .to_vec()
+        .expect("serialization failed");
+    assert_eq!(again, bytes);
+}
+
+#[test]
+fn test_parse_record() {
+    // no version, SET components in a different order, no parameters
+    let bytes = hex!(
+        "30 23"
+        "30 05 06 03 2a 03 04"
+        "30 0d 82 0b 65 78 61 6d 70 6c 65 2e 63 6f 6d"
+        "31 06 81 01 61 80 01 ff"
+        "04 03 01 02 03"
+    );
+    let (rem, parsed) = parse_record(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert!(parsed.version.is_none());
+    assert_eq!(parsed.algorithm.algorithm, oid!(1.2.3 .4));
+    assert!(parsed.algorithm.parameters.is_none());
+    assert_eq!(parsed.names, vec![GeneralName::DNSName("example.com")]);
+    assert_eq!(parsed.flags.critical, Some(true));
+    assert_eq!(parsed.flags.label, "a");
+    assert_eq!(parsed.data, Some(&[1, 2, 3][..]));
+
+    // missing required component of the SET
+    let bytes = hex!("31 03 80 01 ff");
+    assert!(parse_record_flags(&bytes).is_err());
+    // unknown alternative of the CHOICE
+    let bytes = hex!("83 01 61");
+    assert!(parse_general_name(&bytes).is_err());
+}
@@ -0,0 +1,1034 @@
+//! Generation of Rust code from a parsed module
+
+use crate::parser::{Builtin, Component, Module, TagClass, TagMode, Type};
+use crate::Error;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Type of a value, after extraction of the anonymous SEQUENCE, SET and CHOICE types
+#[derive(Clone, Debug)]
+enum Ty {
+    Builtin(Builtin),
+    /// Reference to a definition
+    Ref(String),
+    /// The SEQUENCE, SET or CHOICE defined by the definition itself
+    Body(String),
+    /// SEQUENCE OF (false) or SET OF (true)
+    Of(Box<Ty>, bool),
+    Tagged(TagClass, u32, Option<TagMode>, Box<Ty>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BodyKind {
+    Sequence,
+    Set,
+    Choice,
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    ty: Ty,
+    optional: bool,
+    default: Option<String>,
+}
+
+#[derive(Debug)]
+struct Def {
+    name: String,
+    ty: Ty,
+    body: Option<(BodyKind, Vec<Field>)>,
+}
+
+struct Generator {
+    defs: Vec<Def>,
+    index: HashMap<String, usize>,
+    lifetimes: Vec<bool>,
+    tagging: TagMode,
+    out: String,
+}
+
+const PRELUDE: &str = r#"#[allow(unused_imports)]
+use der_parser::ber::{Class, Header, Length, Tag, MAX_RECURSION};
+#[allow(unused_imports)]
+use der_parser::der::{
+    der_read_element_content_as, der_read_element_header, parse_der, DerObject, DerObjectContent,
+};
+#[allow(unused_imports)]
+use der_parser::error::{BerError, BerResult};
+#[allow(unused_imports)]
+use der_parser::nom::{Err, Needed};
+#[allow(unused_imports)]
+use der_parser::oid::Oid;
+
+/// Parse an object with the expected class and tag, and its content using `f`
+#[allow(dead_code)]
+fn der_tlv<'a, O, F>(i: &'a [u8], class: Class, tag: Tag, f: F) -> BerResult<'a, O>
+where
+    F: FnOnce(&'a [u8], &Header<'a>) -> Result<O, Err<BerError>>,
+{
+    let (rem, hdr) = der_read_element_header(i)?;
+    hdr.assert_class(class)?;
+    hdr.assert_tag(tag)?;
+    let len = hdr.length().definite()?;
+    if rem.len() < len {
+        return Err(Err::Incomplete(Needed::new(len - rem.len())));
+    }
+    let v = f(&rem[..len], &hdr)?;
+    Ok((&rem[len..], v))
+}
+
+/// Decode content as a universal type
+#[allow(dead_code)]
+fn der_primitive<'a>(c: &'a [u8], hdr: &Header<'a>, tag: Tag) -> Result<DerObject<'a>, Err<BerError>> {
+    let length = Length::Definite(c.len());
+    let (_, content) =
+        der_read_element_content_as(c, tag, length, hdr.is_constructed(), MAX_RECURSION)?;
+    let hdr = Header::new(Class::Universal, hdr.is_constructed(), tag, length);
+    Ok(DerObject::from_header_and_content(hdr, content))
+}
+
+#[allow(dead_code)]
+fn der_peek(i: &[u8]) -> Result<(Class, Tag), Err<BerError>> {
+    let (_, hdr) = der_read_element_header(i)?;
+    Ok((hdr.class(), hdr.tag()))
+}
+
+/// Parse an OPTIONAL component, if the next object has the expected class and tag
+#[allow(dead_code)]
+fn der_if_tag<'a, O, F>(i: &'a [u8], class: Class, tag: Tag, f: F) -> BerResult<'a, Option<O>>
+where
+    F: FnOnce(&'a [u8]) -> BerResult<'a, O>,
+{
+    if i.is_empty() || der_peek(i)? != (class, tag) {
+        return Ok((i, None));
+    }
+    let (rem, v) = f(i)?;
+    Ok((rem, Some(v)))
+}
+
+/// Parse an OPTIONAL component of an untagged type (CHOICE or ANY)
+#[allow(dead_code)]
+fn der_try<'a, O, F>(i: &'a [u8], f: F) -> BerResult<'a, Option<O>>
+where
+    F: FnOnce(&'a [u8]) -> BerResult<'a, O>,
+{
+    if i.is_empty() {
+        return Ok((i, None));
+    }
+    match f(i) {
+        Ok((rem, v)) => Ok((rem, Some(v))),
+        Err(Err::Error(_)) => Ok((i, None)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse all the objects of `c` (SEQUENCE OF or SET OF)
+#[allow(dead_code)]
+fn der_many<'a, O, F>(mut c: &'a [u8], mut f: F) -> Result<Vec<O>, Err<BerError>>
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a, O>,
+{
+    let mut v = Vec::new();
+    while !c.is_empty() {
+        let (rem, o) = f(c)?;
+        v.push(o);
+        c = rem;
+    }
+    Ok(v)
+}
+
+/// Parse `c` as exactly one object (EXPLICIT tag)
+#[allow(dead_code)]
+fn der_all<'a, O, F>(c: &'a [u8], f: F) -> Result<O, Err<BerError>>
+where
+    F: FnOnce(&'a [u8]) -> BerResult<'a, O>,
+{
+    let (rem, v) = f(c)?;
+    der_end(rem)?;
+    Ok(v)
+}
+
+#[allow(dead_code)]
+fn der_end(i: &[u8]) -> Result<(), Err<BerError>> {
+    if i.is_empty() {
+        Ok(())
+    } else {
+        Err(Err::Error(BerError::InvalidLength))
+    }
+}
+
+#[allow(dead_code)]
+fn der_build<'a>(class: Class, constructed: bool, tag: Tag, content: DerObjectContent<'a>) -> DerObject<'a> {
+    let hdr = Header::new(class, constructed, tag, Length::Definite(0));
+    DerObject::from_header_and_content(hdr, content)
+}
+
+/// Replace the tag of an object (IMPLICIT tag)
+#[allow(dead_code)]
+fn der_retag<'a>(class: Class, tag: Tag, obj: DerObject<'a>) -> DerObject<'a> {
+    der_build(class, obj.header.is_constructed(), tag, obj.content)
+}
+
+/// Wrap an object in a tag (EXPLICIT tag)
+#[allow(dead_code)]
+fn der_explicit<'a>(class: Class, tag: Tag, obj: DerObject<'a>) -> DerObject<'a> {
+    der_build(class, true, tag, DerObjectContent::Tagged(class, tag, Box::new(obj)))
+}
+"#;
+
+fn camel_case(name: &str) -> String {
+    let mut s = String::new();
+    let mut upper = true;
+    for c in name.chars() {
+        if c == '-' {
+            upper = true;
+        } else if upper {
+            s.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if c == '-' {
+            s.push('_');
+        } else if c.is_ascii_uppercase() {
+            let prev = if idx > 0 { chars[idx - 1] } else { '-' };
+            let next_lower = chars.get(idx + 1).map_or(false, |c| c.is_ascii_lowercase());
+            // start of a word: "tbsCertificate", "RSAPublicKey"
+            if prev != '-' && (prev.is_ascii_lowercase() || prev.is_ascii_digit() || next_lower) {
+                s.push('_');
+            }
+            s.push(c.to_ascii_lowercase());
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+fn field_name(name: &str) -> String {
+    let s = snake_case(name);
+    if KEYWORDS.contains(&s.as_str()) {
+        format!("r#{}", s)
+    } else {
+        s
+    }
+}
+
+/// Dereference an expression `v` of type `&T`
+fn deref(v: &str) -> String {
+    match v.strip_prefix('&') {
+        Some(place) => place.to_string(),
+        None => format!("*{}", v),
+    }
+}
+
+/// Expression `v` of type `&T`, as the receiver of a method call
+fn receiver(v: &str) -> &str {
+    v.strip_prefix('&').unwrap_or(v)
+}
+
+fn class_expr(class: TagClass) -> &'static str {
+    match class {
+        TagClass::Universal => "Class::Universal",
+        TagClass::Application => "Class::Application",
+        TagClass::ContextSpecific => "Class::ContextSpecific",
+        TagClass::Private => "Class::Private",
+    }
+}
+
+/// Name of the `Tag` constant, and tag number of a builtin type
+fn builtin_tag(b: Builtin) -> Option<(&'static str, u32)> {
+    let t = match b {
+        Builtin::Boolean => ("Boolean", 1),
+        Builtin::Integer => ("Integer", 2),
+        Builtin::BitString => ("BitString", 3),
+        Builtin::OctetString => ("OctetString", 4),
+        Builtin::Null => ("Null", 5),
+        Builtin::Oid => ("Oid", 6),
+        Builtin::Enumerated => ("Enumerated", 10),
+        Builtin::Utf8String => ("Utf8String", 12),
+        Builtin::NumericString => ("NumericString", 18),
+        Builtin::PrintableString => ("PrintableString", 19),
+        Builtin::T61String => ("T61String", 20),
+        Builtin::Ia5String => ("Ia5String", 22),
+        Builtin::UtcTime => ("UtcTime", 23),
+        Builtin::GeneralizedTime => ("GeneralizedTime", 24),
+        Builtin::VisibleString => ("VisibleString", 26),
+        Builtin::Any => return None,
+    };
+    Some(t)
+}
+
+fn tag_expr(class: TagClass, number: u32) -> String {
+    if class == TagClass::Universal {
+        let name = [
+            Builtin::Boolean,
+            Builtin::Integer,
+            Builtin::BitString,
+            Builtin::OctetString,
+            Builtin::Null,
+            Builtin::Oid,
+            Builtin::Enumerated,
+            Builtin::Utf8String,
+            Builtin::NumericString,
+            Builtin::PrintableString,
+            Builtin::T61String,
+            Builtin::Ia5String,
+            Builtin::UtcTime,
+            Builtin::GeneralizedTime,
+            Builtin::VisibleString,
+        ]
+        .iter()
+        .filter_map(|&b| builtin_tag(b))
+        .find(|(_, n)| *n == number)
+        .map(|(name, _)| name)
+        .or(match number {
+            16 => Some("Sequence"),
+            17 => Some("Set"),
+            _ => None,
+        });
+        if let Some(name) = name {
+            return format!("Tag::{}", name);
+        }
+    }
+    format!("Tag({})", number)
+}
+
+/// String types: name of the `DerObjectContent` variant
+fn string_variant(b: Builtin) -> Option<&'static str> {
+    match b {
+        Builtin::Utf8String => Some("UTF8String"),
+        Builtin::NumericString => Some("NumericString"),
+        Builtin::PrintableString => Some("PrintableString"),
+        Builtin::T61String => Some("T61String"),
+        Builtin::Ia5String => Some("IA5String"),
+        Builtin::VisibleString => Some("VisibleString"),
+        _ => None,
+    }
+}
+
+fn builtin_type(b: Builtin) -> &'static str {
+    match b {
+        Builtin::Boolean => "bool",
+        Builtin::Enumerated => "u64",
+        Builtin::Null => "()",
+        Builtin::OctetString => "&'a [u8]",
+        Builtin::Oid => "Oid<'a>",
+        b if string_variant(b).is_some() => "&'a str",
+        _ => "DerObject<'a>",
+    }
+}
+
+impl Generator {
+    fn new(module: &Module) -> Result<Self, Error> {
+        let mut g = Generator {
+            defs: Vec::new(),
+            index: HashMap::new(),
+            lifetimes: Vec::new(),
+            tagging: module.tagging,
+            out: String::new(),
+        };
+        for a in &module.assignments {
+            let first = g.defs.len();
+            let ty = g.extract(&a.ty, &a.name, true)?;
+            let name = camel_case(&a.name);
+            match g.index.get(&name) {
+                // the structure was defined by extract, using the name of the assignment
+                Some(&idx) if idx >= first => g.defs[idx].ty = ty,
+                Some(_) => {
+                    return Err(Error::new(0, format!("duplicate definition of '{}'", name)))
+                }
+                None => {
+                    g.index.insert(name.clone(), g.defs.len());
+                    g.defs.push(Def {
+                        name,
+                        ty,
+                        body: None,
+                    });
+                }
+            }
+        }
+        g.check_references()?;
+        g.compute_lifetimes();
+        Ok(g)
+    }
+
+    /// Convert a type, defining the SEQUENCE, SET and CHOICE types it contains
+    ///
+    /// `name` is the name of the definition for a structured type. If `own` is true (top of an
+    /// assignment, possibly below tags), the structure uses the name of the assignment.
+    fn extract(&mut self, ty: &Type, name: &str, own: bool) -> Result<Ty, Error> {
+        let (kind, components) = match ty {
+            Type::Builtin(b) => return Ok(Ty::Builtin(*b)),
+            Type::Reference(r) => return Ok(Ty::Ref(camel_case(r))),
+            Type::SequenceOf(t) | Type::SetOf(t) => {
+                let element = self.extract(t, &format!("{}Item", name), false)?;
+                return Ok(Ty::Of(Box::new(element), matches!(ty, Type::SetOf(_))));
+            }
+            Type::Tagged(class, number, mode, t) => {
+                let inner = self.extract(t, name, own)?;
+                return Ok(Ty::Tagged(*class, *number, *mode, Box::new(inner)));
+            }
+            Type::Sequence(c) => (BodyKind::Sequence, c),
+            Type::Set(c) => (BodyKind::Set, c),
+            Type::Choice(c) => (BodyKind::Choice, c),
+        };
+        let def_name = camel_case(name);
+        if self.index.contains_key(&def_name) {
+            return Err(Error::new(
+                0,
+                format!("duplicate definition of '{}'", def_name),
+            ));
+        }
+        // register the name before the fields, so nested types get the right order
+        let idx = self.defs.len();
+        self.index.insert(def_name.clone(), idx);
+        self.defs.push(Def {
+            name: def_name.clone(),
+            ty: Ty::Body(def_name.clone()),
+            body: None,
+        });
+        let fields = components
+            .iter()
+            .map(|c: &Component| {
+                let nested = format!("{}{}", def_name, camel_case(&c.name));
+                Ok(Field {
+                    name: c.name.clone(),
+                    ty: self.extract(&c.ty, &nested, false)?,
+                    optional: c.optional,
+                    default: c.default.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.defs[idx].body = Some((kind, fields));
+        if own {
+            Ok(Ty::Body(def_name))
+        } else {
+            Ok(Ty::Ref(def_name))
+        }
+    }
+
+    fn check_references(&self) -> Result<(), Error> {
+        fn check(g: &Generator, ty: &Ty) -> Result<(), Error> {
+            match ty {
+                Ty::Ref(r) if !g.index.contains_key(r) => {
+                    Err(Error::new(0, format!("undefined type '{}'", r)))
+                }
+                Ty::Of(t, _) | Ty::Tagged(_, _, _, t) => check(g, t),
+                _ => Ok(()),
+            }
+        }
+        for def in &self.defs {
+            check(self, &def.ty)?;
+            if let Some((_, fields)) = &def.body {
+                for f in fields {
+                    check(self, &f.ty)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn def(&self, name: &str) -> &Def {
+        &self.defs[self.index[name]]
+    }
+
+    fn ty_lifetime(&self, ty: &Ty) -> bool {
+        match ty {
+            Ty::Builtin(b) => builtin_type(*b).contains("'a"),
+            Ty::Ref(n) | Ty::Body(n) => self.lifetimes[self.index[n]],
+            Ty::Of(t, _) | Ty::Tagged(_, _, _, t) => self.ty_lifetime(t),
+        }
+    }
+
+    fn compute_lifetimes(&mut self) {
+        self.lifetimes = vec![false; self.defs.len()];
+        loop {
+            let mut changed = false;
+            for idx in 0..self.defs.len() {
+                let def = &self.defs[idx];
+                let lt = match &def.body {
+                    Some((_, fields)) => fields.iter().any(|f| self.ty_lifetime(&f.ty)),
+                    None => self.ty_lifetime(&def.ty),
+                };
+                if lt && !self.lifetimes[idx] {
+                    self.lifetimes[idx] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Generic parameters of a definition
+    fn generics(&self, name: &str) -> &'static str {
+        if self.lifetimes[self.index[name]] {
+            "<'a>"
+        } else {
+            ""
+        }
+    }
+
+    fn rust_type(&self, ty: &Ty) -> String {
+        match ty {
+            Ty::Builtin(b) => builtin_type(*b).to_string(),
+            Ty::Ref(n) | Ty::Body(n) => format!("{}{}", n, self.generics(n)),
+            Ty::Of(t, _) => format!("Vec<{}>", self.rust_type(t)),
+            Ty::Tagged(_, _, _, t) => self.rust_type(t),
+        }
+    }
+
+    /// Class and tag of the objects of a type, or `None` for untagged CHOICE and ANY types
+    fn tag_of(&self, ty: &Ty, depth: usize) -> Result<Option<(TagClass, u32)>, Error> {
+        if depth > 64 {
+            return Err(Error::new(0, "recursive type definition"));
+        }
+        let tag = match ty {
+            Ty::Builtin(b) => builtin_tag(*b).map(|(_, n)| (TagClass::Universal, n)),
+            Ty::Ref(n) => return self.tag_of(&self.def(n).ty, depth + 1),
+            Ty::Body(n) => match self.def(n).body.as_ref().map(|b| b.0) {
+                Some(BodyKind::Sequence) => Some((TagClass::Universal, 16)),
+                Some(BodyKind::Set) => Some((TagClass::Universal, 17)),
+                _ => None,
+            },
+            Ty::Of(_, false) => Some((TagClass::Universal, 16)),
+            Ty::Of(_, true) => Some((TagClass::Universal, 17)),
+            Ty::Tagged(class, number, _, _) => Some((*class, *number)),
+        };
+        Ok(tag)
+    }
+
+    /// Tagging mode: an IMPLICIT tag on an untagged type is EXPLICIT (X.680 31.2.7)
+    fn tag_mode(&self, mode: Option<TagMode>, inner: &Ty) -> Result<TagMode, Error> {
+        let mode = mode.unwrap_or(self.tagging);
+        if mode == TagMode::Implicit && self.tag_of(inner, 0)?.is_none() {
+            return Ok(TagMode::Explicit);
+        }
+        Ok(mode)
+    }
+
+    /// Expression parsing an object of type `ty` from `i`, with type `BerResult<'a, _>`
+    fn parse_expr(&self, ty: &Ty, i: &str) -> Result<String, Error> {
+        let tagged = |class: TagClass, number: u32, content: String| {
+            format!(
+                "der_tlv({}, {}, {}, |c, {}| {})",
+                i,
+                class_expr(class),
+                tag_expr(class, number),
+                if content.contains("hdr") { "hdr" } else { "_" },
+                content
+            )
+        };
+        let expr = match ty {
+            Ty::Builtin(Builtin::Any) => format!("parse_der({})", i),
+            Ty::Builtin(b) => {
+                let (_, n) = builtin_tag(*b).expect("not a tagged type");
+                tagged(TagClass::Universal, n, self.content_expr(ty, "c", "hdr")?)
+            }
+            Ty::Ref(n) => format!("parse_{}({})", snake_case(n), i),
+            Ty::Body(n) => match self.tag_of(ty, 0)? {
+                Some((class, number)) => tagged(class, number, self.content_expr(ty, "c", "hdr")?),
+                None => format!("{}_choice({})", snake_case(n), i),
+            },
+            Ty::Of(_, set) => {
+                let number = if *set { 17 } else { 16 };
+                tagged(
+                    TagClass::Universal,
+                    number,
+                    self.content_expr(ty, "c", "hdr")?,
+                )
+            }
+            Ty::Tagged(class, number, _, _) => {
+                tagged(*class, *number, self.content_expr(ty, "c", "hdr")?)
+            }
+        };
+        Ok(expr)
+    }
+
+    /// Expression decoding the content `c` (with header `hdr`) of an object of type `ty`, with
+    /// type `Result<_, Err<BerError>>`
+    fn content_expr(&self, ty: &Ty, c: &str, hdr: &str) -> Result<String, Error> {
+        let expr = match ty {
+            Ty::Builtin(Builtin::Any) => {
+                return Err(Error::new(0, "IMPLICIT tag on an ANY type"));
+            }
+            Ty::Builtin(b) => {
+                let (tag, _) = builtin_tag(*b).expect("not a tagged type");
+                let obj = format!("der_primitive({}, {}, Tag::{})", c, hdr, tag);
+                match b {
+                    Builtin::Boolean => format!("Ok({}?.as_bool()?)", obj),
+                    Builtin::Enumerated => format!("Ok({}?.as_u64()?)", obj),
+                    Builtin::Null => format!("{}.map(|_| ())", obj),
+                    Builtin::OctetString => format!("Ok({}?.as_slice()?)", obj),
+                    Builtin::Oid => format!("Ok({}?.as_oid_val()?)", obj),
+                    b if string_variant(*b).is_some() => format!("Ok({}?.as_str()?)", obj),
+                    _ => obj,
+                }
+            }
+            Ty::Ref(n) => {
+                if self.tag_of(ty, 0)?.is_none() {
+                    return Err(Error::new(
+                        0,
+                        format!("IMPLICIT tag on untagged type '{}'", n),
+                    ));
+                }
+                format!("parse_{}_content({}, {})", snake_case(n), c, hdr)
+            }
+            Ty::Body(n) => format!("{}_fields({})", snake_case(n), c),
+            Ty::Of(t, _) => format!("der_many({}, |i| {})", c, self.parse_expr(t, "i")?),
+            Ty::Tagged(_, _, mode, inner) => match self.tag_mode(*mode, inner)? {
+                TagMode::Explicit => {
+                    format!("der_all({}, |i| {})", c, self.parse_expr(inner, "i")?)
+                }
+                TagMode::Implicit => self.content_expr(inner, c, hdr)?,
+            },
+        };
+        Ok(expr)
+    }
+
+    /// Expression encoding the value `v` (a reference) of type `ty` as a `DerObject`
+    fn encode_expr(&self, ty: &Ty, v: &str, depth: usize) -> Result<String, Error> {
+        let expr = match ty {
+            Ty::Builtin(Builtin::Any) => format!("{}.clone()", receiver(v)),
+            Ty::Builtin(b) => {
+                let (tag, _) = builtin_tag(*b).expect("not a tagged type");
+                let content = match b {
+                    Builtin::Boolean => format!("DerObjectContent::Boolean({})", deref(v)),
+                    Builtin::Enumerated => format!("DerObjectContent::Enum({})", deref(v)),
+                    Builtin::Null => "DerObjectContent::Null".to_string(),
+                    Builtin::OctetString => {
+                        format!("DerObjectContent::OctetString({})", deref(v))
+                    }
+                    Builtin::Oid => format!("DerObjectContent::OID({}.clone())", receiver(v)),
+                    b => match string_variant(*b) {
+                        Some(variant) => format!("DerObjectContent::{}({})", variant, deref(v)),
+                        None => format!("{}.content.clone()", receiver(v)),
+                    },
+                };
+                format!("der_build(Class::Universal, false, Tag::{}, {})", tag, content)
+            }
+            Ty::Ref(n) => format!("encode_{}({})", snake_case(n), v),
+            Ty::Body(n) => match self.tag_of(ty, 0)? {
+                Some((_, number)) => format!(
+                    "der_build(Class::Universal, true, {}, DerObjectContent::{}({}_encode_fields({})))",
+                    tag_expr(TagClass::Universal, number),
+                    if number == 17 { "Set" } else { "Sequence" },
+                    snake_case(n),
+                    v
+                ),
+                None => format!("{}_encode_choice({})", snake_case(n), v),
+            },
+            Ty::Of(t, set) => {
+                let x = format!("x{}", depth);
+                format!(
+                    "der_build(Class::Universal, true, {}, DerObjectContent::{}({}.iter().map(|{}| {}).collect()))",
+                    tag_expr(TagClass::Universal, if *set { 17 } else { 16 }),
+                    if *set { "Set" } else { "Sequence" },
+                    receiver(v),
+                    x,
+                    self.encode_expr(t, &x, depth + 1)?
+                )
+            }
+            Ty::Tagged(class, number, mode, inner) => {
+                let f = match self.tag_mode(*mode, inner)? {
+                    TagMode::Explicit => "der_explicit",
+                    TagMode::Implicit => "der_retag",
+                };
+                format!(
+                    "{}({}, {}, {})",
+                    f,
+                    class_expr(*class),
+                    tag_expr(*class, *number),
+                    self.encode_expr(inner, v, depth)?
+                )
+            }
+        };
+        Ok(expr)
+    }
+
+    fn generate(&mut self) -> Result<String, Error> {
+        let mut out = String::new();
+        out.push_str(PRELUDE);
+        for idx in 0..self.defs.len() {
+            self.out.clear();
+            self.gen_def(idx)?;
+            out.push('\n');
+            out.push_str(&self.out);
+        }
+        Ok(out)
+    }
+
+    fn gen_def(&mut self, idx: usize) -> Result<(), Error> {
+        let def = &self.defs[idx];
+        let name = def.name.clone();
+        let snake = snake_case(&name);
+        let generics = self.generics(&name);
+        let rust_type = format!("{}{}", name, generics);
+        let mut out = String::new();
+        match &def.body {
+            Some((kind, fields)) => self.gen_body(&mut out, &name, *kind, fields)?,
+            None => {
+                writeln!(out, "/// ASN.1 type `{}`", name).unwrap();
+                writeln!(
+                    out,
+                    "pub type {} = {};\n",
+                    rust_type,
+                    self.rust_type(&def.ty)
+                )
+                .unwrap();
+            }
+        }
+        if matches!(def.ty, Ty::Body(_)) && def.body.is_none() {
+            return Err(Error::new(0, format!("type '{}' has no definition", name)));
+        }
+        let ty = def.ty.clone();
+        writeln!(out, "/// Parse a DER-encoded `{}`", name).unwrap();
+        writeln!(
+            out,
+            "pub fn parse_{}<'a>(i: &'a [u8]) -> BerResult<'a, {}> {{\n    {}\n}}\n",
+            snake,
+            rust_type,
+            self.parse_expr(&ty, "i")?
+        )
+        .unwrap();
+        if self.tag_of(&ty, 0)?.is_some() {
+            writeln!(out, "#[allow(dead_code)]").unwrap();
+            let content = self.content_expr(&ty, "c", "hdr")?;
+            writeln!(
+                out,
+                "fn parse_{}_content<'a>(c: &'a [u8], {}: &Header<'a>) -> Result<{}, Err<BerError>> {{\n    {}\n}}\n",
+                snake,
+                if content.contains("hdr") { "hdr" } else { "_hdr" },
+                rust_type,
+                content
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "/// Encode a `{}` (use `DerObject::to_vec` to get the DER encoding)",
+            name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "pub fn encode_{}<'a>(v: &{}) -> DerObject<'a> {{\n    {}\n}}",
+            snake,
+            rust_type,
+            self.encode_expr(&ty, "v", 0)?
+        )
+        .unwrap();
+        self.out.push_str(&out);
+        Ok(())
+    }
+
+    fn gen_body(
+        &self,
+        out: &mut String,
+        name: &str,
+        kind: BodyKind,
+        fields: &[Field],
+    ) -> Result<(), Error> {
+        let snake = snake_case(name);
+        let rust_type = format!("{}{}", name, self.generics(name));
+        writeln!(out, "/// ASN.1 type `{}`", name).unwrap();
+        writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        if kind == BodyKind::Choice {
+            writeln!(out, "pub enum {} {{", rust_type).unwrap();
+            for f in fields {
+                writeln!(
+                    out,
+                    "    {}({}),",
+                    camel_case(&f.name),
+                    self.rust_type(&f.ty)
+                )
+                .unwrap();
+            }
+            writeln!(out, "}}\n").unwrap();
+            self.gen_choice(out, name, &snake, &rust_type, fields)?;
+            return Ok(());
+        }
+        writeln!(out, "pub struct {} {{", rust_type).unwrap();
+        for f in fields {
+            if let Some(default) = &f.default {
+                writeln!(out, "    /// DEFAULT {} (`None` if absent)", default).unwrap();
+            }
+            let ty = self.rust_type(&f.ty);
+            let ty = if f.optional {
+                format!("Option<{}>", ty)
+            } else {
+                ty
+            };
+            writeln!(out, "    pub {}: {},", field_name(&f.name), ty).unwrap();
+        }
+        writeln!(out, "}}\n").unwrap();
+
+        // decoding of the fields
+        writeln!(
+            out,
+            "fn {}_fields<'a>(c: &'a [u8]) -> Result<{}, Err<BerError>> {{",
+            snake, rust_type
+        )
+        .unwrap();
+        if kind == BodyKind::Sequence {
+            writeln!(out, "    let i = c;").unwrap();
+            for f in fields {
+                let parse = self.parse_expr(&f.ty, "i")?;
+                let expr = if f.optional {
+                    match self.tag_of(&f.ty, 0)? {
+                        Some((class, number)) => format!(
+                            "der_if_tag(i, {}, {}, |i| {})",
+                            class_expr(class),
+                            tag_expr(class, number),
+                            parse
+                        ),
+                        None => format!("der_try(i, |i| {})", parse),
+                    }
+                } else {
+                    parse
+                };
+                writeln!(out, "    let (i, {}) = {}?;", field_name(&f.name), expr).unwrap();
+            }
+            writeln!(out, "    der_end(i)?;").unwrap();
+        } else {
+            // SET: components in any order
+            for f in fields {
+                writeln!(out, "    let mut {} = None;", field_name(&f.name)).unwrap();
+            }
+            writeln!(out, "    let mut i = c;").unwrap();
+            writeln!(out, "    while !i.is_empty() {{").unwrap();
+            writeln!(out, "        let (rem, found) = match der_peek(i)? {{").unwrap();
+            for f in fields {
+                let (class, number) = match self.tag_of(&f.ty, 0)? {
+                    Some(t) => t,
+                    None => {
+                        return Err(Error::new(
+                            0,
+                            format!("untagged CHOICE or ANY component '{}' in SET", f.name),
+                        ))
+                    }
+                };
+                let pattern = format!("({}, {})", class_expr(class), tag_expr(class, number));
+                let field = field_name(&f.name);
+                writeln!(
+                    out,
+                    "            {} if {}.is_none() => {{\n                let (rem, v) = {}?;\n                {} = Some(v);\n                (rem, true)\n            }}",
+                    pattern,
+                    field,
+                    self.parse_expr(&f.ty, "i")?,
+                    field
+                )
+                .unwrap();
+            }
+            writeln!(out, "            _ => (i, false),").unwrap();
+            writeln!(out, "        }};").unwrap();
+            writeln!(out, "        if !found {{").unwrap();
+            writeln!(
+                out,
+                "            return Err(Err::Error(BerError::BerValueError));\n        }}"
+            )
+            .unwrap();
+            writeln!(out, "        i = rem;").unwrap();
+            writeln!(out, "    }}").unwrap();
+            for f in fields.iter().filter(|f| !f.optional) {
+                let field = field_name(&f.name);
+                writeln!(
+                    out,
+                    "    let {} = {}.ok_or(Err::Error(BerError::BerValueError))?;",
+                    field, field
+                )
+                .unwrap();
+            }
+        }
+        let names: Vec<_> = fields.iter().map(|f| field_name(&f.name)).collect();
+        writeln!(out, "    Ok({} {{ {} }})", name, names.join(", ")).unwrap();
+        writeln!(out, "}}\n").unwrap();
+
+        // encoding of the fields
+        writeln!(
+            out,
+            "fn {}_encode_fields<'a>(v: &{}) -> Vec<DerObject<'a>> {{",
+            snake, rust_type
+        )
+        .unwrap();
+        writeln!(out, "    let mut items = Vec::new();").unwrap();
+        for f in fields {
+            let field = field_name(&f.name);
+            if f.optional {
+                writeln!(
+                    out,
+                    "    if let Some(x) = &v.{} {{\n        items.push({});\n    }}",
+                    field,
+                    self.encode_expr(&f.ty, "x", 0)?
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    out,
+                    "    items.push({});",
+                    self.encode_expr(&f.ty, &format!("&v.{}", field), 0)?
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "    items\n}}\n").unwrap();
+        Ok(())
+    }
+
+    fn gen_choice(
+        &self,
+        out: &mut String,
+        name: &str,
+        snake: &str,
+        rust_type: &str,
+        fields: &[Field],
+    ) -> Result<(), Error> {
+        writeln!(
+            out,
+            "fn {}_choice<'a>(i: &'a [u8]) -> BerResult<'a, {}> {{",
+            snake, rust_type
+        )
+        .unwrap();
+        writeln!(out, "    let (class, tag) = der_peek(i)?;").unwrap();
+        for f in fields {
+            let tags = self.choice_tags(&f.ty, 0)?;
+            let cond = tags
+                .iter()
+                .map(|(class, number)| {
+                    format!(
+                        "class == {} && tag == {}",
+                        class_expr(*class),
+                        tag_expr(*class, *number)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let cond = if cond.len() == 1 {
+                cond[0].clone()
+            } else {
+                format!("({})", cond.join(") || ("))
+            };
+            writeln!(
+                out,
+                "    if {} {{\n        let (rem, v) = {}?;\n        return Ok((rem, {}::{}(v)));\n    }}",
+                cond,
+                self.parse_expr(&f.ty, "i")?,
+                name,
+                camel_case(&f.name)
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "    Err(Err::Error(BerError::UnexpectedTag {{ expected: None, actual: tag }}))\n}}\n"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "fn {}_encode_choice<'a>(v: &{}) -> DerObject<'a> {{\n    match v {{",
+            snake, rust_type
+        )
+        .unwrap();
+        for f in fields {
+            writeln!(
+                out,
+                "        {}::{}(x) => {},",
+                name,
+                camel_case(&f.name),
+                self.encode_expr(&f.ty, "x", 0)?
+            )
+            .unwrap();
+        }
+        writeln!(out, "    }}\n}}\n").unwrap();
+        Ok(())
+    }
+
+    /// Tags of the objects accepted by an alternative of a CHOICE
+    fn choice_tags(&self, ty: &Ty, depth: usize) -> Result<Vec<(TagClass, u32)>, Error> {
+        if let Some(tag) = self.tag_of(ty, 0)? {
+            return Ok(vec![tag]);
+        }
+        if depth > 64 {
+            return Err(Error::new(0, "recursive type definition"));
+        }
+        let name = match ty {
+            Ty::Ref(n) | Ty::Body(n) => n,
+            _ => return Err(Error::new(0, "ANY alternative in CHOICE")),
+        };
+        let def = self.def(name);
+        match &def.body {
+            Some((BodyKind::Choice, fields)) => {
+                let mut tags = Vec::new();
+                for f in fields {
+                    tags.extend(self.choice_tags(&f.ty, depth + 1)?);
+                }
+                Ok(tags)
+            }
+            _ => self.choice_tags(&def.ty, depth + 1),
+        }
+    }
+}
+
+/// Generate the Rust code for a module
+pub(crate) fn generate_module(module: &Module) -> Result<String, Error> {
+    let mut g = Generator::new(module)?;
+    g.generate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(snake_case("tbsCertificate"), "tbs_certificate");
+        assert_eq!(snake_case("RSAPublicKey"), "rsa_public_key");
+        assert_eq!(snake_case("id-ce-keyUsage"), "id_ce_key_usage");
+        assert_eq!(field_name("type"), "r#type");
+        assert_eq!(camel_case("dNSName"), "DNSName");
+        assert_eq!(camel_case("subject-alt-name"), "SubjectAltName");
+    }
+
+    fn gen(body: &str) -> Result<String, Error> {
+        let src = format!("M DEFINITIONS ::= BEGIN {} END", body);
+        generate_module(&crate::parser::parse_module(&src)?)
+    }
+
+    #[test]
+    fn test_generate() {
+        let code = gen("A ::= SEQUENCE { b SEQUENCE OF CHOICE { x INTEGER, y [0] BOOLEAN } }")
+            .expect("generation failed");
+        assert!(code.contains("pub struct A<'a> {"));
+        assert!(code.contains("pub b: Vec<ABItem<'a>>,"));
+        assert!(code.contains("pub enum ABItem<'a> {"));
+        assert!(code.contains("pub fn parse_a<'a>"));
+        // EXPLICIT tags by default
+        assert!(code.contains("der_all(c, |i| der_tlv(i, Class::Universal, Tag::Boolean"));
+        // no lifetime for types without borrowed data
+        let code = gen("B ::= SET { f [1] BOOLEAN OPTIONAL }").expect("generation failed");
+        assert!(code.contains("pub struct B {"));
+
+        assert!(gen("A ::= SEQUENCE { b C }").is_err());
+        assert!(gen("A ::= BOOLEAN A ::= NULL").is_err());
+        assert!(gen("A ::= SET { b CHOICE { x NULL } }").is_err());
+        assert!(gen("A ::= B B ::= A").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! # der-parser-codegen
+//!
+//! Generate Rust parsers and encoders, based on `der-parser`, from ASN.1 modules (X.680).
+//!
+//! For each type assignment of the module, the generated code contains:
+//!
+//! - a Rust type: a `struct` for `SEQUENCE` and `SET` types, an `enum` for `CHOICE` types, and a
+//!   type alias otherwise. Anonymous inner structured types are defined as separate types, named
+//!   after the parent type and the component (`CertificateTbs` for the component `tbs`).
+//! - `parse_<name>`, a DER parsing function returning a `BerResult`
+//! - `encode_<name>`, building a `DerObject` (use `DerObject::to_vec`, with the `serialize`
+//!   feature of `der-parser`, to get the encoding)
+//!
+//! The types are mapped as follows:
+//!
+//! | ASN.1 | Rust |
+//! |-------|------|
+//! | `BOOLEAN` | `bool` |
+//! | `ENUMERATED` | `u64` |
+//! | `NULL` | `()` |
+//! | `OCTET STRING` | `&'a [u8]` |
+//! | `OBJECT IDENTIFIER` | `Oid<'a>` |
+//! | `UTF8String`, `PrintableString`, `IA5String`, ... | `&'a str` |
+//! | `INTEGER`, `BIT STRING`, `UTCTime`, `GeneralizedTime`, `ANY` | `DerObject<'a>` |
+//! | `SEQUENCE OF T`, `SET OF T` | `Vec<T>` |
+//! | `OPTIONAL` or `DEFAULT` component | `Option<T>` |
+//!
+//! Default values are not applied: a component with a `DEFAULT` value is `None` if absent.
+//!
+//! ## Usage
+//!
+//! The generator is meant to be used from a build script:
+//!
+//! ```rust,ignore
+//! // build.rs
+//! fn main() {
+//!     let out = std::env::var("OUT_DIR").unwrap();
+//!     der_parser_codegen::compile("src/pkix.asn1", format!("{}/pkix.rs", out))
+//!         .expect("ASN.1 compilation failed");
+//! }
+//! ```
+//!
+//! The generated file is then included in a module:
+//!
+//! ```rust,ignore
+//! mod pkix {
+//!     include!(concat!(env!("OUT_DIR"), "/pkix.rs"));
+//! }
+//! ```
+//!
+//! The crate using the generated code must depend on `der-parser`.
+//!
+//! ## Limitations
+//!
+//! Only a subset of the syntax is supported:
+//!
+//! - `AUTOMATIC TAGS`, `COMPONENTS OF` and extension additions (`[[ ... ]]`) are not supported
+//! - imported types cannot be used (all types must be defined in the module)
+//! - constraints and value assignments are parsed, but ignored
+//! - components of a `SET` are encoded in the order of the definition, not in the canonical order
+//!   required by DER
+
+#![deny(/*missing_docs,*/
+    unstable_features,
+    unused_import_braces,
+    unused_qualifications,
+    unreachable_pub
+)]
+#![forbid(unsafe_code)]
+
+mod gen;
+mod parser;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Error while compiling a module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    line: usize,
+    msg: String,
+}
+
+impl Error {
+    pub(crate) fn new(line: usize, msg: impl Into<String>) -> Self {
+        Error {
+            line,
+            msg: msg.into(),
+        }
+    }
+
+    /// Line of the module where the error was found (0 if unknown)
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Error message
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: {}", self.line, self.msg)
+        } else {
+            f.write_str(&self.msg)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Generate the Rust code for an ASN.1 module
+pub fn generate(source: &str) -> Result<String, Error> {
+    let module = parser::parse_module(source)?;
+    let code = gen::generate_module(&module)?;
+    Ok(format!(
+        "// Generated by der-parser-codegen from module {}, do not edit\n\n{}",
+        module.name, code
+    ))
+}
+
+/// Compile the ASN.1 module `input` to the Rust file `output`
+///
+/// This function is meant to be called from a build script: it asks cargo to run the build script
+/// again if `input` changes.
+pub fn compile(input: impl AsRef<Path>, output: impl AsRef<Path>) -> io::Result<()> {
+    let input = input.as_ref();
+    println!("cargo:rerun-if-changed={}", input.display());
+    let source = fs::read_to_string(input)?;
+    let code = generate(&source).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", input.display(), e),
+        )
+    })?;
+    fs::write(output, code)
+}
@@ -0,0 +1,25 @@
+use std::env;
+use std::fs;
+use std::process::exit;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <module.asn1>", args[0]);
+        exit(2);
+    }
+    let source = match fs::read_to_string(&args[1]) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}: {}", args[1], e);
+            exit(1);
+        }
+    };
+    match der_parser_codegen::generate(&source) {
+        Ok(code) => print!("{}", code),
+        Err(e) => {
+            eprintln!("{}: {}", args[1], e);
+            exit(1);
+        }
+    }
+}
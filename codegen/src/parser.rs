@@ -0,0 +1,632 @@
+//! Parser for the supported subset of the ASN.1 module syntax (X.680)
+
+use crate::Error;
+
+/// Class of a tag
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TagClass {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+/// Tagging mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TagMode {
+    Explicit,
+    Implicit,
+}
+
+/// Builtin types without components
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Builtin {
+    Boolean,
+    Integer,
+    Enumerated,
+    BitString,
+    OctetString,
+    Null,
+    Oid,
+    Utf8String,
+    NumericString,
+    PrintableString,
+    T61String,
+    Ia5String,
+    VisibleString,
+    UtcTime,
+    GeneralizedTime,
+    Any,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Type {
+    Builtin(Builtin),
+    /// Reference to a type defined in the module
+    Reference(String),
+    Sequence(Vec<Component>),
+    Set(Vec<Component>),
+    SequenceOf(Box<Type>),
+    SetOf(Box<Type>),
+    Choice(Vec<Component>),
+    /// Tagged type (`None` if the mode is the default of the module)
+    Tagged(TagClass, u32, Option<TagMode>, Box<Type>),
+}
+
+/// A component of a SEQUENCE or SET, or an alternative of a CHOICE
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Component {
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+    pub(crate) optional: bool,
+    /// Text of the DEFAULT value
+    pub(crate) default: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Assignment {
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Module {
+    pub(crate) name: String,
+    pub(crate) tagging: TagMode,
+    pub(crate) assignments: Vec<Assignment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Word,
+    Number,
+    Symbol,
+    /// Quoted string, or binary/hexadecimal string
+    Literal,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: usize,
+}
+
+const SYMBOLS: &[&str] = &[
+    "::=", "...", "[[", "]]", "..", "{", "}", "(", ")", "[", "]", ",", ";", "|", "<", ">", "@",
+    "!", "^", ".", ":", "-", "&",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            // comment, until the next "--" or the end of the line
+            i += 2;
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '-' && chars.get(i + 1) == Some(&'-') {
+                    i += 2;
+                    break;
+                }
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c.is_ascii_alphabetic() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric()
+                    || (chars[i] == '-'
+                        && chars
+                            .get(i + 1)
+                            .map_or(false, |c| c.is_ascii_alphanumeric())))
+            {
+                i += 1;
+            }
+            let text = chars[start..i].iter().collect();
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                text,
+                line,
+            });
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text = chars[start..i].iter().collect();
+            tokens.push(Token {
+                kind: TokenKind::Number,
+                text,
+                line,
+            });
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(Error::new(line, "unterminated string"));
+            }
+            i += 1;
+            // 'xxx'B or 'xxx'H
+            if c == '\'' && i < chars.len() && (chars[i] == 'B' || chars[i] == 'H') {
+                i += 1;
+            }
+            let text = chars[start..i].iter().collect();
+            tokens.push(Token {
+                kind: TokenKind::Literal,
+                text,
+                line,
+            });
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            match SYMBOLS.iter().find(|s| rest.starts_with(*s)) {
+                Some(s) => {
+                    i += s.len();
+                    tokens.push(Token {
+                        kind: TokenKind::Symbol,
+                        text: s.to_string(),
+                        line,
+                    });
+                }
+                None => return Err(Error::new(line, format!("unexpected character '{}'", c))),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reserved words of X.680 which are not handled as type references
+const RESERVED: &[&str] = &[
+    "ABSENT",
+    "ABSTRACT-SYNTAX",
+    "ALL",
+    "BEGIN",
+    "BMPString",
+    "BY",
+    "CHARACTER",
+    "CLASS",
+    "COMPONENT",
+    "COMPONENTS",
+    "CONSTRAINED",
+    "CONTAINING",
+    "DATE",
+    "DATE-TIME",
+    "DEFAULT",
+    "DEFINITIONS",
+    "DURATION",
+    "EMBEDDED",
+    "ENCODED",
+    "END",
+    "EXCEPT",
+    "EXPLICIT",
+    "EXPORTS",
+    "EXTERNAL",
+    "FALSE",
+    "FROM",
+    "GeneralString",
+    "GraphicString",
+    "IDENTIFIER",
+    "IMPLICIT",
+    "IMPORTS",
+    "INCLUDES",
+    "INSTANCE",
+    "INTERSECTION",
+    "MAX",
+    "MIN",
+    "MINUS-INFINITY",
+    "OID-IRI",
+    "OPTIONAL",
+    "PATTERN",
+    "PDV",
+    "PLUS-INFINITY",
+    "PRESENT",
+    "REAL",
+    "RELATIVE-OID",
+    "RELATIVE-OID-IRI",
+    "SIZE",
+    "STRING",
+    "SYNTAX",
+    "TIME",
+    "TIME-OF-DAY",
+    "TRUE",
+    "TYPE-IDENTIFIER",
+    "UNION",
+    "UNIQUE",
+    "UNIVERSAL",
+    "UniversalString",
+    "VideotexString",
+    "WITH",
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|t| t.text.as_str())
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&str> {
+        self.tokens.get(self.pos + offset).map(|t| t.text.as_str())
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |t| t.line)
+    }
+
+    fn error<T, S: Into<String>>(&self, msg: S) -> Result<T, Error> {
+        Err(Error::new(self.line(), msg))
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        match self.tokens.get(self.pos) {
+            Some(t) => {
+                self.pos += 1;
+                Ok(t.clone())
+            }
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn accept(&mut self, text: &str) -> bool {
+        if self.peek() == Some(text) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), Error> {
+        if self.accept(text) {
+            Ok(())
+        } else {
+            let found = self.peek().unwrap_or("end of input").to_string();
+            self.error(format!("expected '{}', found '{}'", text, found))
+        }
+    }
+
+    fn word(&mut self) -> Result<String, Error> {
+        let t = self.next()?;
+        if t.kind == TokenKind::Word {
+            Ok(t.text)
+        } else {
+            self.pos -= 1;
+            self.error(format!("expected an identifier, found '{}'", t.text))
+        }
+    }
+
+    /// Skip a group delimited by `open` and the matching `close` (the current token is `open`)
+    fn skip_group(&mut self, open: &str, close: &str) -> Result<String, Error> {
+        let mut depth = 0;
+        let mut text = Vec::new();
+        loop {
+            let t = self.next()?;
+            if t.text == open {
+                depth += 1;
+            } else if t.text == close {
+                depth -= 1;
+            }
+            text.push(t.text);
+            if depth == 0 {
+                return Ok(text.join(" "));
+            }
+        }
+    }
+
+    /// Skip constraints following a type
+    fn skip_constraints(&mut self) -> Result<(), Error> {
+        while self.peek() == Some("(") {
+            self.skip_group("(", ")")?;
+        }
+        Ok(())
+    }
+
+    fn module(&mut self) -> Result<Module, Error> {
+        let name = self.word()?;
+        if self.peek() == Some("{") {
+            self.skip_group("{", "}")?;
+        }
+        self.expect("DEFINITIONS")?;
+        let tagging = match self.peek() {
+            Some("EXPLICIT") => TagMode::Explicit,
+            Some("IMPLICIT") => TagMode::Implicit,
+            Some("AUTOMATIC") => return self.error("AUTOMATIC TAGS are not supported"),
+            _ => TagMode::Explicit,
+        };
+        if self.peek_at(1) == Some("TAGS") {
+            self.pos += 2;
+        }
+        if self.accept("EXTENSIBILITY") {
+            self.expect("IMPLIED")?;
+        }
+        self.expect("::=")?;
+        self.expect("BEGIN")?;
+        for section in &["EXPORTS", "IMPORTS"] {
+            if self.accept(section) {
+                while !self.accept(";") {
+                    self.next()?;
+                }
+            }
+        }
+        let mut assignments = Vec::new();
+        while !self.accept("END") {
+            if let Some(a) = self.assignment()? {
+                assignments.push(a);
+            }
+        }
+        Ok(Module {
+            name,
+            tagging,
+            assignments,
+        })
+    }
+
+    /// Parse a type assignment, or skip a value assignment
+    fn assignment(&mut self) -> Result<Option<Assignment>, Error> {
+        let name = self.word()?;
+        if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            if self.peek() == Some("{") {
+                return self.error("parameterized types are not supported");
+            }
+            self.expect("::=")?;
+            let ty = self.ty()?;
+            Ok(Some(Assignment { name, ty }))
+        } else {
+            // value assignment: the value is ignored
+            self.ty()?;
+            self.expect("::=")?;
+            self.value()?;
+            Ok(None)
+        }
+    }
+
+    /// Parse a value, and return its text
+    fn value(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some("{") => self.skip_group("{", "}"),
+            Some("-") => {
+                self.pos += 1;
+                Ok(format!("-{}", self.next()?.text))
+            }
+            _ => Ok(self.next()?.text),
+        }
+    }
+
+    fn ty(&mut self) -> Result<Type, Error> {
+        let ty = if self.accept("[") {
+            let class = match self.peek() {
+                Some("UNIVERSAL") => TagClass::Universal,
+                Some("APPLICATION") => TagClass::Application,
+                Some("PRIVATE") => TagClass::Private,
+                _ => TagClass::ContextSpecific,
+            };
+            if class != TagClass::ContextSpecific {
+                self.pos += 1;
+            }
+            let t = self.next()?;
+            let number = match (t.kind, t.text.parse::<u32>()) {
+                (TokenKind::Number, Ok(n)) => n,
+                _ => return self.error(format!("invalid tag number '{}'", t.text)),
+            };
+            self.expect("]")?;
+            let mode = if self.accept("IMPLICIT") {
+                Some(TagMode::Implicit)
+            } else if self.accept("EXPLICIT") {
+                Some(TagMode::Explicit)
+            } else {
+                None
+            };
+            Type::Tagged(class, number, mode, Box::new(self.ty()?))
+        } else {
+            self.base_type()?
+        };
+        self.skip_constraints()?;
+        Ok(ty)
+    }
+
+    fn base_type(&mut self) -> Result<Type, Error> {
+        let word = self.word()?;
+        let builtin = match word.as_str() {
+            "BOOLEAN" => Builtin::Boolean,
+            "INTEGER" | "ENUMERATED" | "BIT" => {
+                let builtin = match word.as_str() {
+                    "INTEGER" => Builtin::Integer,
+                    "ENUMERATED" => Builtin::Enumerated,
+                    _ => {
+                        self.expect("STRING")?;
+                        Builtin::BitString
+                    }
+                };
+                // named numbers or bits
+                if self.peek() == Some("{") {
+                    self.skip_group("{", "}")?;
+                }
+                builtin
+            }
+            "OCTET" => {
+                self.expect("STRING")?;
+                Builtin::OctetString
+            }
+            "NULL" => Builtin::Null,
+            "OBJECT" => {
+                self.expect("IDENTIFIER")?;
+                Builtin::Oid
+            }
+            "UTF8String" => Builtin::Utf8String,
+            "NumericString" => Builtin::NumericString,
+            "PrintableString" => Builtin::PrintableString,
+            "TeletexString" | "T61String" => Builtin::T61String,
+            "IA5String" => Builtin::Ia5String,
+            "VisibleString" | "ISO646String" => Builtin::VisibleString,
+            "UTCTime" => Builtin::UtcTime,
+            "GeneralizedTime" => Builtin::GeneralizedTime,
+            "ANY" => {
+                if self.accept("DEFINED") {
+                    self.expect("BY")?;
+                    self.word()?;
+                }
+                Builtin::Any
+            }
+            "SEQUENCE" | "SET" => {
+                let is_seq = word == "SEQUENCE";
+                if self.peek() == Some("{") {
+                    let components = self.components()?;
+                    return Ok(if is_seq {
+                        Type::Sequence(components)
+                    } else {
+                        Type::Set(components)
+                    });
+                }
+                self.accept("SIZE");
+                self.skip_constraints()?;
+                self.expect("OF")?;
+                // SEQUENCE OF may name its elements
+                if self
+                    .peek()
+                    .map_or(false, |w| w.starts_with(char::is_lowercase))
+                    && self.peek_at(1) != Some("::=")
+                {
+                    self.pos += 1;
+                }
+                let element = Box::new(self.ty()?);
+                return Ok(if is_seq {
+                    Type::SequenceOf(element)
+                } else {
+                    Type::SetOf(element)
+                });
+            }
+            "CHOICE" => return Ok(Type::Choice(self.components()?)),
+            w if w.starts_with(|c: char| c.is_ascii_uppercase()) && !RESERVED.contains(&w) => {
+                return Ok(Type::Reference(word));
+            }
+            _ => return self.error(format!("unsupported type '{}'", word)),
+        };
+        Ok(Type::Builtin(builtin))
+    }
+
+    fn components(&mut self) -> Result<Vec<Component>, Error> {
+        self.expect("{")?;
+        let mut components = Vec::new();
+        if self.accept("}") {
+            return Ok(components);
+        }
+        loop {
+            if self.accept("...") {
+                // extension marker (and exception specification)
+                if self.accept("!") {
+                    self.value()?;
+                }
+            } else if self.peek() == Some("[[") {
+                return self.error("extension additions groups are not supported");
+            } else if self.peek() == Some("COMPONENTS") {
+                return self.error("COMPONENTS OF is not supported");
+            } else {
+                let name = self.word()?;
+                let ty = self.ty()?;
+                let mut component = Component {
+                    name,
+                    ty,
+                    optional: false,
+                    default: None,
+                };
+                if self.accept("OPTIONAL") {
+                    component.optional = true;
+                } else if self.accept("DEFAULT") {
+                    component.optional = true;
+                    component.default = Some(self.value()?);
+                }
+                components.push(component);
+            }
+            if self.accept("}") {
+                return Ok(components);
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+/// Parse an ASN.1 module
+pub(crate) fn parse_module(src: &str) -> Result<Module, Error> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let module = parser.module()?;
+    if parser.pos != parser.tokens.len() {
+        return parser.error("unexpected data after END");
+    }
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_module() {
+        let src = r#"
+            Test { iso(1) 2 } DEFINITIONS IMPLICIT TAGS ::= BEGIN
+            IMPORTS Name FROM Other;
+            -- a comment -- Version ::= INTEGER { v1(0), v2(1) } (0..2)
+            Example ::= SEQUENCE {
+                version  [0] EXPLICIT Version DEFAULT v1,
+                names    SEQUENCE SIZE (1..MAX) OF name GeneralName,
+                flag     BOOLEAN OPTIONAL,
+                ...
+            }
+            GeneralName ::= CHOICE { dns [2] IA5String, other [APPLICATION 3] ANY DEFINED BY id }
+            id-test OBJECT IDENTIFIER ::= { 1 2 3 }
+            END
+        "#;
+        let module = parse_module(src).expect("parsing failed");
+        assert_eq!(module.name, "Test");
+        assert_eq!(module.tagging, TagMode::Implicit);
+        let names: Vec<_> = module.assignments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Version", "Example", "GeneralName"]);
+        let components = match &module.assignments[1].ty {
+            Type::Sequence(c) => c,
+            _ => panic!("not a sequence"),
+        };
+        assert_eq!(components.len(), 3);
+        assert_eq!(components[0].default.as_deref(), Some("v1"));
+        assert_eq!(
+            components[0].ty,
+            Type::Tagged(
+                TagClass::ContextSpecific,
+                0,
+                Some(TagMode::Explicit),
+                Box::new(Type::Reference("Version".into()))
+            )
+        );
+        assert_eq!(
+            components[1].ty,
+            Type::SequenceOf(Box::new(Type::Reference("GeneralName".into())))
+        );
+
+        let err = parse_module("M DEFINITIONS ::= BEGIN X ::= REAL END").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: unsupported type 'REAL'");
+    }
+}
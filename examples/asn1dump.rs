@@ -0,0 +1,298 @@
+//! Dump BER/DER objects, with offsets and OID names
+//!
+//! Usage: `asn1dump [--ber|--der|--stats] [FILE...]`
+//!
+//! The input is read from the files (or from stdin if no file is given, or if the file is `-`),
+//! and can be raw DER, PEM, or hexadecimal. Objects encapsulated in OCTET STRINGs or unknown
+//! objects are also printed.
+//!
+//! Each line shows the offset of the object in the (decoded) input, the nesting depth, the header
+//! and content lengths, and the object itself:
+//!
+//! ```text
+//!     0: d=0 hl=2 l=  17 Sequence
+//!     2: d=1 hl=2 l=   9 [Oid] 9 OID(OID(1.2.840.113549.1.7.1)) (id-data)
+//! ```
+//!
+//! With `--stats`, the objects are not printed: statistics on all the files (tag frequencies,
+//! depths and DER violations) are printed instead.
+
+use der_parser::ber::*;
+use der_parser::corpus::CorpusStats;
+use der_parser::der::*;
+use der_parser::oid::Oid;
+use std::env;
+use std::io::{self, Read};
+
+/// Names of some common OIDs
+const OID_NAMES: &[(&str, &str)] = &[
+    ("1.2.840.10040.4.1", "dsa"),
+    ("1.2.840.10045.2.1", "id-ecPublicKey"),
+    ("1.2.840.10045.3.1.7", "prime256v1"),
+    ("1.2.840.10045.4.3.2", "ecdsa-with-SHA256"),
+    ("1.2.840.10045.4.3.3", "ecdsa-with-SHA384"),
+    ("1.2.840.113549.1.1.1", "rsaEncryption"),
+    ("1.2.840.113549.1.1.5", "sha1WithRSAEncryption"),
+    ("1.2.840.113549.1.1.10", "rsassa-pss"),
+    ("1.2.840.113549.1.1.11", "sha256WithRSAEncryption"),
+    ("1.2.840.113549.1.1.12", "sha384WithRSAEncryption"),
+    ("1.2.840.113549.1.1.13", "sha512WithRSAEncryption"),
+    ("1.2.840.113549.1.7.1", "id-data"),
+    ("1.2.840.113549.1.7.2", "id-signedData"),
+    ("1.2.840.113549.1.7.3", "id-envelopedData"),
+    ("1.2.840.113549.1.9.1", "emailAddress"),
+    ("1.2.840.113549.1.9.3", "contentType"),
+    ("1.2.840.113549.1.9.4", "messageDigest"),
+    ("1.2.840.113549.1.9.5", "signingTime"),
+    ("1.3.6.1.5.5.7.1.1", "authorityInfoAccess"),
+    ("1.3.6.1.5.5.7.3.1", "serverAuth"),
+    ("1.3.6.1.5.5.7.3.2", "clientAuth"),
+    ("1.3.6.1.5.5.7.48.1", "ocsp"),
+    ("1.3.6.1.5.5.7.48.2", "caIssuers"),
+    ("1.3.101.112", "Ed25519"),
+    ("1.3.132.0.34", "secp384r1"),
+    ("2.5.4.3", "commonName"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "countryName"),
+    ("2.5.4.7", "localityName"),
+    ("2.5.4.8", "stateOrProvinceName"),
+    ("2.5.4.10", "organizationName"),
+    ("2.5.4.11", "organizationalUnitName"),
+    ("2.5.29.14", "subjectKeyIdentifier"),
+    ("2.5.29.15", "keyUsage"),
+    ("2.5.29.17", "subjectAltName"),
+    ("2.5.29.19", "basicConstraints"),
+    ("2.5.29.31", "cRLDistributionPoints"),
+    ("2.5.29.32", "certificatePolicies"),
+    ("2.5.29.35", "authorityKeyIdentifier"),
+    ("2.5.29.37", "extKeyUsage"),
+    ("2.16.840.1.101.3.4.2.1", "sha256"),
+    ("2.16.840.1.101.3.4.2.2", "sha384"),
+    ("2.16.840.1.101.3.4.2.3", "sha512"),
+];
+
+fn oid_name(oid: &Oid) -> Option<&'static str> {
+    let s = oid.to_id_string();
+    OID_NAMES
+        .iter()
+        .find(|(id, _)| *id == s)
+        .map(|(_, name)| *name)
+}
+
+struct Dumper<'i> {
+    input: &'i [u8],
+    parse_as_ber: bool,
+}
+
+impl<'i> Dumper<'i> {
+    /// Print the objects encapsulated in `data`, if it looks like BER
+    fn try_print_encapsulated(&mut self, data: &[u8], depth: usize) {
+        let mut i = data;
+        let mut objects = Vec::new();
+        while !i.is_empty() {
+            let res = if self.parse_as_ber {
+                parse_ber(i)
+            } else {
+                parse_der(i)
+            };
+            match res {
+                Ok((rem, obj)) => {
+                    objects.push(obj);
+                    i = rem;
+                }
+                // does not look like encapsulated data
+                Err(_) => return,
+            }
+        }
+        for obj in &objects {
+            self.run_at(obj, depth + 1);
+        }
+    }
+}
+
+impl<'a, 'i> Visit<'a> for Dumper<'i> {
+    fn visit_ber(&mut self, ber: &'_ BerObject<'a>, depth: usize) {
+        match ber.span {
+            Some(span) => {
+                let offset = span.offset_in(self.input).unwrap_or_default();
+                print!(
+                    "{:5}: d={} hl={} l={:4} ",
+                    offset,
+                    depth,
+                    span.header_len(),
+                    span.content_len()
+                );
+            }
+            None => print!("{:5}: d={} ", "?", depth),
+        }
+        // recursion is handled by the visitor
+        let pp = PrettyBer::new(ber, vec![PrettyPrinterFlag::ShowHeader], 0, 2);
+        match ber.content {
+            BerObjectContent::OID(ref oid) | BerObjectContent::RelativeOID(ref oid) => {
+                match oid_name(oid) {
+                    Some(name) => println!("{:?} ({})", pp, name),
+                    None => println!("{:?}", pp),
+                }
+            }
+            BerObjectContent::Sequence(_) | BerObjectContent::Set(_) => {
+                println!("{}", ber.header.tag().name());
+            }
+            _ => println!("{:?}", pp),
+        }
+        match ber.content {
+            BerObjectContent::Unknown(ref any) if any.header.is_constructed() => {
+                self.try_print_encapsulated(any.data, depth)
+            }
+            BerObjectContent::OctetString(data) => self.try_print_encapsulated(data, depth),
+            _ => (),
+        }
+    }
+}
+
+/// Decode the input, depending on its format (PEM, hex, or raw)
+fn decode_input(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text.trim(),
+        Err(_) => return Ok(data.to_vec()),
+    };
+    if text.starts_with("-----BEGIN") {
+        decode_pem(text)
+    } else if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c.is_ascii_whitespace() || c == ':')
+    {
+        decode_hex(text)
+    } else {
+        Ok(data.to_vec())
+    }
+}
+
+/// Decode the base64 content of all PEM blocks
+fn decode_pem(text: &str) -> Result<Vec<u8>, String> {
+    let mut b64 = String::new();
+    let mut in_block = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("-----BEGIN") {
+            in_block = true;
+        } else if line.starts_with("-----END") {
+            in_block = false;
+        } else if in_block && !line.contains(':') {
+            b64.push_str(line);
+        }
+    }
+    let mut out = Vec::with_capacity(b64.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in b64.bytes().take_while(|&c| c != b'=') {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("invalid base64 character {:?}", c as char)),
+        };
+        acc = (acc << 6) | u32::from(v);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = text
+        .bytes()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| (c as char).to_digit(16).unwrap_or_default() as u8)
+        .collect();
+    if digits.len() % 2 != 0 {
+        return Err("odd number of hexadecimal digits".to_string());
+    }
+    Ok(digits.chunks(2).map(|c| (c[0] << 4) | c[1]).collect())
+}
+
+fn dump(data: &[u8], parse_as_ber: bool) -> Result<(), String> {
+    let data = decode_input(data)?;
+    let mut i = &data[..];
+    while !i.is_empty() {
+        let (rem, obj) = if parse_as_ber {
+            parse_ber(i).map_err(|e| format!("could not parse BER data: {}", e))?
+        } else {
+            parse_der(i).map_err(|e| format!("could not parse DER data: {}", e))?
+        };
+        let mut dumper = Dumper {
+            input: &data,
+            parse_as_ber,
+        };
+        dumper.run_at(&obj, 0);
+        i = rem;
+    }
+    Ok(())
+}
+
+fn print_stats(stats: &CorpusStats) {
+    println!("inputs: {} ({} rejected)", stats.inputs, stats.rejected);
+    println!("tags:");
+    for (class, tag, n) in &stats.tags {
+        match class {
+            Class::Universal => println!("  {}: {}", tag.name(), n),
+            _ => println!("  {:?} {}: {}", class, tag.0, n),
+        }
+    }
+    println!("depths:");
+    for (depth, n) in stats.depths.iter().enumerate().filter(|(_, n)| **n > 0) {
+        println!("  {}: {}", depth, n);
+    }
+    println!("violations:");
+    for (violation, n) in &stats.violations {
+        println!("  {}: {}", violation, n);
+    }
+}
+
+pub fn main() -> io::Result<()> {
+    let mut parse_as_ber = false;
+    let mut stats = None;
+    let mut files = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_ref() {
+            "--ber" => parse_as_ber = true,
+            "--der" => parse_as_ber = false,
+            "--stats" => stats = Some(CorpusStats::new()),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+    for file_name in files {
+        let data = if file_name == "-" {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            data
+        } else {
+            std::fs::read(&file_name)?
+        };
+        if let Some(stats) = stats.as_mut() {
+            let res = decode_input(&data).and_then(|data| {
+                stats
+                    .add(&data)
+                    .map_err(|e| format!("could not parse data: {}", e))
+            });
+            if let Err(e) = res {
+                eprintln!("{}: {}", file_name, e);
+            }
+            continue;
+        }
+        if let Err(e) = dump(&data, parse_as_ber) {
+            eprintln!("{}: {}", file_name, e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(stats) = stats {
+        print_stats(&stats);
+    }
+    Ok(())
+}
@@ -0,0 +1,12 @@
+#![no_main]
+extern crate libfuzzer_sys;
+use der_parser::ber::{parse_ber, BerObject};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|obj: BerObject| {
+    // generated objects are valid, so they must be encoded and parsed again
+    let bytes = obj.to_vec().expect("encoding failed");
+    let (rem, parsed) = parse_ber(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(parsed.to_vec().expect("encoding failed"), bytes);
+});
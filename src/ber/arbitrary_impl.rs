@@ -0,0 +1,128 @@
+//! Implementation of `arbitrary::Arbitrary` for BER objects
+//!
+//! Generated objects are structurally valid: the content of each object is a valid value for its
+//! type (minimal integers, OIDs, restricted character sets, etc.), so the objects can be encoded
+//! and parsed again. Values borrow the fuzzer input when possible.
+//!
+//! The elements of SET objects are not sorted, so the encoding of a generated object is valid BER
+//! but not always valid DER.
+
+use crate::ber::{BerObject, BerObjectContent, BitStringObject};
+use crate::oid::Oid;
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Maximum nesting level of generated objects
+const MAX_DEPTH: usize = 4;
+
+/// Maximum number of children of a generated constructed object
+const MAX_CHILDREN: usize = 4;
+
+/// Take a prefix of the input, with at most `max_len` bytes
+fn take_bytes<'a>(u: &mut Unstructured<'a>, max_len: usize) -> Result<&'a [u8]> {
+    let len = u.int_in_range(0..=max_len.min(u.len()))?;
+    u.bytes(len)
+}
+
+/// Take a prefix of the input, and keep the longest prefix satisfying `f`
+fn take_str<'a>(u: &mut Unstructured<'a>, f: impl Fn(u8) -> bool) -> Result<&'a str> {
+    let bytes = take_bytes(u, 32)?;
+    let len = bytes.iter().position(|&b| !f(b)).unwrap_or(bytes.len());
+    // only ASCII characters are accepted by the callers
+    core::str::from_utf8(&bytes[..len]).map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
+fn arbitrary_integer<'a>(u: &mut Unstructured<'a>) -> Result<&'a [u8]> {
+    let len = u.int_in_range(1..=9)?;
+    let bytes = u.bytes(len)?;
+    // remove redundant leading octets (X.690 8.3.2)
+    let mut skip = 0;
+    while skip + 1 < bytes.len()
+        && ((bytes[skip] == 0 && bytes[skip + 1] & 0x80 == 0)
+            || (bytes[skip] == 0xff && bytes[skip + 1] & 0x80 != 0))
+    {
+        skip += 1;
+    }
+    Ok(&bytes[skip..])
+}
+
+fn arbitrary_oid(u: &mut Unstructured<'_>) -> Result<Oid<'static>> {
+    // `Oid::from` only accepts a second arc lower than 40 (even if the first arc is 2)
+    let mut arcs = alloc::vec![u.int_in_range(0..=2)?, u.int_in_range(0..=39)?];
+    for _ in 0..u.int_in_range(0..=6)? {
+        arcs.push(u64::from(u.arbitrary::<u32>()?));
+    }
+    Oid::from(&arcs).map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
+fn arbitrary_object<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<BerObject<'a>> {
+    // constructed objects are only generated below the maximum depth
+    let max_choice = if depth < MAX_DEPTH { 11 } else { 9 };
+    let content = match u.int_in_range(0..=max_choice)? {
+        0 => BerObjectContent::Boolean(u.arbitrary()?),
+        1 => BerObjectContent::Integer(arbitrary_integer(u)?),
+        2 => BerObjectContent::BitString(
+            0,
+            BitStringObject {
+                data: take_bytes(u, 32)?,
+            },
+        ),
+        3 => BerObjectContent::OctetString(take_bytes(u, 64)?),
+        4 => BerObjectContent::Null,
+        5 => BerObjectContent::Enum(u64::from(u.arbitrary::<u32>()?)),
+        6 => BerObjectContent::OID(arbitrary_oid(u)?),
+        7 => BerObjectContent::UTF8String(u.arbitrary()?),
+        8 => BerObjectContent::PrintableString(take_str(u, |b| {
+            b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b)
+        })?),
+        9 => BerObjectContent::IA5String(take_str(u, |b| b.is_ascii())?),
+        choice => {
+            let mut children = Vec::new();
+            for _ in 0..u.int_in_range(0..=MAX_CHILDREN)? {
+                children.push(arbitrary_object(u, depth + 1)?);
+            }
+            if choice == 10 {
+                BerObjectContent::Sequence(children)
+            } else {
+                BerObjectContent::Set(children)
+            }
+        }
+    };
+    Ok(BerObject::from_obj(content))
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+impl<'a> Arbitrary<'a> for BerObject<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_object(u, 0)
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod tests {
+    use crate::ber::*;
+    use arbitrary::{Arbitrary, Unstructured};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_arbitrary_roundtrip() {
+        // xorshift, to generate reproducible inputs
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut data = Vec::new();
+        for _ in 0..500 {
+            data.clear();
+            for _ in 0..256 {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                data.push(state as u8);
+            }
+            let mut u = Unstructured::new(&data);
+            let obj = BerObject::arbitrary(&mut u).expect("generation failed");
+            let bytes = obj.to_vec().expect("encoding failed");
+            let (rem, parsed) = parse_ber(&bytes).expect("parsing failed");
+            assert!(rem.is_empty());
+            assert_eq!(parsed.to_vec().expect("encoding failed"), bytes);
+        }
+    }
+}
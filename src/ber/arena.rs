@@ -0,0 +1,397 @@
+use crate::ber::{parse_ber, BerObject, Header, Span, MAX_RECURSION};
+use crate::error::{BerError, BerResult};
+use alloc::vec::Vec;
+#[cfg(feature = "allocator-api2")]
+use allocator_api2::alloc::Allocator;
+use asn1_rs::{Any, FromBer};
+use core::fmt;
+use core::ops::Range;
+use nom::{Err, Offset};
+
+#[derive(Debug)]
+struct Node {
+    header: Header<'static>,
+    offset: usize,
+    header_len: usize,
+    content_len: usize,
+    depth: usize,
+    children: Range<usize>,
+}
+
+/// Reusable storage for parsing trees of BER objects
+///
+/// Parsing with [`BerObject`] allocates a vector for each constructed object. When parsing a large
+/// number of small structures, this puts a lot of pressure on the allocator. A `BerArena` stores
+/// all the nodes of a tree in a single buffer, which is kept (and reused) by the next call to
+/// [`BerArena::parse`]: after a few calls, parsing does not allocate.
+///
+/// The arena does not borrow the input, so it can be reused for inputs with different lifetimes.
+/// Nodes are accessed using [`ArenaObject`] references, and can be decoded into a `BerObject`
+/// when needed.
+///
+/// The content of all constructed objects (not only sequences and sets) is parsed as a list of
+/// objects.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{BerArena, Tag};
+///
+/// let mut arena = BerArena::new();
+/// for bytes in [&[0x30, 0x03, 0x02, 0x01, 0x01][..], &[0x30, 0x03, 0x02, 0x01, 0x02]] {
+///     let (_, root) = arena.parse(bytes).expect("parsing failed");
+///     let int = root.get(0).expect("missing child");
+///     assert_eq!(int.header().tag(), Tag::Integer);
+///     assert_eq!(int.data(), &bytes[4..]);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct BerArena {
+    nodes: Vec<Node>,
+}
+
+impl BerArena {
+    /// Create a new, empty arena
+    pub const fn new() -> Self {
+        BerArena { nodes: Vec::new() }
+    }
+
+    /// Create a new arena, with space for `capacity` objects
+    pub fn with_capacity(capacity: usize) -> Self {
+        BerArena {
+            nodes: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of objects parsed by the last call to `parse`
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Test if the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Parse a BER object and all its children (recursively) into the arena
+    ///
+    /// The previous content of the arena is discarded. The maximum depth is `MAX_RECURSION`.
+    pub fn parse<'a, 'b>(&'b mut self, input: &'a [u8]) -> BerResult<'a, ArenaObject<'a, 'b>> {
+        parse_nodes(self, input)
+    }
+}
+
+/// Reusable storage for parsing trees of BER objects, allocated using `A`
+///
+/// This is the same as [`BerArena`], except that nodes are allocated using a custom allocator
+/// implementing the `allocator_api2::alloc::Allocator` trait (a stable version of the
+/// `allocator_api` of the standard library). For ex., a server can use a per-request bump
+/// allocator, freed wholesale when the request has been processed.
+///
+/// # Examples
+///
+/// ```rust
+/// use bumpalo::Bump;
+/// use der_parser::ber::{BerArenaIn, Tag};
+///
+/// // SEQUENCE { INTEGER 1 }
+/// let bytes = &[0x30, 0x03, 0x02, 0x01, 0x01];
+/// let bump = Bump::new();
+/// let mut arena = BerArenaIn::new_in(&bump);
+/// let (_, root) = arena.parse(bytes).expect("parsing failed");
+/// assert_eq!(root.get(0).map(|o| o.header().tag()), Some(Tag::Integer));
+/// ```
+#[cfg(feature = "allocator-api2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "allocator-api2")))]
+#[derive(Debug)]
+pub struct BerArenaIn<A: Allocator> {
+    nodes: allocator_api2::vec::Vec<Node, A>,
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<A: Allocator> BerArenaIn<A> {
+    /// Create a new, empty arena, allocated using `alloc`
+    pub fn new_in(alloc: A) -> Self {
+        BerArenaIn {
+            nodes: allocator_api2::vec::Vec::new_in(alloc),
+        }
+    }
+
+    /// Create a new arena, with space for `capacity` objects, allocated using `alloc`
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        BerArenaIn {
+            nodes: allocator_api2::vec::Vec::with_capacity_in(capacity, alloc),
+        }
+    }
+
+    /// Return a reference to the allocator of the arena
+    pub fn allocator(&self) -> &A {
+        self.nodes.allocator()
+    }
+
+    /// Return the number of objects parsed by the last call to `parse`
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Test if the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Parse a BER object and all its children (recursively) into the arena
+    ///
+    /// See [`BerArena::parse`].
+    pub fn parse<'a, 'b>(&'b mut self, input: &'a [u8]) -> BerResult<'a, ArenaObject<'a, 'b>> {
+        parse_nodes(self, input)
+    }
+}
+
+/// Storage for the nodes of an arena
+trait NodeStorage {
+    fn nodes(&self) -> &[Node];
+    fn nodes_mut(&mut self) -> &mut [Node];
+    fn push(&mut self, node: Node);
+    fn clear(&mut self);
+}
+
+impl NodeStorage for BerArena {
+    fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    fn nodes_mut(&mut self) -> &mut [Node] {
+        &mut self.nodes
+    }
+    fn push(&mut self, node: Node) {
+        self.nodes.push(node)
+    }
+    fn clear(&mut self) {
+        self.nodes.clear()
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<A: Allocator> NodeStorage for BerArenaIn<A> {
+    fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    fn nodes_mut(&mut self) -> &mut [Node] {
+        &mut self.nodes
+    }
+    fn push(&mut self, node: Node) {
+        self.nodes.push(node)
+    }
+    fn clear(&mut self) {
+        self.nodes.clear()
+    }
+}
+
+fn parse_nodes<'a, 'b, S: NodeStorage>(
+    storage: &'b mut S,
+    input: &'a [u8],
+) -> BerResult<'a, ArenaObject<'a, 'b>> {
+    storage.clear();
+    let rem = push_node(storage, input, input, 0)?;
+    // nodes are stored in breadth-first order, so the children of each node are contiguous
+    let mut idx = 0;
+    while idx < storage.nodes().len() {
+        let node = &storage.nodes()[idx];
+        if node.header.is_constructed() {
+            if node.depth >= MAX_RECURSION {
+                return Err(Err::Error(BerError::BerMaxDepth));
+            }
+            let start = node.offset + node.header_len;
+            let mut data = &input[start..start + node.content_len];
+            let depth = node.depth + 1;
+            let first = storage.nodes().len();
+            while !data.is_empty() {
+                data = push_node(storage, input, data, depth)?;
+            }
+            let last = storage.nodes().len();
+            storage.nodes_mut()[idx].children = first..last;
+        }
+        idx += 1;
+    }
+    let root = ArenaObject {
+        input,
+        nodes: storage.nodes(),
+        idx: 0,
+    };
+    Ok((rem, root))
+}
+
+fn push_node<'a, S: NodeStorage>(
+    storage: &mut S,
+    input: &'a [u8],
+    i: &'a [u8],
+    depth: usize,
+) -> Result<&'a [u8], Err<BerError>> {
+    let (rem, any) = Any::from_ber(i)?;
+    let hdr = &any.header;
+    let header = Header::new(hdr.class(), hdr.constructed(), hdr.tag(), hdr.length());
+    storage.push(Node {
+        header,
+        offset: input.offset(i),
+        header_len: i.offset(any.data),
+        content_len: any.data.len(),
+        depth,
+        children: 0..0,
+    });
+    Ok(rem)
+}
+
+/// Reference on an object stored in a [`BerArena`]
+#[derive(Clone, Copy)]
+pub struct ArenaObject<'a, 'b> {
+    input: &'a [u8],
+    nodes: &'b [Node],
+    idx: usize,
+}
+
+impl<'a, 'b> ArenaObject<'a, 'b> {
+    fn node(&self) -> &'b Node {
+        &self.nodes[self.idx]
+    }
+
+    /// Get the header of the object
+    ///
+    /// The raw tag is not stored in the arena.
+    pub fn header(&self) -> &'b Header<'static> {
+        &self.node().header
+    }
+
+    /// Get the location of the object in the input
+    pub fn span(&self) -> Span<'a> {
+        let node = self.node();
+        let eoc = if node.header.length().is_definite() {
+            0
+        } else {
+            2
+        };
+        let end = node.offset + node.header_len + node.content_len + eoc;
+        Span::from_raw(
+            &self.input[node.offset..end],
+            node.content_len,
+            node.header.length(),
+        )
+    }
+
+    /// Get the content bytes of the object (without end-of-content octets)
+    pub fn data(&self) -> &'a [u8] {
+        self.span().content()
+    }
+
+    /// Get the number of children of the object (0 if the object is primitive)
+    pub fn len(&self) -> usize {
+        self.node().children.len()
+    }
+
+    /// Test if the object has no children
+    pub fn is_empty(&self) -> bool {
+        self.node().children.is_empty()
+    }
+
+    /// Get a child of the object, or `None`
+    pub fn get(&self, idx: usize) -> Option<ArenaObject<'a, 'b>> {
+        let children = &self.node().children;
+        if idx < children.len() {
+            Some(ArenaObject {
+                idx: children.start + idx,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the children of the object
+    pub fn children(&self) -> impl ExactSizeIterator<Item = ArenaObject<'a, 'b>> {
+        let this = *self;
+        self.node()
+            .children
+            .clone()
+            .map(move |idx| ArenaObject { idx, ..this })
+    }
+
+    /// Decode the object (and its children) into a `BerObject`
+    ///
+    /// Note that this allocates the object tree.
+    pub fn to_object(&self) -> Result<BerObject<'a>, BerError> {
+        let (_, obj) = parse_ber(self.span().raw())?;
+        Ok(obj)
+    }
+}
+
+impl<'a, 'b> fmt::Debug for ArenaObject<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArenaObject")
+            .field("header", self.header())
+            .field("data", &self.data())
+            .field("children", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use crate::error::BerError;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_arena_parse() {
+        // SEQUENCE { INTEGER 1, SET (indefinite) { NULL, INTEGER 2 }, [0] { BOOLEAN TRUE } }
+        let bytes = hex!("30 11 02 01 01 31 80 05 00 02 01 02 00 00 a0 03 01 01 ff");
+        let mut arena = BerArena::new();
+        let (rem, root) = arena.parse(&bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(root.len(), 3);
+        let tags = root
+            .children()
+            .map(|o| (o.header().tag(), o.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(tags, vec![(Tag::Integer, 0), (Tag::Set, 2), (Tag(0), 1)]);
+        let set = root.get(1).unwrap();
+        assert_eq!(set.span().range_in(&bytes), Some(5..14));
+        assert_eq!(set.data(), &bytes[7..12]);
+        assert_eq!(set.get(1).unwrap().data(), &[0x02]);
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert_eq!(root.to_object(), Ok(obj));
+        assert_eq!(arena.len(), 7);
+    }
+
+    #[test]
+    fn test_arena_reuse() {
+        let mut arena = BerArena::with_capacity(4);
+        for i in 0..3u8 {
+            let bytes = vec![0x30, 0x06, 0x02, 0x01, i, 0x02, 0x01, i];
+            let (_, root) = arena.parse(&bytes).expect("parsing failed");
+            assert_eq!(root.get(1).map(|o| o.data()), Some(&[i][..]));
+        }
+        assert_eq!(arena.len(), 3);
+
+        let mut bytes = [0x30, 0x80].repeat(MAX_RECURSION + 1);
+        bytes.extend_from_slice(&[0x00; 2 * (MAX_RECURSION + 1)]);
+        let r = arena.parse(&bytes);
+        assert_eq!(r.unwrap_err(), nom::Err::Error(BerError::BerMaxDepth));
+    }
+
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_arena_allocator() {
+        let mut bump = bumpalo::Bump::new();
+        for i in 0..3u8 {
+            let mut arena = BerArenaIn::new_in(&bump);
+            let bytes = vec![0x30, 0x06, 0x02, 0x01, i, 0x02, 0x01, i];
+            let (_, root) = arena.parse(&bytes).expect("parsing failed");
+            assert_eq!(root.get(1).map(|o| o.data()), Some(&[i][..]));
+            assert_eq!(arena.len(), 3);
+            assert!(arena.allocator().allocated_bytes() > 0);
+            // free all nodes at once
+            drop(arena);
+            bump.reset();
+        }
+    }
+}
@@ -2,18 +2,23 @@ use super::{Class, Header, Length, Tag};
 use crate::ber::ber_read_element_content_as;
 use crate::ber::bitstring_to_u64;
 use crate::ber::integer::*;
+use crate::ber::try_str_from_raw;
 use crate::ber::MAX_RECURSION;
 use crate::error::BerError;
 use crate::oid::Oid;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
-use asn1_rs::ASN1DateTime;
 use asn1_rs::Any;
+use asn1_rs::FromBer;
+use asn1_rs::{ASN1DateTime, ASN1TimeZone};
 #[cfg(feature = "bitvec")]
 use bitvec::{order::Msb0, slice::BitSlice};
 use core::convert::TryFrom;
-use core::ops::Index;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::{Index, Range};
+use core::slice;
 
 /// Representation of a BER-encoded (X.690) object
 ///
@@ -22,14 +27,222 @@ use core::ops::Index;
 ///
 /// Note that the content may sometimes not match the header tag (for ex when parsing IMPLICIT
 /// tagged values).
-#[derive(Debug, Clone, PartialEq)]
+///
+/// When the object was read by a parser, `span` contains its location in the input. The span is
+/// not compared by `PartialEq` (or hashed), so parsed objects can be compared to built objects.
+#[derive(Debug, Clone, Eq)]
 pub struct BerObject<'a> {
     pub header: Header<'a>,
     pub content: BerObjectContent<'a>,
+    pub span: Option<Span<'a>>,
+}
+
+/// Encoding of the length octets of an object (X.690 8.1.3)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LengthForm {
+    /// Definite length, in a single octet (lengths up to 127)
+    Short,
+    /// Definite length, in an initial octet followed by the given number of octets
+    Long(u8),
+    /// Indefinite length, the content being terminated by end-of-content octets
+    Indefinite,
+}
+
+/// Location of an encoded object in the input
+///
+/// The span covers the header (identifier and length octets), the content, and the end-of-content
+/// octets if the length is indefinite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span<'a> {
+    raw: &'a [u8],
+    header_len: usize,
+    content_len: usize,
+}
+
+impl<'a> Span<'a> {
+    /// Build a span from the input before the header, the input after the header, and the
+    /// remaining input after the object
+    pub(crate) fn from_input(
+        input: &'a [u8],
+        content: &'a [u8],
+        rem: &'a [u8],
+        length: Length,
+    ) -> Self {
+        let consumed = input.len() - rem.len();
+        let header_len = input.len() - content.len();
+        let content_len = consumed - header_len;
+        let content_len = match length {
+            Length::Definite(_) => content_len,
+            Length::Indefinite => content_len.saturating_sub(2),
+        };
+        Span {
+            raw: &input[..consumed],
+            header_len,
+            content_len,
+        }
+    }
+
+    /// Build a span from the raw object bytes and the length of the content
+    pub(crate) fn from_raw(raw: &'a [u8], content_len: usize, length: Length) -> Self {
+        let eoc = if length.is_definite() { 0 } else { 2 };
+        Span {
+            raw,
+            header_len: raw.len() - content_len - eoc,
+            content_len,
+        }
+    }
+
+    /// Build a span from the raw object bytes, by reading the header again
+    pub(crate) fn from_tlv(raw: &'a [u8]) -> Option<Self> {
+        let (content, hdr) = Header::from_ber(raw).ok()?;
+        let eoc = if hdr.length().is_definite() { 0 } else { 2 };
+        let content_len = content.len().checked_sub(eoc)?;
+        Some(Span::from_raw(raw, content_len, hdr.length()))
+    }
+
+    /// Raw bytes of the object (header, content and end-of-content octets)
+    #[inline]
+    pub const fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Raw bytes of the header (identifier and length octets)
+    #[inline]
+    pub fn header(&self) -> &'a [u8] {
+        &self.raw[..self.header_len]
+    }
+
+    /// Raw bytes of the content (without end-of-content octets)
+    #[inline]
+    pub fn content(&self) -> &'a [u8] {
+        &self.raw[self.header_len..self.header_len + self.content_len]
+    }
+
+    /// Total length of the encoded object
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Test if the span is empty (this is never the case for a parsed object)
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Length of the header (identifier and length octets)
+    #[inline]
+    pub const fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// Length of the content (without end-of-content octets)
+    #[inline]
+    pub const fn content_len(&self) -> usize {
+        self.content_len
+    }
+
+    /// Raw bytes of the identifier octets (class, constructed bit and tag)
+    #[inline]
+    pub fn identifier(&self) -> &'a [u8] {
+        &self.raw[..self.identifier_len()]
+    }
+
+    /// Length of the identifier octets (class, constructed bit and tag)
+    pub fn identifier_len(&self) -> usize {
+        let header = self.header();
+        match header.first() {
+            Some(b) if b & 0x1f == 0x1f => {
+                // high tag number form: the last octet has bit 8 set to 0
+                let n = header[1..].iter().take_while(|&&b| b & 0x80 != 0).count();
+                (n + 2).min(header.len())
+            }
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    /// Length of the length octets
+    #[inline]
+    pub fn length_len(&self) -> usize {
+        self.header_len - self.identifier_len()
+    }
+
+    /// Raw bytes of the length octets
+    #[inline]
+    pub fn length_octets(&self) -> &'a [u8] {
+        &self.header()[self.identifier_len()..]
+    }
+
+    /// Get the form of the length octets
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber, LengthForm};
+    ///
+    /// // NULL, with a length encoded in 3 octets
+    /// let (_, obj) = parse_ber(&[0x05, 0x82, 0x00, 0x00]).expect("parsing failed");
+    /// let span = obj.span.expect("no span");
+    /// assert_eq!(span.length_form(), LengthForm::Long(2));
+    /// assert!(!span.has_minimal_length());
+    /// ```
+    pub fn length_form(&self) -> LengthForm {
+        match self.length_octets() {
+            [0x80] => LengthForm::Indefinite,
+            [l, ..] if *l > 0x80 => LengthForm::Long(l & 0x7f),
+            _ => LengthForm::Short,
+        }
+    }
+
+    /// Test if the length is definite, and encoded in the minimum number of octets (as required
+    /// by DER)
+    pub fn has_minimal_length(&self) -> bool {
+        match self.length_octets() {
+            [l] => *l < 0x80,
+            [0x81, b] => *b >= 0x80,
+            [_, b, ..] => *b != 0,
+            [] => false,
+        }
+    }
+
+    /// Get the offset of the object in `input`, or `None` if the object is not part of `input`
+    ///
+    /// `input` is usually the buffer given to the parser.
+    pub fn offset_in(&self, input: &[u8]) -> Option<usize> {
+        let start = self.raw.as_ptr() as usize;
+        let base = input.as_ptr() as usize;
+        if start >= base && start + self.raw.len() <= base + input.len() {
+            Some(start - base)
+        } else {
+            None
+        }
+    }
+
+    /// Get the range of the object in `input`, or `None` if the object is not part of `input`
+    pub fn range_in(&self, input: &[u8]) -> Option<Range<usize>> {
+        self.offset_in(input)
+            .map(|start| start..start + self.raw.len())
+    }
+
+    /// Get the range of the content (without end-of-content octets) in `input`, or `None` if the
+    /// object is not part of `input`
+    pub fn content_range_in(&self, input: &[u8]) -> Option<Range<usize>> {
+        self.offset_in(input).map(|start| {
+            let start = start + self.header_len;
+            start..start + self.content_len
+        })
+    }
+}
+
+impl<'a> PartialEq for BerObject<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header && self.content == other.content
+    }
 }
 
 /// BER object content
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum BerObjectContent<'a> {
     /// EOC (no content)
@@ -74,6 +287,8 @@ pub enum BerObjectContent<'a> {
     BmpString(&'a str),
     /// UniversalString: raw object bytes
     UniversalString(&'a [u8]),
+    /// CHARACTER STRING (unrestricted): identification and raw string value
+    CharacterString(CharacterStringObject<'a>),
 
     /// SEQUENCE: list of objects
     Sequence(Vec<BerObject<'a>>),
@@ -97,10 +312,28 @@ pub enum BerObjectContent<'a> {
     /// Tagged object (EXPLICIT): class, tag  and content of inner object
     Tagged(Class, Tag, Box<BerObject<'a>>),
 
+    /// Object with a tag number too large to be represented by [`Tag`] (more than 32 bits):
+    /// raw identifier octets, and raw content
+    ///
+    /// The tag of the header of such objects is [`TAG_OVERFLOW`]. The content is not parsed, even
+    /// if the object is constructed. These objects are only read by the BER parsers, and not
+    /// inside objects with an indefinite length.
+    TagOverflow(&'a [u8], &'a [u8]),
+
+    /// Universal primitive object which was not decoded: tag and raw content
+    ///
+    /// These objects are only built when [`ParseOptions::decode_primitives`] is not set. The value
+    /// can be decoded later, for ex. using the `ber_read_content_*` functions.
+    Primitive(Tag, &'a [u8]),
+
     /// Private or Unknown (for ex. unknown tag) object
     Unknown(Any<'a>),
 }
 
+/// Placeholder tag used in the header of objects with a tag number too large to be represented
+/// (see [`BerObjectContent::TagOverflow`])
+pub const TAG_OVERFLOW: Tag = Tag(u32::MAX);
+
 impl<'a> BerObject<'a> {
     /// Build a BerObject from a header and content.
     ///
@@ -111,17 +344,25 @@ impl<'a> BerObject<'a> {
         header: Header<'o>,
         content: BerObjectContent<'o>,
     ) -> BerObject<'o> {
-        BerObject { header, content }
+        BerObject {
+            header,
+            content,
+            span: None,
+        }
     }
 
     /// Build a BerObject from its content, using default flags (no class, correct tag,
-    /// and constructed flag set only for Set and Sequence)
+    /// and constructed flag set only for Set, Sequence and CharacterString)
     pub const fn from_obj(c: BerObjectContent) -> BerObject {
         let class = Class::Universal;
         let tag = c.tag();
-        let constructed = matches!(tag, Tag::Sequence | Tag::Set);
+        let constructed = matches!(tag, Tag::Sequence | Tag::Set | Tag::CharacterString);
         let header = Header::new(class, constructed, tag, Length::Definite(0));
-        BerObject { header, content: c }
+        BerObject {
+            header,
+            content: c,
+            span: None,
+        }
     }
 
     /// Build a DER integer object from a slice containing an encoded integer
@@ -130,6 +371,7 @@ impl<'a> BerObject<'a> {
         BerObject {
             header,
             content: BerObjectContent::Integer(i),
+            span: None,
         }
     }
 
@@ -139,6 +381,71 @@ impl<'a> BerObject<'a> {
         BerObject { header, ..self }
     }
 
+    /// Replace the content of the object, keeping the header
+    ///
+    /// The header (class, tag, constructed flag and raw tag) is not modified, so it can be
+    /// inconsistent with the new content. The location of the object in the input is removed,
+    /// since the object is no longer the original encoding.
+    pub fn with_content(self, content: BerObjectContent<'a>) -> Self {
+        BerObject {
+            header: self.header,
+            content,
+            span: None,
+        }
+    }
+
+    /// Replace the content of the object by the result of `f` applied to the current content,
+    /// keeping the header
+    ///
+    /// See [`BerObject::with_content`].
+    ///
+    /// # Examples
+    ///
+    /// Blanking a secret before logging the object:
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber, BerObjectContent, Tag};
+    ///
+    /// // [1] IMPLICIT OCTET STRING "key"
+    /// let bytes = &[0x81, 0x03, 0x6b, 0x65, 0x79];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let redacted = obj.map_content(|c| match c {
+    ///     BerObjectContent::Unknown(_) => BerObjectContent::OctetString(b"***"),
+    ///     c => c,
+    /// });
+    /// assert_eq!(redacted.tag(), Tag(1));
+    /// assert_eq!(redacted.as_slice(), Ok(&b"***"[..]));
+    /// assert!(redacted.span.is_none());
+    /// ```
+    pub fn map_content<F>(self, f: F) -> Self
+    where
+        F: FnOnce(BerObjectContent<'a>) -> BerObjectContent<'a>,
+    {
+        let content = f(self.content);
+        BerObject {
+            header: self.header,
+            content,
+            span: None,
+        }
+    }
+
+    /// Set the location of the object in the input
+    #[inline]
+    pub(crate) fn with_span(self, span: Span<'a>) -> Self {
+        BerObject {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    /// Set the location of the object from the input before and after the object
+    pub(crate) fn with_span_from(self, input: &'a [u8], rem: &'a [u8]) -> Self {
+        match Span::from_tlv(&input[..input.len() - rem.len()]) {
+            Some(span) => self.with_span(span),
+            None => self,
+        }
+    }
+
     /// Build a DER sequence object from a vector of DER objects
     pub const fn from_seq(l: Vec<BerObject>) -> BerObject {
         BerObject::from_obj(BerObjectContent::Sequence(l))
@@ -149,6 +456,52 @@ impl<'a> BerObject<'a> {
         BerObject::from_obj(BerObjectContent::Set(l))
     }
 
+    /// Build an EXPLICIT tagged object, wrapping `obj`
+    ///
+    /// Any class and tag number can be used, including tag numbers greater than 30
+    /// (encoded using multiple identifier octets).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use der_parser::ber::{BerObject, Class, Tag};
+    /// // [APPLICATION 1000] EXPLICIT INTEGER 1
+    /// let obj = BerObject::from_tagged_explicit(
+    ///     Class::Application,
+    ///     Tag(1000),
+    ///     BerObject::from_int_slice(b"\x01"),
+    /// );
+    /// assert_eq!(obj.header.tag(), Tag(1000));
+    /// assert!(obj.as_tagged().is_ok());
+    /// ```
+    pub fn from_tagged_explicit(class: Class, tag: Tag, obj: BerObject<'a>) -> BerObject<'a> {
+        let header = Header::new(class, true, tag, Length::Definite(0));
+        BerObject {
+            header,
+            content: BerObjectContent::Tagged(class, tag, Box::new(obj)),
+            span: None,
+        }
+    }
+
+    /// Build an IMPLICIT tagged object, with raw content `data`
+    ///
+    /// Any class and tag number can be used, including tag numbers greater than 30
+    /// (encoded using multiple identifier octets). The content is not checked.
+    pub const fn from_tagged_implicit(
+        class: Class,
+        tag: Tag,
+        constructed: bool,
+        data: &'a [u8],
+    ) -> BerObject<'a> {
+        let header = Header::new(class, constructed, tag, Length::Definite(0));
+        let any_header = Header::new(class, constructed, tag, Length::Definite(0));
+        BerObject {
+            header,
+            content: BerObjectContent::Unknown(Any::new(any_header, data)),
+            span: None,
+        }
+    }
+
     /// Attempt to read a signed integer value from DER object.
     ///
     /// This can fail if the object is not an integer, or if it is too large.
@@ -246,6 +599,36 @@ impl<'a> BerObject<'a> {
         self.content.as_optional()
     }
 
+    /// Attempt to convert an optional object to a value of type `T`
+    ///
+    /// An absent `Optional` object returns `None`, and the content of a present one is converted.
+    /// Other objects are considered present, and are converted. This can fail if the conversion
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber_integer, parse_ber_optional};
+    ///
+    /// // INTEGER 2
+    /// let bytes = &[0x02, 0x01, 0x02];
+    /// let (_, obj) = parse_ber_optional(parse_ber_integer)(bytes).expect("parsing failed");
+    /// assert_eq!(obj.as_optional_t::<u32>(), Ok(Some(2)));
+    ///
+    /// let (_, obj) = parse_ber_optional(parse_ber_integer)(&[0x05, 0x00]).expect("parsing failed");
+    /// assert_eq!(obj.as_optional_t::<u32>(), Ok(None));
+    /// ```
+    pub fn as_optional_t<'b, T>(&'b self) -> Result<Option<T>, BerError>
+    where
+        T: TryFrom<&'b BerObject<'a>, Error = BerError>,
+    {
+        match self.content {
+            BerObjectContent::Optional(None) => Ok(None),
+            BerObjectContent::Optional(Some(ref o)) => T::try_from(o).map(Some),
+            _ => T::try_from(self).map(Some),
+        }
+    }
+
     /// Attempt to get a reference on the content from a tagged object.
     /// This can fail if the object is not tagged.
     pub fn as_tagged(&self) -> Result<(Class, Tag, &BerObject<'a>), BerError> {
@@ -293,6 +676,51 @@ impl<'a> BerObject<'a> {
         self.content.as_slice()
     }
 
+    /// Get the original encoding (header and content) of the object, or `None` if the object was
+    /// not read by a parser.
+    ///
+    /// This can be used to hash or verify a signed sub-object (for ex. the `tbsCertificate` of a
+    /// certificate) without encoding it again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::der::parse_der;
+    ///
+    /// let bytes = &[0x30, 0x07, 0x30, 0x03, 0x02, 0x01, 0x01, 0x05, 0x00];
+    /// let (_, obj) = parse_der(bytes).expect("parsing failed");
+    /// let tbs = obj[0].raw_bytes().expect("no span");
+    /// assert_eq!(tbs, &bytes[2..7]);
+    /// ```
+    #[inline]
+    pub fn raw_bytes(&self) -> Option<&'a [u8]> {
+        self.span.map(|span| span.raw())
+    }
+
+    /// Get the length of the encoded header (identifier and length octets), or `None` if the
+    /// object was not read by a parser.
+    ///
+    /// The content of the object starts at this offset in [`BerObject::raw_bytes`], so it can be
+    /// sliced (for ex. to hash the content of a signature) without reading the header again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // OCTET STRING, with a long form length
+    /// let bytes = &[0x04, 0x81, 0x02, 0x61, 0x62];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// assert_eq!(obj.header_len(), Some(3));
+    /// let span = obj.span.expect("no span");
+    /// assert_eq!((span.identifier_len(), span.length_len()), (1, 2));
+    /// assert_eq!(span.content_range_in(bytes), Some(3..5));
+    /// ```
+    #[inline]
+    pub fn header_len(&self) -> Option<usize> {
+        self.span.map(|span| span.header_len())
+    }
+
     /// Attempt to get the content from a DER object, as a str.
     /// This can fail if the object does not contain a string type.
     ///
@@ -302,6 +730,55 @@ impl<'a> BerObject<'a> {
         self.content.as_str()
     }
 
+    /// Get the original text of a UTCTime or GeneralizedTime object
+    ///
+    /// The parsed value drops some details of the encoding (for ex. the precision of the
+    /// fractional seconds), so the text can be used to re-encode the time exactly, or to parse it
+    /// with different rules.
+    ///
+    /// The text is read from the span of the object: this raises a `BerValueError` error if the
+    /// object was not read by a parser (or was modified), and a `BerTypeError` error if the object
+    /// is not a time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// let bytes = b"\x18\x1320230102030405.100Z";
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// assert_eq!(obj.as_time_str(), Ok("20230102030405.100Z"));
+    /// ```
+    pub fn as_time_str(&self) -> Result<&'a str, BerError> {
+        match self.content {
+            BerObjectContent::UTCTime(_) | BerObjectContent::GeneralizedTime(_) => (),
+            _ => return Err(BerError::BerTypeError),
+        }
+        let span = self.span.ok_or(BerError::BerValueError)?;
+        core::str::from_utf8(span.content()).or(Err(BerError::BerValueError))
+    }
+
+    /// Attempt to read an IP address (v4 or v6) from an OCTET STRING.
+    /// This can fail if the object is not an OCTET STRING, or if the content is not 4 or 16
+    /// bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use der_parser::ber::{BerObject,BerObjectContent};
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// let obj = BerObject::from_obj(BerObjectContent::OctetString(&[192, 168, 0, 1]));
+    /// assert_eq!(
+    ///     obj.as_ipaddr(),
+    ///     Ok(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)))
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn as_ipaddr(&self) -> Result<std::net::IpAddr, BerError> {
+        self.content.as_ipaddr()
+    }
+
     /// Get the BER object header's class.
     #[inline]
     pub const fn class(&self) -> Class {
@@ -602,6 +1079,9 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::BitString(_,BitStringObject{data:s}) |
             BerObjectContent::OctetString(s) |
             BerObjectContent::UniversalString(s) => Ok(s),
+            BerObjectContent::CharacterString(ref c) => Ok(c.string_value),
+            BerObjectContent::TagOverflow(_, data) |
+            BerObjectContent::Primitive(_, data) => Ok(data),
             BerObjectContent::Unknown(ref any) => Ok(any.data),
             _ => Err(BerError::BerTypeError),
         }
@@ -621,6 +1101,33 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::VideotexString(s) |
             BerObjectContent::UTF8String(s) |
             BerObjectContent::IA5String(s) => Ok(s),
+            // strings that were not decoded when parsing (see `ParseOptions::lazy_strings`)
+            BerObjectContent::Unknown(ref any) if any.class() == Class::Universal => {
+                try_str_from_raw(any.tag(), any.data)
+            }
+            // primitives that were not decoded (see `ParseOptions::decode_primitives`)
+            BerObjectContent::Primitive(tag, data) => try_str_from_raw(tag, data),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn as_ipaddr(&self) -> Result<std::net::IpAddr, BerError> {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+        match *self {
+            BerObjectContent::OctetString(s) => match s.len() {
+                4 => {
+                    let mut b = [0u8; 4];
+                    b.copy_from_slice(s);
+                    Ok(IpAddr::V4(Ipv4Addr::from(b)))
+                }
+                16 => {
+                    let mut b = [0u8; 16];
+                    b.copy_from_slice(s);
+                    Ok(IpAddr::V6(Ipv6Addr::from(b)))
+                }
+                _ => Err(BerError::InvalidLength),
+            },
             _ => Err(BerError::BerTypeError),
         }
     }
@@ -646,6 +1153,7 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::VideotexString(_)    => Tag::VideotexString,
             BerObjectContent::BmpString(_)         => Tag::BmpString,
             BerObjectContent::UniversalString(_)   => Tag::UniversalString,
+            BerObjectContent::CharacterString(_)   => Tag::CharacterString,
             BerObjectContent::Sequence(_)          => Tag::Sequence,
             BerObjectContent::Set(_)               => Tag::Set,
             BerObjectContent::UTCTime(_)           => Tag::UtcTime,
@@ -654,6 +1162,8 @@ impl<'a> BerObjectContent<'a> {
             BerObjectContent::GraphicString(_)     => Tag::GraphicString,
             BerObjectContent::GeneralString(_)     => Tag::GeneralString,
             BerObjectContent::Tagged(_,x,_) => *x,
+            BerObjectContent::TagOverflow(_, _)    => TAG_OVERFLOW,
+            BerObjectContent::Primitive(tag, _)    => *tag,
             BerObjectContent::Unknown(any) => any.tag(),
             BerObjectContent::Optional(Some(obj))  => obj.content.tag(),
             BerObjectContent::Optional(None)       => Tag(0x00), // XXX invalid !
@@ -718,20 +1228,27 @@ impl<'a> BerObject<'a> {
     }
 }
 
+/// Build a UUID from a 16-bytes OCTET STRING
+#[cfg(feature = "uuid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for uuid::Uuid {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        match obj.content {
+            BerObjectContent::OctetString(s) => {
+                uuid::Uuid::from_slice(s).map_err(|_| BerError::InvalidLength)
+            }
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+}
+
 impl<'a> TryFrom<Any<'a>> for BerObject<'a> {
     type Error = asn1_rs::Error;
 
     fn try_from(any: Any<'a>) -> Result<Self, Self::Error> {
-        let (header, data) = (any.header, any.data);
-        let (_, content) = ber_read_element_content_as(
-            data,
-            header.tag(),
-            header.length(),
-            header.constructed(),
-            MAX_RECURSION,
-        )?;
-        let obj = BerObject::from_header_and_content(header, content);
-        Ok(obj)
+        BerObject::try_from(&any)
     }
 }
 
@@ -740,10 +1257,11 @@ impl<'a, 'b> TryFrom<&'b Any<'a>> for BerObject<'a> {
 
     fn try_from(any: &'b Any<'a>) -> Result<Self, Self::Error> {
         let (header, data) = (any.header.clone(), any.data);
+        // the data of an `Any` with indefinite length does not include the end-of-content octets
         let (_, content) = ber_read_element_content_as(
             data,
             header.tag(),
-            header.length(),
+            Length::Definite(data.len()),
             header.constructed(),
             MAX_RECURSION,
         )?;
@@ -752,6 +1270,47 @@ impl<'a, 'b> TryFrom<&'b Any<'a>> for BerObject<'a> {
     }
 }
 
+/// Convert an object to an `Any`, borrowing the raw content
+///
+/// The conversion is lossless for parsed objects: the header and raw content (including the
+/// encoding of constructed children) are taken from the location of the object in the input.
+/// Objects built from values can only be converted if the content is stored as bytes
+/// (integers, strings, unknown objects etc.), otherwise `BerError::Unsupported` is returned
+/// (use the `serialize` feature to encode them).
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for Any<'a> {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        if let BerObjectContent::Unknown(any) = &obj.content {
+            return Ok(any.clone());
+        }
+        if let Some(span) = obj.span {
+            return Ok(Any::new(obj.header.clone(), span.content()));
+        }
+        let data = match obj.content {
+            BerObjectContent::EndOfContent | BerObjectContent::Null => &[],
+            BerObjectContent::Integer(s)
+            | BerObjectContent::OctetString(s)
+            | BerObjectContent::UniversalString(s) => s,
+            _ => obj
+                .content
+                .as_str()
+                .map_err(|_| BerError::Unsupported)?
+                .as_bytes(),
+        };
+        let header = obj.header.clone().with_length(Length::Definite(data.len()));
+        Ok(Any::new(header, data))
+    }
+}
+
+impl<'a> TryFrom<BerObject<'a>> for Any<'a> {
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        Any::try_from(&obj)
+    }
+}
+
 // This is a consuming iterator
 impl<'a> IntoIterator for BerObject<'a> {
     type Item = BerObject<'a>;
@@ -829,6 +1388,79 @@ impl<'a> BerObject<'a> {
     }
 }
 
+impl<'a> BerObject<'a> {
+    /// Get the children of a constructed object, as a slice
+    ///
+    /// The children of `Sequence` and `Set` objects are their elements, and `Tagged` and
+    /// `Optional` objects have a single child (the inner object). Other objects have no children,
+    /// including constructed `Unknown` objects, whose content is not parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber, BerObject};
+    ///
+    /// fn count(obj: &BerObject) -> usize {
+    ///     1 + obj.children().iter().map(count).sum::<usize>()
+    /// }
+    ///
+    /// // SEQUENCE { INTEGER 1, SET { NULL } }
+    /// let bytes = &[0x30, 0x07, 0x02, 0x01, 0x01, 0x31, 0x02, 0x05, 0x00];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// assert_eq!(count(&obj), 4);
+    /// ```
+    pub fn children(&self) -> &[BerObject<'a>] {
+        match self.content {
+            BerObjectContent::Sequence(ref v) | BerObjectContent::Set(ref v) => v,
+            BerObjectContent::Tagged(_, _, ref obj) | BerObjectContent::Optional(Some(ref obj)) => {
+                slice::from_ref(obj.as_ref())
+            }
+            _ => &[],
+        }
+    }
+
+    /// Get a reference on a child of a constructed object, or `None`
+    ///
+    /// Children of `Sequence` and `Set` objects are indexed by position. `Tagged` and
+    /// `Optional` objects have a single child (the inner object), with index 0.
+    pub fn get(&self, idx: usize) -> Option<&BerObject<'a>> {
+        self.children().get(idx)
+    }
+
+    /// Get a reference on a nested object, following a list of child indexes
+    ///
+    /// See [`BerObject::get`] for the indexing of children. An empty path returns the object
+    /// itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // SEQUENCE { INTEGER 1, SEQUENCE { NULL, INTEGER 2 } }
+    /// let bytes = &[0x30, 0x0a, 0x02, 0x01, 0x01, 0x30, 0x05, 0x05, 0x00, 0x02, 0x01, 0x02];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let int = obj.get_path(&[1, 1]).expect("no such object");
+    /// assert_eq!(int.as_u32(), Ok(2));
+    /// assert!(obj.get_path(&[0, 0]).is_none());
+    /// ```
+    pub fn get_path(&self, path: &[usize]) -> Option<&BerObject<'a>> {
+        path.iter().try_fold(self, |obj, &idx| obj.get(idx))
+    }
+
+    /// Get a reference on the first child with the context-specific tag `tag`, or `None`
+    ///
+    /// Only direct children of constructed objects (see [`BerObject::get`]) are searched. This
+    /// is useful to access optional tagged fields, for ex. the extensions (`[3]`) of a
+    /// certificate.
+    pub fn get_tagged<T: Into<Tag>>(&self, tag: T) -> Option<&BerObject<'a>> {
+        let tag = tag.into();
+        (0..)
+            .map_while(|idx| self.get(idx))
+            .find(|obj| obj.is_contextspecific() && obj.tag() == tag)
+    }
+}
+
 impl<'a> Index<usize> for BerObject<'a> {
     type Output = BerObject<'a>;
 
@@ -847,7 +1479,7 @@ impl<'a> Index<usize> for BerObject<'a> {
 }
 
 /// BitString wrapper
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BitStringObject<'a> {
     pub data: &'a [u8],
 }
@@ -863,6 +1495,26 @@ impl<'a> BitStringObject<'a> {
         (self.data[byte_pos] & (1 << b)) != 0
     }
 
+    /// Return the last octet if its `unused_bits` unused bits are not all zero
+    ///
+    /// The unused bits must be zero in DER (X.690 11.2.1). `None` is returned if the bits are zero,
+    /// or if `unused_bits` is invalid (greater than 7) or the data is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::BitStringObject;
+    ///
+    /// let bits = BitStringObject { data: &[0x0f, 0xf1] };
+    /// assert_eq!(bits.nonzero_unused_bits(1), Some(0xf1));
+    /// assert_eq!(bits.nonzero_unused_bits(0), None);
+    /// ```
+    pub fn nonzero_unused_bits(&self, unused_bits: u8) -> Option<u8> {
+        let last = *self.data.last()?;
+        let mask = 1u8.checked_shl(unused_bits.into())?.wrapping_sub(1);
+        (unused_bits < 8 && last & mask != 0).then_some(last)
+    }
+
     /// Constructs a shared `&BitSlice` reference over the object data.
     #[cfg(feature = "bitvec")]
     pub fn as_bitslice(&self) -> Option<&BitSlice<Msb0, u8>> {
@@ -876,9 +1528,109 @@ impl<'a> AsRef<[u8]> for BitStringObject<'a> {
     }
 }
 
+/// Identification of the character abstract syntax of an unrestricted CHARACTER STRING
+///
+/// See X.680 section 44.5. Integer values are stored as raw bytes, like `BerObjectContent::Integer`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CharacterStringIdentification<'a> {
+    /// Abstract and transfer syntaxes
+    Syntaxes {
+        abstract_syntax: Oid<'a>,
+        transfer_syntax: Oid<'a>,
+    },
+    /// Single object identifier for the abstract and transfer syntaxes
+    Syntax(Oid<'a>),
+    /// Presentation context identifier (raw integer bytes)
+    PresentationContextId(&'a [u8]),
+    /// Presentation context identifier (raw integer bytes) and transfer syntax
+    ContextNegotiation {
+        presentation_context_id: &'a [u8],
+        transfer_syntax: Oid<'a>,
+    },
+    /// Transfer syntax only (abstract syntax is known by the application)
+    TransferSyntax(Oid<'a>),
+    /// Syntax is fixed by the application
+    Fixed,
+}
+
+/// Unrestricted CHARACTER STRING value (X.680 section 44)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CharacterStringObject<'a> {
+    pub identification: CharacterStringIdentification<'a>,
+    /// Raw string value, to be decoded according to `identification`
+    pub string_value: &'a [u8],
+}
+
+/// Hash the fields of a header used by `PartialEq` (length and raw tag are not always compared)
+fn hash_header<H: Hasher>(header: &Header, state: &mut H) {
+    (header.class() as u8).hash(state);
+    header.tag().0.hash(state);
+    header.constructed().hash(state);
+}
+
+fn hash_datetime<H: Hasher>(t: &ASN1DateTime, state: &mut H) {
+    (t.year, t.month, t.day, t.hour, t.minute, t.second).hash(state);
+    t.millisecond.hash(state);
+    match t.tz {
+        ASN1TimeZone::Undefined => 0u8.hash(state),
+        ASN1TimeZone::Z => 1u8.hash(state),
+        ASN1TimeZone::Offset(h, m) => (2u8, h, m).hash(state),
+    }
+}
+
+impl<'a> Hash for BerObject<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_header(&self.header, state);
+        self.content.hash(state);
+    }
+}
+
+impl<'a> Hash for BerObjectContent<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            BerObjectContent::EndOfContent | BerObjectContent::Null => (),
+            BerObjectContent::Boolean(b) => b.hash(state),
+            BerObjectContent::Integer(s)
+            | BerObjectContent::OctetString(s)
+            | BerObjectContent::UniversalString(s) => s.hash(state),
+            BerObjectContent::BitString(u, b) => (u, b).hash(state),
+            BerObjectContent::Enum(e) => e.hash(state),
+            BerObjectContent::OID(oid) | BerObjectContent::RelativeOID(oid) => oid.hash(state),
+            BerObjectContent::NumericString(s)
+            | BerObjectContent::VisibleString(s)
+            | BerObjectContent::PrintableString(s)
+            | BerObjectContent::IA5String(s)
+            | BerObjectContent::UTF8String(s)
+            | BerObjectContent::T61String(s)
+            | BerObjectContent::VideotexString(s)
+            | BerObjectContent::BmpString(s)
+            | BerObjectContent::ObjectDescriptor(s)
+            | BerObjectContent::GraphicString(s)
+            | BerObjectContent::GeneralString(s) => s.hash(state),
+            BerObjectContent::CharacterString(c) => c.hash(state),
+            BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => v.hash(state),
+            BerObjectContent::UTCTime(t) | BerObjectContent::GeneralizedTime(t) => {
+                hash_datetime(t, state)
+            }
+            BerObjectContent::Optional(o) => o.hash(state),
+            BerObjectContent::Tagged(class, tag, obj) => {
+                (*class as u8, tag.0).hash(state);
+                obj.hash(state);
+            }
+            BerObjectContent::TagOverflow(identifier, data) => (identifier, data).hash(state),
+            BerObjectContent::Primitive(tag, data) => (tag.0, data).hash(state),
+            BerObjectContent::Unknown(any) => {
+                hash_header(&any.header, state);
+                any.data.hash(state);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use asn1_rs::{Any, FromDer};
+    use asn1_rs::{Any, FromBer, FromDer};
     use core::convert::TryFrom;
 
     use crate::ber::*;
@@ -978,6 +1730,239 @@ mod tests {
         assert_equal(obj, b);
     }
 
+    #[test]
+    fn test_ber_get_path() {
+        // SEQUENCE { INTEGER 1, [0] { INTEGER 2 }, [3] { NULL } }
+        let bytes = &hex_literal::hex!("30 0c 02 01 01 a0 03 02 01 02 a3 02 05 00");
+        let obj = BerObject::from_seq(vec![
+            BerObject::from_int_slice(&bytes[4..5]),
+            BerObject::from_obj(BerObjectContent::Tagged(
+                Class::ContextSpecific,
+                Tag(0),
+                Box::new(BerObject::from_int_slice(&bytes[9..10])),
+            )),
+        ]);
+        assert_eq!(obj.get_path(&[]), Some(&obj));
+        assert_eq!(obj.get_path(&[0]).map(|o| o.as_u32()), Some(Ok(1)));
+        assert_eq!(obj.get_path(&[1, 0]).map(|o| o.as_u32()), Some(Ok(2)));
+        assert_eq!(obj.get_path(&[2]), None);
+        assert_eq!(obj.get_path(&[0, 0]), None);
+        // the child of a tagged object is the inner object
+        assert_eq!(obj[1].children().len(), 1);
+        assert_eq!(obj[1].children()[0].as_u32(), Ok(2));
+
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let ext = obj.get_tagged(3).expect("missing tagged object");
+        assert_eq!(ext.tag(), Tag(3));
+        assert!(obj.get_tagged(1).is_none());
+        // constructed unknown objects are not parsed
+        assert_eq!(obj.children().len(), 3);
+        assert!(ext.children().is_empty());
+    }
+
+    #[test]
+    fn test_ber_with_content() {
+        // SEQUENCE { OCTET STRING "secret", INTEGER 1 }
+        let bytes = &hex_literal::hex!("30 0b 04 06 73 65 63 72 65 74 02 01 01");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let header = obj.header.clone();
+        let redacted = obj.map_content(|c| match c {
+            BerObjectContent::Sequence(v) => BerObjectContent::Sequence(
+                v.into_iter()
+                    .map(|o| match o.content {
+                        BerObjectContent::OctetString(_) => {
+                            o.with_content(BerObjectContent::OctetString(b""))
+                        }
+                        _ => o,
+                    })
+                    .collect(),
+            ),
+            c => c,
+        });
+        assert_eq!(redacted.header, header);
+        assert!(redacted.span.is_none());
+        assert_eq!(redacted[0].as_slice(), Ok(&b""[..]));
+        assert_eq!(redacted[0].header.length(), Length::Definite(6));
+        assert!(redacted[0].span.is_none());
+        // other objects are unchanged
+        assert!(redacted[1].span.is_some());
+    }
+
+    #[test]
+    fn test_ber_span() {
+        // SEQUENCE { INTEGER 1, SET { INTEGER 2 } }
+        let bytes = &hex_literal::hex!("30 81 08 02 01 01 31 03 02 01 02");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let span = obj.span.expect("no span");
+        assert_eq!(span.range_in(bytes), Some(0..11));
+        assert_eq!((span.header_len(), span.content_len()), (3, 8));
+        assert_eq!((span.identifier_len(), span.length_len()), (1, 2));
+        assert_eq!(span.content_range_in(bytes), Some(3..11));
+        assert_eq!(span.length_octets(), &bytes[1..3]);
+        assert_eq!(span.length_form(), LengthForm::Long(1));
+        // 0x81 0x08: a short form length would be enough
+        assert!(!span.has_minimal_length());
+        assert!(obj[0].span.map_or(false, |s| s.has_minimal_length()));
+        assert_eq!(span.content(), &bytes[3..]);
+        let offsets = obj
+            .iter_depth_first()
+            .map(|(_, o)| o.span.and_then(|s| s.offset_in(bytes)))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![Some(0), Some(3), Some(6), Some(8)]);
+        // a built object has no span, and compares equal to the parsed object
+        assert_eq!(obj.get(0), Some(&BerObject::from_int_slice(b"\x01")));
+        assert!(BerObject::from_int_slice(b"\x01").span.is_none());
+
+        // indefinite length: end-of-content octets are part of the object, not of the content
+        let bytes = &hex_literal::hex!("30 80 02 01 01 00 00");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let span = obj.span.expect("no span");
+        assert_eq!(span.raw(), bytes);
+        assert_eq!(span.content(), &bytes[2..5]);
+        assert_eq!(span.content_range_in(bytes), Some(2..5));
+        assert_eq!(obj.header_len(), Some(2));
+        assert_eq!(span.length_form(), LengthForm::Indefinite);
+        assert!(!span.has_minimal_length());
+        assert_eq!(
+            obj[0].span.map(|s| s.length_form()),
+            Some(LengthForm::Short)
+        );
+        let (_, (_, header)) = ber_read_element_header_with_span(bytes).expect("invalid header");
+        assert_eq!(header.raw(), span.header());
+        assert_eq!(header.length_form(), LengthForm::Indefinite);
+        let (_, (any, raw)) = parse_ber_any_with_span(bytes).expect("parsing failed");
+        assert_eq!((raw, any.data), (span, span.content()));
+
+        // high tag number form: [APPLICATION 200] IMPLICIT, with a long form length
+        let bytes = &hex_literal::hex!("5f 81 48 81 01 00");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let span = obj.span.expect("no span");
+        assert_eq!((span.identifier_len(), span.length_len()), (3, 2));
+        assert_eq!(span.length_octets(), &bytes[3..5]);
+        assert_eq!(span.identifier(), &bytes[..3]);
+        // 0x81 0x01: a short form length would be enough
+        assert!(!span.has_minimal_length());
+        assert_eq!(span.content_range_in(bytes), Some(5..6));
+        assert_eq!(span.content_range_in(&bytes[1..]), None);
+        assert_eq!(BerObject::from_int_slice(b"\x01").header_len(), None);
+
+        // combinators
+        let bytes = &hex_literal::hex!("a1 05 30 03 02 01 01");
+        let (_, obj) = parse_ber_tagged_explicit(1, |i| {
+            parse_ber_sequence_defined(|i| parse_ber_integer(i).map(|(rem, o)| (rem, vec![o])))(i)
+        })(bytes)
+        .expect("parsing failed");
+        assert_eq!(obj.span.map(|s| s.raw()), Some(&bytes[..]));
+        let inner = obj.get_path(&[0, 0]).expect("no inner object");
+        assert_eq!(inner.span.and_then(|s| s.offset_in(bytes)), Some(4));
+
+        // DER
+        let bytes = &hex_literal::hex!("31 03 01 01 ff");
+        let (_, obj) = crate::der::parse_der(bytes).expect("parsing failed");
+        assert_eq!(obj.span.map(|s| s.header()), Some(&bytes[..2]));
+        let inner = obj.get(0).and_then(|o| o.span).expect("no span");
+        assert_eq!(inner.range_in(bytes), Some(2..5));
+    }
+
+    #[test]
+    fn test_ber_to_any() {
+        // SEQUENCE (indefinite) { INTEGER 1, OCTET STRING (constructed) { "ab" } }
+        let bytes = &hex_literal::hex!("30 80 02 01 01 24 04 04 02 61 62 00 00");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        let any = Any::try_from(&obj).expect("conversion failed");
+        assert_eq!(any.header, obj.header);
+        assert_eq!(any.data, &bytes[2..11]);
+        let (_, expected) = Any::from_ber(bytes).expect("parsing failed");
+        assert_eq!(any, expected);
+        let back = BerObject::try_from(any).expect("conversion failed");
+        assert_eq!(back.content, obj.content);
+
+        // built objects
+        let obj = BerObject::from_obj(BerObjectContent::OctetString(b"abc"));
+        let any = Any::try_from(obj).expect("conversion failed");
+        assert_eq!(any.header.length(), Length::Definite(3));
+        assert_eq!(any.data, b"abc");
+        let obj = BerObject::from_obj(BerObjectContent::UTF8String("é"));
+        assert_eq!(Any::try_from(&obj).map(|a| a.data), Ok(&b"\xc3\xa9"[..]));
+        let obj = BerObject::from_seq(vec![BerObject::from_obj(BerObjectContent::Null)]);
+        assert_eq!(
+            Any::try_from(&obj),
+            Err(crate::error::BerError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_ber_raw_bytes() {
+        // SEQUENCE (indefinite) { SEQUENCE (indefinite) { INTEGER 1 }, NULL }
+        let bytes = &hex_literal::hex!("30 80 30 80 02 01 01 00 00 05 00 00 00");
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        assert_eq!(obj.raw_bytes(), Some(&bytes[..]));
+        assert_eq!(obj[0].raw_bytes(), Some(&bytes[2..9]));
+        assert_eq!(obj[1].raw_bytes(), Some(&bytes[9..11]));
+        assert_eq!(BerObject::from_seq(vec![]).raw_bytes(), None);
+    }
+
+    #[test]
+    fn test_ber_as_time_str() {
+        use crate::error::BerError;
+
+        // SEQUENCE { UTCTime "991231235959+0100", NULL }
+        let bytes = b"\x30\x15\x17\x11991231235959+0100\x05\x00";
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        assert_eq!(obj[0].as_time_str(), Ok("991231235959+0100"));
+        assert_eq!(obj[1].as_time_str(), Err(BerError::BerTypeError));
+        let built = BerObject::from_obj(obj[0].content.clone());
+        assert_eq!(built.as_time_str(), Err(BerError::BerValueError));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ber_hash() {
+        use std::collections::HashSet;
+
+        let (_, a) = parse_ber(&hex_literal::hex!("30 06 02 01 01 06 01 2a")).unwrap();
+        // same object, with a different length encoding
+        let (_, b) = parse_ber(&hex_literal::hex!("30 81 06 02 01 01 06 01 2a")).unwrap();
+        let (_, c) = parse_ber(&hex_literal::hex!("30 06 02 01 02 06 01 2a")).unwrap();
+        assert_eq!(a, b);
+        let set: HashSet<_> = vec![a, b, c].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_der_as_ipaddr() {
+        use crate::error::BerError;
+        use std::net::{IpAddr, Ipv6Addr};
+
+        let bytes = &hex_literal::hex!("20 01 0d b8 00 00 00 00 00 00 00 00 00 00 00 01");
+        let obj = BerObject::from_obj(BerObjectContent::OctetString(bytes));
+        assert_eq!(
+            obj.as_ipaddr(),
+            Ok(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)))
+        );
+        let obj = BerObject::from_obj(BerObjectContent::OctetString(&bytes[..5]));
+        assert_eq!(obj.as_ipaddr(), Err(BerError::InvalidLength));
+        let obj = BerObject::from_int_slice(b"\x01\x02\x03\x04");
+        assert_eq!(obj.as_ipaddr(), Err(BerError::BerTypeError));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_der_to_uuid() {
+        use crate::error::BerError;
+        let bytes = &hex_literal::hex!("67 e5 50 44 10 b1 42 6f 92 47 bb 68 0e 5f e0 c8");
+        let obj = BerObject::from_obj(BerObjectContent::OctetString(bytes));
+        let uuid = uuid::Uuid::try_from(&obj).expect("conversion failed");
+        assert_eq!(uuid.as_bytes(), bytes);
+        // wrong length
+        let obj = BerObject::from_obj(BerObjectContent::OctetString(&bytes[..15]));
+        assert_eq!(uuid::Uuid::try_from(&obj), Err(BerError::InvalidLength));
+        // wrong type
+        let obj = BerObject::from_int_slice(b"\x01");
+        assert_eq!(uuid::Uuid::try_from(&obj), Err(BerError::BerTypeError));
+    }
+
     #[cfg(feature = "bigint")]
     #[test]
     fn test_der_to_bigint() {
@@ -0,0 +1,97 @@
+use crate::ber::*;
+use crate::error::*;
+use nom::combinator::{complete, cut};
+use nom::error::ParseError;
+use nom::{Err, IResult, Parser};
+
+/// Apply `f` to all the content of a constructed object, and collect the results in a fixed-size
+/// vector
+///
+/// If there are more than `N` objects, a [`limit_exceeded`] failure is returned.
+pub(crate) fn collect_bounded<'a, T, F, E, const N: usize>(
+    f: &mut F,
+    mut data: &'a [u8],
+) -> IResult<&'a [u8], heapless::Vec<T, N>, E>
+where
+    F: Parser<&'a [u8], T, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    let mut items = heapless::Vec::new();
+    let mut item = cut(complete(|i| f.parse(i)));
+    while !data.is_empty() {
+        let (rem, obj) = item(data)?;
+        if items.push(obj).is_err() {
+            return Err(Err::Failure(E::from(limit_exceeded())));
+        }
+        data = rem;
+    }
+    Ok((data, items))
+}
+
+/// Parse a SEQUENCE OF object, returning a vector with a fixed capacity `N`
+///
+/// This is the same as [`parse_ber_sequence_of_v`], except that the objects are stored in a
+/// `heapless::Vec`, so parsing the sequence does not allocate. This can be used on targets
+/// without a heap, if the subparser does not allocate either (for ex. when parsing integers).
+///
+/// If the sequence contains more than `N` objects, a [`limit_exceeded`] failure is returned.
+///
+/// ```rust
+/// # use der_parser::ber::{parse_ber_sequence_of_bounded, parse_ber_u32};
+/// # use der_parser::error::BerResult;
+/// #
+/// /// Read a SEQUENCE OF INTEGER, with at most 4 elements
+/// fn parser(i: &[u8]) -> BerResult<'_, heapless::Vec<u32, 4>> {
+///     parse_ber_sequence_of_bounded(parse_ber_u32)(i)
+/// }
+///
+/// let bytes = [0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02];
+/// let (rem, v) = parser(&bytes).expect("parsing failed");
+/// assert_eq!(v, [1, 2]);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+pub fn parse_ber_sequence_of_bounded<'a, T, F, E, const N: usize>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], heapless::Vec<T, N>, E>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    parse_ber_sequence_defined_g(move |data, _| collect_bounded(&mut f, data))
+}
+
+/// Parse a SET OF object, returning a vector with a fixed capacity `N`
+///
+/// This is the same as [`parse_ber_set_of_v`], except that the objects are stored in a
+/// `heapless::Vec` (see [`parse_ber_sequence_of_bounded`]).
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+pub fn parse_ber_set_of_bounded<'a, T, F, E, const N: usize>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], heapless::Vec<T, N>, E>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    parse_ber_set_defined_g(move |data, _| collect_bounded(&mut f, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_sequence_of_bounded() {
+        let bytes = hex!("30 09 02 01 01 02 01 02 02 01 03");
+        let r = parse_ber_sequence_of_bounded::<_, _, BerError, 3>(parse_ber_u32)(&bytes);
+        let (_, v) = r.expect("parsing failed");
+        assert_eq!(v, [1, 2, 3]);
+
+        let r = parse_ber_sequence_of_bounded::<_, _, BerError, 2>(parse_ber_u32)(&bytes);
+        assert_eq!(r, Err(Err::Failure(limit_exceeded())));
+
+        let bytes = hex!("31 05 02 01 01 05 00");
+        let r = parse_ber_set_of_bounded::<_, _, BerError, 4>(parse_ber_u32)(&bytes);
+        assert!(matches!(r, Err(Err::Failure(_))));
+    }
+}
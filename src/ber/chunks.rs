@@ -0,0 +1,174 @@
+use crate::ber::scan::Scan;
+use crate::ber::{ber_get_object_content, Class, Header, Tag, MAX_RECURSION};
+use crate::error::{BerError, BerResult};
+use asn1_rs::FromBer;
+use core::iter::FusedIterator;
+use nom::Err;
+
+/// Iterator over the content of an OCTET STRING or BIT STRING, in chunks
+///
+/// The chunks are slices of the input: the content is never copied or reassembled, which allows
+/// processing very large values (for ex. firmware images) with a bounded amount of memory.
+///
+/// Constructed (segmented) strings are supported: the chunks of each segment are returned in
+/// order. For BIT STRING objects, the initial octet of each segment (the number of unused bits)
+/// is not part of the chunks, and can be read using [`ContentChunks::unused_bits`].
+///
+/// Each chunk has at most `chunk_size` bytes, and is never empty. If a segment cannot be read, the
+/// error is returned and the iteration stops.
+#[derive(Debug)]
+pub struct ContentChunks<'a> {
+    tag: Tag,
+    current: &'a [u8],
+    segments: Option<Scan<'a>>,
+    chunk_size: usize,
+    unused_bits: u8,
+}
+
+/// Read the header of an OCTET STRING or BIT STRING, and return an iterator over its content
+///
+/// The content is returned in chunks of at most `chunk_size` bytes (see [`ContentChunks`]). The
+/// object length is checked, but the content (including segments of constructed strings) is only
+/// read when iterating.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::ber_content_chunks;
+///
+/// // OCTET STRING (constructed) { OCTET STRING "abc", OCTET STRING "de" }
+/// let bytes = &[0x24, 0x09, 0x04, 0x03, 0x61, 0x62, 0x63, 0x04, 0x02, 0x64, 0x65];
+/// let (rem, chunks) = ber_content_chunks(bytes, 2).expect("parsing failed");
+/// assert!(rem.is_empty());
+/// let chunks = chunks.collect::<Result<Vec<_>, _>>().expect("invalid segment");
+/// assert_eq!(chunks, [&b"ab"[..], b"c", b"de"]);
+/// ```
+pub fn ber_content_chunks(i: &[u8], chunk_size: usize) -> BerResult<'_, ContentChunks<'_>> {
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+    let (content, header) = Header::from_ber(i)?;
+    if header.class() != Class::Universal
+        || !(header.tag() == Tag::OctetString || header.tag() == Tag::BitString)
+    {
+        return Err(Err::Error(BerError::BerTypeError));
+    }
+    let (rem, data) = ber_get_object_content(content, &header, MAX_RECURSION)?;
+    let mut chunks = ContentChunks {
+        tag: header.tag(),
+        current: &[],
+        segments: None,
+        chunk_size,
+        unused_bits: 0,
+    };
+    if header.is_constructed() {
+        chunks.segments = Some(Scan::new(data));
+    } else {
+        chunks.set_segment(data).map_err(Err::Error)?;
+    }
+    Ok((rem, chunks))
+}
+
+impl<'a> ContentChunks<'a> {
+    /// Return the number of unused bits of the last BIT STRING segment read
+    ///
+    /// This is always 0 for OCTET STRING objects. For a BIT STRING, the value is the number of
+    /// unused bits in the last octet of the value after the iteration is complete.
+    pub fn unused_bits(&self) -> u8 {
+        self.unused_bits
+    }
+
+    fn set_segment(&mut self, data: &'a [u8]) -> Result<(), BerError> {
+        if self.tag == Tag::BitString {
+            match data.split_first() {
+                Some((&unused_bits, data)) if unused_bits < 8 => {
+                    self.unused_bits = unused_bits;
+                    self.current = data;
+                }
+                _ => return Err(BerError::BerValueError),
+            }
+        } else {
+            self.current = data;
+        }
+        Ok(())
+    }
+
+    fn next_segment(&mut self) -> Option<Result<(), BerError>> {
+        let segments = self.segments.as_mut()?;
+        loop {
+            let tlv = match segments.next()? {
+                Ok(tlv) => tlv,
+                Err(e) => return Some(Err(e)),
+            };
+            // segments must have the same type as the constructed string
+            if tlv.header.class() != Class::Universal || tlv.header.tag() != self.tag {
+                return Some(Err(BerError::UnexpectedTag {
+                    expected: Some(self.tag),
+                    actual: tlv.header.tag(),
+                }));
+            }
+            if tlv.header.is_primitive() {
+                return Some(self.set_segment(tlv.data));
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for ContentChunks<'a> {
+    type Item = Result<&'a [u8], BerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current.is_empty() {
+            if let Err(e) = self.next_segment()? {
+                self.segments = None;
+                return Some(Err(e));
+            }
+        }
+        let len = self.chunk_size.min(self.current.len());
+        let (chunk, rem) = self.current.split_at(len);
+        self.current = rem;
+        Some(Ok(chunk))
+    }
+}
+
+impl<'a> FusedIterator for ContentChunks<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_content_chunks() {
+        // BIT STRING (indefinite) { BIT STRING 'ff ff', BIT STRING (constructed) { 'f0' (4 unused) } }
+        let bytes = hex!("23 80 03 03 00 ff ff 23 04 03 02 04 f0 00 00 05 00");
+        let (rem, mut chunks) = ber_content_chunks(&bytes, 4).expect("parsing failed");
+        assert_eq!(rem, &hex!("05 00"));
+        assert_eq!(chunks.next(), Some(Ok(&hex!("ff ff")[..])));
+        assert_eq!(chunks.next(), Some(Ok(&hex!("f0")[..])));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(chunks.unused_bits(), 4);
+
+        let bytes = [0x04, 0x82, 0x01, 0x00].iter().chain(&[0u8; 256]).copied();
+        let bytes = bytes.collect::<Vec<_>>();
+        let (_, chunks) = ber_content_chunks(&bytes, 100).expect("parsing failed");
+        let lengths = chunks.map(|c| c.map(<[u8]>::len)).collect::<Vec<_>>();
+        assert_eq!(lengths, [Ok(100), Ok(100), Ok(56)]);
+    }
+
+    #[test]
+    fn test_content_chunks_errors() {
+        // not a string
+        let r = ber_content_chunks(&hex!("02 01 01"), 4);
+        assert_eq!(r.unwrap_err(), Err::Error(BerError::BerTypeError));
+        // segment with a different type
+        let bytes = hex!("24 06 04 01 61 0c 01 62");
+        let (_, chunks) = ber_content_chunks(&bytes, 4).expect("parsing failed");
+        let r = chunks.collect::<Vec<_>>();
+        assert_eq!(r.len(), 2);
+        assert!(matches!(r[1], Err(BerError::UnexpectedTag { .. })));
+    }
+}
@@ -0,0 +1,264 @@
+//! Decoders for the content of universal types
+//!
+//! These functions decode the content of a single object, when the header has already been read
+//! (for ex. using [`ber_read_element_header`](crate::ber::ber_read_element_header)), without
+//! running the full object parser.
+//!
+//! The tag of the header is *not* checked (so the decoders can be used for implicitly tagged
+//! objects), but the primitive/constructed bit is. `data` must be the complete content of the
+//! object.
+
+use crate::ber::wrap_any::{try_str_from_raw, try_utctime};
+use crate::ber::{BitStringObject, Class, Header, Length, Tag};
+use crate::error::BerError;
+use crate::oid::Oid;
+use asn1_rs::{ASN1DateTime, Any};
+use rusticata_macros::custom_check;
+
+/// Build an object of the universal type `tag`, using the constructed bit of `hdr`
+fn universal<'a>(tag: Tag, data: &'a [u8], hdr: &Header) -> Any<'a> {
+    let header = Header::new(
+        Class::Universal,
+        hdr.is_constructed(),
+        tag,
+        Length::Definite(data.len()),
+    );
+    Any::new(header, data)
+}
+
+/// Decode the content of a BOOLEAN
+///
+/// As in BER, any non-zero value is `true`.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_content_boolean, ber_read_element_header};
+///
+/// let bytes = &[0x01, 0x01, 0xff];
+/// let (data, hdr) = ber_read_element_header(bytes).expect("invalid header");
+/// assert_eq!(ber_read_content_boolean(data, &hdr), Ok(true));
+/// ```
+pub fn ber_read_content_boolean(data: &[u8], hdr: &Header) -> Result<bool, BerError> {
+    hdr.assert_primitive()?;
+    let b = universal(Tag::Boolean, data, hdr).as_boolean()?;
+    Ok(b.bool())
+}
+
+/// Decode the content of an INTEGER, returning the raw big-endian bytes
+///
+/// The bytes are the two's complement encoding of the value: use the conversion functions of
+/// [`BerObject`](crate::ber::BerObject) (for ex. `as_u64`) to get the value.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_content_integer, ber_read_element_header};
+///
+/// let bytes = &[0x02, 0x03, 0x01, 0x00, 0x01];
+/// let (data, hdr) = ber_read_element_header(bytes).expect("invalid header");
+/// assert_eq!(ber_read_content_integer(data, &hdr), Ok(&[0x01, 0x00, 0x01][..]));
+/// ```
+pub fn ber_read_content_integer<'a>(data: &'a [u8], hdr: &Header) -> Result<&'a [u8], BerError> {
+    hdr.assert_primitive()?;
+    custom_check!(data, data.is_empty(), BerError::InvalidLength)?;
+    Ok(data)
+}
+
+/// Decode the content of an ENUMERATED
+pub fn ber_read_content_enumerated(data: &[u8], hdr: &Header) -> Result<u64, BerError> {
+    hdr.assert_primitive()?;
+    let e = universal(Tag::Enumerated, data, hdr).enumerated()?;
+    Ok(e.0 as u64)
+}
+
+/// Decode the content of an OBJECT IDENTIFIER
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_content_oid, ber_read_element_header};
+/// use der_parser::oid;
+///
+/// // [0] IMPLICIT OBJECT IDENTIFIER
+/// let bytes = &[0x80, 0x03, 0x2a, 0x03, 0x04];
+/// let (data, hdr) = ber_read_element_header(bytes).expect("invalid header");
+/// assert_eq!(ber_read_content_oid(data, &hdr), Ok(oid!(1.2.3.4)));
+/// ```
+pub fn ber_read_content_oid<'a>(data: &'a [u8], hdr: &Header) -> Result<Oid<'a>, BerError> {
+    hdr.assert_primitive()?;
+    universal(Tag::Oid, data, hdr).oid()
+}
+
+/// Decode the content of a RELATIVE-OID
+pub fn ber_read_content_relative_oid<'a>(
+    data: &'a [u8],
+    hdr: &Header,
+) -> Result<Oid<'a>, BerError> {
+    hdr.assert_primitive()?;
+    universal(Tag::RelativeOid, data, hdr).relative_oid()
+}
+
+/// Decode the content of a BIT STRING, returning the number of unused bits and the data
+///
+/// Constructed BIT STRINGs are not supported.
+pub fn ber_read_content_bitstring<'a>(
+    data: &'a [u8],
+    hdr: &Header,
+) -> Result<(u8, BitStringObject<'a>), BerError> {
+    custom_check!(data, hdr.is_constructed(), BerError::Unsupported)?; // XXX valid in BER (8.6.3)
+    match data.split_first() {
+        Some((&ignored_bits, data)) => Ok((ignored_bits, BitStringObject { data })),
+        None => Err(BerError::BerValueError),
+    }
+}
+
+/// Decode the content of a UTCTime
+pub fn ber_read_content_utctime(data: &[u8], hdr: &Header) -> Result<ASN1DateTime, BerError> {
+    try_utctime(universal(Tag::UtcTime, data, hdr))
+}
+
+/// Decode the content of a GeneralizedTime
+pub fn ber_read_content_generalizedtime(
+    data: &[u8],
+    hdr: &Header,
+) -> Result<ASN1DateTime, BerError> {
+    let time = universal(Tag::GeneralizedTime, data, hdr).generalizedtime()?;
+    Ok(time.0)
+}
+
+macro_rules! string_content_decoder {
+    ($(#[$meta:meta])* $name:ident, $tag:expr) => {
+        $(#[$meta])*
+        ///
+        /// The charset is verified. Constructed strings are not supported.
+        pub fn $name<'a>(data: &'a [u8], hdr: &Header) -> Result<&'a str, BerError> {
+            custom_check!(data, hdr.is_constructed(), BerError::Unsupported)?; // XXX valid in BER (8.21)
+            try_str_from_raw($tag, data)
+        }
+    };
+}
+
+string_content_decoder!(
+    /// Decode the content of a BMPString
+    ber_read_content_bmpstring,
+    Tag::BmpString
+);
+string_content_decoder!(
+    /// Decode the content of a GeneralString
+    ber_read_content_generalstring,
+    Tag::GeneralString
+);
+string_content_decoder!(
+    /// Decode the content of a GraphicString
+    ber_read_content_graphicstring,
+    Tag::GraphicString
+);
+string_content_decoder!(
+    /// Decode the content of an IA5String
+    ber_read_content_ia5string,
+    Tag::Ia5String
+);
+string_content_decoder!(
+    /// Decode the content of a NumericString
+    ber_read_content_numericstring,
+    Tag::NumericString
+);
+string_content_decoder!(
+    /// Decode the content of an ObjectDescriptor
+    ber_read_content_objectdescriptor,
+    Tag::ObjectDescriptor
+);
+string_content_decoder!(
+    /// Decode the content of a PrintableString
+    ber_read_content_printablestring,
+    Tag::PrintableString
+);
+string_content_decoder!(
+    /// Decode the content of a TeletexString (T61String)
+    ber_read_content_t61string,
+    Tag::TeletexString
+);
+string_content_decoder!(
+    /// Decode the content of a UTF8String
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use der_parser::ber::{ber_read_content_utf8string, ber_read_element_header};
+    ///
+    /// let bytes = &[0x0c, 0x02, 0x68, 0x69];
+    /// let (data, hdr) = ber_read_element_header(bytes).expect("invalid header");
+    /// assert_eq!(ber_read_content_utf8string(data, &hdr), Ok("hi"));
+    /// ```
+    ber_read_content_utf8string,
+    Tag::Utf8String
+);
+string_content_decoder!(
+    /// Decode the content of a VideotexString
+    ber_read_content_videotexstring,
+    Tag::VideotexString
+);
+string_content_decoder!(
+    /// Decode the content of a VisibleString
+    ber_read_content_visiblestring,
+    Tag::VisibleString
+);
+
+/// Decode the content of a UniversalString, returning the raw UCS-4 data
+///
+/// The charset is not verified (the conversion to UTF-8 would require an allocation).
+pub fn ber_read_content_universalstring<'a>(
+    data: &'a [u8],
+    hdr: &Header,
+) -> Result<&'a [u8], BerError> {
+    custom_check!(data, hdr.is_constructed(), BerError::Unsupported)?; // XXX valid in BER (8.21)
+    custom_check!(data, data.len() % 4 != 0, BerError::InvalidLength)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::ber_read_element_header;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_content_decoders() {
+        // [1] IMPLICIT PrintableString, followed by other data
+        let bytes = hex!("81 03 61 62 63 05 00");
+        let (i, hdr) = ber_read_element_header(&bytes).expect("invalid header");
+        let data = &i[..3];
+        assert_eq!(ber_read_content_printablestring(data, &hdr), Ok("abc"));
+        assert_eq!(
+            ber_read_content_numericstring(data, &hdr),
+            Err(BerError::StringInvalidCharset)
+        );
+        assert_eq!(
+            ber_read_content_integer(&[], &hdr),
+            Err(BerError::InvalidLength)
+        );
+        assert_eq!(
+            ber_read_content_bitstring(&hex!("04 f0"), &hdr).map(|(b, s)| (b, s.data)),
+            Ok((4, &hex!("f0")[..]))
+        );
+        assert_eq!(
+            ber_read_content_bitstring(&[], &hdr).map(|_| ()),
+            Err(BerError::BerValueError)
+        );
+        assert_eq!(ber_read_content_enumerated(&hex!("02"), &hdr), Ok(2));
+        assert_eq!(ber_read_content_boolean(&hex!("00"), &hdr), Ok(false));
+        let time = ber_read_content_utctime(b"991231235959Z", &hdr).expect("invalid time");
+        assert_eq!((time.year, time.second), (99, 59));
+        // constructed header
+        let (_, hdr) = ber_read_element_header(&hex!("a1 00")).expect("invalid header");
+        assert_eq!(
+            ber_read_content_boolean(&hex!("ff"), &hdr),
+            Err(BerError::ConstructUnexpected)
+        );
+        assert_eq!(
+            ber_read_content_utf8string(b"abc", &hdr),
+            Err(BerError::Unsupported)
+        );
+    }
+}
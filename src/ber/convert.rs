@@ -0,0 +1,425 @@
+//! Conversions between BER objects and standard Rust types
+//!
+//! Conversions from objects consume the object, and can be used with `try_into()` and the `?`
+//! operator. Some conversions are also implemented for borrowed objects (`&BerObject`), to read
+//! fields without cloning (see [`BerObject::as_optional_t`]). Conversions to objects build values
+//! with a default header (universal class, tag matching the content).
+
+use crate::ber::{BerObject, BerObjectContent, BerObjectContentOwned, BerObjectOwned};
+use crate::error::BerError;
+use crate::oid::Oid;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use asn1_rs::{Class, Header, Length, Tag};
+use core::convert::TryFrom;
+use core::fmt;
+
+/// Get the content of an object, as bytes (see [`BerObject::as_slice`])
+impl<'a> TryFrom<BerObject<'a>> for Vec<u8> {
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_slice().map(|s| s.to_vec())
+    }
+}
+
+/// Get the content of a string object (see [`BerObject::as_str`])
+impl<'a> TryFrom<BerObject<'a>> for String {
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_str().map(|s| s.to_owned())
+    }
+}
+
+impl<'a> TryFrom<BerObject<'a>> for bool {
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_bool()
+    }
+}
+
+macro_rules! impl_tryfrom_int {
+    ($int:ty, $method:ident) => {
+        impl<'a> TryFrom<BerObject<'a>> for $int {
+            type Error = BerError;
+
+            fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+                <$int>::try_from(&obj)
+            }
+        }
+
+        impl<'a, 'b> TryFrom<&'b BerObject<'a>> for $int {
+            type Error = BerError;
+
+            fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+                let v = obj.$method()?;
+                <$int>::try_from(v).map_err(|_| BerError::IntegerTooLarge)
+            }
+        }
+    };
+}
+
+impl_tryfrom_int!(u8, as_u64);
+impl_tryfrom_int!(u16, as_u64);
+impl_tryfrom_int!(u32, as_u64);
+impl_tryfrom_int!(u64, as_u64);
+impl_tryfrom_int!(i8, as_i64);
+impl_tryfrom_int!(i16, as_i64);
+impl_tryfrom_int!(i32, as_i64);
+impl_tryfrom_int!(i64, as_i64);
+
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for bool {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_bool()
+    }
+}
+
+/// Get the content of an object, as bytes (see [`BerObject::as_slice`])
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for &'a [u8] {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_slice()
+    }
+}
+
+/// Get the content of a string object (see [`BerObject::as_str`])
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for &'a str {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_str()
+    }
+}
+
+/// Get the value of an OBJECT IDENTIFIER or RELATIVE-OID
+impl<'a, 'b> TryFrom<&'b BerObject<'a>> for Oid<'a> {
+    type Error = BerError;
+
+    fn try_from(obj: &'b BerObject<'a>) -> Result<Self, Self::Error> {
+        obj.as_oid_val()
+    }
+}
+
+/// Get the list of objects from a SEQUENCE or SET
+impl<'a> TryFrom<BerObject<'a>> for Vec<BerObject<'a>> {
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        match obj.content {
+            BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => Ok(v),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+}
+
+/// Convert a SEQUENCE of exactly two objects
+impl<'a, T, U> TryFrom<BerObject<'a>> for (T, U)
+where
+    T: TryFrom<BerObject<'a>, Error = BerError>,
+    U: TryFrom<BerObject<'a>, Error = BerError>,
+{
+    type Error = BerError;
+
+    fn try_from(obj: BerObject<'a>) -> Result<Self, Self::Error> {
+        match obj.content {
+            BerObjectContent::Sequence(v) => {
+                let mut it = v.into_iter();
+                match (it.next(), it.next(), it.next()) {
+                    (Some(a), Some(b), None) => Ok((T::try_from(a)?, U::try_from(b)?)),
+                    _ => Err(BerError::BerValueError),
+                }
+            }
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+}
+
+/// Error returned by [`BerObject::extract_sequence`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractError {
+    /// Index of the element which could not be converted
+    ///
+    /// If the number of elements is wrong, this is the index of the first missing (or extra)
+    /// element. This is `None` if the object is not a SEQUENCE.
+    pub index: Option<usize>,
+    /// The conversion error
+    pub error: BerError,
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "element {}: {}", index, self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExtractError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Conversion of the elements of a SEQUENCE to a tuple
+///
+/// This is implemented for tuples of up to 12 elements, where each element implements
+/// `TryFrom<&BerObject>`. See [`BerObject::extract_sequence`].
+pub trait FromSequence<'a, 'b>: Sized {
+    /// Convert `items`, which must have exactly one element for each member of the tuple
+    fn from_sequence(items: &'b [BerObject<'a>]) -> Result<Self, ExtractError>;
+}
+
+macro_rules! impl_from_sequence {
+    ($n:expr; $($idx:tt $t:ident),+) => {
+        impl<'a: 'b, 'b, $($t),+> FromSequence<'a, 'b> for ($($t,)+)
+        where
+            $($t: TryFrom<&'b BerObject<'a>, Error = BerError>),+
+        {
+            fn from_sequence(items: &'b [BerObject<'a>]) -> Result<Self, ExtractError> {
+                if items.len() != $n {
+                    return Err(ExtractError {
+                        index: Some(items.len().min($n)),
+                        error: BerError::BerValueError,
+                    });
+                }
+                Ok(($(
+                    $t::try_from(&items[$idx]).map_err(|error| ExtractError {
+                        index: Some($idx),
+                        error,
+                    })?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_sequence!(1; 0 A);
+impl_from_sequence!(2; 0 A, 1 B);
+impl_from_sequence!(3; 0 A, 1 B, 2 C);
+impl_from_sequence!(4; 0 A, 1 B, 2 C, 3 D);
+impl_from_sequence!(5; 0 A, 1 B, 2 C, 3 D, 4 E);
+impl_from_sequence!(6; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+impl_from_sequence!(7; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+impl_from_sequence!(8; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
+impl_from_sequence!(9; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
+impl_from_sequence!(10; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
+impl_from_sequence!(11; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
+impl_from_sequence!(12; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
+
+impl<'a> BerObject<'a> {
+    /// Convert the elements of a SEQUENCE to a tuple of values
+    ///
+    /// Each element is converted using `TryFrom<&BerObject>`, and the sequence must have exactly
+    /// one element for each member of the tuple (up to 12). The error contains the index of the
+    /// element which could not be converted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // SEQUENCE { INTEGER 1, UTF8String "ab", BOOLEAN TRUE }
+    /// let bytes = &[0x30, 0x0a, 0x02, 0x01, 0x01, 0x0c, 0x02, 0x61, 0x62, 0x01, 0x01, 0xff];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let (version, name, critical) = obj.extract_sequence::<(u32, &str, bool)>()?;
+    /// assert_eq!((version, name, critical), (1, "ab", true));
+    ///
+    /// let err = obj.extract_sequence::<(u32, u32, bool)>().unwrap_err();
+    /// assert_eq!(err.index, Some(1));
+    /// # Ok::<(), der_parser::ber::ExtractError>(())
+    /// ```
+    pub fn extract_sequence<'b, T>(&'b self) -> Result<T, ExtractError>
+    where
+        T: FromSequence<'a, 'b>,
+    {
+        match self.content {
+            BerObjectContent::Sequence(ref v) => T::from_sequence(v),
+            _ => Err(ExtractError {
+                index: None,
+                error: BerError::BerTypeError,
+            }),
+        }
+    }
+}
+
+/// Build a BOOLEAN object
+impl<'a> From<bool> for BerObject<'a> {
+    fn from(b: bool) -> Self {
+        BerObject::from_obj(BerObjectContent::Boolean(b))
+    }
+}
+
+/// Build a UTF8String object
+impl<'a> From<&'a str> for BerObject<'a> {
+    fn from(s: &'a str) -> Self {
+        BerObject::from_obj(BerObjectContent::UTF8String(s))
+    }
+}
+
+/// Build an OCTET STRING object
+impl<'a> From<&'a [u8]> for BerObject<'a> {
+    fn from(s: &'a [u8]) -> Self {
+        BerObject::from_obj(BerObjectContent::OctetString(s))
+    }
+}
+
+/// Build a SEQUENCE object
+impl<'a> From<Vec<BerObject<'a>>> for BerObject<'a> {
+    fn from(v: Vec<BerObject<'a>>) -> Self {
+        BerObject::from_seq(v)
+    }
+}
+
+/// Build an INTEGER object
+///
+/// A `BerObject` only borrows the encoded value of integers, so the integer is converted to an
+/// owned object. Use [`BerObjectOwned::as_ber`] to get a `BerObject`.
+impl From<i64> for BerObjectOwned {
+    fn from(i: i64) -> Self {
+        let bytes = i.to_be_bytes();
+        // remove redundant leading bytes (the minimal encoding is required by X.690 8.3.2)
+        let redundant = bytes
+            .windows(2)
+            .take_while(|w| (w[0] == 0 && w[1] & 0x80 == 0) || (w[0] == 0xff && w[1] & 0x80 != 0))
+            .count();
+        BerObjectOwned {
+            header: Header::new(Class::Universal, false, Tag::Integer, Length::Definite(0)),
+            content: BerObjectContentOwned::Integer(bytes[redundant..].to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use crate::error::BerError;
+    use crate::oid::Oid;
+    use alloc::boxed::Box;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::convert::{TryFrom, TryInto};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_convert_primitives() {
+        let (_, obj) = parse_ber(&hex!("04 03 01 02 03")).expect("parsing failed");
+        let v: Vec<u8> = obj.try_into().expect("conversion failed");
+        assert_eq!(v, [1, 2, 3]);
+
+        let (_, obj) = parse_ber(&hex!("0c 03 61 62 63")).expect("parsing failed");
+        let s: String = obj.try_into().expect("conversion failed");
+        assert_eq!(s, "abc");
+
+        let (_, obj) = parse_ber(&hex!("01 01 ff")).expect("parsing failed");
+        let b: bool = obj.try_into().expect("conversion failed");
+        assert!(b);
+
+        let (_, obj) = parse_ber(&hex!("02 01 ff")).expect("parsing failed");
+        let i: i8 = obj.clone().try_into().expect("conversion failed");
+        assert_eq!(i, -1);
+        let r: Result<u8, _> = obj.try_into();
+        assert_eq!(r, Err(BerError::IntegerNegative));
+
+        let (_, obj) = parse_ber(&hex!("02 02 01 00")).expect("parsing failed");
+        let r: Result<u8, _> = obj.try_into();
+        assert_eq!(r, Err(BerError::IntegerTooLarge));
+    }
+
+    #[test]
+    fn test_convert_borrowed() {
+        // SEQUENCE { INTEGER 1, UTF8String "ab", OID 1.2 }
+        let bytes = hex!("30 0a 02 01 01 0c 02 61 62 06 01 2a");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert_eq!(u8::try_from(&obj[0]), Ok(1));
+        assert_eq!(<&str>::try_from(&obj[1]), Ok("ab"));
+        assert_eq!(Oid::try_from(&obj[2]), Ok(crate::oid!(1.2)));
+        assert_eq!(bool::try_from(&obj[0]), Err(BerError::BerTypeError));
+        // optional fields
+        let absent = BerObject::from_obj(BerObjectContent::Optional(None));
+        assert_eq!(absent.as_optional_t::<u32>(), Ok(None));
+        let present =
+            BerObject::from_obj(BerObjectContent::Optional(Some(Box::new(obj[1].clone()))));
+        assert_eq!(present.as_optional_t::<&str>(), Ok(Some("ab")));
+        assert_eq!(obj[0].as_optional_t::<i32>(), Ok(Some(1)));
+        assert!(present.as_optional_t::<u32>().is_err());
+    }
+
+    #[test]
+    fn test_convert_constructed() {
+        let bytes = hex!("30 06 02 01 01 01 01 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let v: Vec<BerObject> = obj.clone().try_into().expect("conversion failed");
+        assert_eq!(v.len(), 2);
+        let (i, b): (u32, bool) = obj.try_into().expect("conversion failed");
+        assert_eq!(i, 1);
+        assert!(!b);
+
+        let (_, obj) = parse_ber(&hex!("30 03 02 01 01")).expect("parsing failed");
+        let r: Result<(u32, u32), _> = obj.try_into();
+        assert_eq!(r, Err(BerError::BerValueError));
+    }
+
+    #[test]
+    fn test_extract_sequence() {
+        // SEQUENCE { INTEGER 1, OCTET STRING 01 02, OID 1.2 }
+        let bytes = hex!("30 0a 02 01 01 04 02 01 02 06 01 2a");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let (i, s, oid) = obj
+            .extract_sequence::<(i64, &[u8], Oid)>()
+            .expect("conversion failed");
+        assert_eq!((i, s, oid), (1, &[1, 2][..], crate::oid!(1.2)));
+        assert_eq!(
+            obj.extract_sequence::<(i64, &str, Oid)>(),
+            Err(ExtractError {
+                index: Some(1),
+                error: BerError::BerTypeError
+            })
+        );
+        // wrong number of elements
+        let r = obj.extract_sequence::<(i64, &[u8])>();
+        assert_eq!(r.map_err(|e| e.index), Err(Some(2)));
+        let r = obj.extract_sequence::<(i64, &[u8], Oid, bool)>();
+        assert_eq!(r.map_err(|e| e.index), Err(Some(3)));
+        let r = obj[0].extract_sequence::<(i64,)>();
+        assert_eq!(r.map_err(|e| e.index), Err(None));
+    }
+
+    #[test]
+    fn test_convert_from_values() {
+        let obj = BerObject::from(vec![
+            BerObject::from(true),
+            BerObject::from("abc"),
+            BerObject::from(&b"\x01\x02"[..]),
+            BerObject::from(crate::oid!(1.2.3)),
+        ]);
+        assert_eq!(obj.header.tag(), Tag::Sequence);
+        assert_eq!(obj[0].as_bool(), Ok(true));
+        assert_eq!(obj[1].header.tag(), Tag::Utf8String);
+        assert_eq!(obj[1].as_str(), Ok("abc"));
+        assert_eq!(obj[2].header.tag(), Tag::OctetString);
+        assert_eq!(obj[3].as_oid_val(), Ok(crate::oid!(1.2.3)));
+
+        for (i, encoded) in [
+            (0, &[0x00][..]),
+            (127, &[0x7f]),
+            (128, &[0x00, 0x80]),
+            (-1, &[0xff]),
+            (-128, &[0x80]),
+            (-129, &[0xff, 0x7f]),
+            (i64::MIN, &[0x80, 0, 0, 0, 0, 0, 0, 0]),
+        ] {
+            let owned = BerObjectOwned::from(i);
+            let obj = owned.as_ber();
+            assert_eq!(obj.content, BerObjectContent::Integer(encoded));
+            assert_eq!(obj.as_i64(), Ok(i));
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::ber::{BerObject, BerObjectContent, Tag};
+use crate::error::BerError;
+use asn1_rs::{ASN1DateTime, ASN1TimeZone};
+use core::cmp::Ordering;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A parsed UTCTime or GeneralizedTime, compared as an instant
+///
+/// The two types are handled uniformly:
+/// - the two-digit years of UTCTime are interpreted as in RFC 5280 (4.1.2.5.1): years 50 to 99
+///   are in the 20th century, and years 00 to 49 in the 21st century
+/// - offsets from UTC are applied, and times without time zone (local times, only allowed in
+///   GeneralizedTime) are considered as UTC
+///
+/// Times are ordered by their instant (`PartialOrd`), so the same instant encoded using different
+/// types or time zones compares equal. Invalid dates (for ex. February 30) cannot be compared.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::parse_ber;
+///
+/// // UTCTime 2049-12-31 23:59:59 UTC, and GeneralizedTime 2050-01-01 01:00:00 +0100
+/// let (_, not_before) = parse_ber(b"\x17\x0d491231235959Z").expect("parsing failed");
+/// let (_, not_after) = parse_ber(b"\x18\x1320500101010000+0100").expect("parsing failed");
+/// let not_before = not_before.as_time().expect("not a time");
+/// let not_after = not_after.as_time().expect("not a time");
+/// assert_eq!(not_before.year(), 2049);
+/// assert!(not_before < not_after);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BerTime {
+    /// Tag of the object (`UtcTime` or `GeneralizedTime`)
+    pub tag: Tag,
+    /// Date and time, as encoded (with a two-digit year for UTCTime)
+    pub datetime: ASN1DateTime,
+}
+
+impl BerTime {
+    /// Build a time from the content of a UTCTime or GeneralizedTime object
+    ///
+    /// Return a `BerTypeError` if the content is not a time.
+    pub fn from_content(content: &BerObjectContent) -> Result<Self, BerError> {
+        let (tag, datetime) = match content {
+            BerObjectContent::UTCTime(t) => (Tag::UtcTime, t),
+            BerObjectContent::GeneralizedTime(t) => (Tag::GeneralizedTime, t),
+            _ => return Err(BerError::BerTypeError),
+        };
+        let datetime = datetime.clone();
+        Ok(BerTime { tag, datetime })
+    }
+
+    /// Get the year, with four digits
+    pub fn year(&self) -> u32 {
+        match self.tag {
+            Tag::UtcTime if self.datetime.year < 50 => self.datetime.year + 2000,
+            Tag::UtcTime if self.datetime.year < 100 => self.datetime.year + 1900,
+            _ => self.datetime.year,
+        }
+    }
+
+    /// Get the offset from UTC, in seconds (0 for UTC times and local times)
+    pub fn utc_offset_seconds(&self) -> i32 {
+        match self.datetime.tz {
+            ASN1TimeZone::Offset(h, m) => {
+                let (h, m) = (i32::from(h), i32::from(m));
+                let sign = if h < 0 { -1 } else { 1 };
+                h * 3600 + sign * m * 60
+            }
+            ASN1TimeZone::Undefined | ASN1TimeZone::Z => 0,
+        }
+    }
+
+    /// Get the local time differential, in minutes, if the time was encoded with an offset from
+    /// UTC (for ex. `+0530` is 330 minutes)
+    ///
+    /// Return `None` for UTC times and local times without time zone.
+    pub fn local_offset_minutes(&self) -> Option<i16> {
+        match self.datetime.tz {
+            ASN1TimeZone::Offset(..) => Some((self.utc_offset_seconds() / 60) as i16),
+            ASN1TimeZone::Undefined | ASN1TimeZone::Z => None,
+        }
+    }
+
+    /// Get the number of milliseconds since the Unix epoch (1970-01-01 00:00:00 UTC), or `None`
+    /// if the date is invalid
+    pub fn unix_timestamp_millis(&self) -> Option<i64> {
+        let t = &self.datetime;
+        let year = i64::from(self.year());
+        if !(1..=12).contains(&t.month) || t.day == 0 || t.day > days_in_month(year, t.month) {
+            return None;
+        }
+        let days = days_from_civil(year, t.month, t.day);
+        let seconds = days * 86_400
+            + i64::from(t.hour) * 3600
+            + i64::from(t.minute) * 60
+            + i64::from(t.second)
+            - i64::from(self.utc_offset_seconds());
+        Some(seconds * 1000 + i64::from(t.millisecond.unwrap_or(0)))
+    }
+
+    /// Get the duration between `other` and `self`, with its sign, or `None` if one of the dates
+    /// is invalid
+    ///
+    /// The sign is `Ordering::Greater` if `self` is after `other`, `Ordering::Less` if `self` is
+    /// before `other`, and `Ordering::Equal` if the duration is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::cmp::Ordering;
+    /// use core::time::Duration;
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // validity period of a certificate
+    /// let (_, not_before) = parse_ber(b"\x17\x0d991231230000Z").expect("parsing failed");
+    /// let (_, not_after) = parse_ber(b"\x18\x0f20000101000000Z").expect("parsing failed");
+    /// let not_before = not_before.as_time().expect("not a time");
+    /// let not_after = not_after.as_time().expect("not a time");
+    /// assert_eq!(
+    ///     not_after.signed_duration_since(&not_before),
+    ///     Some((Ordering::Greater, Duration::from_secs(3600)))
+    /// );
+    /// ```
+    pub fn signed_duration_since(&self, other: &BerTime) -> Option<(Ordering, Duration)> {
+        let diff = self.unix_timestamp_millis()? - other.unix_timestamp_millis()?;
+        Some((diff.cmp(&0), Duration::from_millis(diff.unsigned_abs())))
+    }
+
+    /// Get the duration between `other` and `self` (negative if `self` is before `other`), or
+    /// `None` if one of the dates is invalid
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    pub fn time_duration_since(&self, other: &BerTime) -> Option<time::Duration> {
+        let diff = self.unix_timestamp_millis()? - other.unix_timestamp_millis()?;
+        Some(time::Duration::milliseconds(diff))
+    }
+
+    /// Convert the time to a `SystemTime`, or `None` if the date is invalid
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let millis = self.unix_timestamp_millis()?;
+        let duration = Duration::from_millis(millis.unsigned_abs());
+        if millis >= 0 {
+            UNIX_EPOCH.checked_add(duration)
+        } else {
+            UNIX_EPOCH.checked_sub(duration)
+        }
+    }
+
+    /// Test if the time is strictly before `t` (`false` if the date is invalid)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    /// use std::time::SystemTime;
+    ///
+    /// let (_, obj) = parse_ber(b"\x17\x0d991231235959Z").expect("parsing failed");
+    /// let time = obj.as_time().expect("not a time");
+    /// assert!(time.is_before(SystemTime::now()));
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn is_before(&self, t: SystemTime) -> bool {
+        self.to_system_time().map_or(false, |s| s < t)
+    }
+
+    /// Test if the time is strictly after `t` (`false` if the date is invalid)
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn is_after(&self, t: SystemTime) -> bool {
+        self.to_system_time().map_or(false, |s| s > t)
+    }
+}
+
+/// Times are equal if they represent the same instant
+impl PartialEq for BerTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for BerTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let a = self.unix_timestamp_millis()?;
+        let b = other.unix_timestamp_millis()?;
+        Some(a.cmp(&b))
+    }
+}
+
+impl<'a> BerObject<'a> {
+    /// Attempt to read a UTCTime or GeneralizedTime, as a [`BerTime`]
+    ///
+    /// Return a `BerTypeError` if the object is not a time.
+    pub fn as_time(&self) -> Result<BerTime, BerError> {
+        self.content.as_time()
+    }
+}
+
+impl<'a> BerObjectContent<'a> {
+    /// Attempt to read a UTCTime or GeneralizedTime, as a [`BerTime`]
+    pub fn as_time(&self) -> Result<BerTime, BerError> {
+        BerTime::from_content(self)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days since 1970-01-01 in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let (month, day) = (i64::from(month), i64::from(day));
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12; // March is 0
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::parse_ber;
+
+    fn time(bytes: &[u8]) -> BerTime {
+        let (_, obj) = parse_ber(bytes).expect("parsing failed");
+        obj.as_time().expect("not a time")
+    }
+
+    #[test]
+    fn test_ber_time() {
+        let utc = time(b"\x17\x0d500101000000Z");
+        assert_eq!(utc.year(), 1950);
+        assert_eq!(utc.unix_timestamp_millis(), Some(-631_152_000_000));
+        let epoch = time(b"\x18\x0f19700101000000Z");
+        assert_eq!(epoch.unix_timestamp_millis(), Some(0));
+        // 2000-02-29 02:00:00 UTC, and 2000-03-01 00:00:00.5 UTC
+        let a = time(b"\x17\x11000229010000-0100");
+        let b = time(b"\x18\x1520000301003000.5+0030");
+        assert_eq!(
+            (a.utc_offset_seconds(), b.utc_offset_seconds()),
+            (-3600, 1800)
+        );
+        assert!(a < b);
+        assert_eq!(
+            a.signed_duration_since(&b),
+            Some((Ordering::Less, Duration::from_millis(79_200_500)))
+        );
+        // same instant as b
+        let c = time(b"\x18\x1120000301000000.5Z");
+        assert_eq!(b, c);
+        assert_eq!(
+            b.signed_duration_since(&c),
+            Some((Ordering::Equal, Duration::ZERO))
+        );
+        #[cfg(feature = "time")]
+        assert_eq!(
+            a.time_duration_since(&b),
+            Some(time::Duration::milliseconds(-79_200_500))
+        );
+        assert_eq!(c.unix_timestamp_millis(), Some(951_868_800_500));
+        assert_eq!(a.local_offset_minutes(), Some(-60));
+        assert_eq!(c.local_offset_minutes(), None);
+        // offset of less than one hour
+        let d = time(b"\x18\x1319991231233000-0030");
+        assert_eq!(d.local_offset_minutes(), Some(-30));
+        assert_eq!(d.unix_timestamp_millis(), Some(946_684_800_000));
+        let e = time(b"\x17\x11991231233000-0030");
+        assert_eq!(e.utc_offset_seconds(), -1800);
+        assert_eq!(e, d);
+        // local time, considered as UTC
+        assert_eq!(
+            time(b"\x18\x0e19700101000001").unix_timestamp_millis(),
+            Some(1000)
+        );
+        // invalid date
+        let invalid = time(b"\x17\x0d010229000000Z");
+        assert_eq!(invalid.unix_timestamp_millis(), None);
+        assert_eq!(invalid.partial_cmp(&invalid), None);
+        assert_eq!(invalid.signed_duration_since(&a), None);
+        assert_eq!(
+            BerObjectContent::Null.as_time().map(|_| ()),
+            Err(BerError::BerTypeError)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ber_time_system_time() {
+        let t = time(b"\x18\x0f19691231235959Z");
+        let system_time = t.to_system_time().expect("invalid time");
+        assert_eq!(
+            UNIX_EPOCH.duration_since(system_time).ok(),
+            Some(Duration::from_secs(1))
+        );
+        assert!(t.is_before(UNIX_EPOCH) && !t.is_after(UNIX_EPOCH));
+    }
+}
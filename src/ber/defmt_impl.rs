@@ -0,0 +1,234 @@
+//! Implementation of `defmt::Format` for BER objects
+//!
+//! Objects are formatted in a compact form: the name of the content variant, followed by the
+//! value for primitive objects (as bytes for integers and octet strings), or the number of
+//! children for constructed objects. Children are not formatted, so the size of a log message does
+//! not depend on the size of the tree.
+//!
+//! `Header`, `Tag` and `BerError` are defined in `asn1-rs`, so they are formatted using the
+//! [`Defmt`] wrapper.
+
+use crate::ber::{BerObject, BerObjectContent, BitStringObject, Class, Header, Length, Tag};
+use crate::error::BerError;
+use crate::oid::Oid;
+use asn1_rs::{ASN1DateTime, DerConstraint};
+use defmt::{Format, Formatter};
+use nom::{Err, Needed};
+
+/// Wrapper implementing `defmt::Format` for types defined in `asn1-rs`
+///
+/// The implementation is provided for `Header`, `Tag`, `BerError`, and `nom::Err<BerError>` (the
+/// error type of parsing functions).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use der_parser::ber::Defmt;
+/// use der_parser::der::parse_der;
+///
+/// match parse_der(bytes) {
+///     Ok((_, obj)) => defmt::info!("{} ({})", obj, Defmt(&obj.header)),
+///     Err(e) => defmt::error!("parsing failed: {}", Defmt(&e)),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Defmt<'a, T: ?Sized>(pub &'a T);
+
+fn class_name(class: Class) -> &'static str {
+    match class {
+        Class::Universal => "Universal",
+        Class::Application => "Application",
+        Class::ContextSpecific => "ContextSpecific",
+        Class::Private => "Private",
+    }
+}
+
+fn format_datetime(f: Formatter, name: &str, t: &ASN1DateTime) {
+    defmt::write!(
+        f,
+        "{=str}({=u32}-{=u8:02}-{=u8:02}T{=u8:02}:{=u8:02}:{=u8:02})",
+        name,
+        t.year,
+        t.month,
+        t.day,
+        t.hour,
+        t.minute,
+        t.second
+    );
+}
+
+// OIDs are formatted without allocating, one arc at a time
+fn format_oid(f: Formatter, name: &str, oid: &Oid) {
+    defmt::write!(f, "{=str}(", name);
+    match oid.iter() {
+        Some(arcs) => {
+            for (idx, arc) in arcs.enumerate() {
+                if idx > 0 {
+                    defmt::write!(f, ".");
+                }
+                defmt::write!(f, "{=u64}", arc);
+            }
+        }
+        // arcs do not fit in a u64
+        None => defmt::write!(f, "{=[u8]:x}", oid.as_bytes()),
+    }
+    defmt::write!(f, ")");
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<'a> Format for Defmt<'a, Tag> {
+    fn format(&self, f: Formatter) {
+        defmt::write!(f, "Tag({=u32})", (self.0).0);
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<'a, 'b> Format for Defmt<'a, Header<'b>> {
+    fn format(&self, f: Formatter) {
+        let header = self.0;
+        defmt::write!(
+            f,
+            "Header({=str}, {=u32}, constructed: {=bool}, length: ",
+            class_name(header.class()),
+            header.tag().0,
+            header.is_constructed()
+        );
+        match header.length() {
+            Length::Definite(len) => defmt::write!(f, "{=usize})", len),
+            Length::Indefinite => defmt::write!(f, "indefinite)"),
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<'a> Format for Defmt<'a, BerError> {
+    fn format(&self, f: Formatter) {
+        match self.0 {
+            BerError::BerTypeError => defmt::write!(f, "BerTypeError"),
+            BerError::BerValueError => defmt::write!(f, "BerValueError"),
+            BerError::InvalidLength => defmt::write!(f, "InvalidLength"),
+            BerError::InvalidValue { tag, msg } => {
+                defmt::write!(f, "InvalidValue({=u32}, {=str})", tag.0, msg.as_str())
+            }
+            BerError::InvalidTag => defmt::write!(f, "InvalidTag"),
+            BerError::UnknownTag(tag) => defmt::write!(f, "UnknownTag({=u32})", tag),
+            BerError::UnexpectedTag { expected, actual } => defmt::write!(
+                f,
+                "UnexpectedTag(expected: {=?}, actual: {=u32})",
+                expected.map(|t| t.0),
+                actual.0
+            ),
+            BerError::UnexpectedClass { expected, actual } => defmt::write!(
+                f,
+                "UnexpectedClass(expected: {=?}, actual: {=str})",
+                expected.map(class_name),
+                class_name(*actual)
+            ),
+            BerError::IndefiniteLengthUnexpected => defmt::write!(f, "IndefiniteLengthUnexpected"),
+            BerError::ConstructExpected => defmt::write!(f, "ConstructExpected"),
+            BerError::ConstructUnexpected => defmt::write!(f, "ConstructUnexpected"),
+            BerError::IntegerTooLarge => defmt::write!(f, "IntegerTooLarge"),
+            BerError::IntegerNegative => defmt::write!(f, "IntegerNegative"),
+            BerError::BerMaxDepth => defmt::write!(f, "BerMaxDepth"),
+            BerError::StringInvalidCharset => defmt::write!(f, "StringInvalidCharset"),
+            BerError::InvalidDateTime => defmt::write!(f, "InvalidDateTime"),
+            BerError::DerConstraintFailed(c) => {
+                let name = match c {
+                    DerConstraint::IndefiniteLength => "IndefiniteLength",
+                    DerConstraint::Constructed => "Constructed",
+                    DerConstraint::NotConstructed => "NotConstructed",
+                    DerConstraint::MissingTimeZone => "MissingTimeZone",
+                    DerConstraint::MissingSeconds => "MissingSeconds",
+                    DerConstraint::UnusedBitsNotZero => "UnusedBitsNotZero",
+                    DerConstraint::InvalidBoolean => "InvalidBoolean",
+                    DerConstraint::IntegerEmpty => "IntegerEmpty",
+                    DerConstraint::IntegerLeadingZeroes => "IntegerLeadingZeroes",
+                    DerConstraint::IntegerLeadingFF => "IntegerLeadingFF",
+                };
+                defmt::write!(f, "DerConstraintFailed({=str})", name)
+            }
+            BerError::LifetimeError => defmt::write!(f, "LifetimeError"),
+            BerError::Unsupported => defmt::write!(f, "Unsupported"),
+            BerError::Incomplete(Needed::Size(n)) => {
+                defmt::write!(f, "Incomplete({=usize})", n.get())
+            }
+            BerError::Incomplete(Needed::Unknown) => defmt::write!(f, "Incomplete"),
+            BerError::NomError(kind) => defmt::write!(f, "NomError({=str})", kind.description()),
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<'a> Format for Defmt<'a, Err<BerError>> {
+    fn format(&self, f: Formatter) {
+        match self.0 {
+            Err::Incomplete(Needed::Size(n)) => defmt::write!(f, "Incomplete({=usize})", n.get()),
+            Err::Incomplete(Needed::Unknown) => defmt::write!(f, "Incomplete"),
+            Err::Error(e) => defmt::write!(f, "Error({})", Defmt(e)),
+            Err::Failure(e) => defmt::write!(f, "Failure({})", Defmt(e)),
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<'a> Format for BerObject<'a> {
+    fn format(&self, f: Formatter) {
+        match &self.content {
+            BerObjectContent::EndOfContent => defmt::write!(f, "EndOfContent"),
+            BerObjectContent::Boolean(b) => defmt::write!(f, "Boolean({=bool})", b),
+            BerObjectContent::Integer(i) => defmt::write!(f, "Integer({=[u8]:x})", i),
+            BerObjectContent::BitString(unused_bits, BitStringObject { data }) => {
+                defmt::write!(f, "BitString({=u8}, {=[u8]:x})", unused_bits, data)
+            }
+            BerObjectContent::OctetString(s) => defmt::write!(f, "OctetString({=[u8]:x})", s),
+            BerObjectContent::Null => defmt::write!(f, "Null"),
+            BerObjectContent::Enum(e) => defmt::write!(f, "Enum({=u64})", e),
+            BerObjectContent::OID(oid) => format_oid(f, "OID", oid),
+            BerObjectContent::RelativeOID(oid) => format_oid(f, "RelativeOID", oid),
+            BerObjectContent::NumericString(s)
+            | BerObjectContent::VisibleString(s)
+            | BerObjectContent::PrintableString(s)
+            | BerObjectContent::IA5String(s)
+            | BerObjectContent::UTF8String(s)
+            | BerObjectContent::T61String(s)
+            | BerObjectContent::VideotexString(s)
+            | BerObjectContent::BmpString(s)
+            | BerObjectContent::ObjectDescriptor(s)
+            | BerObjectContent::GraphicString(s)
+            | BerObjectContent::GeneralString(s) => {
+                defmt::write!(f, "String({=u32}, {=str})", self.tag().0, s)
+            }
+            BerObjectContent::UniversalString(s) => {
+                defmt::write!(f, "UniversalString({=[u8]:x})", s)
+            }
+            BerObjectContent::CharacterString(c) => {
+                defmt::write!(f, "CharacterString({=[u8]:x})", c.string_value)
+            }
+            BerObjectContent::Sequence(v) => defmt::write!(f, "Sequence[{=usize}]", v.len()),
+            BerObjectContent::Set(v) => defmt::write!(f, "Set[{=usize}]", v.len()),
+            BerObjectContent::UTCTime(t) => format_datetime(f, "UTCTime", t),
+            BerObjectContent::GeneralizedTime(t) => format_datetime(f, "GeneralizedTime", t),
+            BerObjectContent::Optional(None) => defmt::write!(f, "Optional(None)"),
+            BerObjectContent::Optional(Some(_)) => defmt::write!(f, "Optional(Some)"),
+            BerObjectContent::Tagged(class, tag, _) => {
+                defmt::write!(f, "Tagged({=str}, {=u32})", class_name(*class), tag.0)
+            }
+            BerObjectContent::TagOverflow(identifier, data) => defmt::write!(
+                f,
+                "TagOverflow({=[u8]:x}, {=usize} bytes)",
+                identifier,
+                data.len()
+            ),
+            BerObjectContent::Primitive(tag, data) => {
+                defmt::write!(f, "Primitive({=u32}, {=usize} bytes)", tag.0, data.len())
+            }
+            BerObjectContent::Unknown(any) => defmt::write!(
+                f,
+                "Unknown({=str}, {=u32}, {=usize} bytes)",
+                class_name(any.class()),
+                any.tag().0,
+                any.data.len()
+            ),
+        }
+    }
+}
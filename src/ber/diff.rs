@@ -0,0 +1,314 @@
+use crate::ber::{BerObject, BerObjectContent, Tag, MAX_RECURSION};
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use asn1_rs::{Any, FromBer};
+
+/// Kind of a [`Difference`] between two objects
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// Objects have different classes
+    Class,
+    /// Objects have different tags
+    Tag,
+    /// Objects have the same class and tag, but different contents
+    Content,
+    /// Object is present in the first tree only
+    Missing,
+    /// Object is present in the second tree only
+    Extra,
+}
+
+/// Location where two object trees diverge
+///
+/// The path is a list of child indexes, and can be used with [`BerObject::get_path`] on both trees.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference<'a> {
+    /// Child indexes from the root objects, usable with [`BerObject::get_path`]
+    pub path: Vec<usize>,
+    /// Kind of the difference
+    pub kind: DifferenceKind,
+    /// Object from the first tree (`None` if `kind` is `Extra`)
+    pub expected: Option<&'a BerObject<'a>>,
+    /// Object from the second tree (`None` if `kind` is `Missing`)
+    pub found: Option<&'a BerObject<'a>>,
+}
+
+/// Compare two object trees, and return the list of differences
+///
+/// Headers are compared on class and tag only (not on length, which depends on the encoding).
+/// When class or tag differ, the children of the objects are not compared.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{ber_diff, parse_ber, DifferenceKind};
+///
+/// let (_, a) = parse_ber(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02]).unwrap();
+/// let (_, b) = parse_ber(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x03]).unwrap();
+/// let diffs = ber_diff(&a, &b);
+/// assert_eq!(diffs.len(), 1);
+/// assert_eq!(diffs[0].path, vec![1]);
+/// assert_eq!(diffs[0].kind, DifferenceKind::Content);
+/// ```
+pub fn ber_diff<'a>(a: &'a BerObject<'a>, b: &'a BerObject<'a>) -> Vec<Difference<'a>> {
+    let mut diffs = Vec::new();
+    let mut path = Vec::new();
+    diff_rec(a, b, &mut path, &mut diffs);
+    diffs
+}
+
+fn diff_rec<'a>(
+    a: &'a BerObject<'a>,
+    b: &'a BerObject<'a>,
+    path: &mut Vec<usize>,
+    diffs: &mut Vec<Difference<'a>>,
+) {
+    let push = |diffs: &mut Vec<_>, kind, expected, found, path: &[usize]| {
+        diffs.push(Difference {
+            path: path.to_vec(),
+            kind,
+            expected,
+            found,
+        })
+    };
+    if a.class() != b.class() {
+        push(diffs, DifferenceKind::Class, Some(a), Some(b), path);
+        return;
+    }
+    if a.tag() != b.tag() {
+        push(diffs, DifferenceKind::Tag, Some(a), Some(b), path);
+        return;
+    }
+    let (children_a, children_b) = match (&a.content, &b.content) {
+        (BerObjectContent::Sequence(va), BerObjectContent::Sequence(vb))
+        | (BerObjectContent::Set(va), BerObjectContent::Set(vb)) => {
+            (va.iter().collect::<Vec<_>>(), vb.iter().collect::<Vec<_>>())
+        }
+        (BerObjectContent::Tagged(ca, ta, oa), BerObjectContent::Tagged(cb, tb, ob))
+            if ca == cb && ta == tb =>
+        {
+            (vec![oa.as_ref()], vec![ob.as_ref()])
+        }
+        (BerObjectContent::Optional(oa), BerObjectContent::Optional(ob)) => (
+            oa.iter().map(|o| o.as_ref()).collect(),
+            ob.iter().map(|o| o.as_ref()).collect(),
+        ),
+        (ca, cb) => {
+            if ca != cb {
+                push(diffs, DifferenceKind::Content, Some(a), Some(b), path);
+            }
+            return;
+        }
+    };
+    for idx in 0..children_a.len().max(children_b.len()) {
+        path.push(idx);
+        match (children_a.get(idx), children_b.get(idx)) {
+            (Some(ca), Some(cb)) => diff_rec(ca, cb, path, diffs),
+            (Some(ca), None) => push(diffs, DifferenceKind::Missing, Some(*ca), None, path),
+            (None, Some(cb)) => push(diffs, DifferenceKind::Extra, None, Some(*cb), path),
+            (None, None) => unreachable!(),
+        }
+        path.pop();
+    }
+}
+
+impl<'a> BerObject<'a> {
+    /// Test if two objects have the same decoded value
+    ///
+    /// Unlike `PartialEq`, this ignores the differences allowed by BER for the encoding of a
+    /// value:
+    /// - the form of the header (length, encoding of the tag, primitive or constructed form): the
+    ///   segments of constructed OCTET STRINGs are joined before comparing
+    /// - the encoding of booleans (any non-zero value is TRUE)
+    /// - leading redundant octets of integers
+    /// - the value of the unused bits of BIT STRINGs
+    /// - the order of the elements of SETs
+    ///
+    /// This is useful to compare the result of lenient and strict parsers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // SEQUENCE { BOOLEAN TRUE }, encoded with an indefinite length and a non-DER boolean
+    /// let (_, a) = parse_ber(&[0x30, 0x80, 0x01, 0x01, 0x01, 0x00, 0x00]).unwrap();
+    /// let (_, b) = parse_ber(&[0x30, 0x03, 0x01, 0x01, 0xff]).unwrap();
+    /// assert!(a.semantically_eq(&b));
+    /// ```
+    pub fn semantically_eq(&self, other: &BerObject) -> bool {
+        if self.class() != other.class() || self.tag() != other.tag() {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (octet_string_value(self), octet_string_value(other)) {
+            return a == b;
+        }
+        content_semantically_eq(&self.content, &other.content)
+    }
+}
+
+/// Get the value of an OCTET STRING, joining the segments of constructed strings
+///
+/// Return `None` if the object is not an OCTET STRING, or if the segments are invalid.
+fn octet_string_value<'a>(obj: &BerObject<'a>) -> Option<Cow<'a, [u8]>> {
+    match obj.content {
+        BerObjectContent::OctetString(data) if obj.header.is_constructed() => {
+            let mut value = Vec::new();
+            join_segments(data, &mut value, MAX_RECURSION)?;
+            Some(Cow::Owned(value))
+        }
+        BerObjectContent::OctetString(data) => Some(Cow::Borrowed(data)),
+        _ => None,
+    }
+}
+
+fn join_segments(mut data: &[u8], value: &mut Vec<u8>, max_depth: usize) -> Option<()> {
+    if max_depth == 0 {
+        return None;
+    }
+    while !data.is_empty() {
+        let (rem, segment) = Any::from_ber(data).ok()?;
+        match segment.tag() {
+            // terminating EOC of an indefinite-length string
+            Tag::EndOfContent if rem.is_empty() => (),
+            Tag::OctetString if segment.header.is_constructed() => {
+                join_segments(segment.data, value, max_depth - 1)?
+            }
+            Tag::OctetString => value.extend_from_slice(segment.data),
+            _ => return None,
+        }
+        data = rem;
+    }
+    Some(())
+}
+
+fn content_semantically_eq(a: &BerObjectContent, b: &BerObjectContent) -> bool {
+    match (a, b) {
+        (BerObjectContent::Integer(ia), BerObjectContent::Integer(ib)) => {
+            trim_integer(ia) == trim_integer(ib)
+        }
+        (BerObjectContent::BitString(ua, ba), BerObjectContent::BitString(ub, bb)) => {
+            ua == ub && bits_eq(*ua, ba.data, bb.data)
+        }
+        (BerObjectContent::Sequence(va), BerObjectContent::Sequence(vb)) => {
+            va.len() == vb.len() && va.iter().zip(vb).all(|(a, b)| a.semantically_eq(b))
+        }
+        (BerObjectContent::Set(va), BerObjectContent::Set(vb)) => {
+            // match each element with a distinct element of the other set
+            let mut used = vec![false; vb.len()];
+            va.len() == vb.len()
+                && va.iter().all(|a| {
+                    match (0..vb.len()).find(|&idx| !used[idx] && a.semantically_eq(&vb[idx])) {
+                        Some(idx) => {
+                            used[idx] = true;
+                            true
+                        }
+                        None => false,
+                    }
+                })
+        }
+        (BerObjectContent::Tagged(ca, ta, oa), BerObjectContent::Tagged(cb, tb, ob)) => {
+            ca == cb && ta == tb && oa.semantically_eq(ob)
+        }
+        (BerObjectContent::Optional(Some(oa)), BerObjectContent::Optional(Some(ob))) => {
+            oa.semantically_eq(ob)
+        }
+        (BerObjectContent::Unknown(aa), BerObjectContent::Unknown(ab)) => {
+            aa.class() == ab.class() && aa.tag() == ab.tag() && aa.data == ab.data
+        }
+        _ => a == b,
+    }
+}
+
+/// Remove the leading octets that do not change the value of an integer
+fn trim_integer(i: &[u8]) -> &[u8] {
+    let redundant = i
+        .windows(2)
+        .take_while(|w| (w[0] == 0 && w[1] & 0x80 == 0) || (w[0] == 0xff && w[1] & 0x80 != 0))
+        .count();
+    &i[redundant..]
+}
+
+/// Compare the significant bits of two BIT STRINGs
+fn bits_eq(unused_bits: u8, a: &[u8], b: &[u8]) -> bool {
+    match (a.split_last(), b.split_last()) {
+        (Some((last_a, a)), Some((last_b, b))) => {
+            let mask = 0xffu8.checked_shl(unused_bits.into()).unwrap_or(0);
+            a == b && last_a & mask == last_b & mask
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ber_diff() {
+        // equal trees, different encodings of the length
+        let (_, a) = parse_ber(&hex!("30 03 02 01 01")).expect("parsing failed");
+        let (_, b) = parse_ber(&hex!("30 81 03 02 01 01")).expect("parsing failed");
+        assert!(ber_diff(&a, &b).is_empty());
+
+        // SEQUENCE { INTEGER 1, SET { NULL }, INTEGER 2 }
+        let (_, a) =
+            parse_ber(&hex!("30 0a 02 01 01 31 02 05 00 02 01 02")).expect("parsing failed");
+        // SEQUENCE { INTEGER 1, SET { BOOLEAN } }
+        let (_, b) = parse_ber(&hex!("30 08 02 01 01 31 03 01 01 00")).expect("parsing failed");
+        let diffs = ber_diff(&a, &b);
+        let summary = diffs
+            .iter()
+            .map(|d| (d.path.clone(), d.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (vec![1, 0], DifferenceKind::Tag),
+                (vec![2], DifferenceKind::Missing),
+            ]
+        );
+        assert_eq!(diffs[0].found.map(|o| o.tag()), Some(Tag::Boolean));
+        assert_eq!(a.get_path(&diffs[1].path), diffs[1].expected);
+    }
+
+    #[test]
+    fn test_semantically_eq() {
+        let eq = |a: &[u8], b: &[u8]| {
+            let (_, a) = parse_ber(a).expect("parsing failed");
+            let (_, b) = parse_ber(b).expect("parsing failed");
+            a.semantically_eq(&b)
+        };
+        // non-minimal integers
+        assert!(eq(&hex!("02 02 00 01"), &hex!("02 01 01")));
+        assert!(eq(&hex!("02 02 ff 80"), &hex!("02 01 80")));
+        assert!(!eq(&hex!("02 02 00 80"), &hex!("02 01 80")));
+        // unused bits of BIT STRING
+        assert!(eq(&hex!("03 02 04 f3"), &hex!("03 02 04 f0")));
+        assert!(!eq(&hex!("03 02 04 e0"), &hex!("03 02 04 f0")));
+        // SET elements in a different order, with indefinite length
+        assert!(eq(
+            &hex!("31 80 02 01 01 05 00 00 00"),
+            &hex!("31 05 05 00 02 01 01")
+        ));
+        assert!(!eq(
+            &hex!("31 06 02 01 01 02 01 01"),
+            &hex!("31 06 02 01 01 02 01 02")
+        ));
+        // constructed OCTET STRING, with definite and indefinite length
+        assert!(eq(&hex!("24 03 04 01 61"), &hex!("04 01 61")));
+        assert!(eq(
+            &hex!("24 80 04 01 61 24 03 04 01 62 00 00"),
+            &hex!("04 02 61 62")
+        ));
+        assert!(!eq(&hex!("24 03 04 01 61"), &hex!("04 01 62")));
+        // but SEQUENCE is ordered
+        assert!(!eq(
+            &hex!("30 05 02 01 01 05 00"),
+            &hex!("30 05 05 00 02 01 01")
+        ));
+        assert!(!eq(&hex!("0c 01 61"), &hex!("16 01 61")));
+    }
+}
@@ -0,0 +1,80 @@
+//! Hashing of the original encoding of parsed objects
+//!
+//! The bytes are read from the span of the objects (see [`BerObject::raw_bytes`]), and fed to the
+//! hasher without being copied or encoded again. Objects which were not read by a parser have no
+//! span, and cannot be hashed.
+
+use crate::ber::BerObject;
+use crate::error::BerError;
+use digest::Update;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+impl<'a> BerObject<'a> {
+    /// Feed the original encoding (header and content) of the object into `digest`
+    ///
+    /// Return a `BerValueError` if the object was not read by a parser.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::der::parse_der;
+    /// # struct Collect(Vec<u8>);
+    /// # impl digest::Update for Collect {
+    /// #     fn update(&mut self, data: &[u8]) { self.0.extend_from_slice(data) }
+    /// # }
+    /// # let mut hasher = Collect(Vec::new());
+    ///
+    /// // SEQUENCE { SEQUENCE { INTEGER 1 }, NULL }
+    /// let bytes = &[0x30, 0x07, 0x30, 0x03, 0x02, 0x01, 0x01, 0x05, 0x00];
+    /// let (_, obj) = parse_der(bytes).expect("parsing failed");
+    /// // hash the first element (for ex. the `tbsCertificate` of a certificate)
+    /// obj[0].hash_into(&mut hasher).expect("no span");
+    /// # assert_eq!(hasher.0, &bytes[2..7]);
+    /// ```
+    pub fn hash_into(&self, digest: &mut impl Update) -> Result<(), BerError> {
+        let raw = self.raw_bytes().ok_or(BerError::BerValueError)?;
+        digest.update(raw);
+        Ok(())
+    }
+
+    /// Feed the original content of the object into `digest`
+    ///
+    /// The header and the end-of-content octets (if the length is indefinite) are not included.
+    /// Return a `BerValueError` if the object was not read by a parser.
+    pub fn hash_content_into(&self, digest: &mut impl Update) -> Result<(), BerError> {
+        let span = self.span.ok_or(BerError::BerValueError)?;
+        digest.update(span.content());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use crate::error::BerError;
+    use alloc::vec::Vec;
+    use digest::Update;
+    use hex_literal::hex;
+
+    struct Collect(Vec<u8>);
+
+    impl Update for Collect {
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data);
+        }
+    }
+
+    #[test]
+    fn test_hash_into() {
+        // SEQUENCE (indefinite) { OCTET STRING "ab" }
+        let bytes = hex!("30 80 04 02 61 62 00 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut hasher = Collect(Vec::new());
+        obj.hash_into(&mut hasher).expect("no span");
+        obj.hash_content_into(&mut hasher).expect("no span");
+        obj[0].hash_content_into(&mut hasher).expect("no span");
+        assert_eq!(hasher.0, [&bytes[..], &bytes[2..6], b"ab"].concat());
+        let built = BerObject::from_obj(BerObjectContent::OctetString(b"ab"));
+        assert_eq!(built.hash_into(&mut hasher), Err(BerError::BerValueError));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::ber::redact::Fnv1a;
+use crate::ber::BerObject;
+use core::hash::Hasher;
+
+impl<'a> BerObject<'a> {
+    /// Compute a hash of the structure of this object and all its descendants
+    ///
+    /// The hash covers the class, tag and constructed flag of every object, and the shape of the
+    /// tree, but not the values or lengths: objects with the same structure have the same
+    /// fingerprint. This can be used to cluster a corpus of inputs by shape (for ex. to minimize
+    /// a set of fuzzing seeds).
+    ///
+    /// The hash (64-bit FNV-1a) is stable across platforms and versions of this crate, but is
+    /// not a cryptographic hash.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// // SEQUENCE { INTEGER 1 } and SEQUENCE { INTEGER 0x0100 }
+    /// let (_, a) = parse_ber(&[0x30, 0x03, 0x02, 0x01, 0x01]).expect("parsing failed");
+    /// let (_, b) = parse_ber(&[0x30, 0x04, 0x02, 0x02, 0x01, 0x00]).expect("parsing failed");
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// assert_ne!(a.fingerprint_with_values(), b.fingerprint_with_values());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(self, false)
+    }
+
+    /// Compute a hash of the structure of this object and all its descendants, including the
+    /// values of the objects without children
+    ///
+    /// Values are read from the span of the objects (or, for objects which were not read by a
+    /// parser, from [`BerObject::as_slice`] when possible). See [`BerObject::fingerprint`].
+    pub fn fingerprint_with_values(&self) -> u64 {
+        fingerprint(self, true)
+    }
+}
+
+fn fingerprint(obj: &BerObject, values: bool) -> u64 {
+    let mut hasher = Fnv1a::default();
+    for (depth, o) in obj.iter_depth_first() {
+        hasher.write(&(depth as u32).to_le_bytes());
+        hasher.write(&[o.class() as u8, o.is_constructed() as u8]);
+        hasher.write(&o.tag().0.to_le_bytes());
+        if values && o.get(0).is_none() {
+            let data = match o.span {
+                Some(span) => Some(span.content()),
+                None => o.as_slice().ok(),
+            };
+            if let Some(data) = data {
+                hasher.write(&(data.len() as u64).to_le_bytes());
+                hasher.write(data);
+            }
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_fingerprint() {
+        // SEQUENCE { SEQUENCE { NULL }, NULL } and SEQUENCE { SEQUENCE { NULL, NULL } }
+        let (_, a) = parse_ber(&hex!("30 06 30 02 05 00 05 00")).expect("parsing failed");
+        let (_, b) = parse_ber(&hex!("30 06 30 04 05 00 05 00")).expect("parsing failed");
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        // the encoding of the length is ignored
+        let (_, c) = parse_ber(&hex!("30 81 06 30 02 05 00 05 00")).expect("parsing failed");
+        assert_eq!(a.fingerprint(), c.fingerprint());
+        assert_eq!(a.fingerprint_with_values(), c.fingerprint_with_values());
+        // built objects have the same fingerprint as parsed objects
+        let (_, d) = parse_ber(&hex!("30 03 04 01 61")).expect("parsing failed");
+        let built = BerObject::from_seq(vec![BerObject::from_obj(BerObjectContent::OctetString(
+            b"a",
+        ))]);
+        assert_eq!(d.fingerprint_with_values(), built.fingerprint_with_values());
+        // stable value
+        assert_eq!(
+            BerObject::from_obj(BerObjectContent::Null).fingerprint(),
+            0x49ed_aba2_71ea_40b8
+        );
+    }
+}
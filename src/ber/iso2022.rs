@@ -0,0 +1,165 @@
+use crate::ber::{BerObject, BerObjectContent};
+use crate::error::BerError;
+use core::iter::FusedIterator;
+
+const ESC: u8 = 0x1b;
+const SO: u8 = 0x0e;
+const SI: u8 = 0x0f;
+
+/// Part of a string encoded using ISO 2022 code extension techniques
+///
+/// GeneralString and GraphicString (X.690 8.23.5) may switch between character sets using escape
+/// sequences and shift functions. Bytes following a designation are *not* ASCII, so callers should
+/// only interpret [`Text`](Iso2022Segment::Text) segments according to the currently invoked set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Iso2022Segment<'a> {
+    /// Characters encoded in the currently invoked character set
+    Text(&'a str),
+    /// Escape sequence, including the leading ESC (0x1b) byte
+    ///
+    /// Escape sequences are used to designate character sets (for ex. `ESC ( B` for ASCII in G0),
+    /// and for single shifts `SS2` (`ESC N`) and `SS3` (`ESC O`).
+    Escape(&'a str),
+    /// Locking shift SHIFT OUT (0x0e): G1 is invoked
+    ShiftOut,
+    /// Locking shift SHIFT IN (0x0f): G0 is invoked
+    ShiftIn,
+}
+
+/// Iterator over the [`Iso2022Segment`]s of a string
+///
+/// An incomplete escape sequence at the end of the input is reported as
+/// `BerError::StringInvalidCharset`, after which the iterator is exhausted.
+#[derive(Clone, Debug)]
+pub struct Iso2022Segments<'a> {
+    s: &'a str,
+}
+
+impl<'a> Iso2022Segments<'a> {
+    pub const fn new(s: &'a str) -> Self {
+        Iso2022Segments { s }
+    }
+}
+
+impl<'a> Iterator for Iso2022Segments<'a> {
+    type Item = Result<Iso2022Segment<'a>, BerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.s.as_bytes();
+        let (segment, len) = match *bytes.first()? {
+            SO => (Iso2022Segment::ShiftOut, 1),
+            SI => (Iso2022Segment::ShiftIn, 1),
+            ESC => {
+                // ESC, intermediate bytes (0x20-0x2f), then a final byte (0x30-0x7e)
+                let n = bytes[1..]
+                    .iter()
+                    .take_while(|&&b| (0x20..=0x2f).contains(&b))
+                    .count();
+                match bytes.get(n + 1) {
+                    Some(0x30..=0x7e) => (Iso2022Segment::Escape(&self.s[..n + 2]), n + 2),
+                    _ => {
+                        self.s = "";
+                        return Some(Err(BerError::StringInvalidCharset));
+                    }
+                }
+            }
+            _ => {
+                let len = bytes
+                    .iter()
+                    .position(|&b| b == ESC || b == SO || b == SI)
+                    .unwrap_or(bytes.len());
+                (Iso2022Segment::Text(&self.s[..len]), len)
+            }
+        };
+        self.s = &self.s[len..];
+        Some(Ok(segment))
+    }
+}
+
+impl<'a> FusedIterator for Iso2022Segments<'a> {}
+
+/// Test if string contains ISO 2022 escape sequences or shift functions
+///
+/// If this function returns `false`, the string can be treated as plain ASCII (for ex. in
+/// Kerberos, where GeneralString is restricted to IA5 characters).
+pub fn has_iso2022_escapes(s: &str) -> bool {
+    s.bytes().any(|b| b == ESC || b == SO || b == SI)
+}
+
+impl<'a> BerObjectContent<'a> {
+    /// Split a GeneralString or GraphicString into ISO 2022 segments
+    pub fn as_iso2022(&self) -> Result<Iso2022Segments<'a>, BerError> {
+        match *self {
+            BerObjectContent::GeneralString(s) | BerObjectContent::GraphicString(s) => {
+                Ok(Iso2022Segments::new(s))
+            }
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    /// Test if a GeneralString or GraphicString uses ISO 2022 escape sequences or shifts
+    ///
+    /// Returns `false` for all other types.
+    pub fn has_iso2022_escapes(&self) -> bool {
+        match *self {
+            BerObjectContent::GeneralString(s) | BerObjectContent::GraphicString(s) => {
+                has_iso2022_escapes(s)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> BerObject<'a> {
+    /// Split a GeneralString or GraphicString into ISO 2022 segments
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber_generalstring, Iso2022Segment};
+    ///
+    /// // "a", then designate JIS X 0208 into G0, then one kanji
+    /// let bytes = b"\x1b\x06a\x1b$B0!";
+    /// let (_, obj) = parse_ber_generalstring(bytes).expect("parsing failed");
+    /// assert!(obj.has_iso2022_escapes());
+    /// let segments = obj
+    ///     .as_iso2022()
+    ///     .expect("not a GeneralString")
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .expect("invalid escape sequence");
+    /// assert_eq!(
+    ///     segments,
+    ///     vec![
+    ///         Iso2022Segment::Text("a"),
+    ///         Iso2022Segment::Escape("\x1b$B"),
+    ///         Iso2022Segment::Text("0!"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn as_iso2022(&self) -> Result<Iso2022Segments<'a>, BerError> {
+        self.content.as_iso2022()
+    }
+
+    /// Test if a GeneralString or GraphicString uses ISO 2022 escape sequences or shifts
+    pub fn has_iso2022_escapes(&self) -> bool {
+        self.content.has_iso2022_escapes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Iso2022Segment, Iso2022Segments};
+    use crate::error::BerError;
+    use alloc::vec::Vec;
+    use test_case::test_case;
+
+    #[test_case("abc", Ok(vec![Iso2022Segment::Text("abc")]) ; "ascii")]
+    #[test_case("\x1b(Bab", Ok(vec![Iso2022Segment::Escape("\x1b(B"), Iso2022Segment::Text("ab")]) ; "designate ascii")]
+    #[test_case("a\x0eb\x0f", Ok(vec![Iso2022Segment::Text("a"), Iso2022Segment::ShiftOut, Iso2022Segment::Text("b"), Iso2022Segment::ShiftIn]) ; "locking shifts")]
+    #[test_case("\x1b$(D", Ok(vec![Iso2022Segment::Escape("\x1b$(D")]) ; "multiple intermediate bytes")]
+    #[test_case("ab\x1b$", Err(BerError::StringInvalidCharset) ; "truncated escape")]
+    fn tc_iso2022_segments(s: &str, expected: Result<Vec<Iso2022Segment>, BerError>) {
+        let res = Iso2022Segments::new(s).collect::<Result<Vec<_>, _>>();
+        assert_eq!(res, expected);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::ber::{BerObject, BerObjectContent, Tag};
+use crate::oid::Oid;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+/// Depth-first (pre-order) iterator over a tree of BER objects
+///
+/// Each item is a tuple `(depth, object)`, where the depth of the root object is 0.
+/// Children of `Sequence`, `Set`, `Tagged` and `Optional` objects are visited after their parent.
+///
+/// This iterator is created by the [`BerObject::iter_depth_first`] method.
+#[derive(Debug)]
+pub struct DepthFirstIter<'a, 'b> {
+    stack: Vec<(usize, &'b BerObject<'a>)>,
+}
+
+impl<'a, 'b> Iterator for DepthFirstIter<'a, 'b> {
+    type Item = (usize, &'b BerObject<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, obj) = self.stack.pop()?;
+        match obj.content {
+            BerObjectContent::Sequence(ref v) | BerObjectContent::Set(ref v) => {
+                self.stack.extend(v.iter().rev().map(|o| (depth + 1, o)));
+            }
+            BerObjectContent::Tagged(_, _, ref o) | BerObjectContent::Optional(Some(ref o)) => {
+                self.stack.push((depth + 1, o.as_ref()));
+            }
+            _ => (),
+        }
+        Some((depth, obj))
+    }
+}
+
+impl<'a, 'b> FusedIterator for DepthFirstIter<'a, 'b> {}
+
+impl<'a> BerObject<'a> {
+    /// Iterate over this object and all its descendants, in depth-first order
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    /// use der_parser::der::Tag;
+    ///
+    /// let bytes = &[0x30, 0x08, 0x02, 0x01, 0x01, 0x30, 0x03, 0x02, 0x01, 0x02];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let integers = obj
+    ///     .iter_depth_first()
+    ///     .filter(|(_, o)| o.tag() == Tag::Integer)
+    ///     .map(|(depth, _)| depth)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(integers, vec![1, 2]);
+    /// ```
+    pub fn iter_depth_first<'b>(&'b self) -> DepthFirstIter<'a, 'b> {
+        DepthFirstIter {
+            stack: vec![(0, self)],
+        }
+    }
+
+    /// Find the first object (in depth-first order) matching `predicate`, searching this object
+    /// and all its descendants
+    ///
+    /// The path of the object is returned, and can be used with [`BerObject::get_path`]. The
+    /// path of this object is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::parse_ber;
+    ///
+    /// let bytes = &[0x30, 0x08, 0x02, 0x01, 0x01, 0x30, 0x03, 0x02, 0x01, 0x02];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let (path, int) = obj
+    ///     .find_first(|o| o.as_u32() == Ok(2))
+    ///     .expect("object not found");
+    /// assert_eq!(path, vec![1, 0]);
+    /// assert_eq!(obj.get_path(&path), Some(int));
+    /// ```
+    pub fn find_first<'b, F>(&'b self, mut predicate: F) -> Option<(Vec<usize>, &'b BerObject<'a>)>
+    where
+        F: FnMut(&BerObject<'a>) -> bool,
+    {
+        let mut found = None;
+        walk(self, &mut Vec::new(), &mut |path, obj| {
+            if predicate(obj) {
+                found = Some((path.to_vec(), obj));
+                true
+            } else {
+                false
+            }
+        });
+        found
+    }
+
+    /// Find the first OBJECT IDENTIFIER with value `oid` in this object and its descendants
+    ///
+    /// The OID itself is returned: to locate a structure identified by an OID (for ex. an
+    /// extension of a certificate), remove the last index of the path to get its parent.
+    pub fn find_oid<'b>(&'b self, oid: &Oid) -> Option<(Vec<usize>, &'b BerObject<'a>)> {
+        self.find_first(|obj| matches!(obj.content, BerObjectContent::OID(ref o) if o == oid))
+    }
+
+    /// Find all the descendants of this object with tag `tag`, in depth-first order
+    ///
+    /// Only the tag number is compared, not the class: use [`BerObject::find_first`] or
+    /// [`BerObject::iter_depth_first`] for more specific searches.
+    pub fn children_with_tag<'b, T: Into<Tag>>(
+        &'b self,
+        tag: T,
+    ) -> Vec<(Vec<usize>, &'b BerObject<'a>)> {
+        let tag = tag.into();
+        let mut v = Vec::new();
+        walk(self, &mut Vec::new(), &mut |path, obj| {
+            if !path.is_empty() && obj.tag() == tag {
+                v.push((path.to_vec(), obj));
+            }
+            false
+        });
+        v
+    }
+}
+
+/// Visit `obj` and its descendants in depth-first order, until `f` returns `true`
+/// Visit `obj` and its descendants in depth-first order, with their paths, until `f` returns
+/// true
+///
+/// Return true if `f` returned true.
+pub(crate) fn walk<'a, 'b>(
+    obj: &'b BerObject<'a>,
+    path: &mut Vec<usize>,
+    f: &mut dyn FnMut(&[usize], &'b BerObject<'a>) -> bool,
+) -> bool {
+    if f(path, obj) {
+        return true;
+    }
+    for (idx, child) in (0..).map_while(|idx| obj.get(idx).map(|o| (idx, o))) {
+        path.push(idx);
+        let done = walk(child, path, f);
+        path.pop();
+        if done {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_iter_depth_first() {
+        // SEQUENCE { INTEGER 1, SET { NULL, INTEGER 2 }, OCTET STRING }
+        let bytes = hex!("30 0f 02 01 01 31 05 05 00 02 01 02 04 03 01 02 03");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let tags = obj
+            .iter_depth_first()
+            .map(|(depth, o)| (depth, o.tag()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                (0, Tag::Sequence),
+                (1, Tag::Integer),
+                (1, Tag::Set),
+                (2, Tag::Null),
+                (2, Tag::Integer),
+                (1, Tag::OctetString),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_depth_first_tagged() {
+        let inner = BerObject::from_int_slice(b"\x01");
+        let obj = BerObject::from_obj(BerObjectContent::Tagged(
+            Class::ContextSpecific,
+            Tag(0),
+            Box::new(inner),
+        ));
+        let depths = obj.iter_depth_first().map(|(d, _)| d).collect::<Vec<_>>();
+        assert_eq!(depths, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_find() {
+        // SEQUENCE { SEQUENCE { OID 1.2.3, BOOLEAN TRUE }, SEQUENCE { OID 2.5.29.15, INTEGER 1 } }
+        let bytes = hex!("30 14 30 07 06 02 2a 03 01 01 ff 30 09 06 03 55 1d 0f 02 02 01 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let oid = crate::oid!(2.5.29 .15);
+        let (path, found) = obj.find_oid(&oid).expect("OID not found");
+        assert_eq!(path, vec![1, 0]);
+        assert_eq!(found.as_oid(), Ok(&oid));
+        assert!(obj.find_oid(&crate::oid!(1.2.4)).is_none());
+
+        let (path, _) = obj.find_first(|o| o.tag() == Tag::Sequence).unwrap();
+        assert!(path.is_empty());
+
+        let oids = obj
+            .children_with_tag(Tag::Oid)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(oids, vec![vec![0, 0], vec![1, 0]]);
+        assert_eq!(obj.children_with_tag(Tag::Sequence).len(), 2);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::ber::{parse_ber_with_options, BerObject, ParseOptions};
+use crate::error::BerError;
+use core::fmt;
+use nom::Err;
+use once_cell::unsync::OnceCell;
+
+/// An encoded object, parsed on first access
+///
+/// This is used to represent encapsulated structures (for ex. the content of a `ContentInfo`, or
+/// the value of an extension stored in an OCTET STRING) without parsing them: only the raw bytes
+/// are stored, and the object is parsed (and cached) the first time it is accessed. The result
+/// of parsing, including errors, is kept: the bytes are never parsed twice.
+///
+/// The bytes must contain exactly one object (trailing bytes raise an `InvalidLength` error).
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, LazyBer};
+///
+/// // SEQUENCE { OCTET STRING (encapsulating INTEGER 1) }
+/// let bytes = &[0x30, 0x05, 0x04, 0x03, 0x02, 0x01, 0x01];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// let inner = LazyBer::from_content(&obj[0]).expect("not an OCTET STRING");
+/// assert!(!inner.is_parsed());
+/// assert_eq!(inner.get().and_then(|o| o.as_u32()), Ok(1));
+/// assert!(inner.is_parsed());
+/// ```
+#[derive(Clone)]
+pub struct LazyBer<'a> {
+    raw: &'a [u8],
+    options: ParseOptions,
+    parsed: OnceCell<Result<BerObject<'a>, BerError>>,
+}
+
+impl<'a> LazyBer<'a> {
+    /// Wrap the encoded object `raw`, which will be parsed using BER rules
+    pub fn new(raw: &'a [u8]) -> Self {
+        Self::with_options(raw, ParseOptions::ber())
+    }
+
+    /// Wrap the encoded object `raw`, which will be parsed using `options`
+    pub fn with_options(raw: &'a [u8], options: ParseOptions) -> Self {
+        LazyBer {
+            raw,
+            options,
+            parsed: OnceCell::new(),
+        }
+    }
+
+    /// Wrap the content of `obj`, which must be an encoded object
+    ///
+    /// This is used for objects encapsulated in an OCTET STRING, or in an explicitly tagged object
+    /// when the tag is not known to the parser. Other objects raise a `BerTypeError`.
+    pub fn from_content(obj: &BerObject<'a>) -> Result<Self, BerError> {
+        use crate::ber::BerObjectContent as C;
+        match obj.content {
+            C::OctetString(data) => Ok(Self::new(data)),
+            C::Unknown(ref any) if any.header.is_constructed() => Ok(Self::new(any.data)),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    /// Raw bytes of the encoded object
+    #[inline]
+    pub const fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Test if the object was already parsed
+    #[inline]
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    /// Get the object, parsing it if this is the first access
+    pub fn get(&self) -> Result<&BerObject<'a>, BerError> {
+        self.parsed
+            .get_or_init(|| parse_lazy(self.raw, &self.options))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Get the object, parsing it if it was not accessed
+    pub fn into_object(self) -> Result<BerObject<'a>, BerError> {
+        match self.parsed.into_inner() {
+            Some(res) => res,
+            None => parse_lazy(self.raw, &self.options),
+        }
+    }
+}
+
+fn parse_lazy<'a>(raw: &'a [u8], options: &ParseOptions) -> Result<BerObject<'a>, BerError> {
+    match parse_ber_with_options(raw, options) {
+        Ok((&[], obj)) => Ok(obj),
+        Ok(_) | Err(Err::Incomplete(_)) => Err(BerError::InvalidLength),
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(e),
+    }
+}
+
+impl<'a> fmt::Debug for LazyBer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("LazyBer");
+        s.field("raw", &self.raw);
+        match self.parsed.get() {
+            Some(parsed) => s.field("parsed", parsed),
+            None => s.field("parsed", &format_args!("<not parsed>")),
+        };
+        s.finish()
+    }
+}
+
+/// Objects are equal if their encodings are equal
+impl<'a> PartialEq for LazyBer<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<'a> Eq for LazyBer<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::{parse_ber, Tag};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_lazy_ber() {
+        // [0] { SEQUENCE { NULL } }, as in a ContentInfo
+        let bytes = hex!("a0 04 30 02 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let lazy = LazyBer::from_content(&obj).expect("not encapsulated");
+        assert_eq!(lazy.raw(), &bytes[2..]);
+        assert_eq!(lazy.get().map(|o| o.tag()), Ok(Tag::Sequence));
+        assert_eq!(lazy.into_object().map(|o| o.children().len()), Ok(1));
+        // errors are cached
+        let lazy = LazyBer::new(&hex!("05 00 05 00"));
+        assert_eq!(lazy.get().map(|_| ()), Err(BerError::InvalidLength));
+        assert!(lazy.is_parsed());
+        let options = ParseOptions::der();
+        let lazy = LazyBer::with_options(&hex!("30 80 00 00"), options);
+        assert!(lazy.get().is_err());
+        let (_, null) = parse_ber(&hex!("05 00")).expect("parsing failed");
+        assert_eq!(
+            LazyBer::from_content(&null).map(|_| ()),
+            Err(BerError::BerTypeError)
+        );
+    }
+}
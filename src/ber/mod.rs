@@ -46,30 +46,90 @@
 //!     .expect("parsing failed");
 //! ```
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+mod arena;
 mod ber;
+#[cfg(feature = "heapless")]
+mod bounded;
+mod chunks;
+mod content;
+mod convert;
+mod datetime;
+#[cfg(feature = "defmt")]
+mod defmt_impl;
+mod diff;
+#[cfg(feature = "digest")]
+mod digest_impl;
+mod fingerprint;
 mod integer;
+mod iso2022;
+mod iter;
+mod lazy;
 mod multi;
+mod options;
+mod owned;
 mod parser;
 mod print;
+mod query;
+mod reader;
+mod recover;
+mod redact;
+mod reuse;
+#[cfg(feature = "serde")]
+mod serde_impl;
 #[cfg(feature = "serialize")]
 mod serialize;
+mod snapshot;
+mod stats;
+#[cfg(feature = "std")]
+mod stream;
+mod tag_name;
 mod tagged;
 mod visit;
 mod visit_mut;
 mod wrap_any;
+#[cfg(feature = "zeroize")]
+mod zeroize_impl;
 
+pub use crate::ber::arena::*;
 pub use crate::ber::ber::*;
+#[cfg(feature = "heapless")]
+pub use crate::ber::bounded::*;
+pub use crate::ber::chunks::*;
+pub use crate::ber::content::*;
+pub use crate::ber::convert::*;
+pub use crate::ber::datetime::*;
+#[cfg(feature = "defmt")]
+pub use crate::ber::defmt_impl::*;
+pub use crate::ber::diff::*;
+pub use crate::ber::iso2022::*;
+pub use crate::ber::iter::*;
+pub use crate::ber::lazy::*;
 pub use crate::ber::multi::*;
+pub use crate::ber::options::*;
+pub use crate::ber::owned::*;
 pub use crate::ber::parser::*;
 pub use crate::ber::print::*;
+pub use crate::ber::query::*;
+pub use crate::ber::reader::*;
+pub use crate::ber::recover::*;
+pub use crate::ber::redact::*;
+pub use crate::ber::reuse::*;
 #[cfg(feature = "serialize")]
 pub use crate::ber::serialize::*;
+pub use crate::ber::snapshot::*;
+pub use crate::ber::stats::*;
+#[cfg(feature = "std")]
+pub use crate::ber::stream::*;
+pub use crate::ber::tag_name::*;
 pub use crate::ber::tagged::*;
 pub use crate::ber::visit::*;
 pub use crate::ber::visit_mut::*;
 pub use crate::ber::wrap_any::*;
 
 pub mod compat;
+pub mod scan;
 
 pub use asn1_rs::{Class, Header, Length, Tag};
 
@@ -31,11 +31,14 @@ use nom::{Err, IResult};
 /// # assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// let (rem, v) = parser(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_ber_sequence_of<'a, F>(f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_sequence_of<'a, F, E>(
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: Fn(&'a [u8]) -> BerResult,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(parse_ber_sequence_of_v(f), BerObject::from_seq)
+    with_span(map(parse_ber_sequence_of_v(f), BerObject::from_seq))
 }
 
 /// Parse a SEQUENCE OF object (returning a vec)
@@ -154,14 +157,17 @@ where
 /// # assert_eq!(localparse_seq(&bytes), Ok((empty, expected)));
 /// let (rem, v) = localparse_seq(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_ber_sequence_defined<'a, F>(mut f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_sequence_defined<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: FnMut(&'a [u8]) -> BerResult<Vec<BerObject>>,
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<BerObject<'a>>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(
+    with_span(map(
         parse_ber_sequence_defined_g(move |data, _| f(data)),
         BerObject::from_seq,
-    )
+    ))
 }
 
 /// Parse a defined SEQUENCE object (generic function)
@@ -256,11 +262,14 @@ where
 /// # assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// let (rem, v) = parser(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_ber_set_of<'a, F>(f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_set_of<'a, F, E>(
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: Fn(&'a [u8]) -> BerResult,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(parse_ber_set_of_v(f), BerObject::from_set)
+    with_span(map(parse_ber_set_of_v(f), BerObject::from_set))
 }
 
 /// Parse a SET OF object (returning a vec)
@@ -377,14 +386,17 @@ where
 /// # assert_eq!(localparse_set(&bytes), Ok((empty, expected)));
 /// let (rem, v) = localparse_set(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_ber_set_defined<'a, F>(mut f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_set_defined<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: FnMut(&'a [u8]) -> BerResult<Vec<BerObject>>,
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<BerObject<'a>>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(
+    with_span(map(
         parse_ber_set_defined_g(move |data, _| f(data)),
         BerObject::from_set,
-    )
+    ))
 }
 
 /// Parse a defined SET object (generic version)
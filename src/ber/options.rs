@@ -0,0 +1,434 @@
+use crate::ber::{ber_read_element_header, BerObject, Header, Length, MAX_RECURSION};
+use crate::der::{DerViolation, Warning};
+use crate::error::{limit_exceeded, object_too_large, BerError, DerConstraint};
+use crate::trace;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::mem::size_of;
+use nom::Offset;
+
+/// Policy used when decoding BOOLEAN values
+///
+/// In BER, any non-zero content octet is TRUE (X.690 8.2.2). In DER, TRUE must be encoded as
+/// `0xff` (X.690 11.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanPolicy {
+    /// Any non-zero octet is decoded as TRUE
+    Lenient,
+    /// Only `0x00` and `0xff` are accepted
+    ///
+    /// Other values raise `DerConstraintFailed(DerConstraint::InvalidBoolean)`.
+    Strict,
+}
+
+/// Policy used when decoding INTEGER and ENUMERATED values with redundant leading octets
+///
+/// The encoding of an integer shall be minimal: the first nine bits cannot be all zeroes or all
+/// ones (X.690 8.3.2). This rule is often violated by BER encoders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerPolicy {
+    /// Non-minimal encodings are accepted
+    Lenient,
+    /// Non-minimal encodings are accepted, and reported as a [`DerViolation::NonMinimalInteger`]
+    /// warning
+    ///
+    /// Warnings are returned by [`parse_ber_with_warnings`](crate::ber::parse_ber_with_warnings)
+    /// (other entry points ignore them).
+    Warn,
+    /// Non-minimal encodings are rejected
+    ///
+    /// They raise `DerConstraintFailed(DerConstraint::IntegerLeadingZeroes)` (or
+    /// `IntegerLeadingFF`) errors.
+    Strict,
+}
+
+/// Policy used for BIT STRING values with non-zero unused bits
+///
+/// The unused bits of the last octet can have any value in BER (X.690 8.6.2.3), but must be zero
+/// in DER (X.690 11.2.1). For signatures and keys, accepting several encodings of the same value
+/// can be a security issue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitStringPolicy {
+    /// Non-zero unused bits are accepted
+    Lenient,
+    /// Non-zero unused bits are accepted, and reported as a [`DerViolation::NonZeroUnusedBits`]
+    /// warning
+    ///
+    /// Use [`BitStringObject::nonzero_unused_bits`](crate::ber::BitStringObject::nonzero_unused_bits)
+    /// to get the offending octet.
+    Warn,
+    /// Non-zero unused bits are rejected
+    ///
+    /// They raise `DerConstraintFailed(DerConstraint::UnusedBitsNotZero)` errors.
+    Strict,
+}
+
+/// Policy used for the primitive/constructed bit of universal objects
+///
+/// Some universal types must use the primitive encoding (for ex. BOOLEAN, INTEGER, NULL,
+/// OBJECT IDENTIFIER, X.690 8.2 to 8.20), and others the constructed encoding (SEQUENCE, SET,
+/// EXTERNAL, EMBEDDED PDV and CHARACTER STRING). Strings and times can use both in BER.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstructedPolicy {
+    /// The bit is only checked when it is required to decode the value
+    ///
+    /// Constructed objects of primitive types are decoded as if they were primitive, and
+    /// primitive SEQUENCE or SET objects raise a `ConstructExpected` error.
+    Lenient,
+    /// The bit is checked for all universal types
+    ///
+    /// Constructed objects of primitive types raise a `ConstructUnexpected` error, and primitive
+    /// objects of constructed types raise a `ConstructExpected` error.
+    Strict,
+}
+
+/// Policy used for end-of-content (EOC) octets
+///
+/// An indefinite-length object is terminated by exactly one EOC, encoded as two zero octets
+/// (X.690 8.1.5). EOC found elsewhere (for ex. padding after the terminating EOC, in the content
+/// of a constructed object) or with content octets are invalid, but are produced by some encoders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EocPolicy {
+    /// Invalid EOC are accepted, and returned as `EndOfContent` objects (their content is
+    /// ignored)
+    Lenient,
+    /// Invalid EOC are accepted, and reported as [`DerViolation::UnexpectedEndOfContent`] (EOC
+    /// not terminating an object) or [`DerViolation::InvalidEndOfContent`] (EOC with content)
+    /// warnings
+    Warn,
+    /// Invalid EOC are rejected
+    ///
+    /// EOC not terminating an object raise an `UnexpectedTag` error, and EOC with content raise
+    /// an `InvalidLength` error.
+    Strict,
+}
+
+/// Options controlling the behavior of the parsers
+///
+/// Options are independent from the entry point used: the [`ber`](ParseOptions::ber) and
+/// [`der`](ParseOptions::der) constructors provide defaults matching the encoding rules, and each
+/// policy can then be changed using the `with_` methods.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber_with_options, BooleanPolicy, ParseOptions};
+///
+/// let bytes = &[0x01, 0x01, 0x01];
+/// let options = ParseOptions::ber().with_boolean_policy(BooleanPolicy::Strict);
+/// assert!(parse_ber_with_options(bytes, &options).is_err());
+///
+/// // SEQUENCE { NULL, NULL }: 3 objects
+/// let bytes = &[0x30, 0x04, 0x05, 0x00, 0x05, 0x00];
+/// let options = ParseOptions::ber().with_max_nodes(2);
+/// let err = parse_ber_with_options(bytes, &options).expect_err("too many objects");
+/// assert_eq!(err, nom::Err::Error(der_parser::error::limit_exceeded()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Maximum recursion depth
+    pub max_depth: usize,
+    /// Policy for BOOLEAN values
+    pub boolean: BooleanPolicy,
+    /// Policy for non-minimal INTEGER and ENUMERATED values
+    pub integer: IntegerPolicy,
+    /// Policy for BIT STRING values with non-zero unused bits
+    pub bitstring: BitStringPolicy,
+    /// Policy for invalid end-of-content octets
+    pub eoc: EocPolicy,
+    /// Policy for the primitive/constructed bit of universal objects
+    pub constructed: ConstructedPolicy,
+    /// Maximum number of objects in the parsed tree (`None` for no limit)
+    pub max_nodes: Option<usize>,
+    /// Maximum declared content length of any object (`None` for no limit)
+    ///
+    /// The length is checked when reading the header, before reading the content: this allows
+    /// rejecting huge objects early, without waiting for (or reading) their content. The content
+    /// of indefinite-length objects is not limited, but their children are.
+    pub max_object_len: Option<usize>,
+    /// Maximum number of bytes allocated to store the parsed tree (`None` for no limit)
+    ///
+    /// Contents are borrowed from the input, so only the storage of the children of constructed
+    /// objects is allocated. This is an approximation: the overhead of vectors growing is ignored.
+    pub max_alloc: Option<usize>,
+    /// Do not decode character strings when parsing
+    ///
+    /// If set, the character set of strings is not checked: primitive strings are stored as
+    /// `Unknown` objects, and decoded when calling [`BerObject::as_str`]. This speeds up parsing
+    /// when only a few strings are used.
+    pub lazy_strings: bool,
+    /// Accept objects with an indefinite length
+    ///
+    /// Indefinite lengths are valid in BER, but not in DER (X.690 10.1). If not set, they raise
+    /// `DerConstraintFailed(DerConstraint::IndefiniteLength)` errors. The DER entry points (for
+    /// ex. [`parse_der_with_warnings`](crate::der::parse_der_with_warnings)) report accepted
+    /// indefinite lengths as warnings.
+    pub allow_indefinite: bool,
+    /// Decode the values of universal primitive objects when parsing
+    ///
+    /// If not set, the values of universal primitive objects (except end-of-content) are not
+    /// decoded or checked: they are stored as
+    /// [`BerObjectContent::Primitive`](crate::ber::BerObjectContent::Primitive) objects, with their
+    /// raw content. This speeds up parsing when only the structure of the tree is used, and
+    /// accepts invalid values (for ex. a BOOLEAN with 2 bytes). Constructed objects are still
+    /// parsed.
+    pub decode_primitives: bool,
+}
+
+impl ParseOptions {
+    /// Default options for BER parsing
+    pub const fn ber() -> Self {
+        ParseOptions {
+            max_depth: MAX_RECURSION,
+            boolean: BooleanPolicy::Lenient,
+            integer: IntegerPolicy::Lenient,
+            bitstring: BitStringPolicy::Lenient,
+            eoc: EocPolicy::Lenient,
+            constructed: ConstructedPolicy::Strict,
+            max_nodes: None,
+            max_object_len: None,
+            max_alloc: None,
+            lazy_strings: false,
+            allow_indefinite: true,
+            decode_primitives: true,
+        }
+    }
+
+    /// Default options for DER parsing
+    ///
+    /// These options only set the policies listed in [`ParseOptions`]: BOOLEAN, INTEGER, BIT
+    /// STRING, EOC and the primitive/constructed bit are strict, and indefinite lengths are
+    /// rejected. Other DER rules, like the format of times (X.690 11.7 and 11.8) or the order of
+    /// SET elements, are not checked by the BER entry points (for ex.
+    /// [`parse_ber_with_options`](crate::ber::parse_ber_with_options)). Use
+    /// [`parse_der_with_options`](crate::der::parse_der_with_options) or
+    /// [`validate_der`](crate::der::validate_der) to check them.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber_with_options, ParseOptions};
+    /// use der_parser::der::parse_der_with_options;
+    ///
+    /// // GeneralizedTime without a time zone
+    /// let bytes = b"\x18\x0e20231010101010";
+    /// assert!(parse_ber_with_options(bytes, &ParseOptions::der()).is_ok());
+    /// assert!(parse_der_with_options(bytes, &ParseOptions::der()).is_err());
+    /// ```
+    pub const fn der() -> Self {
+        ParseOptions {
+            max_depth: MAX_RECURSION,
+            boolean: BooleanPolicy::Strict,
+            integer: IntegerPolicy::Strict,
+            bitstring: BitStringPolicy::Strict,
+            eoc: EocPolicy::Strict,
+            constructed: ConstructedPolicy::Strict,
+            max_nodes: None,
+            max_object_len: None,
+            max_alloc: None,
+            lazy_strings: false,
+            allow_indefinite: false,
+            decode_primitives: true,
+        }
+    }
+
+    /// Set the maximum recursion depth
+    #[inline]
+    pub const fn with_max_depth(self, max_depth: usize) -> Self {
+        ParseOptions { max_depth, ..self }
+    }
+
+    /// Set the policy for BOOLEAN values
+    #[inline]
+    pub const fn with_boolean_policy(self, boolean: BooleanPolicy) -> Self {
+        ParseOptions { boolean, ..self }
+    }
+
+    /// Set the policy for non-minimal INTEGER and ENUMERATED values
+    #[inline]
+    pub const fn with_integer_policy(self, integer: IntegerPolicy) -> Self {
+        ParseOptions { integer, ..self }
+    }
+
+    /// Set the policy for BIT STRING values with non-zero unused bits
+    #[inline]
+    pub const fn with_bitstring_policy(self, bitstring: BitStringPolicy) -> Self {
+        ParseOptions { bitstring, ..self }
+    }
+
+    /// Set the policy for invalid end-of-content octets
+    #[inline]
+    pub const fn with_eoc_policy(self, eoc: EocPolicy) -> Self {
+        ParseOptions { eoc, ..self }
+    }
+
+    /// Set the policy for the primitive/constructed bit of universal objects
+    #[inline]
+    pub const fn with_constructed_policy(self, constructed: ConstructedPolicy) -> Self {
+        ParseOptions {
+            constructed,
+            ..self
+        }
+    }
+
+    /// Set the maximum number of objects in the parsed tree
+    ///
+    /// When the limit is exceeded, parsing fails with [`limit_exceeded`].
+    #[inline]
+    pub const fn with_max_nodes(self, max_nodes: usize) -> Self {
+        let max_nodes = Some(max_nodes);
+        ParseOptions { max_nodes, ..self }
+    }
+
+    /// Set the maximum declared content length of any object
+    ///
+    /// When the limit is exceeded, parsing fails with [`object_too_large`].
+    #[inline]
+    pub const fn with_max_object_len(self, max_object_len: usize) -> Self {
+        let max_object_len = Some(max_object_len);
+        ParseOptions {
+            max_object_len,
+            ..self
+        }
+    }
+
+    /// Set the maximum number of bytes allocated to store the parsed tree
+    ///
+    /// When the limit is exceeded, parsing fails with [`limit_exceeded`].
+    #[inline]
+    pub const fn with_max_alloc(self, max_alloc: usize) -> Self {
+        let max_alloc = Some(max_alloc);
+        ParseOptions { max_alloc, ..self }
+    }
+
+    /// Set whether character strings are decoded when parsing, or when they are accessed
+    #[inline]
+    pub const fn with_lazy_strings(self, lazy_strings: bool) -> Self {
+        ParseOptions {
+            lazy_strings,
+            ..self
+        }
+    }
+
+    /// Set whether objects with an indefinite length are accepted
+    #[inline]
+    pub const fn with_allow_indefinite(self, allow_indefinite: bool) -> Self {
+        ParseOptions {
+            allow_indefinite,
+            ..self
+        }
+    }
+
+    /// Set whether the values of universal primitive objects are decoded when parsing
+    #[inline]
+    pub const fn with_decode_primitives(self, decode_primitives: bool) -> Self {
+        ParseOptions {
+            decode_primitives,
+            ..self
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::ber()
+    }
+}
+
+/// State of a parsing operation: remaining budget, location of objects (for tracing and
+/// warnings), and warnings
+///
+/// All objects must be accounted for using [`ParseState::take`], which also applies the options
+/// checked on headers.
+pub(crate) struct ParseState<'a> {
+    input: &'a [u8],
+    max_depth: usize,
+    nodes: Cell<Option<usize>>,
+    alloc: Cell<Option<usize>>,
+    max_object_len: Option<usize>,
+    allow_indefinite: bool,
+    offset: Cell<usize>,
+    warnings: RefCell<Vec<Warning>>,
+}
+
+impl<'a> ParseState<'a> {
+    /// Create the state for parsing `input`, which starts at depth 0 (with `max_depth` levels)
+    pub(crate) fn new(input: &'a [u8], max_depth: usize, options: &ParseOptions) -> Self {
+        ParseState {
+            input,
+            max_depth,
+            nodes: Cell::new(options.max_nodes),
+            alloc: Cell::new(options.max_alloc),
+            max_object_len: options.max_object_len,
+            allow_indefinite: options.allow_indefinite,
+            offset: Cell::new(0),
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Check the declared length of the object starting at `i`, before reading it
+    ///
+    /// Invalid headers are ignored: they are reported when reading the object.
+    pub(crate) fn check_length(&self, i: &[u8]) -> Result<(), BerError> {
+        let max = match self.max_object_len {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        if let Ok((_, header)) = ber_read_element_header(i) {
+            if let Length::Definite(l) = header.length() {
+                if l > max {
+                    return Err(object_too_large());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Account for the object starting at `i`, which will be stored in the children of a
+    /// constructed object if `child` is true
+    pub(crate) fn take(
+        &self,
+        i: &[u8],
+        header: &Header,
+        max_depth: usize,
+        child: bool,
+    ) -> Result<(), BerError> {
+        fn consume(budget: &Cell<Option<usize>>, n: usize) -> Result<(), BerError> {
+            if let Some(remaining) = budget.get() {
+                let remaining = remaining.checked_sub(n).ok_or_else(limit_exceeded)?;
+                budget.set(Some(remaining));
+            }
+            Ok(())
+        }
+        let offset = self.input.offset(i);
+        self.offset.set(offset);
+        trace::header(offset, self.max_depth - max_depth, header);
+        if !self.allow_indefinite && header.length() == Length::Indefinite {
+            let e = BerError::DerConstraintFailed(DerConstraint::IndefiniteLength);
+            trace::violation(&e);
+            return Err(e);
+        }
+        consume(&self.nodes, 1)?;
+        if child {
+            consume(&self.alloc, size_of::<BerObject>())?;
+        }
+        Ok(())
+    }
+
+    /// Report a violation for the last object accounted for by [`ParseState::take`]
+    pub(crate) fn warn(&self, violation: DerViolation) {
+        let offset = self.offset.get();
+        // the path is set when the tree is complete
+        self.warnings.borrow_mut().push(Warning {
+            violation,
+            offset,
+            path: Vec::new(),
+        });
+    }
+
+    /// Return the reported warnings, in the order of the objects
+    pub(crate) fn into_warnings(self) -> Vec<Warning> {
+        self.warnings.into_inner()
+    }
+}
@@ -0,0 +1,345 @@
+use crate::ber::*;
+use crate::oid::Oid;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use asn1_rs::{ASN1DateTime, Any};
+use core::hash::{Hash, Hasher};
+
+/// Owned representation of a BER-encoded (X.690) object
+///
+/// This is a deep copy of a [`BerObject`], which does not borrow the input buffer. It can be
+/// stored, or sent across threads, after the input data has been released.
+///
+/// Use [`BerObject::to_owned_object`] to build an owned object, and [`BerObjectOwned::as_ber`] to
+/// get a borrowed view (for ex. to use the accessors of `BerObject`).
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, BerObjectOwned};
+///
+/// fn parse_owned(bytes: &[u8]) -> Option<BerObjectOwned> {
+///     let (_, obj) = parse_ber(bytes).ok()?;
+///     Some(obj.to_owned_object())
+/// }
+///
+/// let owned = parse_owned(&[0x02, 0x01, 0x05]).expect("parsing failed");
+/// assert_eq!(owned.as_ber().as_u32(), Ok(5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BerObjectOwned {
+    pub header: Header<'static>,
+    pub content: BerObjectContentOwned,
+}
+
+/// Owned BER object content
+///
+/// See [`BerObjectContent`] for the description of each variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum BerObjectContentOwned {
+    EndOfContent,
+    Boolean(bool),
+    Integer(Vec<u8>),
+    BitString(u8, Vec<u8>),
+    OctetString(Vec<u8>),
+    Null,
+    Enum(u64),
+    OID(Oid<'static>),
+    RelativeOID(Oid<'static>),
+    NumericString(String),
+    VisibleString(String),
+    PrintableString(String),
+    IA5String(String),
+    UTF8String(String),
+    T61String(String),
+    VideotexString(String),
+    BmpString(String),
+    UniversalString(Vec<u8>),
+    CharacterString(CharacterStringIdentificationOwned, Vec<u8>),
+    Sequence(Vec<BerObjectOwned>),
+    Set(Vec<BerObjectOwned>),
+    UTCTime(ASN1DateTime),
+    GeneralizedTime(ASN1DateTime),
+    ObjectDescriptor(String),
+    GraphicString(String),
+    GeneralString(String),
+    Optional(Option<Box<BerObjectOwned>>),
+    Tagged(Class, Tag, Box<BerObjectOwned>),
+    /// Object with a tag number too large for `Tag`: raw identifier octets and raw content
+    TagOverflow(Vec<u8>, Vec<u8>),
+    /// Universal primitive object which was not decoded: tag and raw content
+    Primitive(Tag, Vec<u8>),
+    /// Private or Unknown object: header and raw content
+    Unknown(Header<'static>, Vec<u8>),
+}
+
+/// Owned identification of a CHARACTER STRING
+///
+/// See [`CharacterStringIdentification`] for the description of each variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CharacterStringIdentificationOwned {
+    Syntaxes {
+        abstract_syntax: Oid<'static>,
+        transfer_syntax: Oid<'static>,
+    },
+    Syntax(Oid<'static>),
+    PresentationContextId(Vec<u8>),
+    ContextNegotiation {
+        presentation_context_id: Vec<u8>,
+        transfer_syntax: Oid<'static>,
+    },
+    TransferSyntax(Oid<'static>),
+    Fixed,
+}
+
+fn header_to_owned(header: &Header) -> Header<'static> {
+    Header::new(
+        header.class(),
+        header.constructed(),
+        header.tag(),
+        header.length(),
+    )
+    .with_raw_tag(header.raw_tag().map(|t| Cow::Owned(t.to_vec())))
+}
+
+fn header_as_ref<'a>(header: &'a Header<'static>) -> Header<'a> {
+    Header::new(
+        header.class(),
+        header.constructed(),
+        header.tag(),
+        header.length(),
+    )
+    .with_raw_tag(header.raw_tag().map(Cow::Borrowed))
+}
+
+impl<'a> BerObject<'a> {
+    /// Build an owned deep copy of the object, which does not borrow the input
+    ///
+    /// This is the same as `BerObjectOwned::from(&obj)`.
+    pub fn to_owned_object(&self) -> BerObjectOwned {
+        BerObjectOwned {
+            header: header_to_owned(&self.header),
+            content: self.content.to_owned_content(),
+        }
+    }
+}
+
+impl<'a> BerObjectContent<'a> {
+    /// Build an owned deep copy of the content, which does not borrow the input
+    pub fn to_owned_content(&self) -> BerObjectContentOwned {
+        use BerObjectContentOwned as O;
+        match self {
+            BerObjectContent::EndOfContent => O::EndOfContent,
+            BerObjectContent::Boolean(b) => O::Boolean(*b),
+            BerObjectContent::Integer(i) => O::Integer(i.to_vec()),
+            BerObjectContent::BitString(u, b) => O::BitString(*u, b.data.to_vec()),
+            BerObjectContent::OctetString(s) => O::OctetString(s.to_vec()),
+            BerObjectContent::Null => O::Null,
+            BerObjectContent::Enum(e) => O::Enum(*e),
+            BerObjectContent::OID(oid) => O::OID(oid.to_owned()),
+            BerObjectContent::RelativeOID(oid) => O::RelativeOID(oid.to_owned()),
+            BerObjectContent::NumericString(s) => O::NumericString((*s).to_owned()),
+            BerObjectContent::VisibleString(s) => O::VisibleString((*s).to_owned()),
+            BerObjectContent::PrintableString(s) => O::PrintableString((*s).to_owned()),
+            BerObjectContent::IA5String(s) => O::IA5String((*s).to_owned()),
+            BerObjectContent::UTF8String(s) => O::UTF8String((*s).to_owned()),
+            BerObjectContent::T61String(s) => O::T61String((*s).to_owned()),
+            BerObjectContent::VideotexString(s) => O::VideotexString((*s).to_owned()),
+            BerObjectContent::BmpString(s) => O::BmpString((*s).to_owned()),
+            BerObjectContent::UniversalString(s) => O::UniversalString(s.to_vec()),
+            BerObjectContent::CharacterString(c) => O::CharacterString(
+                c.identification.to_owned_identification(),
+                c.string_value.to_vec(),
+            ),
+            BerObjectContent::Sequence(v) => {
+                O::Sequence(v.iter().map(BerObject::to_owned_object).collect())
+            }
+            BerObjectContent::Set(v) => O::Set(v.iter().map(BerObject::to_owned_object).collect()),
+            BerObjectContent::UTCTime(t) => O::UTCTime(t.clone()),
+            BerObjectContent::GeneralizedTime(t) => O::GeneralizedTime(t.clone()),
+            BerObjectContent::ObjectDescriptor(s) => O::ObjectDescriptor((*s).to_owned()),
+            BerObjectContent::GraphicString(s) => O::GraphicString((*s).to_owned()),
+            BerObjectContent::GeneralString(s) => O::GeneralString((*s).to_owned()),
+            BerObjectContent::Optional(o) => O::Optional(
+                o.as_ref()
+                    .map(|obj| Box::new(BerObject::to_owned_object(obj))),
+            ),
+            BerObjectContent::Tagged(class, tag, obj) => {
+                O::Tagged(*class, *tag, Box::new(BerObject::to_owned_object(obj)))
+            }
+            BerObjectContent::TagOverflow(identifier, data) => {
+                O::TagOverflow(identifier.to_vec(), data.to_vec())
+            }
+            BerObjectContent::Primitive(tag, data) => O::Primitive(*tag, data.to_vec()),
+            BerObjectContent::Unknown(any) => {
+                O::Unknown(header_to_owned(&any.header), any.data.to_vec())
+            }
+        }
+    }
+}
+
+impl<'a> CharacterStringIdentification<'a> {
+    /// Build an owned deep copy of the identification, which does not borrow the input
+    pub fn to_owned_identification(&self) -> CharacterStringIdentificationOwned {
+        use CharacterStringIdentificationOwned as O;
+        match self {
+            CharacterStringIdentification::Syntaxes {
+                abstract_syntax,
+                transfer_syntax,
+            } => O::Syntaxes {
+                abstract_syntax: abstract_syntax.to_owned(),
+                transfer_syntax: transfer_syntax.to_owned(),
+            },
+            CharacterStringIdentification::Syntax(oid) => O::Syntax(oid.to_owned()),
+            CharacterStringIdentification::PresentationContextId(id) => {
+                O::PresentationContextId(id.to_vec())
+            }
+            CharacterStringIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax,
+            } => O::ContextNegotiation {
+                presentation_context_id: presentation_context_id.to_vec(),
+                transfer_syntax: transfer_syntax.to_owned(),
+            },
+            CharacterStringIdentification::TransferSyntax(oid) => O::TransferSyntax(oid.to_owned()),
+            CharacterStringIdentification::Fixed => O::Fixed,
+        }
+    }
+}
+
+impl BerObjectOwned {
+    /// Get a borrowed view of this object
+    pub fn as_ber(&self) -> BerObject<'_> {
+        BerObject {
+            header: header_as_ref(&self.header),
+            content: self.content.as_ber(),
+            span: None,
+        }
+    }
+}
+
+impl BerObjectContentOwned {
+    /// Get a borrowed view of this content
+    pub fn as_ber(&self) -> BerObjectContent<'_> {
+        use BerObjectContentOwned as O;
+        match self {
+            O::EndOfContent => BerObjectContent::EndOfContent,
+            O::Boolean(b) => BerObjectContent::Boolean(*b),
+            O::Integer(i) => BerObjectContent::Integer(i),
+            O::BitString(u, data) => BerObjectContent::BitString(*u, BitStringObject { data }),
+            O::OctetString(s) => BerObjectContent::OctetString(s),
+            O::Null => BerObjectContent::Null,
+            O::Enum(e) => BerObjectContent::Enum(*e),
+            O::OID(oid) => BerObjectContent::OID(oid.clone()),
+            O::RelativeOID(oid) => BerObjectContent::RelativeOID(oid.clone()),
+            O::NumericString(s) => BerObjectContent::NumericString(s),
+            O::VisibleString(s) => BerObjectContent::VisibleString(s),
+            O::PrintableString(s) => BerObjectContent::PrintableString(s),
+            O::IA5String(s) => BerObjectContent::IA5String(s),
+            O::UTF8String(s) => BerObjectContent::UTF8String(s),
+            O::T61String(s) => BerObjectContent::T61String(s),
+            O::VideotexString(s) => BerObjectContent::VideotexString(s),
+            O::BmpString(s) => BerObjectContent::BmpString(s),
+            O::UniversalString(s) => BerObjectContent::UniversalString(s),
+            O::CharacterString(id, value) => {
+                BerObjectContent::CharacterString(CharacterStringObject {
+                    identification: id.as_ber(),
+                    string_value: value,
+                })
+            }
+            O::Sequence(v) => {
+                BerObjectContent::Sequence(v.iter().map(BerObjectOwned::as_ber).collect())
+            }
+            O::Set(v) => BerObjectContent::Set(v.iter().map(BerObjectOwned::as_ber).collect()),
+            O::UTCTime(t) => BerObjectContent::UTCTime(t.clone()),
+            O::GeneralizedTime(t) => BerObjectContent::GeneralizedTime(t.clone()),
+            O::ObjectDescriptor(s) => BerObjectContent::ObjectDescriptor(s),
+            O::GraphicString(s) => BerObjectContent::GraphicString(s),
+            O::GeneralString(s) => BerObjectContent::GeneralString(s),
+            O::Optional(o) => {
+                BerObjectContent::Optional(o.as_ref().map(|obj| Box::new(obj.as_ber())))
+            }
+            O::Tagged(class, tag, obj) => {
+                BerObjectContent::Tagged(*class, *tag, Box::new(obj.as_ber()))
+            }
+            O::TagOverflow(identifier, data) => BerObjectContent::TagOverflow(identifier, data),
+            O::Primitive(tag, data) => BerObjectContent::Primitive(*tag, data),
+            O::Unknown(header, data) => {
+                BerObjectContent::Unknown(Any::new(header_as_ref(header), data))
+            }
+        }
+    }
+}
+
+impl CharacterStringIdentificationOwned {
+    /// Get a borrowed view of this identification
+    pub fn as_ber(&self) -> CharacterStringIdentification<'_> {
+        use CharacterStringIdentificationOwned as O;
+        match self {
+            O::Syntaxes {
+                abstract_syntax,
+                transfer_syntax,
+            } => CharacterStringIdentification::Syntaxes {
+                abstract_syntax: abstract_syntax.clone(),
+                transfer_syntax: transfer_syntax.clone(),
+            },
+            O::Syntax(oid) => CharacterStringIdentification::Syntax(oid.clone()),
+            O::PresentationContextId(id) => {
+                CharacterStringIdentification::PresentationContextId(id)
+            }
+            O::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax,
+            } => CharacterStringIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax: transfer_syntax.clone(),
+            },
+            O::TransferSyntax(oid) => CharacterStringIdentification::TransferSyntax(oid.clone()),
+            O::Fixed => CharacterStringIdentification::Fixed,
+        }
+    }
+}
+
+impl Hash for BerObjectOwned {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ber().hash(state)
+    }
+}
+
+impl<'a> From<&BerObject<'a>> for BerObjectOwned {
+    fn from(obj: &BerObject<'a>) -> Self {
+        obj.to_owned_object()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_owned_roundtrip() {
+        let bytes = hex!(
+            "30 1b 02 01 01 06 03 2a 03 04 0c 03 61 62 63 a0 03 01 01 ff 3d 07 a0 02 85 00 82 01 61"
+        );
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let owned = {
+            let copy = bytes.to_vec();
+            let (_, obj) = parse_ber(&copy).expect("parsing failed");
+            obj.to_owned_object()
+        };
+        assert_eq!(owned.as_ber(), obj);
+    }
+
+    #[test]
+    fn test_owned_is_static_send() {
+        fn assert_static_send<T: Send + Sync + 'static>(_: &T) {}
+        let (_, obj) = parse_ber(&hex!("04 02 01 02")).expect("parsing failed");
+        let owned = obj.to_owned_object();
+        assert_static_send(&owned);
+        assert_eq!(owned.as_ber().as_slice(), Ok(&[1, 2][..]));
+    }
+}
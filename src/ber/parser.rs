@@ -1,8 +1,14 @@
+use crate::ber::options::ParseState;
+use crate::ber::wrap_any::parse_ber_any_with_state_r;
 use crate::ber::*;
+use crate::der::{locate_warnings, Warning};
 use crate::error::*;
+use alloc::vec::Vec;
 use asn1_rs::FromBer;
+use core::mem::size_of;
 use nom::bytes::streaming::take;
-use nom::{Err, Offset};
+use nom::{Err, IResult, Needed, Offset};
+use rusticata_macros::custom_check;
 
 /// Default maximum recursion limit
 pub const MAX_RECURSION: usize = 50;
@@ -35,6 +41,10 @@ pub(crate) fn ber_skip_object_content<'a>(
             // this is recursive
             let mut i = i;
             loop {
+                if i.is_empty() {
+                    // at least the end-of-content octets are missing
+                    return Err(Err::Incomplete(Needed::new(2)));
+                }
                 let (i2, header2) = ber_read_element_header(i)?;
                 let (i3, eoc) = ber_skip_object_content(i2, &header2, max_depth - 1)?;
                 if eoc {
@@ -114,7 +124,86 @@ pub(crate) fn bitstring_to_u64(
 /// ```
 #[inline]
 pub fn ber_read_element_header(i: &[u8]) -> BerResult<Header> {
-    Header::from_ber(i)
+    refine_length_overflow(i, Header::from_ber(i))
+}
+
+/// Read an object header, and return the location of the identifier and length octets
+///
+/// The content is not read: the span only covers the header (its content is empty). The content
+/// starts after the span, and its length is given by the header.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::ber::{ber_read_element_header_with_span, Length};
+///
+/// // [APPLICATION 200] IMPLICIT, with a long form length
+/// let bytes = &[0x5f, 0x81, 0x48, 0x81, 0x02, 0x01, 0x02];
+/// let (i, (hdr, span)) = ber_read_element_header_with_span(bytes).expect("could not read header");
+/// assert_eq!(span.identifier(), &bytes[..3]);
+/// assert_eq!(span.length_octets(), &bytes[3..5]);
+/// assert_eq!(span.range_in(bytes), Some(0..5));
+/// assert_eq!((hdr.length(), i.len()), (Length::Definite(2), 2));
+/// ```
+pub fn ber_read_element_header_with_span(i: &[u8]) -> BerResult<(Header, Span)> {
+    let (rem, hdr) = ber_read_element_header(i)?;
+    let span = Span::from_raw(&i[..i.len() - rem.len()], 0, Length::Definite(0));
+    Ok((rem, (hdr, span)))
+}
+
+/// Replace an error by `UnexpectedTag` if the tag of the object at `i` is not `tag`
+///
+/// This is used after parsers checking other properties (for ex. the primitive bit) first, so
+/// the error reports the expected and actual tags. The header is read again only on error.
+pub(crate) fn refine_unexpected_tag<'a, T>(
+    i: &[u8],
+    tag: Tag,
+    r: BerResult<'a, T>,
+) -> BerResult<'a, T> {
+    match r {
+        Err(Err::Error(e)) => match ber_read_element_header(i) {
+            Ok((_, hdr)) if hdr.tag() != tag => Err(Err::Error(BerError::UnexpectedTag {
+                expected: Some(tag),
+                actual: hdr.tag(),
+            })),
+            _ => Err(Err::Error(e)),
+        },
+        r => r,
+    }
+}
+
+/// Replace an `InvalidLength` error by [`length_overflow`] if the length declared in the header
+/// at `i` does not fit in a `usize`
+pub(crate) fn refine_length_overflow<'a, T>(i: &[u8], r: BerResult<'a, T>) -> BerResult<'a, T> {
+    match r {
+        Err(Err::Error(BerError::InvalidLength)) if length_overflows(i) => {
+            Err(Err::Error(length_overflow()))
+        }
+        r => r,
+    }
+}
+
+/// Test if the header at `i` declares a length which does not fit in a `usize`
+fn length_overflows(i: &[u8]) -> bool {
+    let mut idx = 1;
+    if i.first().map_or(false, |b| b & 0x1f == 0x1f) {
+        // skip subsequent octets of the tag
+        while i.get(idx).map_or(false, |b| b & 0x80 != 0) {
+            idx += 1;
+        }
+        idx += 1;
+    }
+    let n = match i.get(idx) {
+        Some(&b) if b > 0x80 && b != 0xff => usize::from(b & 0x7f),
+        _ => return false,
+    };
+    match i.get(idx + 1..idx + 1 + n) {
+        Some(octets) => {
+            let significant = octets.iter().skip_while(|&&b| b == 0).count();
+            significant > size_of::<usize>()
+        }
+        None => false,
+    }
 }
 
 /// Parse the next bytes as the *content* of a BER object.
@@ -151,6 +240,59 @@ pub fn ber_read_element_content_as(
     try_read_berobjectcontent_as(i, tag, length, constructed, max_depth)
 }
 
+/// Parse `data` as the content of a universal type, selected at runtime by `tag`
+///
+/// All of `data` must be used by the content. This is the building block for implicitly tagged
+/// values, where the type is defined by a schema and not by the header: `tag` and `constructed`
+/// describe the universal type, regardless of the class and tag of the enclosing object.
+///
+/// Content type is *not* checked to match tag. Unknown tags return the raw content
+/// (`BerObjectContent::Unknown`). An error is returned if the content is invalid for the type,
+/// or if it does not use all of `data` (`InvalidLength`).
+///
+/// ### Example
+///
+/// Decoding `[0] IMPLICIT` values, where the type is only known at runtime:
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_content, parse_ber_tagged_implicit_g, Tag};
+/// use der_parser::error::BerResult;
+///
+/// fn parse_implicit(i: &[u8], tag: Tag) -> BerResult<'_, u32> {
+///     parse_ber_tagged_implicit_g(0, |content, hdr, _| {
+///         let value = ber_read_content(tag, hdr.is_constructed(), content)?;
+///         Ok((&b""[..], value.as_u32()?))
+///     })(i)
+/// }
+///
+/// let bytes = &[0x80, 0x02, 0x01, 0x00];
+/// assert_eq!(parse_implicit(bytes, Tag::Integer), Ok((&b""[..], 256)));
+/// // [0] IMPLICIT BOOLEAN: invalid length
+/// assert!(parse_implicit(bytes, Tag::Boolean).is_err());
+/// ```
+pub fn ber_read_content(
+    tag: Tag,
+    constructed: bool,
+    data: &[u8],
+) -> Result<BerObjectContent, BerError> {
+    let length = Length::Definite(data.len());
+    let (rem, content) =
+        try_read_berobjectcontent_as(data, tag, length, constructed, MAX_RECURSION).map_err(
+            |e| match e {
+                // the content is complete, so incomplete data means an invalid length
+                Err::Incomplete(_) => BerError::InvalidLength,
+                Err::Error(BerError::Incomplete(_)) | Err::Failure(BerError::Incomplete(_)) => {
+                    BerError::InvalidLength
+                }
+                Err::Error(e) | Err::Failure(e) => e,
+            },
+        )?;
+    if !rem.is_empty() {
+        return Err(BerError::InvalidLength);
+    }
+    Ok(content)
+}
+
 /// Parse the next bytes as the content of a BER object (combinator, header reference)
 ///
 /// Content type is *not* checked to match tag, caller is responsible of providing the correct tag
@@ -217,6 +359,9 @@ pub fn parse_ber_content2<'a>(
 
 /// Parse a BER object, expecting a value with specified tag
 ///
+/// The class is not checked. If the tag does not match, an `UnexpectedTag` error is returned,
+/// with the expected and actual tags.
+///
 /// The object is parsed recursively, with a maximum depth of `MAX_RECURSION`.
 ///
 /// ### Example
@@ -231,17 +376,58 @@ pub fn parse_ber_content2<'a>(
 /// assert_eq!(obj.header.tag(), Tag::Integer);
 /// ```
 pub fn parse_ber_with_tag<T: Into<Tag>>(i: &[u8], tag: T) -> BerResult {
+    parse_ber_with_tag_r(i, tag, MAX_RECURSION)
+}
+
+/// Parse a BER object, expecting a value with specified tag, specifying the maximum recursion
+/// depth
+///
+/// This is the same as [`parse_ber_with_tag`], with the remaining depth passed explicitly (see
+/// [`parse_ber_recursive`]), so the parser can be called from another recursive parser sharing
+/// the same budget. Raise a `BerMaxDepth` error if `max_depth` is 0.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::ber::{ber_read_element_header, parse_ber_with_tag_r, Class, Tag};
+/// use der_parser::error::{BerError, BerResult};
+///
+/// // Value ::= CHOICE { int INTEGER, nested [0] EXPLICIT Value }
+/// fn parse_value(i: &[u8], max_depth: usize) -> BerResult<'_, u32> {
+///     if max_depth == 0 {
+///         return Err(nom::Err::Error(BerError::BerMaxDepth));
+///     }
+///     let (data, hdr) = ber_read_element_header(i)?;
+///     if hdr.class() == Class::ContextSpecific && hdr.tag() == Tag(0) {
+///         let len = hdr.length().definite()?;
+///         let content = data.get(..len).ok_or(BerError::InvalidLength)?;
+///         let (_, value) = parse_value(content, max_depth - 1)?;
+///         return Ok((&data[len..], value));
+///     }
+///     let (rem, obj) = parse_ber_with_tag_r(i, Tag::Integer, max_depth)?;
+///     Ok((rem, obj.as_u32()?))
+/// }
+///
+/// let bytes = &[0xa0, 0x05, 0xa0, 0x03, 0x02, 0x01, 0x07];
+/// assert_eq!(parse_value(bytes, 3).map(|(_, v)| v), Ok(7));
+/// // the depth budget is shared with the nested parsers
+/// assert!(parse_value(bytes, 2).is_err());
+/// ```
+pub fn parse_ber_with_tag_r<T: Into<Tag>>(i: &[u8], tag: T, max_depth: usize) -> BerResult {
+    custom_check!(i, max_depth == 0, BerError::BerMaxDepth)?;
     let tag = tag.into();
-    let (i, hdr) = ber_read_element_header(i)?;
+    let (data, hdr) = ber_read_element_header(i)?;
     hdr.assert_tag(tag)?;
-    let (i, content) = ber_read_element_content_as(
-        i,
+    let (rem, content) = ber_read_element_content_as(
+        data,
         hdr.tag(),
         hdr.length(),
         hdr.is_constructed(),
-        MAX_RECURSION,
+        max_depth,
     )?;
-    Ok((i, BerObject::from_header_and_content(hdr, content)))
+    let span = Span::from_input(i, data, rem, hdr.length());
+    let obj = BerObject::from_header_and_content(hdr, content).with_span(span);
+    Ok((rem, obj))
 }
 
 /// Read end of content marker
@@ -442,6 +628,14 @@ pub fn parse_ber_universalstring(i: &[u8]) -> BerResult {
     parse_ber_with_tag(i, Tag::UniversalString)
 }
 
+/// Read an (unrestricted) CHARACTER STRING value
+///
+/// The identification component is decoded, and the string value is returned as raw bytes.
+#[inline]
+pub fn parse_ber_characterstring(i: &[u8]) -> BerResult {
+    parse_ber_with_tag(i, Tag::CharacterString)
+}
+
 /// Parse an optional tagged object, applying function to get content
 ///
 /// This function returns a `BerObject`, trying to read content as generic BER objects.
@@ -456,12 +650,15 @@ pub fn parse_ber_explicit_optional<F>(i: &[u8], tag: Tag, f: F) -> BerResult
 where
     F: Fn(&[u8]) -> BerResult,
 {
-    parse_ber_optional(parse_ber_tagged_explicit_g(tag, |content, hdr| {
-        let (rem, obj) = f(content)?;
-        let content = BerObjectContent::Tagged(hdr.class(), hdr.tag(), Box::new(obj));
-        let tagged = BerObject::from_header_and_content(hdr, content);
-        Ok((rem, tagged))
-    }))(i)
+    parse_ber_optional(with_span(parse_ber_tagged_explicit_g(
+        tag,
+        |content, hdr| {
+            let (rem, obj) = f(content)?;
+            let content = BerObjectContent::Tagged(hdr.class(), hdr.tag(), Box::new(obj));
+            let tagged = BerObject::from_header_and_content(hdr, content);
+            Ok((rem, tagged))
+        },
+    )))(i)
 }
 
 /// Parse an implicit tagged object, applying function to read content
@@ -535,18 +732,22 @@ where
 /// assert_eq!(obj.header.tag(), Tag::Integer);
 /// assert!(obj.as_optional().is_ok());
 /// ```
-pub fn parse_ber_optional<'a, F>(mut f: F) -> impl FnMut(&'a [u8]) -> BerResult<'a>
+pub fn parse_ber_optional<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: FnMut(&'a [u8]) -> BerResult<'a>,
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
 {
     move |i: &[u8]| {
         let res = f(i);
         match res {
             Ok((rem, inner)) => {
-                let opt = BerObject::from_header_and_content(
+                let span = inner.span;
+                let mut opt = BerObject::from_header_and_content(
                     inner.header.clone(),
                     BerObjectContent::Optional(Some(Box::new(inner))),
                 );
+                opt.span = span;
                 Ok((rem, opt))
             }
             Err(_) => Ok((i, BerObject::from_obj(BerObjectContent::Optional(None)))),
@@ -554,13 +755,26 @@ where
     }
 }
 
+/// Wrap a parser building an object, and set the location of the object in the input
+pub(crate) fn with_span<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+{
+    move |i: &'a [u8]| {
+        let (rem, obj) = f(i)?;
+        Ok((rem, obj.with_span_from(i, rem)))
+    }
+}
+
 /// Parse BER object and try to decode it as a 32-bits signed integer
 ///
 /// Return `IntegerTooLarge` if object is an integer, but can not be represented in the target
 /// integer type.
 #[inline]
 pub fn parse_ber_i32(i: &[u8]) -> BerResult<i32> {
-    <i32>::from_ber(i)
+    refine_unexpected_tag(i, Tag::Integer, <i32>::from_ber(i))
 }
 
 /// Parse BER object and try to decode it as a 64-bits signed integer
@@ -569,7 +783,7 @@ pub fn parse_ber_i32(i: &[u8]) -> BerResult<i32> {
 /// integer type.
 #[inline]
 pub fn parse_ber_i64(i: &[u8]) -> BerResult<i64> {
-    <i64>::from_ber(i)
+    refine_unexpected_tag(i, Tag::Integer, <i64>::from_ber(i))
 }
 
 /// Parse BER object and try to decode it as a 32-bits unsigned integer
@@ -578,7 +792,7 @@ pub fn parse_ber_i64(i: &[u8]) -> BerResult<i64> {
 /// integer type.
 #[inline]
 pub fn parse_ber_u32(i: &[u8]) -> BerResult<u32> {
-    <u32>::from_ber(i)
+    refine_unexpected_tag(i, Tag::Integer, <u32>::from_ber(i))
 }
 
 /// Parse BER object and try to decode it as a 64-bits unsigned integer
@@ -587,7 +801,7 @@ pub fn parse_ber_u32(i: &[u8]) -> BerResult<u32> {
 /// integer type.
 #[inline]
 pub fn parse_ber_u64(i: &[u8]) -> BerResult<u64> {
-    <u64>::from_ber(i)
+    refine_unexpected_tag(i, Tag::Integer, <u64>::from_ber(i))
 }
 
 /// Parse BER object and get content as slice
@@ -604,6 +818,11 @@ pub fn parse_ber_slice<T: Into<Tag>>(i: &[u8], tag: T) -> BerResult<&[u8]> {
 ///
 /// Return a tuple containing the remaining (unparsed) bytes and the BER Object, or an error.
 ///
+/// `max_depth` is the remaining depth budget: each level of nesting, including the object
+/// itself, uses one unit, and a `BerMaxDepth` error is raised when it is exhausted. For ex.
+/// a SEQUENCE containing an INTEGER requires a value of at least 2. Parsers embedding this one in
+/// another recursive grammar should pass their own remaining budget.
+///
 /// ### Example
 ///
 /// ```
@@ -640,6 +859,82 @@ pub fn parse_ber(i: &[u8]) -> BerResult {
     parse_ber_recursive(i, MAX_RECURSION)
 }
 
+/// Parse BER object recursively, and return the location of the error if parsing failed
+///
+/// See [`LocatedError`] for details on how the error is located.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::ber::parse_ber_located;
+///
+/// // the length of the second INTEGER is invalid
+/// let bytes = &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x02, 0x01];
+/// let err = parse_ber_located(bytes).expect_err("parsing should fail");
+/// assert_eq!(err.offset, 5);
+/// ```
+pub fn parse_ber_located<'a>(i: &'a [u8]) -> Result<(&'a [u8], BerObject<'a>), LocatedError> {
+    parse_ber(i).map_err(|e| LocatedError::locate(i, e, parse_ber))
+}
+
+/// Parse BER object recursively, using the provided options
+///
+/// Return a tuple containing the remaining (unparsed) bytes and the BER Object, or an error.
+///
+/// The policies (for ex. BOOLEAN decoding) are applied to all objects, including nested ones.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::ber::{parse_ber_with_options, ParseOptions};
+///
+/// let bytes = &[0x01, 0x01, 0x01];
+/// let (_, obj) = parse_ber_with_options(bytes, &ParseOptions::ber()).expect("parsing failed");
+/// assert_eq!(obj.as_bool(), Ok(true));
+///
+/// // DER does not accept 0x01 for TRUE
+/// assert!(parse_ber_with_options(bytes, &ParseOptions::der()).is_err());
+/// ```
+#[inline]
+pub fn parse_ber_with_options<'a>(i: &'a [u8], options: &ParseOptions) -> BerResult<'a> {
+    parse_ber_any_with_options_r(i, options.max_depth, options)
+}
+
+/// Parse BER object recursively, using the provided options, and return the warnings
+///
+/// Return a tuple containing the remaining (unparsed) bytes and the BER Object with the list of
+/// warnings, or an error.
+///
+/// Warnings are DER violations accepted by the policies (for ex. [`IntegerPolicy::Warn`]), in
+/// the order of the objects. Their offsets are relative to `i`.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::ber::{parse_ber_with_warnings, IntegerPolicy, ParseOptions};
+/// use der_parser::der::DerViolation;
+///
+/// // INTEGER 1, with a redundant leading zero
+/// let bytes = &[0x02, 0x02, 0x00, 0x01];
+/// let options = ParseOptions::ber().with_integer_policy(IntegerPolicy::Warn);
+/// let (_, (obj, warnings)) = parse_ber_with_warnings(bytes, &options).expect("parsing failed");
+/// assert_eq!(obj.as_u32(), Ok(1));
+/// assert_eq!(warnings[0].violation, DerViolation::NonMinimalInteger);
+///
+/// let options = ParseOptions::ber().with_integer_policy(IntegerPolicy::Strict);
+/// assert!(parse_ber_with_warnings(bytes, &options).is_err());
+/// ```
+pub fn parse_ber_with_warnings<'a>(
+    i: &'a [u8],
+    options: &ParseOptions,
+) -> BerResult<'a, (BerObject<'a>, Vec<Warning>)> {
+    let state = ParseState::new(i, options.max_depth, options);
+    let (rem, obj) = parse_ber_any_with_state_r(i, options.max_depth, options, &state)?;
+    let mut warnings = state.into_warnings();
+    locate_warnings(&obj, i, &mut warnings);
+    Ok((rem, (obj, warnings)))
+}
+
 #[test]
 fn test_bitstring_to_u64() {
     // ignored bits modulo 8 to 0
@@ -1,5 +1,6 @@
 use crate::ber::BitStringObject;
-use crate::ber::{BerObject, BerObjectContent};
+use crate::ber::{universal_tag_name, BerObject, BerObjectContent};
+use crate::schema::{self, Schema};
 use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
@@ -20,18 +21,37 @@ pub enum PrettyPrinterFlag {
 /// Pretty-print BER object
 ///
 /// This method is recursive by default. To prevent that, unset the `Recursive` flag.
+///
+/// If a [`Schema`] is set, objects are prefixed by the name of the matching field (for ex.
+/// `serialNumber: Integer(<12 34>)`), the name of the alternative is printed for CHOICE types,
+/// and the content of tagged objects is decoded and printed as the underlying type. Objects not
+/// matching the schema are printed without name.
 pub struct PrettyBer<'a> {
     obj: &'a BerObject<'a>,
     indent: usize,
     inc: usize,
 
     flags: Vec<PrettyPrinterFlag>,
+    schema: Option<&'a Schema>,
+    label: Option<&'a str>,
 }
 
 impl<'a> BerObject<'a> {
     pub fn as_pretty(&'a self, indent: usize, increment: usize) -> PrettyBer<'a> {
         PrettyBer::new(self, vec![PrettyPrinterFlag::Recursive], indent, increment)
     }
+
+    /// Pretty-print the object, using `schema` to print the names of the fields
+    pub fn as_pretty_with_schema(
+        &'a self,
+        schema: &'a Schema,
+        indent: usize,
+        increment: usize,
+    ) -> PrettyBer<'a> {
+        let mut pp = self.as_pretty(indent, increment);
+        pp.set_schema(schema);
+        pp
+    }
 }
 
 impl<'a> PrettyBer<'a> {
@@ -46,9 +66,16 @@ impl<'a> PrettyBer<'a> {
             indent,
             inc: increment,
             flags,
+            schema: None,
+            label: None,
         }
     }
 
+    /// Set the schema used to name the fields and decode tagged objects
+    pub fn set_schema(&mut self, schema: &'a Schema) {
+        self.schema = Some(schema);
+    }
+
     pub fn set_flag(&mut self, flag: PrettyPrinterFlag) {
         if !self.flags.contains(&flag) {
             self.flags.push(flag);
@@ -69,6 +96,24 @@ impl<'a> PrettyBer<'a> {
             indent: self.indent + self.inc,
             inc: self.inc,
             flags: self.flags.to_vec(),
+            schema: None,
+            label: None,
+        }
+    }
+
+    fn child<'b>(
+        &'b self,
+        obj: &'b BerObject<'b>,
+        schema: Option<&'b Schema>,
+        label: Option<&'b str>,
+    ) -> PrettyBer<'b> {
+        PrettyBer {
+            obj,
+            indent: self.indent + self.inc,
+            inc: self.inc,
+            flags: self.flags.to_vec(),
+            schema,
+            label,
         }
     }
 
@@ -85,9 +130,10 @@ fn dbg_header(header: &Header, f: &mut fmt::Formatter) -> fmt::Result {
         Length::Indefinite => "Indefinite".to_string(),
     };
     match header.class() {
-        Class::Universal => {
-            write!(f, "[{}]{} {}", header.tag(), s_constructed, l)?;
-        }
+        Class::Universal => match universal_tag_name(header.tag()) {
+            Some(name) => write!(f, "[{}]{} {}", name, s_constructed, l)?,
+            None => write!(f, "[UNIVERSAL {}]{} {}", header.tag().0, s_constructed, l)?,
+        },
         Class::ContextSpecific => {
             write!(f, "[{}]{} {}", header.tag().0, s_constructed, l)?;
         }
@@ -100,13 +146,37 @@ fn dbg_header(header: &Header, f: &mut fmt::Formatter) -> fmt::Result {
 }
 
 impl<'a> fmt::Debug for PrettyBer<'a> {
-    #[rustfmt::skip]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.indent > 0 {
             write!(f, "{:1$}", " ", self.indent)?;
         };
+        if let Some(label) = self.label {
+            write!(f, "{}: ", label)?;
+        }
+        self.fmt_object(self.obj, self.schema, f)
+    }
+}
+
+impl<'a> PrettyBer<'a> {
+    #[rustfmt::skip]
+    fn fmt_object(&self, obj: &BerObject, schema: Option<&Schema>, f: &mut fmt::Formatter) -> fmt::Result {
+        match schema {
+            Some(Schema::Choice(alternatives)) => {
+                if let Some(alt) = alternatives.iter().find(|alt| alt.schema.accepts(obj)) {
+                    write!(f, "{}: ", alt.name)?;
+                    return self.fmt_object(obj, Some(&alt.schema), f);
+                }
+            }
+            Some(Schema::Constrained(inner, _)) => return self.fmt_object(obj, Some(inner), f),
+            Some(Schema::Tagged(_, _, mode, inner)) if schema.and_then(Schema::tag) == Some((obj.class(), obj.tag())) => {
+                if let Ok(decoded) = schema::decode_tagged(obj, *mode, inner) {
+                    return self.fmt_object(&decoded, Some(inner), f);
+                }
+            }
+            _ => (),
+        }
         if self.flags.contains(&PrettyPrinterFlag::ShowHeader) {
-            dbg_header(&self.obj.header, f)?;
+            dbg_header(&obj.header, f)?;
             write!(f, " ")?;
         };
         fn print_utf32_string_with_type(f: &mut fmt::Formatter, s: &[u8], ty: &str) -> fmt::Result {
@@ -120,7 +190,7 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 None => writeln!(f, "{}({:?}) <error decoding utf32 string>", ty, s),
             }
         }
-        match self.obj.content {
+        match obj.content {
             BerObjectContent::EndOfContent           => write!(f, "EndOfContent"),
             BerObjectContent::Boolean(b)             => write!(f, "Boolean({:?})", b),
             BerObjectContent::Integer(i)             => write!(f, "Integer({:?})", HexSlice(i)),
@@ -145,6 +215,7 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
             BerObjectContent::ObjectDescriptor(s)    => write!(f, "ObjectDescriptor(\"{}\")", s),
             BerObjectContent::BmpString(s)           => write!(f, "BmpString(\"{}\")", s),
             BerObjectContent::UniversalString(s)     => print_utf32_string_with_type(f, s, "UniversalString"),
+            BerObjectContent::CharacterString(ref c) => write!(f, "CharacterString({:?},{:?})", c.identification, HexSlice(c.string_value)),
             BerObjectContent::Optional(ref o) => {
                 match o {
                     Some(obj) => write!(f, "OPTION {:?}", obj),
@@ -152,8 +223,8 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 }
             }
             BerObjectContent::Tagged(class, tag, ref obj) => {
-                writeln!(f, "ContextSpecific [{} {}] {{", class, tag.0)?;
-                write!(f, "{:?}", self.next_indent(obj))?;
+                writeln!(f, "{:?} [{}] {{", class, tag.0)?;
+                write!(f, "{:?}", self.child(obj, None, None))?;
                 if self.indent > 0 {
                     write!(f, "{:1$}", " ", self.indent)?;
                 };
@@ -162,11 +233,24 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
             },
             BerObjectContent::Set(ref v) |
             BerObjectContent::Sequence(ref v)        => {
-                let ty = if self.obj.header.tag() == Tag::Sequence { "Sequence" } else { "Set" };
+                let ty = if obj.header.tag() == Tag::Sequence { "Sequence" } else { "Set" };
                 if self.is_recursive() {
                     writeln!(f, "{}[", ty)?;
-                    for o in v {
-                        write!(f, "{:?}", self.next_indent(o))?;
+                    let fields = match schema {
+                        Some(Schema::Sequence(fields)) => schema::sequence_fields(fields, v),
+                        Some(Schema::Set(fields)) => schema::set_fields(fields, v),
+                        _ => vec![None; v.len()],
+                    };
+                    let element = match schema {
+                        Some(Schema::SequenceOf(element)) | Some(Schema::SetOf(element)) => Some(&**element),
+                        _ => None,
+                    };
+                    for (o, field) in v.iter().zip(fields) {
+                        let pp = match field {
+                            Some(field) => self.child(o, Some(&field.schema), Some(&field.name)),
+                            None => self.child(o, element, None),
+                        };
+                        write!(f, "{:?}", pp)?;
                     };
                     if self.indent > 0 {
                         write!(f, "{:1$}", " ", self.indent)?;
@@ -177,6 +261,12 @@ impl<'a> fmt::Debug for PrettyBer<'a> {
                 }
                 Ok(())
             },
+            BerObjectContent::TagOverflow(identifier, data) => {
+                write!(f, "TagOverflow {:x?} {:x?}", HexSlice(identifier), HexSlice(data))
+            },
+            BerObjectContent::Primitive(tag, data) => {
+                write!(f, "Primitive {} {:x?}", tag, HexSlice(data))
+            },
             BerObjectContent::Unknown(ref any) => {
                 write!(f, "Unknown {:x?}", HexSlice(any.data))
             },
@@ -206,4 +296,70 @@ mod tests {
         pp.set_flag(PrettyPrinterFlag::ShowHeader);
         println!("{:?}", pp);
     }
+
+    #[test]
+    fn test_pretty_print_header() {
+        let (_, obj) = parse_ber(&[0x04, 0x01, 0x61]).expect("parsing failed");
+        let mut pp = obj.as_pretty(0, 2);
+        pp.set_flag(PrettyPrinterFlag::ShowHeader);
+        assert_eq!(format!("{:?}", pp), "[OCTET STRING] 1 OctetString([61])");
+    }
+
+    #[test]
+    fn test_pretty_print_tagged() {
+        use alloc::format;
+
+        let int = BerObject::from_int_slice(b"\x01");
+        for (class, expected) in [
+            (Class::Application, "Application [1] {"),
+            (Class::ContextSpecific, "ContextSpecific [1] {"),
+            (Class::Private, "Private [1] {"),
+        ] {
+            let obj = BerObject::from_tagged_explicit(class, Tag(1), int.clone());
+            let s = format!("{:?}", obj.as_pretty(0, 2));
+            assert!(s.starts_with(expected), "{}", s);
+            assert_eq!(obj.as_tagged().map(|(c, t, _)| (c, t)), Ok((class, Tag(1))));
+        }
+    }
+
+    #[test]
+    fn test_pretty_print_schema() {
+        use crate::schema::{Field, Schema};
+        use alloc::boxed::Box;
+        use alloc::format;
+
+        // SEQUENCE {
+        //     serialNumber  INTEGER,
+        //     names         SEQUENCE OF CHOICE { dns [2] IMPLICIT IA5String, id OBJECT IDENTIFIER },
+        //     version       [0] EXPLICIT INTEGER OPTIONAL }
+        let name = Schema::Choice(vec![
+            Field::new(
+                "dns",
+                Schema::implicit(2, Schema::Primitive(Tag::Ia5String)),
+            ),
+            Field::new("id", Schema::Primitive(Tag::Oid)),
+        ]);
+        let schema = Schema::Sequence(vec![
+            Field::new("serialNumber", Schema::Primitive(Tag::Integer)),
+            Field::new("names", Schema::SequenceOf(Box::new(name))),
+            Field::new(
+                "version",
+                Schema::explicit(0, Schema::Primitive(Tag::Integer)),
+            )
+            .optional(),
+        ]);
+        let bytes = &[
+            0x30, 0x13, 0x02, 0x02, 0x12, 0x34, 0x30, 0x06, 0x82, 0x01, 0x61, 0x06, 0x01, 0x2a,
+            0xa0, 0x03, 0x02, 0x01, 0x02, 0x05, 0x00,
+        ];
+        let (_, obj) = crate::der::parse_der(bytes).expect("parsing failed");
+        let s = format!("{:?}", obj.as_pretty_with_schema(&schema, 0, 2));
+        assert!(s.contains("serialNumber: Integer([12 34])"));
+        assert!(s.contains("names: Sequence["));
+        assert!(s.contains("dns: IA5String(\"a\")"));
+        assert!(s.contains("id: OID(OID(1.2))"));
+        assert!(s.contains("version: Integer([02])"));
+        // objects not in the schema are printed without name
+        assert!(s.contains("version: Integer([02])  Null"));
+    }
 }
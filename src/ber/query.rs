@@ -0,0 +1,404 @@
+use crate::ber::{parse_ber, BerObject, Span, MAX_RECURSION};
+use crate::error::BerError;
+use crate::oid::Oid;
+use alloc::vec;
+use alloc::vec::Vec;
+use asn1_rs::{Any, Class, FromBer, Header, Tag};
+use core::fmt;
+use core::str::FromStr;
+
+/// Error returned when parsing a query string
+///
+/// The value is the index of the invalid step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    /// Empty step (for ex. `seq//int`)
+    EmptyStep(usize),
+    /// Unknown selector name
+    UnknownSelector(usize),
+    /// Invalid or missing bracketed index
+    InvalidIndex(usize),
+    /// Invalid OID value
+    InvalidOid(usize),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryError::EmptyStep(i) => write!(f, "empty query step {}", i),
+            QueryError::UnknownSelector(i) => write!(f, "unknown selector in query step {}", i),
+            QueryError::InvalidIndex(i) => write!(f, "invalid index in query step {}", i),
+            QueryError::InvalidOid(i) => write!(f, "invalid OID in query step {}", i),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QueryError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Selector {
+    Any,
+    Universal(Tag),
+    Class(Class, Option<u32>),
+    OidValue(Oid<'static>),
+    Descendants,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    selector: Selector,
+    index: Option<usize>,
+}
+
+const UNIVERSAL_NAMES: &[(&str, Tag)] = &[
+    ("eoc", Tag::EndOfContent),
+    ("bool", Tag::Boolean),
+    ("int", Tag::Integer),
+    ("bits", Tag::BitString),
+    ("octets", Tag::OctetString),
+    ("null", Tag::Null),
+    ("oid", Tag::Oid),
+    ("objectdescriptor", Tag::ObjectDescriptor),
+    ("external", Tag::External),
+    ("real", Tag::RealType),
+    ("enum", Tag::Enumerated),
+    ("embeddedpdv", Tag::EmbeddedPdv),
+    ("utf8", Tag::Utf8String),
+    ("reloid", Tag::RelativeOid),
+    ("seq", Tag::Sequence),
+    ("set", Tag::Set),
+    ("numeric", Tag::NumericString),
+    ("printable", Tag::PrintableString),
+    ("t61", Tag::T61String),
+    ("videotex", Tag::VideotexString),
+    ("ia5", Tag::Ia5String),
+    ("utctime", Tag::UtcTime),
+    ("gentime", Tag::GeneralizedTime),
+    ("graphic", Tag::GraphicString),
+    ("visible", Tag::VisibleString),
+    ("general", Tag::GeneralString),
+    ("universal", Tag::UniversalString),
+    ("characterstring", Tag::CharacterString),
+    ("bmp", Tag::BmpString),
+];
+
+/// A compiled path query over BER-encoded data
+///
+/// A query is a list of steps separated by `/`. Evaluation starts with the top-level objects of
+/// the input, and each step selects objects among the children of the objects selected by the
+/// previous step (for the first step, among the top-level objects).
+///
+/// Steps can be:
+///
+/// - a type name, for ex. `seq`, `set`, `int`, `oid`, `octets`, `utf8` (see below for the full
+///   list), optionally followed by an index `[n]` to select only the n-th (0-based) matching
+///   sibling: `seq[1]` is the second SEQUENCE child
+/// - `*` to select all children (`*[n]` selects the n-th child)
+/// - `ctx`, `app` or `priv`, to select objects of the context-specific, application or private
+///   class. For these selectors, the index is the tag number: `ctx[3]` selects `[3]` objects
+/// - `oid=<dotted-string>`, to select OBJECT IDENTIFIER objects with this value
+/// - `**`, to select the objects and all their descendants (recursively)
+///
+/// Universal type names are: `eoc`, `bool`, `int`, `bits`, `octets`, `null`, `oid`,
+/// `objectdescriptor`, `external`, `real`, `enum`, `embeddedpdv`, `utf8`, `reloid`, `seq`,
+/// `set`, `numeric`, `printable`, `t61`, `videotex`, `ia5`, `utctime`, `gentime`, `graphic`,
+/// `visible`, `general`, `universal`, `characterstring`, `bmp`.
+///
+/// For example, `seq/seq[0]/ctx[3]/**/oid` selects all OIDs in the extensions of an X.509
+/// certificate.
+///
+/// Results are returned in document order, with their offset in the input.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::Query;
+///
+/// // SEQUENCE { INTEGER 1, [0] { OID 1.2.3 } }
+/// let bytes = &[0x30, 0x09, 0x02, 0x01, 0x01, 0xa0, 0x04, 0x06, 0x02, 0x2a, 0x03];
+/// let query = "seq/ctx[0]/oid".parse::<Query>().expect("invalid query");
+/// let matches = query.run(bytes).expect("parsing failed");
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].offset, 7);
+/// assert_eq!(matches[0].object.as_oid().map(|oid| oid.to_id_string()), Ok("1.2.3".to_string()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+/// Object selected by a [`Query`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryMatch<'a> {
+    /// Offset of the first byte of the object (header included), relative to the input
+    pub offset: usize,
+    /// Raw bytes of the object (header and content)
+    pub raw: &'a [u8],
+    /// Parsed object
+    pub object: BerObject<'a>,
+}
+
+fn parse_step(idx: usize, s: &str) -> Result<Step, QueryError> {
+    if s.is_empty() {
+        return Err(QueryError::EmptyStep(idx));
+    }
+    if s == "**" {
+        return Ok(Step {
+            selector: Selector::Descendants,
+            index: None,
+        });
+    }
+    if let Some(oid) = s.strip_prefix("oid=") {
+        let oid = Oid::from_str(oid).map_err(|_| QueryError::InvalidOid(idx))?;
+        return Ok(Step {
+            selector: Selector::OidValue(oid),
+            index: None,
+        });
+    }
+    let (name, index) = match s.find('[') {
+        Some(pos) => {
+            let n = s[pos + 1..]
+                .strip_suffix(']')
+                .and_then(|n| n.parse::<u32>().ok())
+                .ok_or(QueryError::InvalidIndex(idx))?;
+            (&s[..pos], Some(n))
+        }
+        None => (s, None),
+    };
+    let class = match name {
+        "ctx" => Some(Class::ContextSpecific),
+        "app" => Some(Class::Application),
+        "priv" => Some(Class::Private),
+        _ => None,
+    };
+    if let Some(class) = class {
+        return Ok(Step {
+            selector: Selector::Class(class, index),
+            index: None,
+        });
+    }
+    let selector = if name == "*" {
+        Selector::Any
+    } else {
+        let tag = UNIVERSAL_NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, tag)| *tag)
+            .ok_or(QueryError::UnknownSelector(idx))?;
+        Selector::Universal(tag)
+    };
+    Ok(Step {
+        selector,
+        index: index.map(|n| n as usize),
+    })
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .split('/')
+            .enumerate()
+            .map(|(idx, step)| parse_step(idx, step.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Query { steps })
+    }
+}
+
+/// Object being evaluated (offset, header, content and raw bytes)
+#[derive(Clone, Debug)]
+struct Node<'a> {
+    offset: usize,
+    header: Header<'a>,
+    content: &'a [u8],
+    raw: &'a [u8],
+}
+
+/// Split `data` (located at `offset` in the input) into a list of objects
+fn read_nodes<'a>(data: &'a [u8], offset: usize) -> Result<Vec<Node<'a>>, BerError> {
+    let mut nodes = Vec::new();
+    let mut rem = data;
+    while !rem.is_empty() {
+        let (next, any) = Any::from_ber(rem).map_err(BerError::from)?;
+        let len = rem.len() - next.len();
+        nodes.push(Node {
+            offset: offset + (data.len() - rem.len()),
+            header: any.header,
+            content: any.data,
+            raw: &rem[..len],
+        });
+        rem = next;
+    }
+    Ok(nodes)
+}
+
+impl<'a> Node<'a> {
+    fn children(&self) -> Result<Vec<Node<'a>>, BerError> {
+        if !self.header.is_constructed() {
+            return Ok(Vec::new());
+        }
+        // for indefinite length, the raw bytes also contain the EOC, after the content
+        let span = Span::from_raw(self.raw, self.content.len(), self.header.length());
+        read_nodes(self.content, self.offset + span.header_len())
+    }
+
+    fn matches(&self, selector: &Selector) -> bool {
+        match selector {
+            Selector::Any | Selector::Descendants => true,
+            Selector::Universal(tag) => {
+                self.header.class() == Class::Universal && self.header.tag() == *tag
+            }
+            Selector::Class(class, tag) => {
+                self.header.class() == *class && tag.map_or(true, |t| self.header.tag() == Tag(t))
+            }
+            Selector::OidValue(oid) => {
+                self.header.class() == Class::Universal
+                    && self.header.tag() == Tag::Oid
+                    && self.content == oid.as_bytes()
+            }
+        }
+    }
+}
+
+fn push_descendants<'a>(
+    node: &Node<'a>,
+    out: &mut Vec<Node<'a>>,
+    depth: usize,
+) -> Result<(), BerError> {
+    if depth > MAX_RECURSION {
+        return Err(BerError::BerMaxDepth);
+    }
+    for child in node.children()? {
+        out.push(child.clone());
+        push_descendants(&child, out, depth + 1)?;
+    }
+    Ok(())
+}
+
+impl Query {
+    /// Run the query on BER-encoded data, and return the matching objects
+    ///
+    /// The input can contain multiple top-level objects. Any encoding error in the parts of the
+    /// input traversed by the query is returned.
+    pub fn run<'a>(&self, input: &'a [u8]) -> Result<Vec<QueryMatch<'a>>, BerError> {
+        // candidates are grouped by parent, so indexes are relative to the siblings
+        let mut groups = vec![read_nodes(input, 0)?];
+        for (i, step) in self.steps.iter().enumerate() {
+            let mut selected = Vec::new();
+            for group in &groups {
+                if step.selector == Selector::Descendants {
+                    for node in group {
+                        selected.push(node.clone());
+                        push_descendants(node, &mut selected, 1)?;
+                    }
+                } else {
+                    let mut matching = group.iter().filter(|n| n.matches(&step.selector));
+                    match step.index {
+                        Some(idx) => selected.extend(matching.nth(idx).cloned()),
+                        None => selected.extend(matching.cloned()),
+                    }
+                }
+            }
+            selected.sort_by_key(|n| n.offset);
+            selected.dedup_by_key(|n| n.offset);
+            if i + 1 < self.steps.len() {
+                groups = selected
+                    .iter()
+                    .map(Node::children)
+                    .collect::<Result<Vec<_>, _>>()?;
+            } else {
+                groups = vec![selected];
+            }
+        }
+        let candidates = groups.into_iter().flatten();
+        candidates
+            .map(|node| {
+                let (_, object) = parse_ber(node.raw)?;
+                Ok(QueryMatch {
+                    offset: node.offset,
+                    raw: node.raw,
+                    object,
+                })
+            })
+            .collect::<Result<Vec<_>, nom::Err<BerError>>>()
+            .map_err(BerError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Query, QueryError};
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+    use test_case::test_case;
+
+    // SEQUENCE {
+    //   INTEGER 1,
+    //   [0] { OID 1.2.3 },
+    //   SEQUENCE { OID 1.2.4, SET { OID 1.2.3 } },
+    //   OCTET STRING (empty),
+    // }
+    const DATA: &[u8] =
+        &hex!("30 17 02 01 01 a0 04 06 02 2a 03 30 0a 06 02 2a 04 31 04 06 02 2a 03 04 00");
+
+    #[test_case("seq", Ok(vec![0]) ; "root")]
+    #[test_case("seq/*", Ok(vec![2, 5, 11, 23]) ; "children")]
+    #[test_case("seq/*[1]", Ok(vec![5]) ; "child by position")]
+    #[test_case("seq/seq[0]/oid", Ok(vec![13]) ; "indexed type")]
+    #[test_case("seq/ctx[0]/oid", Ok(vec![7]) ; "context-specific")]
+    #[test_case("seq/ctx[1]", Ok(vec![]) ; "context-specific no match")]
+    #[test_case("**/oid", Ok(vec![7, 13, 19]) ; "descendants")]
+    #[test_case("**/oid=1.2.3", Ok(vec![7, 19]) ; "oid value")]
+    #[test_case("seq/**/set", Ok(vec![17]) ; "nested descendants")]
+    #[test_case("seq//int", Err(QueryError::EmptyStep(1)) ; "empty step")]
+    #[test_case("seq/foo", Err(QueryError::UnknownSelector(1)) ; "unknown selector")]
+    #[test_case("seq[x]", Err(QueryError::InvalidIndex(0)) ; "invalid index")]
+    #[test_case("oid=1.a", Err(QueryError::InvalidOid(0)) ; "invalid oid")]
+    fn tc_query(q: &str, expected: Result<Vec<usize>, QueryError>) {
+        let res = q.parse::<Query>().map(|query| {
+            query
+                .run(DATA)
+                .expect("query failed")
+                .iter()
+                .map(|m| m.offset)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_query_index_per_parent() {
+        // SEQUENCE { SEQUENCE { INTEGER 1, INTEGER 2 }, SEQUENCE { INTEGER 3 } }
+        let data = hex!("30 0d 30 06 02 01 01 02 01 02 30 03 02 01 03");
+        let offsets = |q: &str| {
+            let query: Query = q.parse().expect("invalid query");
+            let matches = query.run(&data).expect("query failed");
+            matches.iter().map(|m| m.offset).collect::<Vec<_>>()
+        };
+        assert_eq!(offsets("seq/seq/int[0]"), vec![4, 12]);
+        assert_eq!(offsets("seq/seq/int[1]"), vec![7]);
+        assert_eq!(offsets("seq/*/*[0]"), vec![4, 12]);
+    }
+
+    #[test]
+    fn test_query_indefinite() {
+        // SEQUENCE (indefinite) { INTEGER 1, SEQUENCE (indefinite) { INTEGER 2 } }
+        let data = hex!("30 80 02 01 01 30 80 02 01 02 00 00 00 00");
+        let query: Query = "**/int".parse().expect("invalid query");
+        let matches = query.run(&data).expect("query failed");
+        let offsets = matches.iter().map(|m| m.offset).collect::<Vec<_>>();
+        assert_eq!(offsets, vec![2, 7]);
+        assert_eq!(matches[1].raw, &data[7..10]);
+    }
+
+    #[test]
+    fn test_query_raw() {
+        let query: Query = "seq/ctx".parse().expect("invalid query");
+        let matches = query.run(DATA).expect("query failed");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].raw, &DATA[5..11]);
+        assert_eq!(matches[0].object.header.tag().0, 0);
+    }
+}
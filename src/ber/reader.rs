@@ -0,0 +1,420 @@
+use crate::ber::{BerObject, BerObjectContent, Class, Tag};
+use crate::error::BerError;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::slice;
+
+/// Cursor over the children of a parsed constructed object
+///
+/// This gives an imperative style to decode structures: children are read one at a time, in
+/// order, and [`BerReader::finish`] checks that all of them were read. The reader borrows the
+/// parsed object, and never copies children.
+///
+/// The children of a `Sequence` or `Set` are its elements, and the child of a `Tagged` object
+/// (or of a present `Optional` object) is the inner object.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, BerReader, Class, Tag};
+/// use der_parser::error::BerError;
+///
+/// // SEQUENCE { INTEGER 2, OCTET STRING "ab" }, where an optional [0] field is absent
+/// let bytes = &[0x30, 0x07, 0x02, 0x01, 0x02, 0x04, 0x02, 0x61, 0x62];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+///
+/// let mut reader = BerReader::new(&obj)?;
+/// let version = reader.expect_tag(Tag::Integer)?.as_u32()?;
+/// let extra = reader.read_optional(Class::ContextSpecific, Tag(0));
+/// let data = reader.expect_tag(Tag::OctetString)?.as_slice()?;
+/// reader.finish()?;
+///
+/// assert_eq!((version, extra.is_none(), data), (2, true, &b"ab"[..]));
+/// # Ok::<(), BerError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct BerReader<'a, 'b> {
+    children: slice::Iter<'b, BerObject<'a>>,
+}
+
+impl<'a, 'b> BerReader<'a, 'b> {
+    /// Create a reader over the children of `obj`
+    ///
+    /// Raise a `BerTypeError` error if `obj` has no children (for ex. if it is a primitive
+    /// object, or a constructed object which was not parsed).
+    pub fn new(obj: &'b BerObject<'a>) -> Result<Self, BerError> {
+        let children = match obj.content {
+            BerObjectContent::Sequence(ref v) | BerObjectContent::Set(ref v) => v.as_slice(),
+            BerObjectContent::Tagged(_, _, ref o) | BerObjectContent::Optional(Some(ref o)) => {
+                slice::from_ref(o.as_ref())
+            }
+            _ => return Err(BerError::BerTypeError),
+        };
+        Ok(BerReader {
+            children: children.iter(),
+        })
+    }
+
+    /// Return the next child, without reading it
+    pub fn peek(&self) -> Option<&'b BerObject<'a>> {
+        self.children.as_slice().first()
+    }
+
+    /// Number of children not read yet
+    pub fn remaining(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Test if all children were read
+    pub fn is_empty(&self) -> bool {
+        self.children.len() == 0
+    }
+
+    /// Read the next child
+    ///
+    /// Raise an `InvalidLength` error if all children were read.
+    pub fn next_child(&mut self) -> Result<&'b BerObject<'a>, BerError> {
+        self.children.next().ok_or(BerError::InvalidLength)
+    }
+
+    /// Read the next child, checking that its tag is `tag` (the class is not checked)
+    ///
+    /// Raise an `UnexpectedTag` error if the tag does not match (the child is not read in this
+    /// case), or an `InvalidLength` error if all children were read.
+    pub fn expect_tag(&mut self, tag: Tag) -> Result<&'b BerObject<'a>, BerError> {
+        let child = self.peek().ok_or(BerError::InvalidLength)?;
+        child.header.assert_tag(tag)?;
+        self.children.next();
+        Ok(child)
+    }
+
+    /// Read the next child if its class and tag are `class` and `tag`
+    ///
+    /// If the next child does not match (or if all children were read), return `None` and do not
+    /// read the child. This is used for `OPTIONAL` (or `DEFAULT`) fields.
+    pub fn read_optional(&mut self, class: Class, tag: Tag) -> Option<&'b BerObject<'a>> {
+        let child = self.peek()?;
+        if child.class() != class || child.tag() != tag {
+            return None;
+        }
+        self.children.next();
+        Some(child)
+    }
+
+    /// Check that all children were read
+    ///
+    /// Raise an `InvalidLength` error if some children are remaining.
+    pub fn finish(self) -> Result<(), BerError> {
+        if !self.is_empty() {
+            return Err(BerError::InvalidLength);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> BerObject<'a> {
+    /// Create a [`BerReader`] over the children of this object
+    pub fn reader<'b>(&'b self) -> Result<BerReader<'a, 'b>, BerError> {
+        BerReader::new(self)
+    }
+}
+
+/// Error returned by a [`SequenceReader`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError {
+    /// Path of the field (see [`BerObject::get_path`]), relative to the outermost reader
+    ///
+    /// If a field is missing, the last index is the position where the field was expected.
+    pub path: Vec<usize>,
+    /// The error
+    pub error: BerError,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (field {:?})", self.error, self.path)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Cursor over the fields of a SEQUENCE, with positional `OPTIONAL` and `DEFAULT` fields
+///
+/// Unlike [`BerReader`], the errors contain the path of the field (see [`FieldError`]), and
+/// nested readers created by [`SequenceReader::nested`] keep track of the path of their parent.
+///
+/// Fields are matched by class and tag, as in [`BerReader::read_optional`].
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, Class, FieldError, SequenceReader, Tag};
+///
+/// // SEQUENCE { INTEGER 2, SEQUENCE { NULL } }, where an optional BOOLEAN is absent
+/// let bytes = &[0x30, 0x07, 0x02, 0x01, 0x02, 0x30, 0x02, 0x05, 0x00];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// let mut reader = SequenceReader::from_object(&obj)?;
+/// let version = reader.next_required(Class::Universal, Tag::Integer)?;
+/// let critical = reader.next_default(Class::Universal, Tag::Boolean, false)?;
+/// let mut inner = reader.nested(Class::Universal, Tag::Sequence)?;
+/// let err = inner.next_required(Class::Universal, Tag::Integer).unwrap_err();
+/// assert_eq!(err.path, vec![1, 0]);
+///
+/// assert_eq!((version.as_u32(), critical), (Ok(2), false));
+/// # Ok::<(), FieldError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct SequenceReader<'a, 'b> {
+    items: &'b [BerObject<'a>],
+    position: usize,
+    path: Vec<usize>,
+}
+
+impl<'a, 'b> SequenceReader<'a, 'b> {
+    /// Create a reader over the fields `items`
+    pub fn new(items: &'b [BerObject<'a>]) -> Self {
+        SequenceReader {
+            items,
+            position: 0,
+            path: Vec::new(),
+        }
+    }
+
+    /// Create a reader over the elements of `obj`, which must be a SEQUENCE (or a SET)
+    ///
+    /// Raise a `BerTypeError` error otherwise.
+    pub fn from_object(obj: &'b BerObject<'a>) -> Result<Self, FieldError> {
+        Self::with_path(obj, Vec::new())
+    }
+
+    fn with_path(obj: &'b BerObject<'a>, path: Vec<usize>) -> Result<Self, FieldError> {
+        match obj.content {
+            BerObjectContent::Sequence(ref v) | BerObjectContent::Set(ref v) => {
+                Ok(SequenceReader {
+                    items: v,
+                    position: 0,
+                    path,
+                })
+            }
+            _ => Err(FieldError {
+                path,
+                error: BerError::BerTypeError,
+            }),
+        }
+    }
+
+    /// Index of the next field
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of fields not read yet
+    pub fn remaining(&self) -> usize {
+        self.items.len() - self.position
+    }
+
+    fn error(&self, error: BerError) -> FieldError {
+        let mut path = self.path.clone();
+        path.push(self.position);
+        FieldError { path, error }
+    }
+
+    fn peek_tag(&self, class: Class, tag: Tag) -> Option<&'b BerObject<'a>> {
+        self.items
+            .get(self.position)
+            .filter(|o| o.header.class() == class && o.header.tag() == tag)
+    }
+
+    /// Read the next field, checking that its class and tag are `class` and `tag`
+    ///
+    /// Raise an `UnexpectedClass` or `UnexpectedTag` error if the field does not match (the field
+    /// is not read in this case), or an `InvalidLength` error if all fields were read.
+    pub fn next_required(
+        &mut self,
+        class: Class,
+        tag: Tag,
+    ) -> Result<&'b BerObject<'a>, FieldError> {
+        let item = self
+            .items
+            .get(self.position)
+            .ok_or_else(|| self.error(BerError::InvalidLength))?;
+        item.header
+            .assert_class(class)
+            .and_then(|_| item.header.assert_tag(tag))
+            .map_err(|e| self.error(e))?;
+        self.position += 1;
+        Ok(item)
+    }
+
+    /// Read the next field if its class and tag are `class` and `tag`
+    ///
+    /// If the next field does not match (or if all fields were read), return `None` and do not
+    /// read the field.
+    pub fn next_optional(&mut self, class: Class, tag: Tag) -> Option<&'b BerObject<'a>> {
+        let item = self.peek_tag(class, tag)?;
+        self.position += 1;
+        Some(item)
+    }
+
+    /// Read and convert the next field if its class and tag are `class` and `tag`, or return
+    /// `default`
+    ///
+    /// Raise an error if the field is present but cannot be converted.
+    pub fn next_default<T>(&mut self, class: Class, tag: Tag, default: T) -> Result<T, FieldError>
+    where
+        T: TryFrom<&'b BerObject<'a>, Error = BerError>,
+    {
+        match self.peek_tag(class, tag) {
+            Some(item) => {
+                let value = T::try_from(item).map_err(|e| self.error(e))?;
+                self.position += 1;
+                Ok(value)
+            }
+            None => Ok(default),
+        }
+    }
+
+    /// Read the next field, checking that its class and tag are `class` and `tag`, and create a
+    /// reader over its elements
+    ///
+    /// The errors of the nested reader contain the path of the field.
+    pub fn nested(&mut self, class: Class, tag: Tag) -> Result<SequenceReader<'a, 'b>, FieldError> {
+        let mut path = self.path.clone();
+        path.push(self.position);
+        let item = self.next_required(class, tag)?;
+        Self::with_path(item, path)
+    }
+
+    /// Check that all fields were read
+    ///
+    /// Raise an `InvalidLength` error, with the path of the first remaining field, otherwise.
+    pub fn finish(self) -> Result<(), FieldError> {
+        if self.remaining() > 0 {
+            return Err(self.error(BerError::InvalidLength));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::parse_ber;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ber_reader() {
+        // SEQUENCE { [0] { INTEGER 1 }, NULL, NULL }
+        let bytes = hex!("30 09 a0 03 02 01 01 05 00 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut reader = obj.reader().expect("not constructed");
+        assert_eq!(reader.remaining(), 3);
+        assert!(reader
+            .read_optional(Class::ContextSpecific, Tag(1))
+            .is_none());
+        let tagged = reader
+            .read_optional(Class::ContextSpecific, Tag(0))
+            .expect("missing [0]");
+        // tagged objects are unknown, unless they were parsed as explicit
+        assert_eq!(tagged.reader().map(|_| ()), Err(BerError::BerTypeError));
+        assert_eq!(
+            reader.expect_tag(Tag::Integer).map(|_| ()),
+            Err(BerError::UnexpectedTag {
+                expected: Some(Tag::Integer),
+                actual: Tag::Null
+            })
+        );
+        assert_eq!(reader.expect_tag(Tag::Null).map(|o| o.tag()), Ok(Tag::Null));
+        assert_eq!(reader.clone().finish(), Err(BerError::InvalidLength));
+        assert!(reader.next_child().is_ok());
+        assert_eq!(
+            reader.next_child().map(|_| ()),
+            Err(BerError::InvalidLength)
+        );
+        assert_eq!(reader.finish(), Ok(()));
+
+        let null = BerObject::from_obj(BerObjectContent::Null);
+        assert!(BerReader::new(&null).is_err());
+    }
+
+    #[test]
+    fn test_sequence_reader() {
+        // SEQUENCE { INTEGER 1, BOOLEAN TRUE, SET { NULL, NULL } }
+        let bytes = hex!("30 0c 02 01 01 01 01 ff 31 04 05 00 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut reader = SequenceReader::from_object(&obj).expect("not a sequence");
+        const U: Class = Class::Universal;
+        assert!(reader.next_optional(U, Tag::Boolean).is_none());
+        // present, but not a BOOLEAN
+        assert_eq!(
+            reader.next_default(U, Tag::Integer, false),
+            Err(FieldError {
+                path: vec![0],
+                error: BerError::BerTypeError
+            })
+        );
+        assert!(reader.next_required(U, Tag::Integer).is_ok());
+        assert_eq!(reader.next_default(U, Tag::Boolean, false), Ok(true));
+        assert_eq!(reader.next_default(U, Tag::Boolean, false), Ok(false));
+        assert_eq!(
+            reader
+                .clone()
+                .next_required(U, Tag::Sequence)
+                .map_err(|e| e.path),
+            Err(vec![2])
+        );
+        let mut inner = reader.nested(U, Tag::Set).expect("nested reader failed");
+        assert_eq!(
+            inner.next_optional(U, Tag::Null).map(|o| o.tag()),
+            Some(Tag::Null)
+        );
+        assert_eq!(
+            inner.clone().finish(),
+            Err(FieldError {
+                path: vec![2, 1],
+                error: BerError::InvalidLength
+            })
+        );
+        assert!(inner.next_required(U, Tag::Null).is_ok());
+        assert_eq!(
+            inner.next_required(U, Tag::Null).map_err(|e| e.path),
+            Err(vec![2, 2])
+        );
+        assert_eq!(inner.finish(), Ok(()));
+        assert_eq!(reader.position(), 3);
+        assert_eq!(reader.finish(), Ok(()));
+        // fields are matched by class and tag
+        // SEQUENCE { [2] 1 (implicit), [APPLICATION 2] 1 }
+        let bytes = hex!("30 06 82 01 01 42 01 01");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut reader = SequenceReader::from_object(&obj).expect("not a sequence");
+        assert!(reader.next_optional(U, Tag::Integer).is_none());
+        assert_eq!(
+            reader.next_required(U, Tag::Integer),
+            Err(FieldError {
+                path: vec![0],
+                error: BerError::UnexpectedClass {
+                    expected: Some(U),
+                    actual: Class::ContextSpecific
+                }
+            })
+        );
+        assert!(reader
+            .next_optional(Class::ContextSpecific, Tag(2))
+            .is_some());
+        assert!(reader
+            .next_optional(Class::ContextSpecific, Tag(2))
+            .is_none());
+        assert!(reader.next_required(Class::Application, Tag(2)).is_ok());
+        assert_eq!(reader.finish(), Ok(()));
+        // only sequences and sets have fields
+        assert!(SequenceReader::from_object(&obj[0]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::ber::{parse_ber, BerObject, Tag};
+use crate::error::{BerError, BerResult};
+use asn1_rs::{FromBer, Header};
+use core::iter::FusedIterator;
+use core::ops::Range;
+use nom::Err;
+
+/// An item returned by [`RecoverIter`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Recovered<'a> {
+    /// An object was parsed
+    Object(BerObject<'a>),
+    /// Bytes were skipped because they could not be parsed
+    Skipped {
+        /// Range of the skipped bytes in the input of the iterator
+        range: Range<usize>,
+        /// Error returned when parsing the first skipped byte
+        error: BerError,
+    },
+}
+
+/// Iterator over consecutive objects, skipping corrupted bytes
+///
+/// When an object cannot be parsed, the iterator looks for the next offset where an object can be
+/// parsed, and returns a [`Recovered::Skipped`] item with the range of skipped bytes. Parsing then
+/// continues at this offset.
+///
+/// Note that resynchronizing is a heuristic: arbitrary bytes can look like a valid header, so
+/// some objects returned after a skipped range may not be real objects. This is intended for
+/// forensic tools, to extract as much data as possible from a corrupted input.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{RecoverIter, Recovered};
+///
+/// // INTEGER 1, garbage, INTEGER 2
+/// let bytes = &[0x02, 0x01, 0x01, 0x04, 0x7f, 0x02, 0x01, 0x02];
+/// let items = RecoverIter::new(bytes).collect::<Vec<_>>();
+/// assert_eq!(items.len(), 3);
+/// assert!(matches!(items[1], Recovered::Skipped { ref range, .. } if *range == (3..5)));
+/// ```
+#[derive(Debug)]
+pub struct RecoverIter<'a, F = fn(&'a [u8]) -> BerResult<'a>> {
+    input: &'a [u8],
+    pos: usize,
+    parser: F,
+}
+
+impl<'a> RecoverIter<'a> {
+    /// Create an iterator over the objects of `input`, using [`parse_ber`] to parse objects
+    pub fn new(input: &'a [u8]) -> Self {
+        RecoverIter::with_parser(input, parse_ber)
+    }
+}
+
+impl<'a, F> RecoverIter<'a, F>
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a>,
+{
+    /// Create an iterator over the objects of `input`, using `parser` to parse objects
+    pub fn with_parser(input: &'a [u8], parser: F) -> Self {
+        RecoverIter {
+            input,
+            pos: 0,
+            parser,
+        }
+    }
+
+    /// Find the next offset where an object can be parsed (or the end of input)
+    fn resync(&mut self, from: usize) -> usize {
+        for pos in from..self.input.len() {
+            let i = &self.input[pos..];
+            // quickly reject candidates with an invalid header, end-of-content, or a long form
+            // for a tag that does not require it
+            match Header::from_ber(i) {
+                Ok((_, hdr))
+                    if hdr.tag() != Tag::EndOfContent
+                        && (hdr.tag().0 >= 31 || hdr.raw_tag().map_or(true, |t| t.len() == 1)) => {}
+                _ => continue,
+            }
+            if (self.parser)(i).is_ok() {
+                return pos;
+            }
+        }
+        self.input.len()
+    }
+}
+
+impl<'a, F> Iterator for RecoverIter<'a, F>
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a>,
+{
+    type Item = Recovered<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.input.len() {
+            return None;
+        }
+        let start = self.pos;
+        let error = match (self.parser)(&self.input[start..]) {
+            Ok((rem, obj)) => {
+                self.pos = self.input.len() - rem.len();
+                return Some(Recovered::Object(obj));
+            }
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => e,
+            Err(Err::Incomplete(n)) => BerError::Incomplete(n),
+        };
+        // the next object is parsed again by the next call
+        self.pos = self.resync(start + 1);
+        Some(Recovered::Skipped {
+            range: start..self.pos,
+            error,
+        })
+    }
+}
+
+impl<'a, F> FusedIterator for RecoverIter<'a, F> where F: FnMut(&'a [u8]) -> BerResult<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use crate::error::BerError;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_recover_iter() {
+        // SEQUENCE { INTEGER 1 }, truncated OCTET STRING, NULL, BOOLEAN (truncated)
+        let bytes = hex!("30 03 02 01 01 04 08 ff 05 00 01 01");
+        let items = RecoverIter::new(&bytes).collect::<Vec<_>>();
+        let summary = items
+            .iter()
+            .map(|item| match item {
+                Recovered::Object(obj) => Ok(obj.tag()),
+                Recovered::Skipped { range, .. } => Err(range.clone()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [Ok(Tag::Sequence), Err(5..8), Ok(Tag::Null), Err(10..12)]
+        );
+        assert!(matches!(
+            items[3],
+            Recovered::Skipped {
+                error: BerError::Incomplete(_),
+                ..
+            }
+        ));
+    }
+}
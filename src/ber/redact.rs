@@ -0,0 +1,255 @@
+use crate::ber::{BerObject, BerObjectContent, BitStringObject, Class, Tag, VisitMut};
+use crate::oid::Oid;
+use alloc::vec::Vec;
+use core::hash::Hasher;
+
+/// A value removed by a [`Redactor`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedactedValue {
+    /// Path of the object in the tree (see [`BerObject::get_path`])
+    pub path: Vec<usize>,
+    /// Tag of the object
+    pub tag: Tag,
+    /// Length of the original value
+    pub len: usize,
+    /// 64-bit FNV-1a hash of the original value
+    ///
+    /// The hash is stable, so identical values can be correlated (for ex. in logs) without being
+    /// revealed. It is *not* a cryptographic hash: short or guessable values can be recovered.
+    pub hash: u64,
+}
+
+/// Visitor replacing sensitive values by a placeholder, so parsed objects can be logged
+///
+/// The contents of OCTET STRING and BIT STRING objects are replaced if they are longer than the
+/// threshold set by [`Redactor::with_max_len`], or if they are inside a SEQUENCE starting with one
+/// of the OIDs given to [`Redactor::with_oid`] (for ex. the value of an extension or of an
+/// attribute). By default, nothing is redacted.
+///
+/// Replaced objects keep their header, and the removed values are listed (with their hashes) in
+/// [`Redactor::redacted`]. Replaced objects and their ancestors lose their span, since it
+/// references the original encoding.
+///
+/// Explicitly tagged and present optional objects are visited, their path index being 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, Redactor, VisitMut};
+/// use der_parser::oid;
+///
+/// // SEQUENCE { OID 1.2.3, OCTET STRING "secret" }
+/// let bytes = &[
+///     0x30, 0x0c, 0x06, 0x02, 0x2a, 0x03, 0x04, 0x06, 0x73, 0x65, 0x63, 0x72, 0x65, 0x74,
+/// ];
+/// let (_, mut obj) = parse_ber(bytes).expect("parsing failed");
+/// let mut redactor = Redactor::new().with_oid(oid!(1.2.3));
+/// redactor.run(&mut obj);
+/// assert_eq!(obj[1].as_slice(), Ok(&b"<redacted>"[..]));
+/// assert_eq!(redactor.redacted()[0].path, vec![1]);
+/// assert_eq!(redactor.redacted()[0].len, 6);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Redactor {
+    max_len: Option<usize>,
+    oids: Vec<Oid<'static>>,
+    placeholder: &'static [u8],
+    redacted: Vec<RedactedValue>,
+    // path index, number of redacted values before the object, and oid context of each ancestor
+    stack: Vec<(usize, usize, bool)>,
+    next_index: usize,
+}
+
+impl Redactor {
+    /// Create a redactor, with the `<redacted>` placeholder and no rules
+    pub fn new() -> Self {
+        Redactor {
+            max_len: None,
+            oids: Vec::new(),
+            placeholder: b"<redacted>",
+            redacted: Vec::new(),
+            stack: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    /// Redact values with a length greater than `max_len`
+    pub fn with_max_len(self, max_len: usize) -> Self {
+        Redactor {
+            max_len: Some(max_len),
+            ..self
+        }
+    }
+
+    /// Redact values inside SEQUENCE objects with `oid` as first element
+    pub fn with_oid(mut self, oid: Oid<'static>) -> Self {
+        self.oids.push(oid);
+        self
+    }
+
+    /// Set the content of the redacted objects
+    pub fn with_placeholder(self, placeholder: &'static [u8]) -> Self {
+        Redactor {
+            placeholder,
+            ..self
+        }
+    }
+
+    /// Values removed by the previous runs, in the order of the objects
+    pub fn redacted(&self) -> &[RedactedValue] {
+        &self.redacted
+    }
+
+    fn is_sensitive_sequence(&self, obj: &BerObject) -> bool {
+        match obj.content {
+            BerObjectContent::Sequence(ref v) => match v.first().map(|o| &o.content) {
+                Some(BerObjectContent::OID(oid)) => self.oids.iter().any(|o| o == oid),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> VisitMut<'a> for Redactor {
+    fn visit_ber_mut(&mut self, ber: &'_ mut BerObject<'a>, depth: usize) {
+        if depth == 0 {
+            self.stack.clear();
+            self.next_index = 0;
+        }
+        let index = self.next_index;
+        let in_oid_context = self.stack.last().map_or(false, |&(_, _, s)| s);
+        let data = match ber.content {
+            BerObjectContent::OctetString(data) if ber.header.class() == Class::Universal => data,
+            BerObjectContent::BitString(_, BitStringObject { data })
+                if ber.header.class() == Class::Universal =>
+            {
+                data
+            }
+            _ => {
+                let sensitive = in_oid_context || self.is_sensitive_sequence(ber);
+                self.stack.push((index, self.redacted.len(), sensitive));
+                self.next_index = 0;
+                return;
+            }
+        };
+        self.stack
+            .push((index, self.redacted.len(), in_oid_context));
+        self.next_index = 0;
+        if !in_oid_context && self.max_len.map_or(true, |max| data.len() <= max) {
+            return;
+        }
+        let mut hasher = Fnv1a::default();
+        hasher.write(data);
+        self.redacted.push(RedactedValue {
+            path: self.stack[1..].iter().map(|&(i, _, _)| i).collect(),
+            tag: ber.tag(),
+            len: data.len(),
+            hash: hasher.finish(),
+        });
+        ber.content = match ber.content {
+            BerObjectContent::BitString(..) => BerObjectContent::BitString(
+                0,
+                BitStringObject {
+                    data: self.placeholder,
+                },
+            ),
+            _ => BerObjectContent::OctetString(self.placeholder),
+        };
+    }
+
+    fn visit_ber_exit_mut(&mut self, ber: &'_ mut BerObject<'a>, _depth: usize) {
+        if let Some((index, redacted, _)) = self.stack.pop() {
+            if self.redacted.len() > redacted {
+                ber.span = None;
+            }
+            self.next_index = index + 1;
+        }
+    }
+
+    fn visit_ber_optional_mut(&mut self, obj: Option<&'_ mut BerObject<'a>>, depth: usize) {
+        if let Some(obj) = obj {
+            self.run_at(obj, depth + 1);
+        }
+    }
+
+    fn visit_ber_tagged_mut(
+        &mut self,
+        _class: &'_ mut Class,
+        _tag: &'_ mut Tag,
+        obj: &'_ mut BerObject<'a>,
+        depth: usize,
+    ) {
+        self.run_at(obj, depth + 1);
+    }
+}
+
+/// 64-bit FNV-1a hasher (stable across platforms and versions)
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::parse_ber;
+    use crate::oid;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_redactor() {
+        // SEQUENCE { [0] { OCTET STRING "abcd" }, BIT STRING 0xffff, SEQUENCE { OID 1.2, INTEGER 1 } }
+        let bytes = hex!("30 15 a0 06 04 04 61 62 63 64 03 03 00 ff ff 30 06 06 01 2a 02 01 01");
+        let (_, mut obj) = parse_ber(&bytes).expect("parsing failed");
+        // parse [0] as EXPLICIT
+        let (_, inner) = parse_ber(&bytes[4..10]).expect("parsing failed");
+        obj = obj.map_content(|c| match c {
+            BerObjectContent::Sequence(mut v) => {
+                v[0] = BerObject::from_tagged_explicit(Class::ContextSpecific, Tag(0), inner);
+                BerObjectContent::Sequence(v)
+            }
+            c => c,
+        });
+        let mut redactor = Redactor::new().with_max_len(1).with_placeholder(b"");
+        redactor.run(&mut obj);
+        let paths: Vec<_> = redactor.redacted().iter().map(|r| &r.path[..]).collect();
+        assert_eq!(paths, vec![&[0, 0][..], &[1]]);
+        assert_eq!(
+            obj.get_path(&[0, 0]).map(|o| o.as_slice()),
+            Some(Ok(&b""[..]))
+        );
+        assert_eq!(obj[1].as_bitstring().map(|b| b.data), Ok(&b""[..]));
+        assert!(obj[1].span.is_none() && obj[2].span.is_some());
+        // the hash of "abcd"
+        assert_eq!(redactor.redacted()[0].hash, 0xfc17_9f83_ee07_24dd);
+        // the integer is not redacted
+        let mut redactor = Redactor::new().with_oid(oid!(1.2));
+        redactor.run(&mut obj);
+        assert!(redactor.redacted().is_empty());
+        assert_eq!(obj[2][1].as_u32(), Ok(1));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::ber::{ber_content_chunks, parse_ber_with_options, ArenaObject, BerArena, ParseOptions};
+use crate::error::BerResult;
+use crate::oid::Oid;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// A reusable BER parser, with internal buffers
+///
+/// The parser owns the buffers used to build temporary values: a [`BerArena`] for trees of
+/// objects, a buffer for reassembling segmented strings, and a buffer for formatting OIDs. The
+/// buffers are kept between calls, so when the same parser is used to process many inputs (for
+/// ex. in a server), parsing stops allocating after a few calls.
+///
+/// Values returned by the methods using a buffer borrow the parser, and are invalidated by the
+/// next call using the same buffer.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::BerParser;
+///
+/// let mut parser = BerParser::new();
+/// for bytes in [&[0x06, 0x02, 0x2a, 0x03][..], &[0x06, 0x02, 0x2a, 0x04]] {
+///     let (_, obj) = parser.parse(bytes).expect("parsing failed");
+///     let oid = obj.as_oid().expect("not an OID");
+///     assert!(parser.oid_str(oid).starts_with("1.2."));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct BerParser {
+    options: ParseOptions,
+    arena: BerArena,
+    data: Vec<u8>,
+    oid: String,
+}
+
+impl BerParser {
+    /// Create a new parser, using the default (BER) options
+    pub fn new() -> Self {
+        BerParser::with_options(ParseOptions::ber())
+    }
+
+    /// Create a new parser, using `options` to parse objects
+    pub fn with_options(options: ParseOptions) -> Self {
+        BerParser {
+            options,
+            arena: BerArena::new(),
+            data: Vec::new(),
+            oid: String::new(),
+        }
+    }
+
+    /// Return the options used by this parser
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Parse a BER object, using the options of this parser
+    ///
+    /// See [`parse_ber_with_options`].
+    pub fn parse<'a>(&self, i: &'a [u8]) -> BerResult<'a> {
+        parse_ber_with_options(i, &self.options)
+    }
+
+    /// Parse a BER object and all its children into the internal arena
+    ///
+    /// See [`BerArena::parse`]. The options of the parser are not used.
+    pub fn parse_tree<'a, 'b>(&'b mut self, i: &'a [u8]) -> BerResult<'a, ArenaObject<'a, 'b>> {
+        self.arena.parse(i)
+    }
+
+    /// Read an OCTET STRING or BIT STRING, and return its content in the internal buffer
+    ///
+    /// Segments of constructed strings are concatenated. For BIT STRING objects, the unused bits
+    /// octets are removed.
+    pub fn reassemble<'a, 'b>(&'b mut self, i: &'a [u8]) -> BerResult<'a, &'b [u8]> {
+        let (rem, chunks) = ber_content_chunks(i, usize::MAX)?;
+        self.data.clear();
+        for chunk in chunks {
+            self.data.extend_from_slice(chunk?);
+        }
+        Ok((rem, &self.data))
+    }
+
+    /// Format `oid` (in dotted notation) in the internal buffer
+    pub fn oid_str(&mut self, oid: &Oid) -> &str {
+        self.oid.clear();
+        // writing to a string cannot fail
+        let _ = write!(self.oid, "{}", oid);
+        &self.oid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use asn1_rs::Tag;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ber_parser() {
+        let mut parser = BerParser::with_options(ParseOptions::ber().with_max_depth(1));
+        assert!(parser.parse(&hex!("30 02 30 00")).is_err());
+
+        let (_, root) = parser
+            .parse_tree(&hex!("30 02 30 00"))
+            .expect("parsing failed");
+        assert_eq!(root.get(0).map(|o| o.header().tag()), Some(Tag::Sequence));
+
+        let bytes = hex!("24 80 04 02 61 62 04 01 63 00 00");
+        let (rem, data) = parser.reassemble(&bytes).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(data, b"abc");
+        let (_, data) = parser
+            .reassemble(&hex!("03 02 00 ff"))
+            .expect("parsing failed");
+        assert_eq!(data, vec![0xff]);
+
+        let oid = Oid::from(&[1, 2, 840, 113549]).expect("invalid OID");
+        assert_eq!(parser.oid_str(&oid), "1.2.840.113549");
+    }
+}
@@ -0,0 +1,213 @@
+//! Zero-allocation scanning of BER objects
+//!
+//! The functions of this module read the headers of objects, and return them with the location of
+//! the content, without decoding values or building a tree of [`BerObject`](super::BerObject):
+//! no memory is allocated. This is useful to index or measure large structures, when only a few
+//! objects have to be decoded.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use der_parser::ber::scan::Scan;
+//! use der_parser::ber::Tag;
+//!
+//! // SEQUENCE { INTEGER 1, SET { NULL } }
+//! let bytes = &[0x30, 0x07, 0x02, 0x01, 0x01, 0x31, 0x02, 0x05, 0x00];
+//! let tags = Scan::new(bytes)
+//!     .map(|tlv| tlv.map(|tlv| (tlv.depth, tlv.header.tag())))
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .expect("scan failed");
+//! assert_eq!(
+//!     tags,
+//!     [(0, Tag::Sequence), (1, Tag::Integer), (1, Tag::Set), (2, Tag::Null)]
+//! );
+//! ```
+
+use crate::ber::parser::refine_length_overflow;
+use crate::ber::{ber_get_object_content, Header, Span, MAX_RECURSION};
+use crate::error::BerError;
+use asn1_rs::FromBer;
+use core::iter::FusedIterator;
+use nom::Err;
+
+/// The header and location of an object
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tlv<'a> {
+    /// The object header
+    pub header: Header<'a>,
+    /// The raw content of the object (without end-of-content octets)
+    pub data: &'a [u8],
+    /// The offset of the object in the scanned input
+    pub offset: usize,
+    /// The nesting level of the object (0 for top-level objects)
+    pub depth: usize,
+    span: Span<'a>,
+}
+
+impl<'a> Tlv<'a> {
+    /// Get the location of the object, including header (and end-of-content octets)
+    pub fn span(&self) -> Span<'a> {
+        self.span
+    }
+}
+
+/// Iterator over the objects of an input, in depth-first order
+///
+/// The content of all constructed objects is scanned (not only sequences and sets), up to a
+/// depth of `MAX_RECURSION`. Use [`Scan::flat`] to iterate only on top-level objects.
+///
+/// If an object cannot be read, the error is returned and the iteration stops.
+#[derive(Debug)]
+pub struct Scan<'a> {
+    input: &'a [u8],
+    pos: usize,
+    recursive: bool,
+    done: bool,
+    /// End of content, and number of end-of-content octets, of the enclosing objects
+    stack: [(usize, usize); MAX_RECURSION],
+    depth: usize,
+}
+
+impl<'a> Scan<'a> {
+    /// Scan all the objects of `input`, descending into constructed objects
+    pub fn new(input: &'a [u8]) -> Self {
+        Scan {
+            input,
+            pos: 0,
+            recursive: true,
+            done: false,
+            stack: [(0, 0); MAX_RECURSION],
+            depth: 0,
+        }
+    }
+
+    /// Scan only the top-level objects of `input`
+    pub fn flat(input: &'a [u8]) -> Self {
+        Scan {
+            recursive: false,
+            ..Scan::new(input)
+        }
+    }
+
+    /// Return the current nesting level
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn read_tlv(&mut self) -> Result<Tlv<'a>, Err<BerError>> {
+        // objects cannot extend beyond the content of the enclosing object
+        let end = match self.depth {
+            0 => self.input.len(),
+            depth => self.stack[depth - 1].0,
+        };
+        let i = &self.input[self.pos..end];
+        let (content, header) = refine_length_overflow(i, Header::from_ber(i))?;
+        let (rem, data) = ber_get_object_content(content, &header, MAX_RECURSION)?;
+        let raw = &i[..i.len() - rem.len()];
+        let span = Span::from_raw(raw, data.len(), header.length());
+        let tlv = Tlv {
+            offset: self.pos,
+            depth: self.depth,
+            data,
+            span,
+            header,
+        };
+        if self.recursive && tlv.header.is_constructed() {
+            if self.depth >= MAX_RECURSION {
+                return Err(Err::Error(BerError::BerMaxDepth));
+            }
+            let start = self.pos + span.header_len();
+            self.stack[self.depth] = (
+                start + data.len(),
+                raw.len() - span.header_len() - data.len(),
+            );
+            self.depth += 1;
+            self.pos = start;
+        } else {
+            self.pos += raw.len();
+        }
+        Ok(tlv)
+    }
+}
+
+impl<'a> Iterator for Scan<'a> {
+    type Item = Result<Tlv<'a>, BerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        // leave the objects whose content was completely scanned
+        while self.depth > 0 && self.pos >= self.stack[self.depth - 1].0 {
+            self.depth -= 1;
+            self.pos += self.stack[self.depth].1;
+        }
+        if self.pos >= self.input.len() {
+            self.done = true;
+            return None;
+        }
+        match self.read_tlv() {
+            Ok(tlv) => Some(Ok(tlv)),
+            Err(e) => {
+                self.done = true;
+                let e = match e {
+                    Err::Error(e) | Err::Failure(e) => e,
+                    Err::Incomplete(n) => BerError::Incomplete(n),
+                };
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for Scan<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::Tag;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_scan() {
+        // SEQUENCE { SET (indefinite) { NULL, INTEGER 2 }, [0] { BOOLEAN TRUE } }, NULL
+        let bytes = hex!("30 0e 31 80 05 00 02 01 02 00 00 a0 03 01 01 ff 05 00");
+        let items = Scan::new(&bytes)
+            .map(|tlv| {
+                let tlv = tlv.expect("scan failed");
+                (tlv.offset, tlv.depth, tlv.header.tag(), tlv.data.len())
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            (0, 0, Tag::Sequence, 14),
+            (2, 1, Tag::Set, 5),
+            (4, 2, Tag::Null, 0),
+            (6, 2, Tag::Integer, 1),
+            (11, 1, Tag(0), 3),
+            (13, 2, Tag::Boolean, 1),
+            (16, 0, Tag::Null, 0),
+        ];
+        assert_eq!(items, expected);
+
+        let flat = Scan::flat(&bytes)
+            .map(|tlv| tlv.map(|tlv| tlv.span().range_in(&bytes)))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("scan failed");
+        assert_eq!(flat, [Some(0..16), Some(16..18)]);
+    }
+
+    #[test]
+    fn test_scan_errors() {
+        // the length of the INTEGER exceeds the length of the SEQUENCE
+        let bytes = hex!("30 03 02 02 01 01");
+        let mut scan = Scan::new(&bytes);
+        assert!(matches!(scan.next(), Some(Ok(_))));
+        assert!(matches!(scan.next(), Some(Err(BerError::Incomplete(_)))));
+        assert!(scan.next().is_none());
+
+        let bytes = [0x30, 0x80].repeat(MAX_RECURSION + 1);
+        let r = Scan::new(&bytes).find_map(Result::err);
+        assert_eq!(r, Some(BerError::BerMaxDepth));
+    }
+}
@@ -0,0 +1,197 @@
+//! Implementation of `serde::Serialize` for BER objects
+//!
+//! Objects are serialized as a structure with fields `class`, `tag`, `constructed` and `content`.
+//! The content is an (externally tagged) enum, with one variant for each variant of
+//! [`BerObjectContent`]:
+//! - byte contents (integers, octet strings, unknown objects) are serialized as bytes
+//! - OIDs are serialized as strings, in dotted notation
+//! - dates are serialized as strings (see the `Display` implementation of `ASN1DateTime`)
+
+use crate::ber::{
+    BerObject, BerObjectContent, BitStringObject, CharacterStringIdentification,
+    CharacterStringObject, Class,
+};
+use alloc::string::ToString;
+use serde::ser::{Serialize, SerializeStruct, SerializeStructVariant, Serializer};
+
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+struct ClassName(Class);
+
+impl Serialize for ClassName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (idx, name) = match self.0 {
+            Class::Universal => (0, "Universal"),
+            Class::Application => (1, "Application"),
+            Class::ContextSpecific => (2, "ContextSpecific"),
+            Class::Private => (3, "Private"),
+        };
+        serializer.serialize_unit_variant("Class", idx, name)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> Serialize for BerObject<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("BerObject", 4)?;
+        s.serialize_field("class", &ClassName(self.class()))?;
+        s.serialize_field("tag", &self.tag().0)?;
+        s.serialize_field("constructed", &self.is_constructed())?;
+        s.serialize_field("content", &self.content)?;
+        s.end()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> Serialize for BerObjectContent<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        const NAME: &str = "BerObjectContent";
+        macro_rules! newtype {
+            ($idx:expr, $variant:expr, $value:expr) => {
+                serializer.serialize_newtype_variant(NAME, $idx, $variant, $value)
+            };
+        }
+        match self {
+            BerObjectContent::EndOfContent => {
+                serializer.serialize_unit_variant(NAME, 0, "EndOfContent")
+            }
+            BerObjectContent::Boolean(b) => newtype!(1, "Boolean", b),
+            BerObjectContent::Integer(i) => newtype!(2, "Integer", &Bytes(i)),
+            BerObjectContent::BitString(unused_bits, BitStringObject { data }) => {
+                let mut s = serializer.serialize_struct_variant(NAME, 3, "BitString", 2)?;
+                s.serialize_field("unused_bits", unused_bits)?;
+                s.serialize_field("data", &Bytes(data))?;
+                s.end()
+            }
+            BerObjectContent::OctetString(s) => newtype!(4, "OctetString", &Bytes(s)),
+            BerObjectContent::Null => serializer.serialize_unit_variant(NAME, 5, "Null"),
+            BerObjectContent::Enum(e) => newtype!(6, "Enum", e),
+            BerObjectContent::OID(oid) => newtype!(7, "OID", &oid.to_id_string()),
+            BerObjectContent::RelativeOID(oid) => {
+                newtype!(8, "RelativeOID", &oid.to_id_string())
+            }
+            BerObjectContent::NumericString(s) => newtype!(9, "NumericString", s),
+            BerObjectContent::VisibleString(s) => newtype!(10, "VisibleString", s),
+            BerObjectContent::PrintableString(s) => newtype!(11, "PrintableString", s),
+            BerObjectContent::IA5String(s) => newtype!(12, "IA5String", s),
+            BerObjectContent::UTF8String(s) => newtype!(13, "UTF8String", s),
+            BerObjectContent::T61String(s) => newtype!(14, "T61String", s),
+            BerObjectContent::VideotexString(s) => newtype!(15, "VideotexString", s),
+            BerObjectContent::BmpString(s) => newtype!(16, "BmpString", s),
+            BerObjectContent::UniversalString(s) => newtype!(17, "UniversalString", &Bytes(s)),
+            BerObjectContent::CharacterString(CharacterStringObject {
+                identification,
+                string_value,
+            }) => {
+                let mut s = serializer.serialize_struct_variant(NAME, 18, "CharacterString", 2)?;
+                s.serialize_field("identification", identification)?;
+                s.serialize_field("string_value", &Bytes(string_value))?;
+                s.end()
+            }
+            BerObjectContent::Sequence(v) => newtype!(19, "Sequence", v),
+            BerObjectContent::Set(v) => newtype!(20, "Set", v),
+            BerObjectContent::UTCTime(t) => newtype!(21, "UTCTime", &t.to_string()),
+            BerObjectContent::GeneralizedTime(t) => {
+                newtype!(22, "GeneralizedTime", &t.to_string())
+            }
+            BerObjectContent::ObjectDescriptor(s) => newtype!(23, "ObjectDescriptor", s),
+            BerObjectContent::GraphicString(s) => newtype!(24, "GraphicString", s),
+            BerObjectContent::GeneralString(s) => newtype!(25, "GeneralString", s),
+            BerObjectContent::Optional(o) => newtype!(26, "Optional", o),
+            BerObjectContent::Tagged(class, tag, obj) => {
+                let mut s = serializer.serialize_struct_variant(NAME, 27, "Tagged", 3)?;
+                s.serialize_field("class", &ClassName(*class))?;
+                s.serialize_field("tag", &tag.0)?;
+                s.serialize_field("object", obj)?;
+                s.end()
+            }
+            BerObjectContent::Unknown(any) => newtype!(28, "Unknown", &Bytes(any.data)),
+            BerObjectContent::TagOverflow(identifier, data) => {
+                let mut s = serializer.serialize_struct_variant(NAME, 29, "TagOverflow", 2)?;
+                s.serialize_field("identifier", &Bytes(identifier))?;
+                s.serialize_field("data", &Bytes(data))?;
+                s.end()
+            }
+            BerObjectContent::Primitive(tag, data) => {
+                let mut s = serializer.serialize_struct_variant(NAME, 30, "Primitive", 2)?;
+                s.serialize_field("tag", &tag.0)?;
+                s.serialize_field("data", &Bytes(data))?;
+                s.end()
+            }
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> Serialize for CharacterStringIdentification<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        const NAME: &str = "CharacterStringIdentification";
+        match self {
+            CharacterStringIdentification::Syntaxes {
+                abstract_syntax,
+                transfer_syntax,
+            } => {
+                let mut s = serializer.serialize_struct_variant(NAME, 0, "Syntaxes", 2)?;
+                s.serialize_field("abstract_syntax", &abstract_syntax.to_id_string())?;
+                s.serialize_field("transfer_syntax", &transfer_syntax.to_id_string())?;
+                s.end()
+            }
+            CharacterStringIdentification::Syntax(oid) => {
+                serializer.serialize_newtype_variant(NAME, 1, "Syntax", &oid.to_id_string())
+            }
+            CharacterStringIdentification::PresentationContextId(id) => {
+                serializer.serialize_newtype_variant(NAME, 2, "PresentationContextId", &Bytes(id))
+            }
+            CharacterStringIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax,
+            } => {
+                let mut s =
+                    serializer.serialize_struct_variant(NAME, 3, "ContextNegotiation", 2)?;
+                s.serialize_field("presentation_context_id", &Bytes(presentation_context_id))?;
+                s.serialize_field("transfer_syntax", &transfer_syntax.to_id_string())?;
+                s.end()
+            }
+            CharacterStringIdentification::TransferSyntax(oid) => {
+                serializer.serialize_newtype_variant(NAME, 4, "TransferSyntax", &oid.to_id_string())
+            }
+            CharacterStringIdentification::Fixed => {
+                serializer.serialize_unit_variant(NAME, 5, "Fixed")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use hex_literal::hex;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde_serialize() {
+        // SEQUENCE { INTEGER 1, OID 1.2.3, UTF8String "ab", NULL }
+        let bytes = hex!("30 0d 02 01 01 06 02 2a 03 0c 02 61 62 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let value = serde_json::to_value(&obj).expect("serialization failed");
+        let item = |tag: u32, content| json!({ "class": "Universal", "tag": tag, "constructed": false, "content": content });
+        let expected = json!({
+            "class": "Universal",
+            "tag": 16,
+            "constructed": true,
+            "content": { "Sequence": [
+                item(2, json!({ "Integer": [1] })),
+                item(6, json!({ "OID": "1.2.3" })),
+                item(12, json!({ "UTF8String": "ab" })),
+                item(5, json!("Null")),
+            ]},
+        });
+        assert_eq!(value, expected);
+    }
+}
@@ -1,7 +1,8 @@
 #![cfg(feature = "std")]
 use crate::ber::*;
 use crate::oid::Oid;
-use asn1_rs::ASN1DateTime;
+use alloc::string::String;
+use asn1_rs::{ASN1DateTime, ASN1TimeZone};
 use cookie_factory::bytes::be_u8;
 use cookie_factory::combinator::slice;
 use cookie_factory::gen_simple;
@@ -44,12 +45,24 @@ pub fn ber_encode_header<'a, 'b: 'a, W: Write + 'a>(hdr: &'b Header) -> impl Ser
         // identifier octets (X.690 8.1.2)
         let class_u8 = (hdr.class() as u8) << 6;
         let pc_u8 = (if hdr.constructed() { 1 } else { 0 }) << 5;
-        if hdr.tag().0 >= 30 {
-            unimplemented!();
+        let tag = hdr.tag().0;
+        if tag < 0x1f {
+            let byte_0 = class_u8 | pc_u8 | (tag as u8);
+            // length octets (X.690 8.1.3)
+            return tuple((be_u8(byte_0), encode_length(hdr.length())))(out);
         }
-        let byte_0 = class_u8 | pc_u8 | (hdr.tag().0 as u8);
-        // length octets (X.690 8.1.3)
-        tuple((be_u8(byte_0), encode_length(hdr.length())))(out)
+        // high tag number: base 128, most significant group first (X.690 8.1.2.4)
+        let byte_0 = class_u8 | pc_u8 | 0x1f;
+        let mut v: Vec<u8> = (0..5)
+            .rev()
+            .map(|idx| ((tag >> (7 * idx)) & 0x7f) as u8)
+            .skip_while(|&b| b == 0)
+            .map(|b| b | 0x80)
+            .collect();
+        if let Some(last) = v.last_mut() {
+            *last &= 0x7f;
+        }
+        tuple((be_u8(byte_0), slice(v), encode_length(hdr.length())))(out)
     }
 }
 
@@ -62,11 +75,87 @@ fn ber_encode_oid<'a, W: Write + 'a>(oid: &'a Oid) -> impl SerializeFn<W> + 'a {
 
 fn ber_encode_datetime<'a, W: Write + 'a>(time: &'a ASN1DateTime) -> impl SerializeFn<W> + 'a {
     move |out| {
+        // the Display implementation always ends with 'Z'
         let s = format!("{}", time);
+        let s = s.trim_end_matches('Z');
+        let s = match time.tz {
+            ASN1TimeZone::Undefined => String::from(s),
+            ASN1TimeZone::Z => format!("{}Z", s),
+            ASN1TimeZone::Offset(h, m) => {
+                let sign = if h < 0 || m < 0 { '-' } else { '+' };
+                format!(
+                    "{}{}{:02}{:02}",
+                    s,
+                    sign,
+                    h.unsigned_abs(),
+                    m.unsigned_abs()
+                )
+            }
+        };
         slice(s)(out)
     }
 }
 
+fn ber_encode_context_specific(
+    tag: u32,
+    constructed: bool,
+    data: &[u8],
+) -> Result<Vec<u8>, GenError> {
+    let hdr = Header::new(
+        Class::ContextSpecific,
+        constructed,
+        Tag(tag),
+        data.len().into(),
+    );
+    gen_simple(tuple((ber_encode_header(&hdr), slice(data))), Vec::new())
+}
+
+fn ber_encode_characterstring<'a, W: Write + 'a>(
+    c: &'a CharacterStringObject,
+) -> impl SerializeFn<W> + 'a {
+    move |out| {
+        let choice = match &c.identification {
+            CharacterStringIdentification::Syntaxes {
+                abstract_syntax,
+                transfer_syntax,
+            } => {
+                let mut v = ber_encode_context_specific(0, false, abstract_syntax.as_bytes())?;
+                v.extend(ber_encode_context_specific(
+                    1,
+                    false,
+                    transfer_syntax.as_bytes(),
+                )?);
+                ber_encode_context_specific(0, true, &v)?
+            }
+            CharacterStringIdentification::Syntax(oid) => {
+                ber_encode_context_specific(1, false, oid.as_bytes())?
+            }
+            CharacterStringIdentification::PresentationContextId(i) => {
+                ber_encode_context_specific(2, false, i)?
+            }
+            CharacterStringIdentification::ContextNegotiation {
+                presentation_context_id,
+                transfer_syntax,
+            } => {
+                let mut v = ber_encode_context_specific(0, false, presentation_context_id)?;
+                v.extend(ber_encode_context_specific(
+                    1,
+                    false,
+                    transfer_syntax.as_bytes(),
+                )?);
+                ber_encode_context_specific(3, true, &v)?
+            }
+            CharacterStringIdentification::TransferSyntax(oid) => {
+                ber_encode_context_specific(4, false, oid.as_bytes())?
+            }
+            CharacterStringIdentification::Fixed => ber_encode_context_specific(5, false, &[])?,
+        };
+        let identification = ber_encode_context_specific(0, true, &choice)?;
+        let value = ber_encode_context_specific(2, false, c.string_value)?;
+        tuple((slice(identification), slice(value)))(out)
+    }
+}
+
 fn ber_encode_sequence<'a, W: Write + Default + AsRef<[u8]> + 'a>(
     v: &'a [BerObject],
 ) -> impl SerializeFn<W> + 'a {
@@ -153,6 +242,7 @@ fn ber_encode_object_content<'a, W: Write + Default + AsRef<[u8]> + 'a>(
         | BerObjectContent::VideotexString(s)
         | BerObjectContent::UTF8String(s) => slice(s)(out),
         BerObjectContent::UniversalString(s) => slice(s)(out),
+        BerObjectContent::CharacterString(c) => ber_encode_characterstring(c)(out),
         BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => ber_encode_sequence(v)(out),
         // best we can do is tagged-explicit, but we don't know
         BerObjectContent::Optional(inner) => {
@@ -167,6 +257,9 @@ fn ber_encode_object_content<'a, W: Write + Default + AsRef<[u8]> + 'a>(
             // XXX wrong, we should wrap it!
             ber_encode_object(inner)(out)
         }
+        BerObjectContent::TagOverflow(_, data) | BerObjectContent::Primitive(_, data) => {
+            slice(data)(out)
+        }
         BerObjectContent::Unknown(any) => slice(any.data)(out),
     }
 }
@@ -201,6 +294,10 @@ pub fn ber_encode_object<'a, 'b: 'a, W: Write + Default + AsRef<[u8]> + 'a>(
         // XXX should we make an exception for tagged values here ?
         let v = gen_simple(ber_encode_object_content(&obj.content), W::default())?;
         let len = v.as_ref().len();
+        if let BerObjectContent::TagOverflow(identifier, _) = obj.content {
+            // the tag cannot be encoded from the header, use the raw identifier octets
+            return tuple((slice(identifier), encode_length(len), slice(v)))(out);
+        }
         let hdr = obj.header.clone().with_length(len.into());
         let v_hdr = gen_simple(ber_encode_header(&hdr), W::default())?;
         tuple((slice(v_hdr), slice(v)))(out)
@@ -260,6 +357,54 @@ mod test {
         assert_eq!(&v[..], &bytes[..2]);
     }
 
+    #[test]
+    fn test_encode_header_high_tag() {
+        for (class, tag, bytes) in [
+            (Class::Universal, 30, &hex!("1e 00")[..]),
+            (Class::ContextSpecific, 31, &hex!("9f 1f 00")),
+            (Class::Application, 1000, &hex!("5f 87 68 00")),
+            (Class::Private, 0x4000, &hex!("df 81 80 00 00")),
+            (Class::Private, u32::MAX, &hex!("df 8f ff ff ff 7f 00")),
+        ] {
+            let hdr = Header::new(class, false, Tag(tag), Length::Definite(0));
+            let v = encode_and_parse!(hdr, ber_encode_header, ber_read_element_header);
+            assert_eq!(&v[..], bytes);
+        }
+    }
+
+    #[test]
+    fn test_encode_tagged_high_tag() {
+        let inner = BerObject::from_int_slice(b"\x01");
+        let obj = BerObject::from_tagged_explicit(Class::Application, Tag(1000), inner);
+        let v = obj.to_vec().expect("could not encode");
+        assert_eq!(&v[..], &hex!("7f 87 68 03 02 01 01"));
+        let obj = BerObject::from_tagged_implicit(Class::Private, Tag(200), false, b"\x01");
+        let v = obj.to_vec().expect("could not encode");
+        assert_eq!(&v[..], &hex!("df 81 48 01 01"));
+        let (_, obj2) = parse_ber(&v).expect("could not re-parse");
+        assert_eq!((obj2.class(), obj2.tag()), (Class::Private, Tag(200)));
+        assert_eq!(format!("{}", obj2.tag()), "Tag(200 / 0xc8)");
+    }
+
+    #[test]
+    fn test_encode_tag_overflow() {
+        let bytes = hex!("30 0a df 90 80 80 80 00 01 ff 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("could not parse");
+        assert_eq!(obj.to_vec().expect("could not encode"), &bytes[..]);
+    }
+
+    #[test]
+    fn test_encode_generalizedtime_offset() {
+        for bytes in [
+            &b"\x18\x1319991231235959+0530"[..],
+            b"\x18\x1319991231235959-0030",
+            b"\x18\x0e19991231235959",
+        ] {
+            let (_, obj) = parse_ber(bytes).expect("could not parse");
+            assert_eq!(obj.to_vec().expect("could not encode"), bytes);
+        }
+    }
+
     #[test]
     fn test_encode_bool() {
         let b_true = BerObject::from_obj(BerObjectContent::Boolean(true));
@@ -325,6 +470,17 @@ mod test {
         assert_eq!(&v[..], bytes);
     }
 
+    #[test]
+    fn test_encode_characterstring() {
+        let bytes = hex!("3d 09 a0 02 85 00 82 03 61 62 63");
+        let obj = BerObject::from_obj(BerObjectContent::CharacterString(CharacterStringObject {
+            identification: CharacterStringIdentification::Fixed,
+            string_value: b"abc",
+        }));
+        let v = encode_and_parse!(obj, ber_encode_object, parse_ber_characterstring);
+        assert_eq!(&v[..], bytes);
+    }
+
     #[test]
     fn test_encode_sequence() {
         let bytes = hex!("30 0a 02 03 01 00 01 02 03 01 00 00");
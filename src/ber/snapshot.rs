@@ -0,0 +1,156 @@
+use crate::ber::{Class, Header, Length, Tag};
+use crate::error::BerError;
+use core::convert::TryFrom;
+
+/// A copy of the metadata of a header, with a C-compatible layout
+///
+/// [`Header`] borrows the input (for the raw tag) and uses Rust enums, so it cannot be passed
+/// across FFI or stored as is. This type contains only integer fields, with a fixed size of 16
+/// bytes and no padding, so it can be passed to C code, or stored in memory-mapped indices
+/// (see [`HeaderSnapshot::to_le_bytes`]).
+///
+/// The raw tag is not stored.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{Header, HeaderSnapshot};
+/// use der_parser::der::parse_der;
+/// use std::convert::TryFrom;
+///
+/// let (_, obj) = parse_der(&[0x30, 0x03, 0x02, 0x01, 0x01]).expect("parsing failed");
+/// let snapshot = HeaderSnapshot::from(&obj.header);
+/// assert_eq!((snapshot.tag, snapshot.constructed, snapshot.length), (16, 1, 3));
+///
+/// let header = Header::try_from(snapshot).expect("invalid header");
+/// assert_eq!(header, obj.header);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct HeaderSnapshot {
+    /// Tag number
+    pub tag: u32,
+    /// Class (0: universal, 1: application, 2: context-specific, 3: private)
+    pub class: u8,
+    /// 1 if the object is constructed, else 0
+    pub constructed: u8,
+    /// 1 if the length is indefinite, else 0
+    pub indefinite: u8,
+    /// Reserved, must be 0
+    pub reserved: u8,
+    /// Length of the content (0 if the length is indefinite)
+    pub length: u64,
+}
+
+impl HeaderSnapshot {
+    /// Size in bytes of the encoding returned by [`HeaderSnapshot::to_le_bytes`]
+    pub const SIZE: usize = 16;
+
+    /// Encode the snapshot as bytes, in the order of the fields (little-endian integers)
+    pub fn to_le_bytes(&self) -> [u8; Self::SIZE] {
+        let mut b = [0; Self::SIZE];
+        b[..4].copy_from_slice(&self.tag.to_le_bytes());
+        b[4] = self.class;
+        b[5] = self.constructed;
+        b[6] = self.indefinite;
+        b[7] = self.reserved;
+        b[8..].copy_from_slice(&self.length.to_le_bytes());
+        b
+    }
+
+    /// Decode a snapshot encoded using [`HeaderSnapshot::to_le_bytes`]
+    ///
+    /// Values are not checked, use `Header::try_from` to validate them.
+    pub fn from_le_bytes(b: [u8; Self::SIZE]) -> Self {
+        let mut tag = [0; 4];
+        tag.copy_from_slice(&b[..4]);
+        let mut length = [0; 8];
+        length.copy_from_slice(&b[8..]);
+        HeaderSnapshot {
+            tag: u32::from_le_bytes(tag),
+            class: b[4],
+            constructed: b[5],
+            indefinite: b[6],
+            reserved: b[7],
+            length: u64::from_le_bytes(length),
+        }
+    }
+}
+
+impl<'a, 'b> From<&'b Header<'a>> for HeaderSnapshot {
+    fn from(header: &'b Header<'a>) -> Self {
+        let (indefinite, length) = match header.length() {
+            Length::Definite(len) => (0, len as u64),
+            Length::Indefinite => (1, 0),
+        };
+        HeaderSnapshot {
+            tag: header.tag().0,
+            class: header.class() as u8,
+            constructed: u8::from(header.is_constructed()),
+            indefinite,
+            reserved: 0,
+            length,
+        }
+    }
+}
+
+impl<'a> From<Header<'a>> for HeaderSnapshot {
+    fn from(header: Header<'a>) -> Self {
+        HeaderSnapshot::from(&header)
+    }
+}
+
+/// Rebuild a header from a snapshot
+///
+/// Returns `BerError::InvalidValue` if a field has an invalid value, or `BerError::InvalidLength`
+/// if the length does not fit in a `usize`.
+impl TryFrom<HeaderSnapshot> for Header<'static> {
+    type Error = BerError;
+
+    fn try_from(s: HeaderSnapshot) -> Result<Self, Self::Error> {
+        let invalid = |msg: &str| BerError::InvalidValue {
+            tag: Tag(s.tag),
+            msg: msg.into(),
+        };
+        let class = Class::try_from(s.class).map_err(|_| invalid("invalid class"))?;
+        if s.constructed > 1 || s.indefinite > 1 || s.reserved != 0 {
+            return Err(invalid("invalid flags"));
+        }
+        let length = match (s.indefinite, s.length) {
+            (0, len) => Length::Definite(usize::try_from(len).or(Err(BerError::InvalidLength))?),
+            (_, 0) => Length::Indefinite,
+            _ => return Err(invalid("indefinite length with a value")),
+        };
+        Ok(Header::new(class, s.constructed == 1, Tag(s.tag), length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::size_of;
+
+    #[test]
+    fn test_header_snapshot() {
+        assert_eq!(size_of::<HeaderSnapshot>(), HeaderSnapshot::SIZE);
+
+        let header = Header::new(Class::ContextSpecific, true, Tag(300), Length::Indefinite);
+        let snapshot = HeaderSnapshot::from(&header);
+        assert_eq!((snapshot.class, snapshot.indefinite), (2, 1));
+        let bytes = snapshot.to_le_bytes();
+        assert_eq!(&bytes[..8], &[0x2c, 0x01, 0, 0, 2, 1, 1, 0]);
+        assert_eq!(HeaderSnapshot::from_le_bytes(bytes), snapshot);
+        assert_eq!(Header::try_from(snapshot), Ok(header));
+
+        let invalid = HeaderSnapshot {
+            class: 4,
+            ..snapshot
+        };
+        assert!(Header::try_from(invalid).is_err());
+        let invalid = HeaderSnapshot {
+            length: 1,
+            ..snapshot
+        };
+        assert!(Header::try_from(invalid).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::ber::{BerObject, Class, Length, Tag};
+use alloc::vec::Vec;
+
+/// Statistics on a tree of BER objects
+///
+/// See [`BerObject::stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BerStats {
+    /// Number of objects, including the root object
+    pub nodes: usize,
+    /// Maximum depth (the depth of the root object is 0)
+    pub max_depth: usize,
+    /// Total length of the content of primitive objects
+    pub content_bytes: usize,
+    /// Number of objects for each class and tag, sorted by class and tag
+    pub tags: Vec<(Class, Tag, usize)>,
+}
+
+impl BerStats {
+    /// Get the number of objects with class `class` and tag `tag`
+    pub fn count(&self, class: Class, tag: Tag) -> usize {
+        self.tags
+            .iter()
+            .find(|(c, t, _)| *c == class && *t == tag)
+            .map_or(0, |(_, _, n)| *n)
+    }
+}
+
+impl<'a> BerObject<'a> {
+    /// Compute statistics on this object and all its descendants
+    ///
+    /// This can be used to enforce limits on the size or complexity of parsed objects.
+    ///
+    /// The length of the content is read from the span of the objects, or from the header for
+    /// objects that were not read by a parser.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use der_parser::ber::{parse_ber, Class, Tag};
+    ///
+    /// let bytes = &[0x30, 0x08, 0x02, 0x01, 0x01, 0x30, 0x03, 0x02, 0x01, 0x02];
+    /// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    /// let stats = obj.stats();
+    /// assert_eq!(stats.nodes, 4);
+    /// assert_eq!(stats.max_depth, 2);
+    /// assert_eq!(stats.count(Class::Universal, Tag::Integer), 2);
+    /// ```
+    pub fn stats(&self) -> BerStats {
+        let mut stats = BerStats::default();
+        for (depth, obj) in self.iter_depth_first() {
+            stats.nodes += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            if obj.get(0).is_none() && !obj.header.is_constructed() {
+                stats.content_bytes += match (obj.span, obj.header.length()) {
+                    (Some(span), _) => span.content_len(),
+                    (None, Length::Definite(l)) => l,
+                    (None, Length::Indefinite) => 0,
+                };
+            }
+            let (class, tag) = (obj.class(), obj.tag());
+            match stats
+                .tags
+                .iter_mut()
+                .find(|(c, t, _)| *c == class && *t == tag)
+            {
+                Some((_, _, n)) => *n += 1,
+                None => stats.tags.push((class, tag, 1)),
+            }
+        }
+        stats.tags.sort_by_key(|(c, t, _)| (*c as u8, t.0));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_stats() {
+        // SEQUENCE { INTEGER 1, SET (indefinite) { NULL, OCTET STRING }, [0] IMPLICIT }
+        let bytes = hex!("30 11 02 01 01 31 80 05 00 04 03 01 02 03 00 00 80 01 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let stats = obj.stats();
+        assert_eq!(stats.nodes, 6);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.content_bytes, 5);
+        assert_eq!(
+            stats.tags,
+            vec![
+                (Class::Universal, Tag::Integer, 1),
+                (Class::Universal, Tag::OctetString, 1),
+                (Class::Universal, Tag::Null, 1),
+                (Class::Universal, Tag::Sequence, 1),
+                (Class::Universal, Tag::Set, 1),
+                (Class::ContextSpecific, Tag(0), 1),
+            ]
+        );
+        assert_eq!(stats.count(Class::Application, Tag(0)), 0);
+    }
+}
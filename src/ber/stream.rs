@@ -0,0 +1,351 @@
+use crate::ber::{Class, Tag, MAX_RECURSION};
+use crate::error::{length_overflow, limit_exceeded, BerError};
+use std::io::{self, Read};
+use std::vec::Vec;
+
+/// Header of an object read from a stream
+///
+/// Unlike [`Header`](crate::ber::Header), the length is stored using 64 bits, so the headers of
+/// very large objects can be read on all platforms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamHeader {
+    /// Class
+    pub class: Class,
+    /// True if the object is constructed
+    pub constructed: bool,
+    /// Tag
+    pub tag: Tag,
+    /// Length of the content, or `None` if the length is indefinite
+    pub length: Option<u64>,
+    /// Number of identifier and length octets
+    pub header_len: usize,
+}
+
+/// Read the header of an object from `reader`
+///
+/// Exactly the identifier and length octets are read. Invalid headers raise an error of kind
+/// `InvalidData`, wrapping a [`BerError`] ([`length_overflow`] if the length does not fit in 64
+/// bits).
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_header_from_reader, Tag};
+///
+/// // OCTET STRING, with a 5 GiB length (only the header is present)
+/// let mut bytes = &[0x04, 0x85, 0x01, 0x40, 0x00, 0x00, 0x00][..];
+/// let header = ber_read_header_from_reader(&mut bytes).expect("invalid header");
+/// assert_eq!(header.tag, Tag::OctetString);
+/// assert_eq!(header.length, Some(5 << 30));
+/// assert_eq!(header.header_len, 7);
+/// ```
+pub fn ber_read_header_from_reader<R: Read + ?Sized>(reader: &mut R) -> io::Result<StreamHeader> {
+    let b0 = read_u8(reader)?;
+    let mut header_len = 1;
+    let class = match b0 >> 6 {
+        0b00 => Class::Universal,
+        0b01 => Class::Application,
+        0b10 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = b0 & 0x20 != 0;
+    let mut tag = u32::from(b0 & 0x1f);
+    if tag == 0x1f {
+        tag = 0;
+        loop {
+            let b = read_u8(reader)?;
+            header_len += 1;
+            if tag >> 25 != 0 {
+                return Err(invalid_data(BerError::InvalidTag));
+            }
+            tag = (tag << 7) | u32::from(b & 0x7f);
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+    let b = read_u8(reader)?;
+    header_len += 1;
+    let length = match b {
+        0x80 if !constructed => return Err(invalid_data(BerError::ConstructExpected)),
+        0x80 => None,
+        0x00..=0x7f => Some(u64::from(b)),
+        0xff => return Err(invalid_data(BerError::InvalidLength)),
+        _ => {
+            let mut l = 0u64;
+            for _ in 0..(b & 0x7f) {
+                let b = read_u8(reader)?;
+                header_len += 1;
+                if l >> 56 != 0 {
+                    return Err(invalid_data(length_overflow()));
+                }
+                l = (l << 8) | u64::from(b);
+            }
+            Some(l)
+        }
+    };
+    Ok(StreamHeader {
+        class,
+        constructed,
+        tag: Tag(tag),
+        length,
+        header_len,
+    })
+}
+
+/// Read an object from `reader` and discard it, returning the number of bytes read
+///
+/// The content is not stored, and lengths are 64-bit values: this allows skipping objects larger
+/// than the memory (or than `usize::MAX` on 32-bit platforms). Objects with an indefinite length
+/// are read until their end-of-content octets.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::ber_skip_object_from_reader;
+///
+/// // OCTET STRING "abc", INTEGER 1
+/// let mut bytes = &[0x04, 0x03, 0x61, 0x62, 0x63, 0x02, 0x01, 0x01][..];
+/// assert_eq!(ber_skip_object_from_reader(&mut bytes).expect("invalid object"), 5);
+/// assert_eq!(bytes, &[0x02, 0x01, 0x01]);
+/// ```
+pub fn ber_skip_object_from_reader<R: Read + ?Sized>(reader: &mut R) -> io::Result<u64> {
+    skip_object(reader, MAX_RECURSION).map(|(len, _)| len)
+}
+
+/// Read a string object from `reader`, reassembling the segments of constructed strings
+///
+/// The object can be primitive, or constructed with a definite or indefinite length (BER only,
+/// X.690 8.23.6): the content of the segments is concatenated. Segments must be universal objects
+/// with the same tag as the string. For BIT STRING objects, the returned data starts with the
+/// number of unused bits, as in a primitive encoding.
+///
+/// At most `max_len` bytes are buffered (the length of the returned data): longer strings raise
+/// an error of kind `InvalidData`, wrapping [`limit_exceeded`], before the segment exceeding the
+/// limit is read. This bounds the memory used for hostile streams. Other errors are the
+/// same as for [`ber_read_header_from_reader`].
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{ber_read_string_from_reader, Tag};
+///
+/// // OCTET STRING (indefinite) { OCTET STRING "ab", OCTET STRING "c" }
+/// let bytes = &[0x24, 0x80, 0x04, 0x02, 0x61, 0x62, 0x04, 0x01, 0x63, 0x00, 0x00];
+/// let (header, data) = ber_read_string_from_reader(&mut &bytes[..], 16).expect("invalid object");
+/// assert_eq!(header.tag, Tag::OctetString);
+/// assert_eq!(data, b"abc");
+/// assert!(ber_read_string_from_reader(&mut &bytes[..], 2).is_err());
+/// ```
+pub fn ber_read_string_from_reader<R: Read + ?Sized>(
+    reader: &mut R,
+    max_len: usize,
+) -> io::Result<(StreamHeader, Vec<u8>)> {
+    let header = ber_read_header_from_reader(reader)?;
+    let mut data = Vec::new();
+    let bitstring = header.tag == Tag::BitString;
+    let mut unused_bits = None;
+    read_string_segments(
+        reader,
+        &header,
+        max_len,
+        MAX_RECURSION,
+        &mut data,
+        &mut unused_bits,
+        bitstring,
+    )?;
+    if bitstring {
+        let unused_bits = unused_bits.ok_or_else(|| invalid_data(BerError::BerValueError))?;
+        data.insert(0, unused_bits);
+    }
+    Ok((header, data))
+}
+
+/// Append the content of the string `header` to `data`
+///
+/// For BIT STRINGs, the unused bits octet of segments is removed, and the value of the last
+/// segment is stored in `unused_bits`.
+fn read_string_segments<R: Read + ?Sized>(
+    reader: &mut R,
+    header: &StreamHeader,
+    max_len: usize,
+    max_depth: usize,
+    data: &mut Vec<u8>,
+    unused_bits: &mut Option<u8>,
+    bitstring: bool,
+) -> io::Result<()> {
+    if max_depth == 0 {
+        return Err(invalid_data(BerError::BerMaxDepth));
+    }
+    if !header.constructed {
+        let l = header
+            .length
+            .ok_or_else(|| invalid_data(BerError::InvalidLength))?;
+        let start = data.len();
+        if l > (max_len.saturating_sub(start)) as u64 {
+            return Err(invalid_data(limit_exceeded()));
+        }
+        reader.take(l).read_to_end(data)?;
+        if (data.len() - start) as u64 != l {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if bitstring {
+            if unused_bits.map_or(false, |b| b != 0) {
+                // only the last segment can have unused bits
+                return Err(invalid_data(BerError::BerValueError));
+            }
+            if start == data.len() {
+                return Err(invalid_data(BerError::BerValueError));
+            }
+            *unused_bits = Some(data.remove(start));
+        }
+        return Ok(());
+    }
+    let mut remaining = header.length;
+    loop {
+        if remaining == Some(0) {
+            return Ok(());
+        }
+        let segment = ber_read_header_from_reader(reader)?;
+        if let Some(r) = remaining.as_mut() {
+            let len = segment.length.unwrap_or(0) + segment.header_len as u64;
+            *r = r
+                .checked_sub(len)
+                .ok_or_else(|| invalid_data(BerError::InvalidLength))?;
+        }
+        if segment.class == Class::Universal
+            && segment.tag == Tag::EndOfContent
+            && segment.length == Some(0)
+        {
+            if remaining.is_none() {
+                return Ok(());
+            }
+            return Err(invalid_data(BerError::unexpected_tag(
+                Some(header.tag),
+                Tag::EndOfContent,
+            )));
+        }
+        if segment.class != Class::Universal || segment.tag != header.tag {
+            return Err(invalid_data(BerError::unexpected_tag(
+                Some(header.tag),
+                segment.tag,
+            )));
+        }
+        if segment.length.is_none() && remaining.is_some() {
+            // an indefinite segment in a definite string: its length is not known in advance
+            return Err(invalid_data(BerError::InvalidLength));
+        }
+        read_string_segments(
+            reader,
+            &segment,
+            max_len,
+            max_depth - 1,
+            data,
+            unused_bits,
+            bitstring,
+        )?;
+    }
+}
+
+/// Skip an object, and return its length, and true if it was an end-of-content
+fn skip_object<R: Read + ?Sized>(reader: &mut R, max_depth: usize) -> io::Result<(u64, bool)> {
+    if max_depth == 0 {
+        return Err(invalid_data(BerError::BerMaxDepth));
+    }
+    let header = ber_read_header_from_reader(reader)?;
+    let mut total = header.header_len as u64;
+    match header.length {
+        Some(0) if header.class == Class::Universal && header.tag == Tag::EndOfContent => {
+            return Ok((total, true));
+        }
+        Some(l) => {
+            let skipped = io::copy(&mut reader.take(l), &mut io::sink())?;
+            if skipped != l {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            total += l;
+        }
+        None => loop {
+            let (len, eoc) = skip_object(reader, max_depth - 1)?;
+            total += len;
+            if eoc {
+                break;
+            }
+        },
+    }
+    Ok((total, false))
+}
+
+fn read_u8<R: Read + ?Sized>(reader: &mut R) -> io::Result<u8> {
+    let mut b = [0u8];
+    reader.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn invalid_data(e: BerError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::ber_read_element_header;
+    use crate::error::{length_overflow, BerError};
+    use hex_literal::hex;
+    use nom::Err;
+
+    #[test]
+    fn test_length_overflow() {
+        // length does not fit in 64 bits
+        let bytes = hex!("04 89 01 00 00 00 00 00 00 00 00");
+        assert_eq!(
+            ber_read_element_header(&bytes),
+            Err(Err::Error(length_overflow()))
+        );
+        let err = ber_read_header_from_reader(&mut &bytes[..]).expect_err("length overflow");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let inner = err.get_ref().and_then(|e| e.downcast_ref::<BerError>());
+        assert_eq!(inner, Some(&length_overflow()));
+        // leading zeroes do not overflow
+        let bytes = hex!("04 89 00 00 00 00 00 00 00 00 01 ff");
+        let (_, hdr) = ber_read_element_header(&bytes).expect("parsing failed");
+        assert_eq!(hdr.length(), crate::ber::Length::Definite(1));
+    }
+
+    #[test]
+    fn test_read_string_from_reader() {
+        // BIT STRING (indefinite) { BIT STRING 0x0f, BIT STRING (definite) { BIT STRING 0xf0/4 } }
+        let bytes = hex!("23 80 03 02 00 0f 23 04 03 02 04 f0 00 00 05 00");
+        let mut reader = &bytes[..];
+        let (header, data) = ber_read_string_from_reader(&mut reader, 3).expect("invalid object");
+        assert_eq!((header.tag, header.length), (Tag::BitString, None));
+        assert_eq!(data, hex!("04 0f f0"));
+        assert_eq!(reader, hex!("05 00"));
+        // limit is checked before reading the content
+        let bytes = hex!("04 84 7f ff ff ff");
+        let err = ber_read_string_from_reader(&mut &bytes[..], 1 << 20).expect_err("too large");
+        let inner = err.get_ref().and_then(|e| e.downcast_ref::<BerError>());
+        assert_eq!(inner, Some(&limit_exceeded()));
+        // segments must have the tag of the string
+        let bytes = hex!("24 80 0c 01 61 00 00");
+        let err = ber_read_string_from_reader(&mut &bytes[..], 16).expect_err("invalid segment");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // unused bits in a segment which is not the last one
+        let bytes = hex!("23 80 03 02 04 f0 03 02 00 0f 00 00");
+        assert!(ber_read_string_from_reader(&mut &bytes[..], 16).is_err());
+    }
+
+    #[test]
+    fn test_skip_from_reader() {
+        // SEQUENCE (indefinite) { OCTET STRING (indefinite) { OCTET STRING "a" }, NULL }, NULL
+        let bytes = hex!("30 80 24 80 04 01 61 00 00 05 00 00 00 05 00");
+        let mut reader = &bytes[..];
+        assert_eq!(ber_skip_object_from_reader(&mut reader).unwrap(), 13);
+        assert_eq!(ber_skip_object_from_reader(&mut reader).unwrap(), 2);
+        assert!(reader.is_empty());
+        // truncated content
+        let mut reader = &hex!("04 85 01 00 00 00 00 61 62")[..];
+        let err = ber_skip_object_from_reader(&mut reader).expect_err("truncated");
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::ber::Tag;
+
+/// Get the ASN.1 name of the universal tag `tag` (for ex. `OCTET STRING` for tag 4)
+///
+/// All the tags assigned in X.680 are named, including the time types added in later revisions
+/// (`DATE`, `TIME-OF-DAY`, `DATE-TIME`, `DURATION`). Unassigned tags are named `unknown`.
+///
+/// The name does not depend on the class: use this function only for universal objects.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{tag_name, Tag, TagExt};
+///
+/// assert_eq!(tag_name(Tag::OctetString), "OCTET STRING");
+/// assert_eq!(Tag(33).name(), "DATE-TIME");
+/// assert_eq!(Tag(15).name(), "unknown");
+/// ```
+pub fn tag_name(tag: Tag) -> &'static str {
+    universal_tag_name(tag).unwrap_or("unknown")
+}
+
+/// Get the ASN.1 name of the universal tag `tag`, or `None` if the tag is not assigned
+pub(crate) fn universal_tag_name(tag: Tag) -> Option<&'static str> {
+    let name = match tag.0 {
+        0 => "END-OF-CONTENTS",
+        1 => "BOOLEAN",
+        2 => "INTEGER",
+        3 => "BIT STRING",
+        4 => "OCTET STRING",
+        5 => "NULL",
+        6 => "OBJECT IDENTIFIER",
+        7 => "ObjectDescriptor",
+        8 => "EXTERNAL",
+        9 => "REAL",
+        10 => "ENUMERATED",
+        11 => "EMBEDDED PDV",
+        12 => "UTF8String",
+        13 => "RELATIVE-OID",
+        14 => "TIME",
+        16 => "SEQUENCE",
+        17 => "SET",
+        18 => "NumericString",
+        19 => "PrintableString",
+        20 => "TeletexString",
+        21 => "VideotexString",
+        22 => "IA5String",
+        23 => "UTCTime",
+        24 => "GeneralizedTime",
+        25 => "GraphicString",
+        26 => "VisibleString",
+        27 => "GeneralString",
+        28 => "UniversalString",
+        29 => "CHARACTER STRING",
+        30 => "BMPString",
+        31 => "DATE",
+        32 => "TIME-OF-DAY",
+        33 => "DATE-TIME",
+        34 => "DURATION",
+        35 => "OID-IRI",
+        36 => "RELATIVE-OID-IRI",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Extension methods for [`Tag`]
+pub trait TagExt {
+    /// Get the ASN.1 name of this universal tag (see [`tag_name`])
+    fn name(&self) -> &'static str;
+}
+
+impl TagExt for Tag {
+    fn name(&self) -> &'static str {
+        tag_name(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_name() {
+        assert_eq!(tag_name(Tag::EndOfContent), "END-OF-CONTENTS");
+        assert_eq!(Tag::BitString.name(), "BIT STRING");
+        assert_eq!(Tag::Utf8String.name(), "UTF8String");
+        assert_eq!(Tag::Sequence.name(), "SEQUENCE");
+        assert_eq!(Tag(34).name(), "DURATION");
+        assert_eq!(universal_tag_name(Tag(37)), None);
+        assert_eq!(Tag(u32::MAX).name(), "unknown");
+    }
+}
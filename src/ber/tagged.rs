@@ -8,7 +8,7 @@ use nom::{Err, IResult};
 /// The built object will use the outer header (and tag), and contains a `Tagged` object
 /// with class, value and content.
 ///
-/// For a generic version (different output and error types), see
+/// For a generic version (different output types), see
 /// [parse_ber_tagged_explicit_g](fn.parse_ber_tagged_explicit_g.html).
 ///
 /// The following parses `[2] EXPLICIT INTEGER`:
@@ -35,13 +35,17 @@ use nom::{Err, IResult};
 /// #     _ => assert!(false)
 /// # }
 /// ```
-pub fn parse_ber_tagged_explicit<'a, T, F>(tag: T, f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_tagged_explicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: Fn(&'a [u8]) -> BerResult<BerObject>,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
     T: Into<Tag>,
 {
     let tag = tag.into();
-    parse_ber_tagged_explicit_g(tag, move |content, hdr| {
+    with_span(parse_ber_tagged_explicit_g(tag, move |content, hdr| {
         let (rem, obj) = f(content)?;
         let class = hdr.class();
         let obj2 = BerObject::from_header_and_content(
@@ -49,7 +53,7 @@ where
             BerObjectContent::Tagged(class, tag, Box::new(obj)),
         );
         Ok((rem, obj2))
-    })
+    }))
 }
 
 /// Read a TAGGED EXPLICIT value (generic version)
@@ -110,7 +114,7 @@ where
 /// The built object will use the original header (and tag), so the content may not match the tag
 /// value.
 ///
-/// For a generic version (different output and error types), see
+/// For a generic version (different output types), see
 /// [parse_ber_tagged_implicit_g](fn.parse_ber_tagged_implicit_g.html).
 ///
 /// # Examples
@@ -167,18 +171,22 @@ where
 /// #     _ => assert!(false)
 /// # }
 /// ```
-pub fn parse_ber_tagged_implicit<'a, T, F>(tag: T, f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_ber_tagged_implicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
 where
-    F: Fn(&'a [u8], &'_ Header, usize) -> BerResult<'a, BerObjectContent<'a>>,
+    F: Fn(&'a [u8], &'_ Header, usize) -> IResult<&'a [u8], BerObjectContent<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
     T: Into<Tag>,
 {
     let tag = tag.into();
-    parse_ber_tagged_implicit_g(tag, move |i, hdr, depth| {
+    with_span(parse_ber_tagged_implicit_g(tag, move |i, hdr, depth| {
         let (rem, content) = f(i, &hdr, depth)?;
         // trailing bytes are ignored
         let obj = BerObject::from_header_and_content(hdr, content);
         Ok((rem, obj))
-    })
+    }))
 }
 
 /// Read a TAGGED IMPLICIT value (generic version)
@@ -257,3 +265,188 @@ where
         // trailing bytes are ignored
     })
 }
+
+/// Read a TAGGED EXPLICIT value with a given class (generic version)
+///
+/// Same as [parse_ber_tagged_explicit_g](fn.parse_ber_tagged_explicit_g.html), but the class of
+/// the tagged object must be `class`. See also the `parse_ber_application_*` and
+/// `parse_ber_private_*` functions.
+///
+/// # Examples
+///
+/// The following parses `[APPLICATION 2] EXPLICIT INTEGER` into an `u32`:
+///
+/// ```rust
+/// # use der_parser::ber::*;
+/// # use der_parser::error::BerResult;
+/// fn parse_int_explicit(i: &[u8]) -> BerResult<'_, u32> {
+///     parse_ber_tagged_explicit_class_g(Class::Application, 2, |content, _hdr| {
+///         let (rem, obj) = parse_ber_integer(content)?;
+///         Ok((rem, obj.as_u32()?))
+///     })(i)
+/// }
+///
+/// let (_, val) = parse_int_explicit(&[0x62, 0x03, 0x02, 0x01, 0x07]).expect("parsing failed");
+/// assert_eq!(val, 7);
+/// // context-specific tag
+/// assert!(parse_int_explicit(&[0xa2, 0x03, 0x02, 0x01, 0x07]).is_err());
+/// ```
+pub fn parse_ber_tagged_explicit_class_g<'a, T, Output, F, E>(
+    class: Class,
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Output, E>
+where
+    F: Fn(&'a [u8], Header<'a>) -> IResult<&'a [u8], Output, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    parse_ber_tagged_explicit_g(tag, move |content, hdr| {
+        hdr.assert_class(class).map_err(|e| Err::Error(e.into()))?;
+        f(content, hdr)
+    })
+}
+
+/// Read a TAGGED IMPLICIT value with a given class (generic version)
+///
+/// Same as [parse_ber_tagged_implicit_g](fn.parse_ber_tagged_implicit_g.html), but the class of
+/// the tagged object must be `class`.
+pub fn parse_ber_tagged_implicit_class_g<'a, T, Output, F, E>(
+    class: Class,
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Output, E>
+where
+    F: Fn(&'a [u8], Header<'a>, usize) -> IResult<&'a [u8], Output, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    parse_ber_tagged_implicit_g(tag, move |content, hdr, depth| {
+        hdr.assert_class(class).map_err(|e| Err::Error(e.into()))?;
+        f(content, hdr, depth)
+    })
+}
+
+fn ber_explicit_object<'a, F, E>(
+    class: Class,
+    tag: Tag,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    with_span(parse_ber_tagged_explicit_class_g(
+        class,
+        tag,
+        move |content, hdr| {
+            let (rem, obj) = f(content)?;
+            let obj2 = BerObject::from_header_and_content(
+                hdr,
+                BerObjectContent::Tagged(class, tag, Box::new(obj)),
+            );
+            Ok((rem, obj2))
+        },
+    ))
+}
+
+fn ber_implicit_object<'a, F, E>(
+    class: Class,
+    tag: Tag,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8], &'_ Header, usize) -> IResult<&'a [u8], BerObjectContent<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    with_span(parse_ber_tagged_implicit_class_g(
+        class,
+        tag,
+        move |i, hdr, depth| {
+            let (rem, content) = f(i, &hdr, depth)?;
+            Ok((rem, BerObject::from_header_and_content(hdr, content)))
+        },
+    ))
+}
+
+/// Read an APPLICATION EXPLICIT value (combinator)
+///
+/// Same as [parse_ber_tagged_explicit](fn.parse_ber_tagged_explicit.html), for an object of
+/// class APPLICATION.
+///
+/// The following parses `[APPLICATION 2] EXPLICIT INTEGER`:
+///
+/// ```rust
+/// # use der_parser::ber::*;
+/// let mut parser = parse_ber_application_explicit(2, parse_ber_integer);
+/// let (_, obj) = parser(&[0x62, 0x03, 0x02, 0x01, 0x07]).expect("parsing failed");
+/// assert_eq!(obj.as_tagged().map(|t| t.2.as_u32()), Ok(Ok(7)));
+/// ```
+pub fn parse_ber_application_explicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    ber_explicit_object(Class::Application, tag.into(), f)
+}
+
+/// Read an APPLICATION IMPLICIT value (combinator)
+///
+/// Same as [parse_ber_tagged_implicit](fn.parse_ber_tagged_implicit.html), for an object of
+/// class APPLICATION.
+///
+/// The following parses `[APPLICATION 2] IMPLICIT INTEGER`:
+///
+/// ```rust
+/// # use der_parser::ber::*;
+/// let mut parser = parse_ber_application_implicit(2, parse_ber_content(Tag::Integer));
+/// let (_, obj) = parser(&[0x42, 0x01, 0x07]).expect("parsing failed");
+/// assert_eq!(obj.as_u32(), Ok(7));
+/// ```
+pub fn parse_ber_application_implicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8], &'_ Header, usize) -> IResult<&'a [u8], BerObjectContent<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    ber_implicit_object(Class::Application, tag.into(), f)
+}
+
+/// Read a PRIVATE EXPLICIT value (combinator)
+///
+/// Same as [parse_ber_tagged_explicit](fn.parse_ber_tagged_explicit.html), for an object of
+/// class PRIVATE.
+pub fn parse_ber_private_explicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    ber_explicit_object(Class::Private, tag.into(), f)
+}
+
+/// Read a PRIVATE IMPLICIT value (combinator)
+///
+/// Same as [parse_ber_tagged_implicit](fn.parse_ber_tagged_implicit.html), for an object of
+/// class PRIVATE.
+pub fn parse_ber_private_implicit<'a, T, F, E>(
+    tag: T,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], BerObject<'a>, E>
+where
+    F: Fn(&'a [u8], &'_ Header, usize) -> IResult<&'a [u8], BerObjectContent<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+    T: Into<Tag>,
+{
+    ber_implicit_object(Class::Private, tag.into(), f)
+}
@@ -1,4 +1,4 @@
-use super::{BerObject, BerObjectContent, BitStringObject};
+use super::{BerObject, BerObjectContent, BitStringObject, CharacterStringObject};
 use asn1_rs::{ASN1DateTime, Any, Class, Oid, Tag};
 
 /// BER object tree traversal to walk a shared borrow of a BER object
@@ -10,7 +10,8 @@ use asn1_rs::{ASN1DateTime, Any, Class, Oid, Tag};
 /// methods are not needed.
 ///
 /// For example, on a `Sequence` item, `visit_ber` is called first, then `visit_ber_sequence`, and then
-/// `visit_ber` for every sequence object (recursively).
+/// `visit_ber` for every sequence object (recursively). After all sequence objects have been visited,
+/// `visit_ber_sequence_exit` and then `visit_ber_exit` are called.
 ///
 /// Entry point: use the [`Visit::run`] or [`Visit::run_at`] methods.
 ///
@@ -20,6 +21,9 @@ pub trait Visit<'a> {
     /// Called for every BER object
     fn visit_ber(&mut self, ber: &'_ BerObject<'a>, depth: usize) {}
 
+    /// Called for every BER object, after the object and its children have been visited
+    fn visit_ber_exit(&mut self, ber: &'_ BerObject<'a>, depth: usize) {}
+
     /// Called for BER bitstring objects
     fn visit_ber_bitstring(&mut self, ignored: u8, data: &'a BitStringObject, depth: usize) {}
 
@@ -29,6 +33,9 @@ pub trait Visit<'a> {
     /// Called for BER boolean objects
     fn visit_ber_boolean(&mut self, b: bool, depth: usize) {}
 
+    /// Called for BER characterstring objects
+    fn visit_ber_characterstring(&mut self, c: &'a CharacterStringObject, depth: usize) {}
+
     /// Called for BER end-of-content objects
     fn visit_ber_endofcontent(&mut self, depth: usize) {}
 
@@ -77,9 +84,15 @@ pub trait Visit<'a> {
     /// Called for BER sequence objects
     fn visit_ber_sequence(&mut self, ber: &'_ [BerObject<'a>], depth: usize) {}
 
+    /// Called for BER sequence objects, after all sequence objects have been visited
+    fn visit_ber_sequence_exit(&mut self, ber: &'_ [BerObject<'a>], depth: usize) {}
+
     /// Called for BER set objects
     fn visit_ber_set(&mut self, ber: &'_ [BerObject<'a>], depth: usize) {}
 
+    /// Called for BER set objects, after all set objects have been visited
+    fn visit_ber_set_exit(&mut self, ber: &'_ [BerObject<'a>], depth: usize) {}
+
     /// Called for BER teletexstring objects
     fn visit_ber_teletexstring(&mut self, s: &'a str, depth: usize) {}
 
@@ -101,6 +114,12 @@ pub trait Visit<'a> {
     /// Called for BER visiblestring objects
     fn visit_ber_visiblestring(&mut self, raw_bytes: &'a str, depth: usize) {}
 
+    /// Called for BER objects with a tag number too large for `Tag`
+    fn visit_ber_tagoverflow(&mut self, identifier: &'a [u8], data: &'a [u8], depth: usize) {}
+
+    /// Called for universal primitive objects which were not decoded
+    fn visit_ber_primitive(&mut self, tag: Tag, data: &'a [u8], depth: usize) {}
+
     /// Called for BER unknown objects
     fn visit_ber_unknown(&mut self, ber: &'_ Any<'a>, depth: usize) {}
 
@@ -133,6 +152,7 @@ where
         }
         BerObjectContent::BmpString(s) => v.visit_ber_bmpstring(s, depth),
         BerObjectContent::Boolean(b) => v.visit_ber_boolean(b, depth),
+        BerObjectContent::CharacterString(ref c) => v.visit_ber_characterstring(c, depth),
         BerObjectContent::EndOfContent => v.visit_ber_endofcontent(depth),
         BerObjectContent::Enum(val) => v.visit_ber_enum(val, depth),
         BerObjectContent::GeneralString(s) => v.visit_ber_generalstring(s, depth),
@@ -156,12 +176,14 @@ where
             for item in l.iter() {
                 visit_ber_bfs(v, item, depth + 1);
             }
+            v.visit_ber_sequence_exit(l, depth);
         }
         BerObjectContent::Set(ref l) => {
             v.visit_ber_set(l, depth);
             for item in l.iter() {
                 visit_ber_bfs(v, item, depth + 1);
             }
+            v.visit_ber_set_exit(l, depth);
         }
         BerObjectContent::T61String(s) => v.visit_ber_teletexstring(s, depth),
         BerObjectContent::Tagged(class, tag, ref obj) => {
@@ -170,16 +192,24 @@ where
         BerObjectContent::UTCTime(ref t) => v.visit_ber_utctime(t, depth),
         BerObjectContent::UTF8String(s) => v.visit_ber_utf8string(s, depth),
         BerObjectContent::UniversalString(b) => v.visit_ber_universalstring(b, depth),
+        BerObjectContent::TagOverflow(identifier, data) => {
+            v.visit_ber_tagoverflow(identifier, data, depth)
+        }
+        BerObjectContent::Primitive(tag, data) => v.visit_ber_primitive(tag, data, depth),
         BerObjectContent::Unknown(ref inner) => v.visit_ber_unknown(inner, depth),
         BerObjectContent::VideotexString(s) => v.visit_ber_videotextstring(s, depth),
         BerObjectContent::VisibleString(s) => v.visit_ber_visiblestring(s, depth),
     }
+
+    v.visit_ber_exit(ber, depth);
 }
 
 #[cfg(test)]
 mod tests {
     use super::Visit;
-    use crate::ber::BerObject;
+    use crate::ber::*;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
 
     #[derive(Debug)]
     struct BerObjectVisitor {}
@@ -189,4 +219,40 @@ mod tests {
             eprintln!("Depth {}: Object with tag {}", depth, ber.tag());
         }
     }
+
+    /// Track the current path (list of tags) using enter/exit callbacks
+    #[derive(Debug, Default)]
+    struct PathCollector {
+        path: Vec<Tag>,
+        paths: Vec<Vec<Tag>>,
+    }
+
+    impl<'a> Visit<'a> for PathCollector {
+        fn visit_ber(&mut self, ber: &'_ BerObject<'a>, _depth: usize) {
+            self.path.push(ber.tag());
+            self.paths.push(self.path.clone());
+        }
+
+        fn visit_ber_exit(&mut self, _ber: &'_ BerObject<'a>, _depth: usize) {
+            self.path.pop();
+        }
+    }
+
+    #[test]
+    fn test_visit_enter_exit() {
+        let bytes = hex!("30 07 31 03 02 01 01 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut v = PathCollector::default();
+        v.run(&obj);
+        assert!(v.path.is_empty());
+        assert_eq!(
+            v.paths,
+            vec![
+                vec![Tag::Sequence],
+                vec![Tag::Sequence, Tag::Set],
+                vec![Tag::Sequence, Tag::Set, Tag::Integer],
+                vec![Tag::Sequence, Tag::Null],
+            ]
+        );
+    }
 }
@@ -1,8 +1,8 @@
-use super::{BerObject, BerObjectContent, BitStringObject};
+use super::{BerObject, BerObjectContent, BitStringObject, CharacterStringObject};
 use alloc::vec::Vec;
 use asn1_rs::{ASN1DateTime, Any, Class, Oid, Tag};
 
-/// BER object tree traversal to walk a shared borrow of a BER object
+/// BER object tree traversal to walk a mutable borrow of a BER object
 ///
 /// When implementing your own visitor, define your own `visit_ber_xxx` methods.
 ///
@@ -11,7 +11,11 @@ use asn1_rs::{ASN1DateTime, Any, Class, Oid, Tag};
 /// methods are not needed.
 ///
 /// For example, on a `Sequence` item, `visit_ber` is called first, then `visit_ber_sequence`, and then
-/// `visit_ber` for every sequence object (recursively).
+/// `visit_ber` for every sequence object (recursively). After all sequence objects have been visited,
+/// `visit_ber_sequence_exit_mut` and then `visit_ber_exit_mut` are called.
+///
+/// The object is only borrowed during the traversal, so it can be used (for ex. serialized) after
+/// the visitor has run.
 ///
 /// Entry point: use the [`VisitMut::run`] or [`VisitMut::run_at`] methods.
 ///
@@ -21,99 +25,128 @@ pub trait VisitMut<'a> {
     /// Called for every BER object
     fn visit_ber_mut(&mut self, ber: &'_ mut BerObject<'a>, depth: usize) {}
 
+    /// Called for every BER object, after the object and its children have been visited
+    fn visit_ber_exit_mut(&mut self, ber: &'_ mut BerObject<'a>, depth: usize) {}
+
     /// Called for BER bitstring objects
     fn visit_ber_bitstring_mut(
         &mut self,
-        ignored: &mut u8,
-        data: &'a mut BitStringObject,
+        ignored: &'_ mut u8,
+        data: &'_ mut BitStringObject<'a>,
         depth: usize,
     ) {
     }
 
     /// Called for BER bmpstring objects
-    fn visit_ber_bmpstring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_bmpstring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER boolean objects
-    fn visit_ber_boolean_mut(&mut self, b: &'a mut bool, depth: usize) {}
+    fn visit_ber_boolean_mut(&mut self, b: &'_ mut bool, depth: usize) {}
+
+    /// Called for BER characterstring objects
+    fn visit_ber_characterstring_mut(
+        &mut self,
+        c: &'_ mut CharacterStringObject<'a>,
+        depth: usize,
+    ) {
+    }
 
     /// Called for BER end-of-content objects
     fn visit_ber_endofcontent_mut(&mut self, depth: usize) {}
 
     /// Called for BER enum objects
-    fn visit_ber_enum_mut(&mut self, e: &'a mut u64, depth: usize) {}
+    fn visit_ber_enum_mut(&mut self, e: &'_ mut u64, depth: usize) {}
 
     /// Called for BER generalstring objects
-    fn visit_ber_generalstring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_generalstring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER generalizedtime objects
-    fn visit_ber_generalizedtime_mut(&mut self, t: &'a ASN1DateTime, depth: usize) {}
+    fn visit_ber_generalizedtime_mut(&mut self, t: &'_ mut ASN1DateTime, depth: usize) {}
 
     /// Called for BER graphicstring objects
-    fn visit_ber_graphicstring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_graphicstring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER ia5string objects
-    fn visit_ber_ia5string_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_ia5string_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER integer objects
-    fn visit_ber_integer_mut(&mut self, raw_bytes: &'a mut &'_ [u8], depth: usize) {}
+    fn visit_ber_integer_mut(&mut self, raw_bytes: &'_ mut &'a [u8], depth: usize) {}
 
     /// Called for BER null objects
     fn visit_ber_null_mut(&mut self, depth: usize) {}
 
     /// Called for BER numericstring objects
-    fn visit_ber_numericstring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_numericstring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER OID objects
-    fn visit_ber_oid_mut(&mut self, oid: &'a mut Oid, depth: usize) {}
+    fn visit_ber_oid_mut(&mut self, oid: &'_ mut Oid<'a>, depth: usize) {}
 
     /// Called for BER object descriptor objects
-    fn visit_ber_objectdescriptor_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_objectdescriptor_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER octetstring objects
-    fn visit_ber_octetstring_mut(&mut self, b: &'a [u8], depth: usize) {}
+    fn visit_ber_octetstring_mut(&mut self, b: &'_ mut &'a [u8], depth: usize) {}
 
     /// Called for BER optional objects
-    fn visit_ber_optional_mut(&mut self, obj: Option<&'a mut BerObject<'a>>, depth: usize) {}
+    fn visit_ber_optional_mut(&mut self, obj: Option<&'_ mut BerObject<'a>>, depth: usize) {}
 
     /// Called for BER printablestring objects
-    fn visit_ber_printablestring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_printablestring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER relative OID objects
-    fn visit_ber_relative_oid_mut(&mut self, oid: &'a mut Oid, depth: usize) {}
+    fn visit_ber_relative_oid_mut(&mut self, oid: &'_ mut Oid<'a>, depth: usize) {}
 
     /// Called for BER sequence objects
     fn visit_ber_sequence_mut(&mut self, l: &'_ mut Vec<BerObject<'a>>, depth: usize) {}
 
+    /// Called for BER sequence objects, after all sequence objects have been visited
+    fn visit_ber_sequence_exit_mut(&mut self, l: &'_ mut Vec<BerObject<'a>>, depth: usize) {}
+
     /// Called for BER set objects
     fn visit_ber_set_mut(&mut self, ber: &'_ mut Vec<BerObject<'a>>, depth: usize) {}
 
+    /// Called for BER set objects, after all set objects have been visited
+    fn visit_ber_set_exit_mut(&mut self, ber: &'_ mut Vec<BerObject<'a>>, depth: usize) {}
+
     /// Called for BER teletexstring objects
-    fn visit_ber_teletexstring_mut(&mut self, s: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_teletexstring_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER tagged objects
     fn visit_ber_tagged_mut(
         &mut self,
-        class: &'a mut Class,
-        tag: &'a mut Tag,
-        obj: &'a mut BerObject<'a>,
+        class: &'_ mut Class,
+        tag: &'_ mut Tag,
+        obj: &'_ mut BerObject<'a>,
         depth: usize,
     ) {
     }
 
     /// Called for BER generalizedtime objects
-    fn visit_ber_utctime_mut(&mut self, t: &'a ASN1DateTime, depth: usize) {}
+    fn visit_ber_utctime_mut(&mut self, t: &'_ mut ASN1DateTime, depth: usize) {}
 
     /// Called for BER utf8string objects
-    fn visit_ber_utf8string_mut(&mut self, s: &'a str, depth: usize) {}
+    fn visit_ber_utf8string_mut(&mut self, s: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER universalstring objects
-    fn visit_ber_universalstring_mut(&mut self, raw_bytes: &'a mut &'_ [u8], depth: usize) {}
+    fn visit_ber_universalstring_mut(&mut self, raw_bytes: &'_ mut &'a [u8], depth: usize) {}
 
     /// Called for BER videotexstring objects
-    fn visit_ber_videotextstring_mut(&mut self, raw_bytes: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_videotextstring_mut(&mut self, raw_bytes: &'_ mut &'a str, depth: usize) {}
 
     /// Called for BER visiblestring objects
-    fn visit_ber_visiblestring_mut(&mut self, raw_bytes: &'a mut &'_ str, depth: usize) {}
+    fn visit_ber_visiblestring_mut(&mut self, raw_bytes: &'_ mut &'a str, depth: usize) {}
+
+    /// Called for BER objects with a tag number too large for `Tag`
+    fn visit_ber_tagoverflow_mut(
+        &mut self,
+        identifier: &'_ mut &'a [u8],
+        data: &'_ mut &'a [u8],
+        depth: usize,
+    ) {
+    }
+
+    /// Called for universal primitive objects which were not decoded
+    fn visit_ber_primitive_mut(&mut self, tag: &'_ mut Tag, data: &'_ mut &'a [u8], depth: usize) {}
 
     /// Called for BER unknown objects
     fn visit_ber_unknown_mut(&mut self, ber: &'_ mut Any<'a>, depth: usize) {}
@@ -121,7 +154,7 @@ pub trait VisitMut<'a> {
     /// Perform a BFS traversal of the BER object, calling the visitor functions during he traversal
     ///
     /// Usually, this method should not be redefined (unless implementing a custom traversal)
-    fn run(&mut self, ber: &'a mut BerObject<'a>) {
+    fn run(&mut self, ber: &'_ mut BerObject<'a>) {
         visit_ber_bfs_mut(self, ber, 0)
     }
 
@@ -130,12 +163,12 @@ pub trait VisitMut<'a> {
     /// Start at specified depth.
     ///
     /// Usually, this method should not be redefined (unless implementing a custom traversal)
-    fn run_at(&mut self, ber: &'a mut BerObject<'a>, depth: usize) {
+    fn run_at(&mut self, ber: &'_ mut BerObject<'a>, depth: usize) {
         visit_ber_bfs_mut(self, ber, depth)
     }
 }
 
-fn visit_ber_bfs_mut<'a, V>(v: &mut V, ber: &'a mut BerObject<'a>, depth: usize)
+fn visit_ber_bfs_mut<'a, V>(v: &mut V, ber: &'_ mut BerObject<'a>, depth: usize)
 where
     V: VisitMut<'a> + ?Sized,
 {
@@ -147,10 +180,11 @@ where
         }
         BerObjectContent::BmpString(ref mut s) => v.visit_ber_bmpstring_mut(s, depth),
         BerObjectContent::Boolean(ref mut b) => v.visit_ber_boolean_mut(b, depth),
+        BerObjectContent::CharacterString(ref mut c) => v.visit_ber_characterstring_mut(c, depth),
         BerObjectContent::EndOfContent => v.visit_ber_endofcontent_mut(depth),
         BerObjectContent::Enum(ref mut val) => v.visit_ber_enum_mut(val, depth),
         BerObjectContent::GeneralString(ref mut s) => v.visit_ber_generalstring_mut(s, depth),
-        BerObjectContent::GeneralizedTime(ref t) => v.visit_ber_generalizedtime_mut(t, depth),
+        BerObjectContent::GeneralizedTime(ref mut t) => v.visit_ber_generalizedtime_mut(t, depth),
         BerObjectContent::GraphicString(ref mut s) => v.visit_ber_graphicstring_mut(s, depth),
         BerObjectContent::IA5String(ref mut s) => v.visit_ber_ia5string_mut(s, depth),
         BerObjectContent::Integer(ref mut s) => v.visit_ber_integer_mut(s, depth),
@@ -170,30 +204,42 @@ where
             for item in l.iter_mut() {
                 visit_ber_bfs_mut(v, item, depth + 1);
             }
+            v.visit_ber_sequence_exit_mut(l, depth);
         }
         BerObjectContent::Set(ref mut l) => {
             v.visit_ber_set_mut(l, depth);
             for item in l.iter_mut() {
                 visit_ber_bfs_mut(v, item, depth + 1);
             }
+            v.visit_ber_set_exit_mut(l, depth);
         }
         BerObjectContent::T61String(ref mut s) => v.visit_ber_teletexstring_mut(s, depth),
         BerObjectContent::Tagged(ref mut class, ref mut tag, ref mut obj) => {
             v.visit_ber_tagged_mut(class, tag, obj.as_mut(), depth)
         }
-        BerObjectContent::UTCTime(ref t) => v.visit_ber_utctime_mut(t, depth),
+        BerObjectContent::UTCTime(ref mut t) => v.visit_ber_utctime_mut(t, depth),
         BerObjectContent::UTF8String(ref mut s) => v.visit_ber_utf8string_mut(s, depth),
         BerObjectContent::UniversalString(ref mut b) => v.visit_ber_universalstring_mut(b, depth),
+        BerObjectContent::TagOverflow(ref mut identifier, ref mut data) => {
+            v.visit_ber_tagoverflow_mut(identifier, data, depth)
+        }
+        BerObjectContent::Primitive(ref mut tag, ref mut data) => {
+            v.visit_ber_primitive_mut(tag, data, depth)
+        }
         BerObjectContent::Unknown(ref mut inner) => v.visit_ber_unknown_mut(inner, depth),
         BerObjectContent::VideotexString(ref mut s) => v.visit_ber_videotextstring_mut(s, depth),
         BerObjectContent::VisibleString(ref mut s) => v.visit_ber_visiblestring_mut(s, depth),
     }
+
+    v.visit_ber_exit_mut(ber, depth);
 }
 
 #[cfg(test)]
 mod tests {
     use super::VisitMut;
-    use crate::ber::BerObject;
+    use crate::ber::*;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
 
     #[derive(Debug)]
     struct BerObjectVisitor {}
@@ -203,4 +249,34 @@ mod tests {
             eprintln!("Depth {}: Object with tag {}", depth, ber.tag());
         }
     }
+
+    /// Replace the content of all OCTET STRING objects, and record exit order
+    #[derive(Debug, Default)]
+    struct Redactor {
+        exits: Vec<(usize, Tag)>,
+    }
+
+    impl<'a> VisitMut<'a> for Redactor {
+        fn visit_ber_octetstring_mut(&mut self, b: &'_ mut &'a [u8], _depth: usize) {
+            *b = b"";
+        }
+
+        fn visit_ber_exit_mut(&mut self, ber: &'_ mut BerObject<'a>, depth: usize) {
+            self.exits.push((depth, ber.tag()));
+        }
+    }
+
+    #[test]
+    fn test_visit_mut_redact() {
+        let bytes = hex!("30 08 02 01 01 04 03 01 02 03");
+        let (_, mut obj) = parse_ber(&bytes).expect("parsing failed");
+        let mut v = Redactor::default();
+        v.run(&mut obj);
+        assert_eq!(
+            v.exits,
+            vec![(1, Tag::Integer), (1, Tag::OctetString), (0, Tag::Sequence)]
+        );
+        // the object is still usable after the traversal
+        assert_eq!(obj[1].as_slice(), Ok(&b""[..]));
+    }
 }
@@ -1,6 +1,17 @@
-use super::{BerObject, BerObjectContent, BitStringObject};
-use crate::ber::{ber_get_object_content, MAX_OBJECT_SIZE};
+use super::{
+    BerObject, BerObjectContent, BitStringObject, CharacterStringIdentification,
+    CharacterStringObject, Span, TAG_OVERFLOW,
+};
+use crate::ber::options::ParseState;
+use crate::ber::parser::refine_length_overflow;
+use crate::ber::{
+    ber_get_object_content, ber_read_element_header, ber_skip_object_content, BitStringPolicy,
+    BooleanPolicy, ConstructedPolicy, EocPolicy, IntegerPolicy, ParseOptions, MAX_OBJECT_SIZE,
+    MAX_RECURSION,
+};
+use crate::der::DerViolation;
 use crate::error::{BerError, BerResult};
+use crate::trace;
 use alloc::vec::Vec;
 use asn1_rs::*;
 use rusticata_macros::custom_check;
@@ -10,39 +21,219 @@ use rusticata_macros::custom_check;
 /// Raise an error if the maximum recursion depth was reached.
 pub fn parse_ber_any_with_tag_r(i: &[u8], tag: Tag, max_depth: usize) -> BerResult {
     custom_check!(i, max_depth == 0, BerError::BerMaxDepth)?;
-    let (rem, any) = Any::from_ber(i)?;
+    let (rem, (any, span)) = parse_ber_any_with_span(i)?;
     any.header.assert_tag(tag)?;
-    let obj = try_berobject_from_any(any, max_depth)?;
-    Ok((rem, obj))
+    let options = ParseOptions::ber();
+    let state = ParseState::new(i, max_depth, &options);
+    state.take(i, &any.header, max_depth, false)?;
+    let obj = try_berobject_from_any_with_options(any, max_depth, &options, &state)?;
+    Ok((rem, obj.with_span(span)))
 }
 
 /// Parse any BER object recursively, specifying the maximum recursion depth
 ///
 /// Raise an error if the maximum recursion depth was reached.
 pub fn parse_ber_any_r(i: &[u8], max_depth: usize) -> BerResult {
+    parse_ber_any_with_options_r(i, max_depth, &ParseOptions::ber())
+}
+
+/// Parse any BER object recursively, using the provided options
+///
+/// Raise an error if the maximum recursion depth was reached.
+pub(crate) fn parse_ber_any_with_options_r<'a>(
+    i: &'a [u8],
+    max_depth: usize,
+    options: &ParseOptions,
+) -> BerResult<'a> {
+    let state = ParseState::new(i, max_depth, options);
+    parse_ber_any_with_state_r(i, max_depth, options, &state)
+}
+
+/// Parse any BER object recursively, using the provided options and parsing state
+///
+/// `state` must have been created for input `i`.
+pub(crate) fn parse_ber_any_with_state_r<'a>(
+    i: &'a [u8],
+    max_depth: usize,
+    options: &ParseOptions,
+    state: &ParseState,
+) -> BerResult<'a> {
     custom_check!(i, max_depth == 0, BerError::BerMaxDepth)?;
-    let (rem, any) = Any::from_ber(i)?;
-    let obj = try_berobject_from_any(any, max_depth)?;
-    Ok((rem, obj))
+    state.check_length(i)?;
+    if let Some(res) = parse_tag_overflow(i, max_depth) {
+        let (rem, obj) = res?;
+        state.take(i, &obj.header, max_depth, false)?;
+        return Ok((rem, obj));
+    }
+    let (rem, (any, span)) = parse_ber_any_with_span(i)?;
+    state.take(i, &any.header, max_depth, false)?;
+    let obj = try_berobject_from_any_with_options(any, max_depth, options, state)?;
+    Ok((rem, obj.with_span(span)))
 }
 
 /// Parse any BER object (not recursive)
 pub fn parse_ber_any(i: &[u8]) -> BerResult<Any> {
-    Any::from_ber(i)
+    refine_length_overflow(i, Any::from_ber(i))
+}
+
+/// Parse any BER object (not recursive), and return its location in the input
+///
+/// The span gives the exact location of the identifier, length and content octets (and of the
+/// end-of-content octets if the length is indefinite), for ex. to patch or sign a part of the
+/// input without encoding it again.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::parse_ber_any_with_span;
+///
+/// // SEQUENCE { NULL }, followed by other data
+/// let bytes = &[0x30, 0x02, 0x05, 0x00, 0xff];
+/// let (rem, (any, span)) = parse_ber_any_with_span(bytes).expect("parsing failed");
+/// assert_eq!(rem, &[0xff]);
+/// assert_eq!((span.identifier(), span.length_octets()), (&bytes[..1], &bytes[1..2]));
+/// assert_eq!(span.content(), any.data);
+/// assert_eq!(span.content_range_in(bytes), Some(2..4));
+/// ```
+pub fn parse_ber_any_with_span(i: &[u8]) -> BerResult<(Any, Span)> {
+    let (rem, any) =
+        refine_length_overflow(i, Any::from_ber(i)).map_err(|e| refine_incomplete(i, e))?;
+    let raw = &i[..i.len() - rem.len()];
+    let span = Span::from_raw(raw, any.data.len(), any.header.length());
+    Ok((rem, (any, span)))
+}
+
+/// Compute the number of missing bytes when the object is truncated
+///
+/// `Any` only reports that one byte is missing when reading an object with an indefinite length.
+fn refine_incomplete(i: &[u8], e: Err<BerError>) -> Err<BerError> {
+    if let Err::Incomplete(_) = e {
+        if let Ok((rem, hdr)) = ber_read_element_header(i) {
+            if let Err(e @ Err::Incomplete(_)) = ber_skip_object_content(rem, &hdr, MAX_RECURSION) {
+                return e;
+            }
+        }
+    }
+    e
+}
+
+/// Read an object with a tag number too large for `Tag` (more than 32 bits)
+///
+/// Returns `None` if the identifier octets at `i` do not encode such a tag number (including if
+/// they are truncated), so the object is read as usual.
+fn parse_tag_overflow(i: &[u8], max_depth: usize) -> Option<BerResult<BerObject>> {
+    let (&first, rest) = i.split_first()?;
+    if first & 0x1f != 0x1f {
+        return None;
+    }
+    let n = rest.iter().position(|b| b & 0x80 == 0)? + 1;
+    let mut tag = 0u64;
+    let overflow = rest[..n].iter().any(|b| {
+        tag = (tag << 7) | u64::from(b & 0x7f);
+        tag > u64::from(u32::MAX)
+    });
+    if !overflow {
+        return None;
+    }
+    let (identifier, rem) = i.split_at(1 + n);
+    Some(read_tag_overflow(i, identifier, rem, max_depth))
+}
+
+fn read_tag_overflow<'a>(
+    i: &'a [u8],
+    identifier: &'a [u8],
+    rem: &'a [u8],
+    max_depth: usize,
+) -> BerResult<'a> {
+    let class = match identifier[0] >> 6 {
+        0b00 => Class::Universal,
+        0b01 => Class::Application,
+        0b10 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = identifier[0] & 0x20 != 0;
+    let (content, length) = read_length(rem)?;
+    let header = Header::new(class, constructed, TAG_OVERFLOW, length);
+    let (rem, data) = match length {
+        Length::Definite(l) => {
+            custom_check!(i, l > MAX_OBJECT_SIZE, BerError::InvalidLength)?;
+            let (rem, data) = nom::bytes::streaming::take(l)(content)?;
+            (rem, data)
+        }
+        Length::Indefinite => {
+            let (rem, _) = ber_skip_object_content(content, &header, max_depth)?;
+            let data = &content[..content.len() - rem.len() - 2];
+            (rem, data)
+        }
+    };
+    let raw = &i[..i.len() - rem.len()];
+    let span = Span::from_raw(raw, data.len(), length);
+    let header = header.with_raw_tag(Some(identifier.into()));
+    let obj =
+        BerObject::from_header_and_content(header, BerObjectContent::TagOverflow(identifier, data));
+    Ok((rem, obj.with_span(span)))
+}
+
+/// Read the length octets (X.690 8.1.3)
+fn read_length(i: &[u8]) -> BerResult<Length> {
+    let (&b, i) = i.split_first().ok_or(Err::Incomplete(Needed::new(1)))?;
+    match b {
+        0x80 => Ok((i, Length::Indefinite)),
+        0x00..=0x7f => Ok((i, Length::Definite(usize::from(b)))),
+        0xff => Err(Err::Error(BerError::InvalidLength)),
+        _ => {
+            let n = usize::from(b & 0x7f);
+            if i.len() < n {
+                return Err(Err::Incomplete(Needed::new(n - i.len())));
+            }
+            let mut l = 0usize;
+            for &b in &i[..n] {
+                custom_check!(i, l >> (usize::BITS - 8) != 0, BerError::InvalidLength)?;
+                l = (l << 8) | usize::from(b);
+            }
+            Ok((&i[n..], Length::Definite(l)))
+        }
+    }
+}
+
+/// Parse the objects of a constructed value, keeping their location in the input
+fn try_read_berobjects<'a>(
+    mut i: &'a [u8],
+    max_depth: usize,
+    options: &ParseOptions,
+    state: &ParseState,
+) -> Result<Vec<BerObject<'a>>> {
+    let mut objects = Vec::new();
+    while !i.is_empty() {
+        state.check_length(i)?;
+        if let Some(res) = parse_tag_overflow(i, max_depth) {
+            let (rem, obj) = res.map_err(BerError::from)?;
+            state.take(i, &obj.header, max_depth, true)?;
+            objects.push(obj);
+            i = rem;
+            continue;
+        }
+        let (rem, (any, span)) = parse_ber_any_with_span(i).map_err(BerError::from)?;
+        state.take(i, &any.header, max_depth, true)?;
+        if any.class() == Class::Universal && any.tag() == Tag::EndOfContent {
+            // terminating EOC are not part of the content
+            let e = BerError::unexpected_tag(None, Tag::EndOfContent);
+            apply_eoc_policy(options.eoc, state, DerViolation::UnexpectedEndOfContent, e)?;
+        }
+        let obj = try_berobject_from_any_with_options(any, max_depth, options, state)?;
+        objects.push(obj.with_span(span));
+        i = rem;
+    }
+    Ok(objects)
 }
 
 macro_rules! from_obj {
     ($header:ident, $content:expr) => {
         BerObject::from_header_and_content($header, $content)
     };
-    (STRING $ty:ident, $any:ident, $header:ident) => {
-        from_obj!(STRING $ty, $ty, $any, $header)
-    };
-    // macro variant when enum variant is not the same as char type
-    (STRING $ty:ident, $variant:ident, $any:ident, $header:ident) => {{
+    (STRING $variant:ident, $any:ident, $header:ident) => {{
         custom_check!($any.data, $header.constructed(), BerError::Unsupported)?; // XXX valid in BER (8.21)
-        <$ty>::test_valid_charset($any.data)?;
-        let s = core::str::from_utf8($any.data)?;
+        let s = try_str_from_raw($header.tag(), $any.data)?;
         Ok(BerObject::from_header_and_content(
             $header,
             BerObjectContent::$variant(s),
@@ -50,6 +241,71 @@ macro_rules! from_obj {
     }};
 }
 
+fn is_string_tag(tag: Tag) -> bool {
+    matches!(
+        tag,
+        Tag::BmpString
+            | Tag::GeneralString
+            | Tag::GraphicString
+            | Tag::Ia5String
+            | Tag::NumericString
+            | Tag::ObjectDescriptor
+            | Tag::PrintableString
+            | Tag::TeletexString
+            | Tag::UniversalString
+            | Tag::Utf8String
+            | Tag::VideotexString
+            | Tag::VisibleString
+    )
+}
+
+/// Test if all bytes of `data` satisfy `f`
+///
+/// Bytes are tested in blocks, without stopping at the first invalid byte of a block: this lets
+/// the compiler vectorize the loop, and is much faster than `Iterator::all` for long strings.
+#[inline]
+fn all_bytes(data: &[u8], f: impl Fn(u8) -> bool) -> bool {
+    data.chunks(64)
+        .all(|block| block.iter().fold(true, |acc, &b| acc & f(b)))
+}
+
+/// Check the character set of a string with the (universal) tag `tag`, and return its value
+///
+/// The checks are the same as `TestValidCharset` for the corresponding `asn1_rs` types, with fast
+/// paths for the restricted character sets.
+///
+/// This is also used to decode strings stored as raw bytes (see [`ParseOptions::lazy_strings`]).
+pub(crate) fn try_str_from_raw(tag: Tag, data: &[u8]) -> Result<&str> {
+    let valid = match tag {
+        Tag::GeneralString | Tag::GraphicString | Tag::Ia5String => data.is_ascii(),
+        Tag::NumericString => all_bytes(data, |b| b.is_ascii_digit() | (b == b' ')),
+        Tag::PrintableString => all_bytes(data, |b| {
+            matches!(b,
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'
+                | b' ' | b'\'' | b'(' | b')' | b'+' | b',' | b'-' | b'.' | b'/' | b':' | b'='
+                | b'?')
+        }),
+        Tag::TeletexString | Tag::VideotexString | Tag::VisibleString => {
+            all_bytes(data, |b| (0x20..=0x7f).contains(&b))
+        }
+        Tag::BmpString => {
+            BmpString::test_valid_charset(data)?;
+            true
+        }
+        Tag::ObjectDescriptor => {
+            ObjectDescriptor::test_valid_charset(data)?;
+            true
+        }
+        // validated by the conversion below
+        Tag::Utf8String => true,
+        _ => return Err(BerError::BerTypeError),
+    };
+    if !valid {
+        return Err(BerError::StringInvalidCharset);
+    }
+    Ok(core::str::from_utf8(data)?)
+}
+
 /// Read element content as Universal object, or Unknown
 // TODO implement the function for BerObjectContent (to replace ber_read_element_content_as)
 // note: we cannot implement TryFrom because of the `max_depth` argument
@@ -63,7 +319,7 @@ pub(crate) fn try_read_berobjectcontent_as(
     if let Length::Definite(l) = length {
         custom_check!(i, l > MAX_OBJECT_SIZE, BerError::InvalidLength)?;
         if i.len() < l {
-            return Err(Err::Incomplete(Needed::new(l)));
+            return Err(Err::Incomplete(Needed::new(l - i.len())));
         }
     }
     let header = Header::new(Class::Universal, constructed, tag, length);
@@ -75,12 +331,33 @@ pub(crate) fn try_read_berobjectcontent_as(
 
 // note: we cannot implement TryFrom because of the `max_depth` argument
 fn try_berobject_from_any(any: Any, max_depth: usize) -> Result<BerObject> {
+    let options = ParseOptions::ber();
+    let state = ParseState::new(any.data, max_depth, &options);
+    try_berobject_from_any_with_options(any, max_depth, &options, &state)
+}
+
+fn try_berobject_from_any_with_options<'a>(
+    any: Any<'a>,
+    max_depth: usize,
+    options: &ParseOptions,
+    state: &ParseState,
+) -> Result<BerObject<'a>> {
     custom_check!(any.data, max_depth == 0, BerError::BerMaxDepth)?;
     let obj_from = BerObject::from_header_and_content;
     let header = any.header.clone();
     if any.class() != Class::Universal {
         return Ok(obj_from(header, BerObjectContent::Unknown(any)));
     }
+    if options.constructed == ConstructedPolicy::Strict {
+        check_constructed_bit(&header)?;
+    }
+    if options.lazy_strings && is_string_tag(any.tag()) && header.is_primitive() {
+        return Ok(obj_from(header, BerObjectContent::Unknown(any)));
+    }
+    if !options.decode_primitives && header.is_primitive() && any.tag() != Tag::EndOfContent {
+        let content = BerObjectContent::Primitive(any.tag(), any.data);
+        return Ok(obj_from(header, content));
+    }
     match any.tag() {
         Tag::BitString => {
             if any.data.is_empty() {
@@ -88,30 +365,51 @@ fn try_berobject_from_any(any: Any, max_depth: usize) -> Result<BerObject> {
             }
             custom_check!(any.data, header.constructed(), BerError::Unsupported)?; // XXX valid in BER (8.6.3)
             let ignored_bits = any.data[0];
-            let data = &any.data[1..];
+            let bits = BitStringObject {
+                data: &any.data[1..],
+            };
+            check_bitstring_padding(ignored_bits, &bits, options.bitstring, state)?;
             Ok(obj_from(
                 header,
-                BerObjectContent::BitString(ignored_bits, BitStringObject { data }),
+                BerObjectContent::BitString(ignored_bits, bits),
             ))
         }
         Tag::BmpString => from_obj!(STRING BmpString, any, header),
+        Tag::CharacterString => {
+            header.assert_constructed()?;
+            let c = try_read_characterstring(any.data)?;
+            Ok(obj_from(header, BerObjectContent::CharacterString(c)))
+        }
         Tag::Boolean => {
-            let b = any.bool()?;
-            Ok(obj_from(header, BerObjectContent::Boolean(b)))
+            let b = any.as_boolean()?;
+            if options.boolean == BooleanPolicy::Strict && !(b.value == 0 || b.value == 0xff) {
+                let e = BerError::DerConstraintFailed(DerConstraint::InvalidBoolean);
+                trace::violation(&e);
+                return Err(e);
+            }
+            Ok(obj_from(header, BerObjectContent::Boolean(b.bool())))
+        }
+        Tag::EndOfContent => {
+            if !any.data.is_empty() {
+                let e = BerError::InvalidLength;
+                apply_eoc_policy(options.eoc, state, DerViolation::InvalidEndOfContent, e)?;
+            }
+            Ok(obj_from(header, BerObjectContent::EndOfContent))
         }
-        Tag::EndOfContent => Ok(obj_from(header, BerObjectContent::EndOfContent)),
         Tag::Enumerated => {
+            check_integer_encoding(any.data, options.integer, state)?;
             let obj = any.enumerated()?;
             Ok(obj_from(header, BerObjectContent::Enum(obj.0 as u64)))
         }
         Tag::GeneralizedTime => {
-            let time = any.generalizedtime()?;
-            Ok(obj_from(header, BerObjectContent::GeneralizedTime(time.0)))
+            let time = try_generalizedtime(any)?;
+            Ok(obj_from(header, BerObjectContent::GeneralizedTime(time)))
         }
         Tag::GeneralString => from_obj!(STRING GeneralString, any, header),
         Tag::GraphicString => from_obj!(STRING GraphicString, any, header),
-        Tag::Ia5String => from_obj!(STRING Ia5String, IA5String, any, header),
+        Tag::Ia5String => from_obj!(STRING IA5String, any, header),
         Tag::Integer => {
+            check_integer_encoding(any.data, options.integer, state)?;
             let obj = obj_from(header, BerObjectContent::Integer(any.data));
             Ok(obj)
         }
@@ -130,30 +428,18 @@ fn try_berobject_from_any(any: Any, max_depth: usize) -> Result<BerObject> {
         }
         Tag::Sequence => {
             header.assert_constructed()?;
-            let objects: Result<Vec<_>> = SequenceIterator::<Any, BerParser>::new(any.data)
-                .map(|item| {
-                    let item = item?;
-                    try_berobject_from_any(item, max_depth - 1)
-                })
-                .collect();
-            let objects = objects?;
+            let objects = try_read_berobjects(any.data, max_depth - 1, options, state)?;
             Ok(obj_from(header, BerObjectContent::Sequence(objects)))
         }
         Tag::Set => {
             header.assert_constructed()?;
-            let objects: Result<Vec<_>> = SetIterator::<Any, BerParser>::new(any.data)
-                .map(|item| {
-                    let item = item?;
-                    try_berobject_from_any(item, max_depth - 1)
-                })
-                .collect();
-            let objects = objects?;
+            let objects = try_read_berobjects(any.data, max_depth - 1, options, state)?;
             Ok(obj_from(header, BerObjectContent::Set(objects)))
         }
-        Tag::TeletexString => from_obj!(STRING TeletexString, T61String, any, header),
+        Tag::TeletexString => from_obj!(STRING T61String, any, header),
         Tag::UtcTime => {
-            let time = any.utctime()?;
-            Ok(obj_from(header, BerObjectContent::UTCTime(time.0)))
+            let time = try_utctime(any)?;
+            Ok(obj_from(header, BerObjectContent::UTCTime(time)))
         }
         Tag::UniversalString => {
             custom_check!(any.data, header.constructed(), BerError::Unsupported)?; // XXX valid in BER (8.21)
@@ -165,7 +451,7 @@ fn try_berobject_from_any(any: Any, max_depth: usize) -> Result<BerObject> {
                 BerObjectContent::UniversalString(any.data),
             ))
         }
-        Tag::Utf8String => from_obj!(STRING Utf8String, UTF8String, any, header),
+        Tag::Utf8String => from_obj!(STRING UTF8String, any, header),
         Tag::VideotexString => from_obj!(STRING VideotexString, any, header),
         Tag::VisibleString => from_obj!(STRING VisibleString, any, header),
         _ => {
@@ -177,36 +463,354 @@ fn try_berobject_from_any(any: Any, max_depth: usize) -> Result<BerObject> {
     }
 }
 
+/// Check the primitive/constructed bit of a universal object against the rules of its type
+fn check_constructed_bit(header: &Header) -> Result<()> {
+    let e = match header.tag() {
+        Tag::EndOfContent
+        | Tag::Boolean
+        | Tag::Integer
+        | Tag::Enumerated
+        | Tag::RealType
+        | Tag::Null
+        | Tag::Oid
+        | Tag::RelativeOid
+            if header.is_constructed() =>
+        {
+            BerError::ConstructUnexpected
+        }
+        Tag::Sequence | Tag::Set | Tag::External | Tag::EmbeddedPdv | Tag::CharacterString
+            if header.is_primitive() =>
+        {
+            BerError::ConstructExpected
+        }
+        _ => return Ok(()),
+    };
+    trace::violation(&e);
+    Err(e)
+}
+
+/// Decode a GeneralizedTime, preserving the local time differential (`+hh`, `+hhmm`, `-hh` or
+/// `-hhmm`) if present
+///
+/// `asn1_rs` does not accept differentials without minutes, and loses the sign of negative
+/// differentials of less than one hour: the differential is decoded here. The minutes carry the
+/// sign when the hours are zero (for ex. `-0030` is `ASN1TimeZone::Offset(0, -30)`).
+fn try_generalizedtime(any: Any) -> Result<ASN1DateTime> {
+    let (datetime, differential) = match any.data.iter().rposition(|&b| b == b'+' || b == b'-') {
+        Some(idx) => any.data.split_at(idx),
+        None => return Ok(any.generalizedtime()?.0),
+    };
+    let digits = |hi: u8, lo: u8| match (hi, lo) {
+        (b'0'..=b'9', b'0'..=b'9') => Some(((hi - b'0') * 10 + (lo - b'0')) as i8),
+        _ => None,
+    };
+    let offset = match differential[1..] {
+        [h1, h2] => digits(h1, h2).map(|hh| (hh, 0)),
+        [h1, h2, m1, m2] => digits(h1, h2).zip(digits(m1, m2)),
+        _ => None,
+    };
+    let (hh, mm) = match offset {
+        Some((hh, mm)) if hh <= 23 && mm <= 59 => (hh, mm),
+        _ => return Err(Tag::GeneralizedTime.invalid_value("invalid local time differential")),
+    };
+    // the date and time must be a local time
+    let header = Header::new(
+        Class::Universal,
+        any.header.is_constructed(),
+        Tag::GeneralizedTime,
+        Length::Definite(datetime.len()),
+    );
+    let mut time = Any::new(header, datetime).generalizedtime()?.0;
+    if time.tz != ASN1TimeZone::Undefined {
+        return Err(Tag::GeneralizedTime.invalid_value("invalid local time differential"));
+    }
+    time.tz = match (differential[0], hh) {
+        (b'-', 0) => ASN1TimeZone::Offset(0, -mm),
+        (b'-', _) => ASN1TimeZone::Offset(-hh, mm),
+        _ => ASN1TimeZone::Offset(hh, mm),
+    };
+    Ok(time)
+}
+
+/// Read the content of a UTCTime, keeping the sign of `-00mm` differentials
+///
+/// asn1-rs stores the sign of the differential in the hours, which is lost if they are zero.
+pub(crate) fn try_utctime(any: Any) -> Result<ASN1DateTime> {
+    let negative = matches!(any.data, [.., b'-', b'0', b'0', _, _]);
+    let mut time = any.utctime()?.0;
+    if let ASN1TimeZone::Offset(0, mm) = time.tz {
+        if negative {
+            time.tz = ASN1TimeZone::Offset(0, -mm);
+        }
+    }
+    Ok(time)
+}
+
+/// Apply the policy for redundant leading octets to the content of an INTEGER or ENUMERATED
+fn check_integer_encoding(data: &[u8], policy: IntegerPolicy, state: &ParseState) -> Result<()> {
+    let constraint = match data {
+        [0x00, b, ..] if b & 0x80 == 0 => DerConstraint::IntegerLeadingZeroes,
+        [0xff, b, ..] if b & 0x80 != 0 => DerConstraint::IntegerLeadingFF,
+        _ => return Ok(()),
+    };
+    match policy {
+        IntegerPolicy::Lenient => Ok(()),
+        IntegerPolicy::Warn => {
+            state.warn(DerViolation::NonMinimalInteger);
+            Ok(())
+        }
+        IntegerPolicy::Strict => {
+            let e = BerError::DerConstraintFailed(constraint);
+            trace::violation(&e);
+            Err(e)
+        }
+    }
+}
+
+/// Apply the policy for non-zero unused bits to the content of a BIT STRING
+fn check_bitstring_padding(
+    unused_bits: u8,
+    bits: &BitStringObject,
+    policy: BitStringPolicy,
+    state: &ParseState,
+) -> Result<()> {
+    if bits.nonzero_unused_bits(unused_bits).is_none() {
+        return Ok(());
+    }
+    match policy {
+        BitStringPolicy::Lenient => Ok(()),
+        BitStringPolicy::Warn => {
+            state.warn(DerViolation::NonZeroUnusedBits);
+            Ok(())
+        }
+        BitStringPolicy::Strict => {
+            let e = BerError::DerConstraintFailed(DerConstraint::UnusedBitsNotZero);
+            trace::violation(&e);
+            Err(e)
+        }
+    }
+}
+
+/// Apply the policy for invalid end-of-content octets, returning `e` if rejected
+fn apply_eoc_policy(
+    policy: EocPolicy,
+    state: &ParseState,
+    violation: DerViolation,
+    e: BerError,
+) -> Result<()> {
+    match policy {
+        EocPolicy::Lenient => Ok(()),
+        EocPolicy::Warn => {
+            state.warn(violation);
+            Ok(())
+        }
+        EocPolicy::Strict => Err(e),
+    }
+}
+
+/// Read the content of an unrestricted CHARACTER STRING (X.690 8.24, X.680 44.5)
+///
+/// The associated type is a SEQUENCE using AUTOMATIC tags, with the `data-value-descriptor`
+/// component absent. Trailing bytes after a component raise an `InvalidLength` error.
+///
+/// The `string-value` is borrowed from the input, so it must use the primitive encoding: a
+/// constructed (segmented) `string-value` raises an `Unsupported` error.
+fn try_read_characterstring(i: &[u8]) -> Result<CharacterStringObject> {
+    // identification [0] CHOICE (always explicit)
+    let (rem, ident) = Any::from_ber(i)?;
+    ident.header.assert_class(Class::ContextSpecific)?;
+    ident.header.assert_tag(Tag(0))?;
+    ident.header.assert_constructed()?;
+    let (r, choice) = Any::from_ber(ident.data)?;
+    assert_no_trailing(r)?;
+    choice.header.assert_class(Class::ContextSpecific)?;
+    let identification = match choice.tag().0 {
+        0 => {
+            // syntaxes SEQUENCE { abstract [0] OID, transfer [1] OID }
+            choice.header.assert_constructed()?;
+            let (abs, tr) = read_component_pair(choice.data)?;
+            CharacterStringIdentification::Syntaxes {
+                abstract_syntax: try_characterstring_oid(abs)?,
+                transfer_syntax: try_characterstring_oid(tr)?,
+            }
+        }
+        1 => {
+            choice.header.assert_primitive()?;
+            CharacterStringIdentification::Syntax(try_characterstring_oid(choice.data)?)
+        }
+        2 => {
+            choice.header.assert_primitive()?;
+            CharacterStringIdentification::PresentationContextId(choice.data)
+        }
+        3 => {
+            // context-negotiation SEQUENCE { presentation-context-id [0] INTEGER,
+            //                                transfer-syntax [1] OID }
+            choice.header.assert_constructed()?;
+            let (id, tr) = read_component_pair(choice.data)?;
+            CharacterStringIdentification::ContextNegotiation {
+                presentation_context_id: id,
+                transfer_syntax: try_characterstring_oid(tr)?,
+            }
+        }
+        4 => {
+            choice.header.assert_primitive()?;
+            CharacterStringIdentification::TransferSyntax(try_characterstring_oid(choice.data)?)
+        }
+        5 => {
+            choice.header.assert_primitive()?;
+            custom_check!(
+                choice.data,
+                !choice.data.is_empty(),
+                BerError::InvalidLength
+            )?;
+            CharacterStringIdentification::Fixed
+        }
+        _ => {
+            return Err(Tag::CharacterString.invalid_value("Invalid identification tag"));
+        }
+    };
+    // string-value [2] IMPLICIT OCTET STRING
+    let (rem, value) = Any::from_ber(rem)?;
+    assert_no_trailing(rem)?;
+    value.header.assert_class(Class::ContextSpecific)?;
+    value.header.assert_tag(Tag(2))?;
+    custom_check!(
+        value.data,
+        value.header.constructed(),
+        BerError::Unsupported
+    )?;
+    Ok(CharacterStringObject {
+        identification,
+        string_value: value.data,
+    })
+}
+
+/// Read the content of a SEQUENCE with two primitive components `[0]` and `[1]` (IMPLICIT)
+fn read_component_pair(i: &[u8]) -> Result<(&[u8], &[u8])> {
+    let (r, first) = Any::from_ber(i)?;
+    let (r, second) = Any::from_ber(r)?;
+    assert_no_trailing(r)?;
+    for (any, tag) in [(&first, Tag(0)), (&second, Tag(1))] {
+        any.header.assert_class(Class::ContextSpecific)?;
+        any.header.assert_tag(tag)?;
+        any.header.assert_primitive()?;
+    }
+    Ok((first.data, second.data))
+}
+
+/// Build an OID from the content of an identification component, checking that the last
+/// subidentifier is complete
+fn try_characterstring_oid(data: &[u8]) -> Result<Oid> {
+    match data.last() {
+        Some(b) if b & 0x80 == 0 => Ok(Oid::new(data.into())),
+        _ => Err(Tag::CharacterString.invalid_value("Invalid object identifier")),
+    }
+}
+
+fn assert_no_trailing(rem: &[u8]) -> Result<()> {
+    custom_check!(rem, !rem.is_empty(), BerError::InvalidLength)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ber::{BerObject, BerObjectContent, MAX_RECURSION};
     use crate::error::BerError;
+    use asn1_rs::ASN1TimeZone;
     use hex_literal::hex;
     use test_case::test_case;
 
     use super::parse_ber_any_r;
 
-    #[test_case(&hex!("01 01 00") => matches Ok(BerObject{header:_, content:BerObjectContent::Boolean(false)}) ; "val false")]
-    #[test_case(&hex!("01 01 ff") => matches Ok(BerObject{header:_, content:BerObjectContent::Boolean(true)}) ; "val true")]
-    #[test_case(&hex!("01 01 7f") => matches Ok(BerObject{header:_, content:BerObjectContent::Boolean(true)}) ; "true not ff")]
-    #[test_case(&hex!("02 02 00 ff") => matches Ok(BerObject{header:_, content:BerObjectContent::Integer(_)}) ; "u32-255")]
-    #[test_case(&hex!("02 02 01 23") => matches Ok(BerObject{header:_, content:BerObjectContent::Integer(_)}) ; "u32-0x123")]
-    #[test_case(&hex!("02 04 ff ff ff ff") => matches Ok(BerObject{header:_, content:BerObjectContent::Integer(_)}) ; "u32-long-neg")]
-    #[test_case(&hex!("0c 04 31 32 33 34") => matches Ok(BerObject{header:_, content:BerObjectContent::UTF8String("1234")}) ; "utf8: numeric")]
-    #[test_case(&hex!("0d 04 c2 7b 03 02") => matches Ok(BerObject{header:_, content:BerObjectContent::RelativeOID(_)}) ; "relative OID")]
-    #[test_case(&hex!("12 04 31 32 33 34") => matches Ok(BerObject{header:_, content:BerObjectContent::NumericString("1234")}) ; "numeric string")]
+    #[test_case(&hex!("01 01 00") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Boolean(false)}) ; "val false")]
+    #[test_case(&hex!("01 01 ff") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Boolean(true)}) ; "val true")]
+    #[test_case(&hex!("01 01 7f") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Boolean(true)}) ; "true not ff")]
+    #[test_case(&hex!("02 02 00 ff") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Integer(_)}) ; "u32-255")]
+    #[test_case(&hex!("02 02 01 23") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Integer(_)}) ; "u32-0x123")]
+    #[test_case(&hex!("02 04 ff ff ff ff") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Integer(_)}) ; "u32-long-neg")]
+    #[test_case(&hex!("0c 04 31 32 33 34") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::UTF8String("1234")}) ; "utf8: numeric")]
+    #[test_case(&hex!("0d 04 c2 7b 03 02") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::RelativeOID(_)}) ; "relative OID")]
+    #[test_case(&hex!("12 04 31 32 33 34") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::NumericString("1234")}) ; "numeric string")]
     #[test_case(&hex!("12 04 01 02 03 04") => matches Err(BerError::StringInvalidCharset) ; "numeric string err")]
-    #[test_case(&hex!("13 04 31 32 33 34") => matches Ok(BerObject{header:_, content:BerObjectContent::PrintableString("1234")}) ; "printable string")]
+    #[test_case(&hex!("13 04 31 32 33 34") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::PrintableString("1234")}) ; "printable string")]
     #[test_case(&hex!("13 04 01 02 03 04") => matches Err(BerError::StringInvalidCharset) ; "printable string err")]
-    #[test_case(&hex!("16 04 31 32 33 34") => matches Ok(BerObject{header:_, content:BerObjectContent::IA5String("1234")}) ; "ia5: numeric")]
-    #[test_case(&hex!("1a 04 31 32 33 34") => matches Ok(BerObject{header:_, content:BerObjectContent::VisibleString("1234")}) ; "visible: numeric")]
-    #[test_case(&hex!("1e 08 00 55 00 73 00 65 00 72") => matches Ok(BerObject{header:_, content:BerObjectContent::BmpString("\x00U\x00s\x00e\x00r")}) ; "bmp")]
-    #[test_case(&hex!("30 80 04 03 56 78 90 00 00") => matches Ok(BerObject{header:_, content:BerObjectContent::Sequence(_)}) ; "indefinite length")]
-    #[test_case(&hex!("c0 03 01 00 01") => matches Ok(BerObject{header:_, content:BerObjectContent::Unknown(_)}) ; "private")]
+    #[test_case(&hex!("16 04 31 32 33 34") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::IA5String("1234")}) ; "ia5: numeric")]
+    #[test_case(&hex!("1a 04 31 32 33 34") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::VisibleString("1234")}) ; "visible: numeric")]
+    #[test_case(&hex!("1e 08 00 55 00 73 00 65 00 72") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::BmpString("\x00U\x00s\x00e\x00r")}) ; "bmp")]
+    #[test_case(&hex!("30 80 04 03 56 78 90 00 00") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Sequence(_)}) ; "indefinite length")]
+    #[test_case(&hex!("c0 03 01 00 01") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::Unknown(_)}) ; "private")]
+    #[test_case(&hex!("3d 09 a0 02 85 00 82 03 61 62 63") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::CharacterString(_)}) ; "character string")]
+    #[test_case(&hex!("1d 01 00") => matches Err(BerError::ConstructExpected) ; "character string primitive")]
+    #[test_case(&hex!("3d 05 a0 03 86 01 00") => matches Err(BerError::InvalidValue{..}) ; "character string invalid identification")]
+    #[test_case(&hex!("3d 0e a0 09 a0 07 80 02 2a 03 81 01 00 82 01 61") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::CharacterString(_)}) ; "character string syntaxes")]
+    #[test_case(&hex!("3d 0d a0 08 a3 06 80 01 01 81 01 2a 82 01 61") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::CharacterString(_)}) ; "character string context negotiation")]
+    #[test_case(&hex!("3d 0c a0 07 a0 05 80 01 2a c1 00 82 01 61") => matches Err(BerError::UnexpectedClass{..}) ; "character string syntaxes class")]
+    #[test_case(&hex!("3d 0c a0 07 a0 05 a0 01 2a 81 00 82 01 61") => matches Err(BerError::ConstructUnexpected) ; "character string syntaxes constructed")]
+    #[test_case(&hex!("3d 09 a0 04 80 02 2a 03 82 01 61") => matches Err(BerError::ConstructExpected) ; "character string syntaxes primitive")]
+    #[test_case(&hex!("3d 0f a0 0a a0 08 80 01 2a 81 01 2a 05 00 82 01 61") => matches Err(BerError::InvalidLength) ; "character string syntaxes trailing")]
+    #[test_case(&hex!("3d 09 a0 04 85 00 05 00 82 01 61") => matches Err(BerError::InvalidLength) ; "character string identification trailing")]
+    #[test_case(&hex!("3d 09 a0 02 85 00 82 01 61 05 00") => matches Err(BerError::InvalidLength) ; "character string value trailing")]
+    #[test_case(&hex!("3d 0b a0 02 85 00 a2 05 04 03 61 62 63") => matches Err(BerError::Unsupported) ; "character string constructed value")]
+    #[test_case(&hex!("3d 0a a0 05 81 03 2a 86 48 82 01 61") => matches Ok(BerObject{header:_, span:_, content:BerObjectContent::CharacterString(_)}) ; "character string syntax")]
+    #[test_case(&hex!("3d 0b a0 06 a1 04 06 02 2a 03 82 01 61") => matches Err(BerError::ConstructUnexpected) ; "character string syntax constructed")]
+    #[test_case(&hex!("3d 09 a0 04 a2 02 02 00 82 01 61") => matches Err(BerError::ConstructUnexpected) ; "character string presentation context id constructed")]
+    #[test_case(&hex!("3d 0b a0 06 a4 04 06 02 2a 03 82 01 61") => matches Err(BerError::ConstructUnexpected) ; "character string transfer syntax constructed")]
+    #[test_case(&hex!("3d 07 a0 02 81 00 82 01 61") => matches Err(BerError::InvalidValue{..}) ; "character string syntax empty oid")]
+    #[test_case(&hex!("3d 09 a0 04 84 02 2a 86 82 01 61") => matches Err(BerError::InvalidValue{..}) ; "character string transfer syntax truncated oid")]
+    #[test_case(&hex!("3d 0c a0 07 a0 05 80 01 2a 81 00 82 01 61") => matches Err(BerError::InvalidValue{..}) ; "character string syntaxes empty oid")]
     fn ber_from_any(i: &[u8]) -> Result<BerObject, BerError> {
         let (rem, res) = parse_ber_any_r(i, MAX_RECURSION)?;
         assert!(rem.is_empty());
         // dbg!(&res);
         Ok(res)
     }
+    #[test_case(b"\x17\x11991231235959-0030" => Ok(ASN1TimeZone::Offset(0, -30)) ; "utc time negative minutes")]
+    #[test_case(b"\x17\x11991231235959-0130" => Ok(ASN1TimeZone::Offset(-1, 30)) ; "utc time negative offset")]
+    #[test_case(b"\x17\x11991231235959+0030" => Ok(ASN1TimeZone::Offset(0, 30)) ; "utc time positive minutes")]
+    #[test_case(b"\x18\x0f19991231235959Z" => Ok(ASN1TimeZone::Z) ; "generalized time utc")]
+    #[test_case(b"\x18\x0e19991231235959" => Ok(ASN1TimeZone::Undefined) ; "generalized time local")]
+    #[test_case(b"\x18\x1319991231235959+0530" => Ok(ASN1TimeZone::Offset(5, 30)) ; "generalized time offset")]
+    #[test_case(b"\x18\x1519991231235959.5-0130" => Ok(ASN1TimeZone::Offset(-1, 30)) ; "generalized time negative offset")]
+    #[test_case(b"\x18\x1319991231235959-0030" => Ok(ASN1TimeZone::Offset(0, -30)) ; "generalized time negative minutes")]
+    #[test_case(b"\x18\x1119991231235959+01" => Ok(ASN1TimeZone::Offset(1, 0)) ; "generalized time offset without minutes")]
+    #[test_case(b"\x18\x1419991231235959Z+0100" => matches Err(BerError::InvalidValue{..}) ; "generalized time utc and offset")]
+    #[test_case(b"\x18\x1319991231235959+2400" => matches Err(BerError::InvalidValue{..}) ; "generalized time invalid offset")]
+    fn ber_generalizedtime_tz(i: &[u8]) -> Result<ASN1TimeZone, BerError> {
+        let (_, obj) = parse_ber_any_r(i, MAX_RECURSION)?;
+        match obj.content {
+            BerObjectContent::UTCTime(t) | BerObjectContent::GeneralizedTime(t) => Ok(t.tz),
+            _ => Err(BerError::BerTypeError),
+        }
+    }
+
+    #[test]
+    fn test_charsets() {
+        use super::try_str_from_raw;
+        use asn1_rs::*;
+        fn check<T: TestValidCharset>(tag: Tag) {
+            // same result as asn1_rs for all bytes, at all positions in a block
+            for b in 0..=255u8 {
+                for pos in [0, 63, 64, 100] {
+                    let mut data = [b'1'; 128];
+                    data[pos] = b;
+                    let expected = T::test_valid_charset(&data).is_ok();
+                    assert_eq!(
+                        try_str_from_raw(tag, &data).is_ok(),
+                        expected,
+                        "{} {}",
+                        tag,
+                        b
+                    );
+                }
+            }
+        }
+        check::<GeneralString>(Tag::GeneralString);
+        check::<GraphicString>(Tag::GraphicString);
+        check::<Ia5String>(Tag::Ia5String);
+        check::<NumericString>(Tag::NumericString);
+        check::<PrintableString>(Tag::PrintableString);
+        check::<TeletexString>(Tag::TeletexString);
+        check::<VideotexString>(Tag::VideotexString);
+        check::<VisibleString>(Tag::VisibleString);
+    }
 }
@@ -0,0 +1,116 @@
+//! Implementation of `Zeroize` for owned BER objects
+//!
+//! The buffers of the values (integers, strings, and raw contents) are overwritten with zeroes.
+//! Headers, object identifiers and times are not considered sensitive, and are only dropped.
+//! `BerObjectOwned` does not erase its content when dropped (this would prevent moving values out
+//! of the object): wrap it in `zeroize::Zeroizing<BerObjectOwned>` to erase it on drop.
+//!
+//! Borrowed objects (`BerObject`) reference the input buffer, which must be erased by the caller.
+
+use crate::ber::{BerObjectContentOwned, BerObjectOwned, CharacterStringIdentificationOwned};
+use zeroize::Zeroize;
+
+impl Zeroize for BerObjectContentOwned {
+    fn zeroize(&mut self) {
+        use BerObjectContentOwned as O;
+        match self {
+            O::Boolean(b) => b.zeroize(),
+            O::Enum(e) => e.zeroize(),
+            O::Integer(v)
+            | O::BitString(_, v)
+            | O::OctetString(v)
+            | O::UniversalString(v)
+            | O::Primitive(_, v)
+            | O::Unknown(_, v) => v.zeroize(),
+            O::NumericString(s)
+            | O::VisibleString(s)
+            | O::PrintableString(s)
+            | O::IA5String(s)
+            | O::UTF8String(s)
+            | O::T61String(s)
+            | O::VideotexString(s)
+            | O::BmpString(s)
+            | O::ObjectDescriptor(s)
+            | O::GraphicString(s)
+            | O::GeneralString(s) => s.zeroize(),
+            O::CharacterString(id, v) => {
+                id.zeroize();
+                v.zeroize();
+            }
+            O::Sequence(v) | O::Set(v) => v.zeroize(),
+            O::Optional(Some(o)) | O::Tagged(_, _, o) => o.as_mut().zeroize(),
+            O::TagOverflow(identifier, data) => {
+                identifier.zeroize();
+                data.zeroize();
+            }
+            O::EndOfContent
+            | O::Null
+            | O::Optional(None)
+            | O::OID(_)
+            | O::RelativeOID(_)
+            | O::UTCTime(_)
+            | O::GeneralizedTime(_) => (),
+        }
+        *self = O::Null;
+    }
+}
+
+impl Zeroize for CharacterStringIdentificationOwned {
+    fn zeroize(&mut self) {
+        use CharacterStringIdentificationOwned as O;
+        match self {
+            O::PresentationContextId(id)
+            | O::ContextNegotiation {
+                presentation_context_id: id,
+                ..
+            } => id.zeroize(),
+            O::Syntaxes { .. } | O::Syntax(_) | O::TransferSyntax(_) | O::Fixed => (),
+        }
+        *self = O::Fixed;
+    }
+}
+
+impl Zeroize for BerObjectOwned {
+    fn zeroize(&mut self) {
+        self.content.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::*;
+    use hex_literal::hex;
+    use zeroize::{Zeroize, Zeroizing};
+
+    #[test]
+    fn test_zeroize_owned() {
+        // SEQUENCE { INTEGER 0x0102, [0] { OCTET STRING "ab" } }
+        let bytes = hex!("30 0a 02 02 01 02 a0 04 04 02 61 62");
+        let (_, mut obj) = parse_ber(&bytes).expect("parsing failed");
+        let (_, inner) = parse_ber(&bytes[8..]).expect("parsing failed");
+        obj = obj.map_content(|c| match c {
+            BerObjectContent::Sequence(mut v) => {
+                v[1] = BerObject::from_tagged_explicit(Class::ContextSpecific, Tag(0), inner);
+                BerObjectContent::Sequence(v)
+            }
+            c => c,
+        });
+        let mut owned = obj.to_owned_object();
+        let mut key = match owned.content {
+            BerObjectContentOwned::Sequence(ref v) => v[0].content.clone(),
+            _ => panic!("not a sequence"),
+        };
+        key.zeroize();
+        assert_eq!(key, BerObjectContentOwned::Null);
+        owned.zeroize();
+        assert_eq!(owned.content, BerObjectContentOwned::Null);
+        // the header is kept
+        assert_eq!(owned.header.tag(), Tag::Sequence);
+        // values can still be moved out of owned objects
+        let BerObjectOwned { content, .. } = obj.to_owned_object();
+        assert!(matches!(content, BerObjectContentOwned::Sequence(ref v) if v.len() == 2));
+        // erase on drop
+        let owned = Zeroizing::new(obj.to_owned_object());
+        assert_eq!(owned.header.tag(), Tag::Sequence);
+    }
+}
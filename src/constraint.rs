@@ -0,0 +1,332 @@
+//! Subtype constraints: SIZE, value ranges and permitted alphabets
+//!
+//! A [`Constraint`] restricts the values of a type, as the subtype constraints of ASN.1 (X.680
+//! clause 51). They can be checked on a parsed object using [`Constraint::check`], or during
+//! parsing using the [`constrained`] combinator. Constraints can also be added to a
+//! [`Schema`](crate::schema::Schema), using `Schema::constrained`.
+//!
+//! The size is measured in bytes for OCTET STRING, in bits for BIT STRING, in characters for
+//! character strings, and in elements for SEQUENCE OF and SET OF.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use der_parser::constraint::{Constraint, ConstraintErrorKind};
+//! use der_parser::der::parse_der_printablestring;
+//!
+//! // PrintableString (SIZE (1..4)) (FROM ("0".."9"))
+//! let constraints = [Constraint::size(1, 4), Constraint::alphabet_range('0', '9')];
+//!
+//! let (_, obj) = parse_der_printablestring(b"\x13\x02\x34\x32").expect("parsing failed");
+//! assert!(constraints.iter().all(|c| c.check(&obj).is_ok()));
+//!
+//! let (_, obj) = parse_der_printablestring(b"\x13\x02\x34\x41").expect("parsing failed");
+//! let e = constraints[1].check(&obj).unwrap_err();
+//! assert_eq!(e.kind, ConstraintErrorKind::CharNotPermitted('A'));
+//! ```
+
+use crate::ber::{BerObject, BerObjectContent, BitStringObject};
+use crate::error::{BerError, BerResult};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// A subtype constraint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    /// `SIZE (min..max)`, with `None` for `MAX`
+    Size(u64, Option<u64>),
+    /// Value range of INTEGER or ENUMERATED (`min..max`, with `None` for `MIN` and `MAX`)
+    Range(Option<i64>, Option<i64>),
+    /// Permitted alphabet (`FROM`) of character strings, as ranges of characters
+    Alphabet(Vec<(char, char)>),
+}
+
+/// Kind of a [`ConstraintError`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstraintErrorKind {
+    /// The size (given) is outside of the permitted range
+    SizeOutOfRange(u64),
+    /// The value is outside of the permitted range (`None` if it does not fit in an `i64`)
+    ValueOutOfRange(Option<i64>),
+    /// A character is not in the permitted alphabet
+    CharNotPermitted(char),
+    /// The constraint cannot be applied to this type of object
+    NotApplicable,
+    /// The value of the object could not be decoded
+    InvalidContent(BerError),
+}
+
+/// An object violating a constraint
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstraintError {
+    /// The constraint which failed
+    pub constraint: Constraint,
+    pub kind: ConstraintErrorKind,
+}
+
+impl Constraint {
+    /// `SIZE (min..max)`
+    pub const fn size(min: u64, max: u64) -> Self {
+        Constraint::Size(min, Some(max))
+    }
+
+    /// `SIZE (min..MAX)`
+    pub const fn min_size(min: u64) -> Self {
+        Constraint::Size(min, None)
+    }
+
+    /// Value range `min..max`
+    pub const fn range(min: i64, max: i64) -> Self {
+        Constraint::Range(Some(min), Some(max))
+    }
+
+    /// Permitted alphabet: the characters of `chars`
+    pub fn alphabet(chars: &str) -> Self {
+        Constraint::Alphabet(chars.chars().map(|c| (c, c)).collect())
+    }
+
+    /// Permitted alphabet: the characters from `first` to `last` (included)
+    pub fn alphabet_range(first: char, last: char) -> Self {
+        Constraint::Alphabet(alloc::vec![(first, last)])
+    }
+
+    /// Check that `obj` satisfies the constraint
+    pub fn check(&self, obj: &BerObject) -> Result<(), ConstraintError> {
+        self.check_kind(obj).map_err(|kind| ConstraintError {
+            constraint: self.clone(),
+            kind,
+        })
+    }
+
+    fn check_kind(&self, obj: &BerObject) -> Result<(), ConstraintErrorKind> {
+        match self {
+            Constraint::Size(min, max) => {
+                let size = object_size(obj).ok_or(ConstraintErrorKind::NotApplicable)?;
+                if size < *min || max.map_or(false, |max| size > max) {
+                    return Err(ConstraintErrorKind::SizeOutOfRange(size));
+                }
+            }
+            Constraint::Range(min, max) => {
+                let value = match obj.content {
+                    BerObjectContent::Integer(_) => obj.as_i64().ok(),
+                    BerObjectContent::Enum(e) => i64::try_from(e).ok(),
+                    _ => return Err(ConstraintErrorKind::NotApplicable),
+                };
+                let inside =
+                    |v: i64| min.map_or(true, |min| v >= min) && max.map_or(true, |max| v <= max);
+                match value {
+                    Some(v) if inside(v) => (),
+                    // a value not fitting in an i64 is inside only if the range is not bounded
+                    None if !is_negative(obj) && max.is_none() => (),
+                    None if is_negative(obj) && min.is_none() => (),
+                    v => return Err(ConstraintErrorKind::ValueOutOfRange(v)),
+                }
+            }
+            Constraint::Alphabet(ranges) => {
+                let s = string_value(obj).ok_or(ConstraintErrorKind::NotApplicable)?;
+                let s = s.map_err(ConstraintErrorKind::InvalidContent)?;
+                if let Some(c) = s
+                    .chars()
+                    .find(|c| !ranges.iter().any(|(first, last)| first <= c && c <= last))
+                {
+                    return Err(ConstraintErrorKind::CharNotPermitted(c));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Test if an INTEGER is negative
+fn is_negative(obj: &BerObject) -> bool {
+    match obj.content {
+        BerObjectContent::Integer(i) => i.first().map_or(false, |b| b & 0x80 != 0),
+        _ => false,
+    }
+}
+
+/// Size of an object, in the unit of SIZE constraints
+fn object_size(obj: &BerObject) -> Option<u64> {
+    let size = match &obj.content {
+        BerObjectContent::OctetString(s) => s.len(),
+        BerObjectContent::BitString(unused, BitStringObject { data }) => {
+            (data.len() * 8).saturating_sub(*unused as usize)
+        }
+        BerObjectContent::UniversalString(s) => s.len() / 4,
+        BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => v.len(),
+        _ => string_value(obj)?.ok()?.chars().count(),
+    };
+    Some(size as u64)
+}
+
+/// Value of a character string, or `None` if the object is not a character string
+fn string_value<'a>(obj: &BerObject<'a>) -> Option<Result<&'a str, BerError>> {
+    match obj.content {
+        BerObjectContent::NumericString(_)
+        | BerObjectContent::VisibleString(_)
+        | BerObjectContent::PrintableString(_)
+        | BerObjectContent::IA5String(_)
+        | BerObjectContent::UTF8String(_)
+        | BerObjectContent::T61String(_)
+        | BerObjectContent::VideotexString(_)
+        | BerObjectContent::BmpString(_)
+        | BerObjectContent::GraphicString(_)
+        | BerObjectContent::GeneralString(_)
+        | BerObjectContent::ObjectDescriptor(_) => Some(obj.as_str()),
+        _ => None,
+    }
+}
+
+/// Wrap a parser, and check the constraints on the parsed object
+///
+/// If a constraint fails, `BerError::InvalidValue` is returned, with a message describing the
+/// constraint and the error.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::constraint::{constrained, Constraint};
+/// use der_parser::der::parse_der_integer;
+///
+/// // INTEGER (0..255)
+/// let constraints = [Constraint::range(0, 255)];
+/// let mut parser = constrained(parse_der_integer, &constraints);
+/// assert!(parser(&[0x02, 0x01, 0x7f]).is_ok());
+/// assert!(parser(&[0x02, 0x02, 0x01, 0x00]).is_err());
+/// ```
+pub fn constrained<'a, 'c, F>(
+    mut f: F,
+    constraints: &'c [Constraint],
+) -> impl FnMut(&'a [u8]) -> BerResult<'a> + 'c
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a> + 'c,
+{
+    move |i| {
+        let (rem, obj) = f(i)?;
+        for c in constraints {
+            c.check(&obj).map_err(|e| {
+                nom::Err::Error(BerError::InvalidValue {
+                    tag: obj.tag(),
+                    msg: alloc::format!("{}", e),
+                })
+            })?;
+        }
+        Ok((rem, obj))
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn bound<T: fmt::Display>(f: &mut fmt::Formatter, b: &Option<T>, s: &str) -> fmt::Result {
+            match b {
+                Some(b) => write!(f, "{}", b),
+                None => f.write_str(s),
+            }
+        }
+        match self {
+            Constraint::Size(min, max) => {
+                write!(f, "SIZE ({}..", min)?;
+                bound(f, max, "MAX")?;
+                f.write_str(")")
+            }
+            Constraint::Range(min, max) => {
+                f.write_str("(")?;
+                bound(f, min, "MIN")?;
+                f.write_str("..")?;
+                bound(f, max, "MAX")?;
+                f.write_str(")")
+            }
+            Constraint::Alphabet(ranges) => {
+                f.write_str("FROM (")?;
+                for (idx, (first, last)) in ranges.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    if first == last {
+                        write!(f, "{:?}", first)?;
+                    } else {
+                        write!(f, "{:?}..{:?}", first, last)?;
+                    }
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "constraint {} failed: ", self.constraint)?;
+        match &self.kind {
+            ConstraintErrorKind::SizeOutOfRange(size) => write!(f, "size is {}", size),
+            ConstraintErrorKind::ValueOutOfRange(Some(v)) => write!(f, "value is {}", v),
+            ConstraintErrorKind::ValueOutOfRange(None) => f.write_str("value is too large"),
+            ConstraintErrorKind::CharNotPermitted(c) => write!(f, "character {:?}", c),
+            ConstraintErrorKind::NotApplicable => f.write_str("not applicable to this type"),
+            ConstraintErrorKind::InvalidContent(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConstraintError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::der::parse_der;
+    use hex_literal::hex;
+
+    fn check(c: &Constraint, bytes: &[u8]) -> Result<(), ConstraintErrorKind> {
+        let (_, obj) = parse_der(bytes).expect("parsing failed");
+        c.check(&obj).map_err(|e| e.kind)
+    }
+
+    #[test]
+    fn test_constraints() {
+        use ConstraintErrorKind::*;
+
+        let size = Constraint::size(1, 2);
+        assert_eq!(check(&size, &hex!("04 02 01 02")), Ok(()));
+        assert_eq!(check(&size, &hex!("04 00")), Err(SizeOutOfRange(0)));
+        // 9 bits
+        assert_eq!(
+            check(&size, &hex!("03 03 07 ff 80")),
+            Err(SizeOutOfRange(9))
+        );
+        // characters, not bytes
+        assert_eq!(check(&size, &hex!("0c 02 c3 a9")), Ok(()));
+        assert_eq!(
+            check(&size, &hex!("30 06 05 00 05 00 05 00")),
+            Err(SizeOutOfRange(3))
+        );
+        assert_eq!(
+            check(&Constraint::min_size(1), &hex!("30 02 05 00")),
+            Ok(())
+        );
+        assert_eq!(check(&size, &hex!("05 00")), Err(NotApplicable));
+
+        let range = Constraint::range(-1, 255);
+        assert_eq!(check(&range, &hex!("02 01 ff")), Ok(()));
+        assert_eq!(
+            check(&range, &hex!("02 02 01 00")),
+            Err(ValueOutOfRange(Some(256)))
+        );
+        assert_eq!(check(&range, &hex!("0a 01 02")), Ok(()));
+        let big = hex!("02 09 01 00 00 00 00 00 00 00 00");
+        assert_eq!(check(&range, &big), Err(ValueOutOfRange(None)));
+        assert_eq!(check(&Constraint::Range(Some(0), None), &big), Ok(()));
+
+        let alphabet = Constraint::Alphabet(vec![('0', '9'), (' ', ' ')]);
+        assert_eq!(check(&alphabet, &hex!("13 02 31 20")), Ok(()));
+        assert_eq!(
+            check(&alphabet, &hex!("13 02 31 41")),
+            Err(CharNotPermitted('A'))
+        );
+        assert_eq!(check(&alphabet, &hex!("04 01 41")), Err(NotApplicable));
+
+        assert_eq!(format!("{}", Constraint::min_size(1)), "SIZE (1..MAX)");
+        assert_eq!(format!("{}", alphabet), "FROM ('0'..'9' | ' ')");
+    }
+}
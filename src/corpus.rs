@@ -0,0 +1,176 @@
+//! Statistics on a corpus of encoded objects
+//!
+//! [`CorpusStats`] aggregates statistics on many inputs (for ex. a dataset of certificates, or
+//! the seeds of a fuzzer): frequencies of the tags, distribution of the nesting depths, and
+//! number of DER rules violated. The inputs are parsed leniently (see
+//! [`parse_der_lenient`]), so BER encodings are accepted and counted as violations.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use der_parser::ber::{Class, Tag};
+//! use der_parser::corpus::CorpusStats;
+//! use der_parser::der::DerViolation;
+//!
+//! let mut stats = CorpusStats::new();
+//! // SEQUENCE { INTEGER 1 }
+//! stats.add(&[0x30, 0x03, 0x02, 0x01, 0x01]).expect("parsing failed");
+//! // INTEGER 1, with a redundant leading zero
+//! stats.add(&[0x02, 0x02, 0x00, 0x01]).expect("parsing failed");
+//! // truncated input
+//! assert!(stats.add(&[0x30, 0x03, 0x02]).is_err());
+//!
+//! assert_eq!((stats.inputs, stats.rejected), (3, 1));
+//! assert_eq!(stats.count_tag(Class::Universal, Tag::Integer), 2);
+//! assert_eq!(stats.depths, vec![1, 1]);
+//! assert_eq!(stats.count_violation(DerViolation::NonMinimalInteger), 1);
+//! ```
+
+use crate::ber::{Class, Tag};
+use crate::der::{parse_der_lenient, DerViolation};
+use crate::error::BerError;
+use alloc::vec::Vec;
+use nom::Err;
+
+/// Statistics aggregated over a corpus of inputs
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CorpusStats {
+    /// Number of inputs added, including rejected inputs
+    pub inputs: usize,
+    /// Number of inputs which could not be parsed
+    pub rejected: usize,
+    /// Number of objects for each class and tag, sorted by class and tag
+    pub tags: Vec<(Class, Tag, usize)>,
+    /// Number of accepted inputs for each maximum depth: `depths[d]` is the number of inputs with
+    /// a maximum depth of `d` (the depth of a top-level object is 0)
+    pub depths: Vec<usize>,
+    /// Number of occurrences of each DER violation, in the order of the first occurrence
+    pub violations: Vec<(DerViolation, usize)>,
+}
+
+impl CorpusStats {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `data` and add its statistics
+    ///
+    /// The input can contain several top-level objects. If the input cannot be parsed, it is
+    /// counted as rejected, the partial statistics are discarded, and the error is returned.
+    pub fn add(&mut self, data: &[u8]) -> Result<(), BerError> {
+        self.inputs += 1;
+        let mut input = CorpusStats::default();
+        let mut max_depth = 0;
+        let mut i = data;
+        while !i.is_empty() {
+            let (rem, (obj, warnings)) = match parse_der_lenient(i) {
+                Ok(res) => res,
+                Err(e) => {
+                    self.rejected += 1;
+                    return Err(match e {
+                        Err::Error(e) | Err::Failure(e) => e,
+                        Err::Incomplete(_) => BerError::InvalidLength,
+                    });
+                }
+            };
+            let stats = obj.stats();
+            max_depth = max_depth.max(stats.max_depth);
+            for (class, tag, n) in stats.tags {
+                input.add_tag(class, tag, n);
+            }
+            for w in warnings {
+                input.add_violation(w.violation, 1);
+            }
+            i = rem;
+        }
+        for (class, tag, n) in input.tags {
+            self.add_tag(class, tag, n);
+        }
+        for (violation, n) in input.violations {
+            self.add_violation(violation, n);
+        }
+        if self.depths.len() <= max_depth {
+            self.depths.resize(max_depth + 1, 0);
+        }
+        self.depths[max_depth] += 1;
+        Ok(())
+    }
+
+    /// Get the number of objects with class `class` and tag `tag`
+    pub fn count_tag(&self, class: Class, tag: Tag) -> usize {
+        self.tags
+            .iter()
+            .find(|(c, t, _)| *c == class && *t == tag)
+            .map_or(0, |(_, _, n)| *n)
+    }
+
+    /// Get the number of occurrences of `violation`
+    pub fn count_violation(&self, violation: DerViolation) -> usize {
+        self.violations
+            .iter()
+            .find(|(v, _)| *v == violation)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    fn add_tag(&mut self, class: Class, tag: Tag, n: usize) {
+        match self
+            .tags
+            .iter_mut()
+            .find(|(c, t, _)| *c == class && *t == tag)
+        {
+            Some((_, _, count)) => *count += n,
+            None => {
+                self.tags.push((class, tag, n));
+                self.tags.sort_by_key(|(c, t, _)| (*c as u8, t.0));
+            }
+        }
+    }
+
+    fn add_violation(&mut self, violation: DerViolation, n: usize) {
+        match self.violations.iter_mut().find(|(v, _)| *v == violation) {
+            Some((_, count)) => *count += n,
+            None => self.violations.push((violation, n)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_corpus_stats() {
+        let mut stats = CorpusStats::new();
+        // two top-level objects: SET (indefinite) { NULL }, NULL
+        stats
+            .add(&hex!("31 80 05 00 00 00 05 00"))
+            .expect("parsing failed");
+        // SEQUENCE { SEQUENCE { BOOLEAN 1 } }
+        stats
+            .add(&hex!("30 05 30 03 01 01 01"))
+            .expect("parsing failed");
+        // trailing bytes are rejected, without partial statistics
+        assert!(stats.add(&hex!("05 00 05")).is_err());
+        assert_eq!((stats.inputs, stats.rejected), (3, 1));
+        assert_eq!(
+            stats.tags,
+            vec![
+                (Class::Universal, Tag::Boolean, 1),
+                (Class::Universal, Tag::Null, 2),
+                (Class::Universal, Tag::Sequence, 2),
+                (Class::Universal, Tag::Set, 1),
+            ]
+        );
+        assert_eq!(stats.depths, vec![0, 1, 1]);
+        assert_eq!(
+            stats.violations,
+            vec![
+                (DerViolation::IndefiniteLengthForbidden, 1),
+                (DerViolation::InvalidBoolean, 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use crate::ber::collect_bounded;
+use crate::der::*;
+use crate::error::*;
+use nom::error::ParseError;
+use nom::IResult;
+
+/// Parse a SEQUENCE OF object, returning a vector with a fixed capacity `N`
+///
+/// This is the same as [`parse_der_sequence_of_v`], except that the objects are stored in a
+/// `heapless::Vec`, so parsing the sequence does not allocate.
+///
+/// If the sequence contains more than `N` objects, a [`limit_exceeded`] failure is returned.
+///
+/// ```rust
+/// # use der_parser::der::{parse_der_sequence_of_bounded, parse_der_u32};
+/// # use der_parser::error::BerResult;
+/// #
+/// /// Read a SEQUENCE OF INTEGER, with at most 4 elements
+/// fn parser(i: &[u8]) -> BerResult<'_, heapless::Vec<u32, 4>> {
+///     parse_der_sequence_of_bounded(parse_der_u32)(i)
+/// }
+///
+/// let bytes = [0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02];
+/// let (rem, v) = parser(&bytes).expect("parsing failed");
+/// assert_eq!(v, [1, 2]);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+pub fn parse_der_sequence_of_bounded<'a, T, F, E, const N: usize>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], heapless::Vec<T, N>, E>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    parse_der_sequence_defined_g(move |data, _| collect_bounded(&mut f, data))
+}
+
+/// Parse a SET OF object, returning a vector with a fixed capacity `N`
+///
+/// This is the same as [`parse_der_set_of_v`], except that the objects are stored in a
+/// `heapless::Vec` (see [`parse_der_sequence_of_bounded`]).
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+pub fn parse_der_set_of_bounded<'a, T, F, E, const N: usize>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], heapless::Vec<T, N>, E>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
+{
+    parse_der_set_defined_g(move |data, _| collect_bounded(&mut f, data))
+}
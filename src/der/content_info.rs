@@ -0,0 +1,173 @@
+use crate::der::{
+    der_read_element_header, parse_der, parse_der_octetstring, parse_der_oid,
+    parse_der_sequence_defined_g, parse_der_tagged_explicit_class_g, Class, DerObject, Tag,
+};
+use crate::error::{BerError, BerResult};
+use crate::oid::Oid;
+
+/// Content type and content, as found in PKCS#7 and CMS (RFC 5652) structures
+///
+/// This type is returned by [`parse_der_content_info`] and
+/// [`parse_der_encapsulated_content_info`]. The content is not parsed: use
+/// [`ContentInfo::parse_content`] to parse it, for ex. after checking the content type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentInfo<'a> {
+    /// Content type
+    pub content_type: Oid<'a>,
+    /// DER encoding of the content (`None` if absent)
+    pub content: Option<&'a [u8]>,
+}
+
+impl<'a> ContentInfo<'a> {
+    /// Parse the content as a DER object
+    ///
+    /// Returns `Ok(None)` if the content is absent, and an error if the content is not a single
+    /// DER object.
+    pub fn parse_content(&self) -> Result<Option<DerObject<'a>>, BerError> {
+        let content = match self.content {
+            Some(content) => content,
+            None => return Ok(None),
+        };
+        let (rem, obj) = parse_der(content)?;
+        if !rem.is_empty() {
+            return Err(BerError::InvalidLength);
+        }
+        Ok(Some(obj))
+    }
+}
+
+/// Parse a ContentInfo (RFC 5652 section 3)
+///
+/// ```asn1
+/// ContentInfo ::= SEQUENCE {
+///   contentType ContentType,
+///   content [0] EXPLICIT ANY DEFINED BY contentType OPTIONAL }
+/// ```
+///
+/// The content is the encoding of the object in the `[0]` tag. The content is optional in
+/// PKCS#7, but required in CMS.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::der::parse_der_content_info;
+/// use der_parser::oid;
+///
+/// // ContentInfo { id-data, [0] { OCTET STRING "ab" } }
+/// let bytes = &[
+///     0x30, 0x11, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01, 0xa0, 0x04,
+///     0x04, 0x02, 0x61, 0x62,
+/// ];
+/// let (_, info) = parse_der_content_info(bytes).expect("parsing failed");
+/// assert_eq!(info.content_type, oid!(1.2.840.113549.1.7.1));
+/// let content = info.parse_content().expect("invalid content");
+/// assert_eq!(content.map(|obj| obj.as_slice()), Some(Ok(&b"ab"[..])));
+/// ```
+pub fn parse_der_content_info(i: &[u8]) -> BerResult<'_, ContentInfo<'_>> {
+    parse_der_sequence_defined_g(|i, _| {
+        let (i, obj) = parse_der_oid(i)?;
+        let content_type = obj.as_oid_val()?;
+        let (i, content) = parse_content(i, |content| Ok((&b""[..], content)))?;
+        Ok((
+            i,
+            ContentInfo {
+                content_type,
+                content,
+            },
+        ))
+    })(i)
+}
+
+/// Parse an EncapsulatedContentInfo (RFC 5652 section 5.2)
+///
+/// ```asn1
+/// EncapsulatedContentInfo ::= SEQUENCE {
+///   eContentType ContentType,
+///   eContent [0] EXPLICIT OCTET STRING OPTIONAL }
+/// ```
+///
+/// The content is the value of the OCTET STRING, which is usually the DER encoding of an object
+/// of type `eContentType` (except for the `id-data` type, where it is arbitrary data).
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::der::parse_der_encapsulated_content_info;
+///
+/// // EncapsulatedContentInfo { 1.2.3, [0] { OCTET STRING { INTEGER 5 } } }
+/// let bytes = &[
+///     0x30, 0x0c, 0x06, 0x02, 0x2a, 0x03, 0xa0, 0x06, 0x04, 0x04, 0x02, 0x02, 0x00, 0x80,
+/// ];
+/// let (_, info) = parse_der_encapsulated_content_info(bytes).expect("parsing failed");
+/// assert_eq!(info.content, Some(&[0x02, 0x02, 0x00, 0x80][..]));
+/// let content = info.parse_content().expect("invalid content");
+/// assert_eq!(content.map(|obj| obj.as_u32()), Some(Ok(128)));
+/// ```
+pub fn parse_der_encapsulated_content_info(i: &[u8]) -> BerResult<'_, ContentInfo<'_>> {
+    parse_der_sequence_defined_g(|i, _| {
+        let (i, obj) = parse_der_oid(i)?;
+        let content_type = obj.as_oid_val()?;
+        let (i, content) = parse_content(i, |content| {
+            let (rem, obj) = parse_der_octetstring(content)?;
+            Ok((rem, obj.as_slice()?))
+        })?;
+        Ok((
+            i,
+            ContentInfo {
+                content_type,
+                content,
+            },
+        ))
+    })(i)
+}
+
+/// Parse the optional `[0] EXPLICIT` content
+///
+/// The content is absent only if the next object is not tagged `[0]`: errors in the content are
+/// returned, instead of being silently ignored.
+fn parse_content<'a, F>(i: &'a [u8], f: F) -> BerResult<'a, Option<&'a [u8]>>
+where
+    F: Fn(&'a [u8]) -> BerResult<'a, &'a [u8]>,
+{
+    if i.is_empty() {
+        return Ok((i, None));
+    }
+    let (_, hdr) = der_read_element_header(i)?;
+    if hdr.class() != Class::ContextSpecific || hdr.tag() != Tag(0) {
+        return Ok((i, None));
+    }
+    let (i, content) =
+        parse_der_tagged_explicit_class_g(Class::ContextSpecific, 0, |content, _| f(content))(i)?;
+    Ok((i, Some(content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_content_info() {
+        // no content
+        let (rem, info) =
+            parse_der_content_info(&hex!("30 04 06 02 2a 03")).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(info.content, None);
+        assert_eq!(info.parse_content(), Ok(None));
+
+        // content with trailing data
+        let bytes = hex!("30 0a 06 02 2a 03 a0 04 05 00 05 00");
+        let (_, info) = parse_der_content_info(&bytes).expect("parsing failed");
+        assert_eq!(info.content, Some(&hex!("05 00 05 00")[..]));
+        assert_eq!(info.parse_content(), Err(BerError::InvalidLength));
+
+        // [1] instead of [0]
+        let bytes = hex!("30 08 06 02 2a 03 a1 02 05 00");
+        let (rem, info) = parse_der_content_info(&bytes).expect("parsing failed");
+        assert_eq!((rem, info.content), (&[][..], None));
+
+        // eContent is not an OCTET STRING
+        let bytes = hex!("30 08 06 02 2a 03 a0 02 05 00");
+        assert!(parse_der_encapsulated_content_info(&bytes).is_err());
+    }
+}
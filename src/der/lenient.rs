@@ -0,0 +1,618 @@
+use crate::ber::{
+    parse_ber, walk, BerObject, BerObjectContent, BitStringObject, Class, LengthForm, Span, Tag,
+};
+use crate::der::DerObject;
+use crate::error::{BerError, BerResult, DerConstraint};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use nom::Err;
+
+/// A deviation from the Distinguished Encoding Rules (X.690 sections 10 and 11)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DerViolation {
+    /// The length is encoded using the indefinite form (X.690 10.1)
+    IndefiniteLengthForbidden,
+    /// The length is not encoded using the minimum number of octets (X.690 10.1)
+    NonMinimalLength,
+    /// A string type uses the constructed form (X.690 10.2)
+    ConstructedStringForbidden,
+    /// A BOOLEAN value is not `0x00` or `0xff` (X.690 11.1)
+    InvalidBoolean,
+    /// An INTEGER or ENUMERATED value has redundant leading octets (X.690 8.3.2)
+    NonMinimalInteger,
+    /// The unused bits of a BIT STRING are not zero (X.690 11.2.1)
+    NonZeroUnusedBits,
+    /// The elements of a SET are sorted neither by tag (X.690 10.3) nor by encoding (X.690 11.6)
+    UnsortedSet,
+    /// A time value is not encoded in the canonical format (X.690 11.7 and 11.8)
+    InvalidTime,
+    /// End-of-content octets do not terminate an indefinite-length object (X.690 8.1.5)
+    UnexpectedEndOfContent,
+    /// End-of-content octets have a non-zero length (X.690 8.1.5)
+    InvalidEndOfContent,
+    /// A component is encoded while its value is equal to the DEFAULT value (X.690 11.5)
+    ///
+    /// This cannot be detected without the ASN.1 definition of the structure, so it is never
+    /// reported by [`parse_der_lenient`]. It is provided for parsers of structures with DEFAULT
+    /// components.
+    DefaultValueEncoded,
+}
+
+impl fmt::Display for DerViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            DerViolation::IndefiniteLengthForbidden => "indefinite length",
+            DerViolation::NonMinimalLength => "non-minimal length",
+            DerViolation::ConstructedStringForbidden => "constructed string",
+            DerViolation::InvalidBoolean => "invalid boolean",
+            DerViolation::NonMinimalInteger => "non-minimal integer",
+            DerViolation::NonZeroUnusedBits => "non-zero unused bits",
+            DerViolation::UnsortedSet => "unsorted set",
+            DerViolation::InvalidTime => "invalid time format",
+            DerViolation::UnexpectedEndOfContent => "unexpected end-of-content",
+            DerViolation::InvalidEndOfContent => "invalid end-of-content",
+            DerViolation::DefaultValueEncoded => "default value encoded",
+        };
+        f.write_str(s)
+    }
+}
+
+impl DerViolation {
+    /// Get the violation corresponding to an error returned by the DER parsers, if any
+    ///
+    /// ```rust
+    /// use der_parser::der::{parse_der, DerViolation};
+    ///
+    /// // BOOLEAN 0x01
+    /// let err = parse_der(&[0x01, 0x01, 0x01]).expect_err("not DER");
+    /// let violation = match err {
+    ///     nom::Err::Error(e) | nom::Err::Failure(e) => DerViolation::from_error(&e),
+    ///     nom::Err::Incomplete(_) => None,
+    /// };
+    /// assert_eq!(violation, Some(DerViolation::InvalidBoolean));
+    /// ```
+    pub fn from_error(e: &BerError) -> Option<DerViolation> {
+        match e {
+            BerError::DerConstraintFailed(c) => match c {
+                DerConstraint::IndefiniteLength => Some(DerViolation::IndefiniteLengthForbidden),
+                DerConstraint::Constructed => Some(DerViolation::ConstructedStringForbidden),
+                DerConstraint::InvalidBoolean => Some(DerViolation::InvalidBoolean),
+                DerConstraint::IntegerLeadingZeroes | DerConstraint::IntegerLeadingFF => {
+                    Some(DerViolation::NonMinimalInteger)
+                }
+                DerConstraint::UnusedBitsNotZero => Some(DerViolation::NonZeroUnusedBits),
+                DerConstraint::MissingTimeZone | DerConstraint::MissingSeconds => {
+                    Some(DerViolation::InvalidTime)
+                }
+                _ => None,
+            },
+            BerError::IndefiniteLengthUnexpected => Some(DerViolation::IndefiniteLengthForbidden),
+            _ => None,
+        }
+    }
+
+    /// Convert the violation to the closest parsing error
+    ///
+    /// Violations without a corresponding [`DerConstraint`] are converted to generic errors
+    /// (`InvalidLength`, `InvalidDateTime`, `UnexpectedTag` or `BerValueError`).
+    pub fn to_error(self) -> BerError {
+        let constraint = match self {
+            DerViolation::IndefiniteLengthForbidden => DerConstraint::IndefiniteLength,
+            DerViolation::ConstructedStringForbidden => DerConstraint::Constructed,
+            DerViolation::InvalidBoolean => DerConstraint::InvalidBoolean,
+            DerViolation::NonMinimalInteger => DerConstraint::IntegerLeadingZeroes,
+            DerViolation::NonZeroUnusedBits => DerConstraint::UnusedBitsNotZero,
+            DerViolation::NonMinimalLength | DerViolation::InvalidEndOfContent => {
+                return BerError::InvalidLength
+            }
+            DerViolation::UnexpectedEndOfContent => {
+                return BerError::unexpected_tag(None, Tag::EndOfContent)
+            }
+            DerViolation::InvalidTime => return BerError::InvalidDateTime,
+            DerViolation::UnsortedSet | DerViolation::DefaultValueEncoded => {
+                return BerError::BerValueError
+            }
+        };
+        BerError::DerConstraintFailed(constraint)
+    }
+}
+
+/// A DER violation, with the location of the object
+///
+/// This type is shared by all functions reporting deviations: the lenient parsers
+/// ([`parse_der_lenient`], [`parse_der_tolerant`]), the validator ([`validate_der`]), and the
+/// parsers returning the deviations accepted by their options
+/// ([`parse_ber_with_warnings`](crate::ber::parse_ber_with_warnings),
+/// [`parse_der_with_warnings`]).
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::der::parse_der_lenient;
+///
+/// // SEQUENCE { INTEGER 0x0001 }
+/// let bytes = &[0x30, 0x04, 0x02, 0x02, 0x00, 0x01];
+/// let (_, (obj, warnings)) = parse_der_lenient(bytes).expect("parsing failed");
+/// assert_eq!(warnings[0].offset, 2);
+/// assert_eq!(warnings[0].path, vec![0]);
+/// assert_eq!(warnings[0].to_string(), "non-minimal integer at offset 2 (path [0])");
+/// assert!(obj.get_path(&warnings[0].path).is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Warning {
+    /// The deviation
+    pub violation: DerViolation,
+    /// Offset of the object in the input
+    pub offset: usize,
+    /// Path of the object in the parsed tree (see [`BerObject::get_path`])
+    ///
+    /// The path of the root object is empty.
+    pub path: Vec<usize>,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {} (path {:?})",
+            self.violation, self.offset, self.path
+        )
+    }
+}
+
+/// Parse a DER object, accepting BER encodings, and return the list of DER violations
+///
+/// The object is parsed using the BER rules (see [`parse_ber`]), so values are decoded even if
+/// they are not valid DER. Every deviation from DER is reported as a [`Warning`], in the order of
+/// the objects in the input.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::der::{parse_der_lenient, DerViolation};
+///
+/// // SEQUENCE (indefinite length) { BOOLEAN 0x01 }
+/// let bytes = &[0x30, 0x80, 0x01, 0x01, 0x01, 0x00, 0x00];
+/// let (_, (obj, warnings)) = parse_der_lenient(bytes).expect("parsing failed");
+/// assert_eq!(obj[0].as_bool(), Ok(true));
+/// let violations = warnings.iter().map(|w| (w.offset, w.violation)).collect::<Vec<_>>();
+/// assert_eq!(
+///     violations,
+///     vec![
+///         (0, DerViolation::IndefiniteLengthForbidden),
+///         (2, DerViolation::InvalidBoolean),
+///     ]
+/// );
+/// ```
+pub fn parse_der_lenient<'a>(i: &'a [u8]) -> BerResult<'a, (DerObject<'a>, Vec<Warning>)> {
+    let (rem, obj) = parse_ber(i)?;
+    let mut warnings = Vec::new();
+    check_der(&obj, i, &mut warnings);
+    Ok((rem, (obj, warnings)))
+}
+
+/// Parse a DER object, tolerating only the listed violations
+///
+/// The object is parsed using the BER rules, and the DER rules are then checked as in
+/// [`parse_der_lenient`]. Violations listed in `tolerated` are returned as warnings, while any
+/// other violation raises an error (see [`DerViolation::to_error`]).
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::der::{parse_der_tolerant, DerViolation};
+///
+/// // OCTET STRING (indefinite length) { OCTET STRING "a" }
+/// let bytes = &[0x24, 0x80, 0x04, 0x01, 0x61, 0x00, 0x00];
+/// assert!(parse_der_tolerant(bytes, &[DerViolation::IndefiniteLengthForbidden]).is_err());
+/// let tolerated = [
+///     DerViolation::IndefiniteLengthForbidden,
+///     DerViolation::ConstructedStringForbidden,
+/// ];
+/// let (_, (_, warnings)) = parse_der_tolerant(bytes, &tolerated).expect("parsing failed");
+/// assert_eq!(warnings.len(), 2);
+/// ```
+pub fn parse_der_tolerant<'a>(
+    i: &'a [u8],
+    tolerated: &[DerViolation],
+) -> BerResult<'a, (DerObject<'a>, Vec<Warning>)> {
+    let (rem, (obj, warnings)) = parse_der_lenient(i)?;
+    if let Some(w) = warnings.iter().find(|w| !tolerated.contains(&w.violation)) {
+        return Err(Err::Error(w.violation.to_error()));
+    }
+    Ok((rem, (obj, warnings)))
+}
+
+/// Check the DER rules on an object parsed using BER, and return all the violations
+///
+/// `input` is the buffer given to the parser: the encoding of the objects is checked using their
+/// spans, and the offsets of the warnings are relative to `input`. Objects without span (built
+/// objects, or objects not located in `input`) are not checked.
+///
+/// This allows parsing leniently once, and deciding afterwards from the complete list of
+/// violations. [`parse_der_lenient`] combines both steps.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::parse_ber;
+/// use der_parser::der::{validate_der, DerViolation};
+///
+/// // SEQUENCE { INTEGER 0x0001, INTEGER (non-minimal length) 1 }
+/// let bytes = &[0x30, 0x08, 0x02, 0x02, 0x00, 0x01, 0x02, 0x81, 0x01, 0x01];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// let warnings = validate_der(&obj, bytes);
+/// let violations = warnings.iter().map(|w| (w.offset, w.violation)).collect::<Vec<_>>();
+/// assert_eq!(
+///     violations,
+///     vec![
+///         (2, DerViolation::NonMinimalInteger),
+///         (6, DerViolation::NonMinimalLength),
+///     ]
+/// );
+/// ```
+pub fn validate_der(obj: &BerObject, input: &[u8]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    check_der(obj, input, &mut warnings);
+    warnings
+}
+
+/// Check that the elements of a SET OF are in canonical order (X.690 11.6)
+///
+/// The elements must be sorted by their encodings, compared as octet strings (the shorter one
+/// being padded with zeroes). Returns the indices of the adjacent elements in the wrong order: for
+/// ex. `(0, 1)` if the first element is greater than the second. The list is empty if the SET OF
+/// is canonical.
+///
+/// The encodings are taken from the spans of the elements, so this function applies to parsed
+/// objects: an error is returned if an element has no span, or if `obj` is not a SET.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::parse_ber;
+/// use der_parser::der::check_der_set_of_order;
+///
+/// // SET OF { INTEGER 2, INTEGER 1, INTEGER 3 }
+/// let bytes = &[0x31, 0x09, 0x02, 0x01, 0x02, 0x02, 0x01, 0x01, 0x02, 0x01, 0x03];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// assert_eq!(check_der_set_of_order(&obj), Ok(vec![(0, 1)]));
+/// ```
+pub fn check_der_set_of_order(obj: &BerObject) -> Result<Vec<(usize, usize)>, BerError> {
+    let elements = obj.as_set()?;
+    let raw = elements
+        .iter()
+        .map(|o| o.raw_bytes().ok_or(BerError::BerValueError))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(unordered_pairs(&raw, |a, b| {
+        set_order(a, b) == Ordering::Greater
+    }))
+}
+
+/// Check that the elements of a SET are in canonical order (X.690 10.3)
+///
+/// The elements must be sorted by tag (in the order universal, application, context-specific,
+/// private, and then by tag number), and tags must be distinct. Returns the indices of the
+/// adjacent elements in the wrong order, or with the same tag. The list is empty if the SET is
+/// canonical.
+///
+/// An error is returned if `obj` is not a SET.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::parse_ber;
+/// use der_parser::der::check_der_set_order;
+///
+/// // SET { [1] NULL, [0] NULL }
+/// let bytes = &[0x31, 0x04, 0x81, 0x00, 0x80, 0x00];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// assert_eq!(check_der_set_order(&obj), Ok(vec![(0, 1)]));
+/// ```
+pub fn check_der_set_order(obj: &BerObject) -> Result<Vec<(usize, usize)>, BerError> {
+    let elements = obj.as_set()?;
+    let tags = elements
+        .iter()
+        .map(|o| (o.class() as u8, o.tag().0))
+        .collect::<Vec<_>>();
+    Ok(unordered_pairs(&tags, |a, b| a >= b))
+}
+
+/// Return the indices of adjacent items for which `unordered` is true
+fn unordered_pairs<T, F>(items: &[T], unordered: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    items
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| unordered(&w[0], &w[1]))
+        .map(|(idx, _)| (idx, idx + 1))
+        .collect()
+}
+
+/// Check the DER rules on `obj` and all its descendants, using the spans of the objects
+pub(crate) fn check_der(obj: &BerObject, input: &[u8], warnings: &mut Vec<Warning>) {
+    walk(obj, &mut Vec::new(), &mut |path, o| {
+        check_der_object(o, path, input, warnings);
+        false
+    });
+}
+
+/// Set the paths of `warnings`, using the offsets of the objects of `obj` in `input`
+pub(crate) fn locate_warnings(obj: &BerObject, input: &[u8], warnings: &mut [Warning]) {
+    if warnings.is_empty() {
+        return;
+    }
+    walk(obj, &mut Vec::new(), &mut |path, o| {
+        if let BerObjectContent::Optional(_) = o.content {
+            return false;
+        }
+        if let Some(offset) = o.span.and_then(|span| span.offset_in(input)) {
+            for w in warnings.iter_mut().filter(|w| w.offset == offset) {
+                w.path = path.to_vec();
+            }
+        }
+        false
+    });
+}
+
+/// Check the DER rules on `o` (but not its children)
+fn check_der_object(o: &BerObject, path: &[usize], input: &[u8], warnings: &mut Vec<Warning>) {
+    if let BerObjectContent::Optional(_) = o.content {
+        // the span is the span of the inner object
+        return;
+    }
+    let span = match o.span {
+        Some(span) => span,
+        None => return,
+    };
+    let offset = match span.offset_in(input) {
+        Some(offset) => offset,
+        None => return,
+    };
+    let mut push = |violation| {
+        warnings.push(Warning {
+            violation,
+            offset,
+            path: path.to_vec(),
+        })
+    };
+    if let Some(violation) = check_length(&span) {
+        push(violation);
+    }
+    if o.class() != Class::Universal {
+        return;
+    }
+    let content = span.content();
+    let violation = match o.tag() {
+        Tag::BitString
+        | Tag::OctetString
+        | Tag::ObjectDescriptor
+        | Tag::Utf8String
+        | Tag(18..=22)
+        | Tag(25..=28)
+        | Tag::BmpString
+            if o.header.is_constructed() =>
+        {
+            Some(DerViolation::ConstructedStringForbidden)
+        }
+        Tag::Boolean => match content {
+            [0x00] | [0xff] => None,
+            _ => Some(DerViolation::InvalidBoolean),
+        },
+        Tag::Integer | Tag::Enumerated => match content {
+            [0x00, b, ..] if b & 0x80 == 0 => Some(DerViolation::NonMinimalInteger),
+            [0xff, b, ..] if b & 0x80 != 0 => Some(DerViolation::NonMinimalInteger),
+            _ => None,
+        },
+        Tag::BitString => match content.split_first() {
+            Some((&unused_bits, data)) => BitStringObject { data }
+                .nonzero_unused_bits(unused_bits)
+                .map(|_| DerViolation::NonZeroUnusedBits),
+            None => None,
+        },
+        // without a schema, SET and SET OF cannot be distinguished: the set is unsorted only if
+        // it follows neither rule
+        Tag::Set => match (check_der_set_order(o), check_der_set_of_order(o)) {
+            (Ok(p1), Ok(p2)) if !p1.is_empty() && !p2.is_empty() => Some(DerViolation::UnsortedSet),
+            _ => None,
+        },
+        Tag::UtcTime => match content {
+            [_, _, _, _, _, _, _, _, _, _, _, _, b'Z'] => None,
+            _ => Some(DerViolation::InvalidTime),
+        },
+        Tag::GeneralizedTime => check_generalizedtime(content),
+        // terminating EOC are not stored in the tree
+        Tag::EndOfContent if !content.is_empty() => Some(DerViolation::InvalidEndOfContent),
+        Tag::EndOfContent => Some(DerViolation::UnexpectedEndOfContent),
+        _ => None,
+    };
+    if let Some(violation) = violation {
+        push(violation);
+    }
+}
+
+/// Check the encoding of the length in the header
+fn check_length(span: &Span) -> Option<DerViolation> {
+    match span.length_form() {
+        LengthForm::Indefinite => Some(DerViolation::IndefiniteLengthForbidden),
+        _ if !span.has_minimal_length() => Some(DerViolation::NonMinimalLength),
+        _ => None,
+    }
+}
+
+/// Check that the GeneralizedTime is in the form `YYYYMMDDHHMMSS[.f*]Z`, without trailing zeroes
+fn check_generalizedtime(content: &[u8]) -> Option<DerViolation> {
+    let valid = match content {
+        [datetime @ .., b'Z'] if datetime.len() >= 14 => match &datetime[14..] {
+            [] => true,
+            [b'.', fraction @ ..] => fraction.last().map_or(false, |b| *b != b'0'),
+            _ => false,
+        },
+        _ => false,
+    };
+    (!valid).then_some(DerViolation::InvalidTime)
+}
+
+/// Compare encodings of SET elements: the shorter one is padded with zeroes (X.690 11.6)
+fn set_order(a: &[u8], b: &[u8]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|idx| {
+            let x = a.get(idx).copied().unwrap_or(0);
+            let y = b.get(idx).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ber::{parse_ber, IntegerPolicy, ParseOptions, Tag};
+    use crate::der::*;
+    use crate::error::{BerError, DerConstraint};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use hex_literal::hex;
+
+    fn violations(bytes: &[u8]) -> Vec<(usize, DerViolation)> {
+        let (_, (_, warnings)) = parse_der_lenient(bytes).expect("parsing failed");
+        warnings.iter().map(|w| (w.offset, w.violation)).collect()
+    }
+
+    #[test]
+    fn test_der_lenient() {
+        // valid DER
+        assert!(violations(&hex!("30 08 02 01 01 31 03 01 01 ff")).is_empty());
+        // non-minimal length, non-minimal integer, non-zero unused bits
+        assert_eq!(
+            violations(&hex!("30 81 09 02 02 00 01 03 03 04 01 0f")),
+            vec![
+                (0, DerViolation::NonMinimalLength),
+                (3, DerViolation::NonMinimalInteger),
+                (7, DerViolation::NonZeroUnusedBits),
+            ]
+        );
+        // unsorted SET
+        assert_eq!(
+            violations(&hex!("31 06 02 01 02 02 01 01")),
+            vec![(0, DerViolation::UnsortedSet)]
+        );
+        assert!(violations(&hex!("31 06 02 01 01 02 01 02")).is_empty());
+        // SET { [0] EXPLICIT INTEGER, [1] IMPLICIT INTEGER }, sorted by tag but not by encoding
+        assert!(violations(&hex!("31 08 a0 03 02 01 01 81 01 01")).is_empty());
+        // CHARACTER STRING is always constructed
+        assert!(violations(&hex!("3d 07 a0 02 85 00 82 01 61")).is_empty());
+        // times
+        assert!(violations(b"\x17\x0d191231235959Z").is_empty());
+        assert!(violations(b"\x18\x0f20191231235959Z").is_empty());
+        assert!(violations(b"\x18\x1120191231235959.5Z").is_empty());
+        assert_eq!(
+            violations(b"\x18\x1220191231235959.50Z"),
+            vec![(0, DerViolation::InvalidTime)]
+        );
+    }
+
+    #[test]
+    fn test_validate_der() {
+        let bytes = hex!("30 0a 01 01 01 31 05 04 01 62 04 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        let warnings = validate_der(&obj, &bytes);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (w.offset, w.violation))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, DerViolation::InvalidBoolean),
+                (5, DerViolation::UnsortedSet)
+            ]
+        );
+        // objects not in the input are ignored
+        assert!(validate_der(&obj, &bytes[..2]).is_empty());
+        assert_eq!(validate_der(&obj[0], &bytes[2..]).len(), 1);
+        // SET sorted by tag (X.690 10.3)
+        let bytes = hex!("31 08 a0 03 02 01 01 81 01 01");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert!(validate_der(&obj, &bytes).is_empty());
+    }
+
+    #[test]
+    fn test_warning_paths() {
+        // SEQUENCE { NULL, BOOLEAN 1 }
+        let bytes = hex!("30 05 05 00 01 01 01");
+        let (_, (obj, warnings)) = parse_der_lenient(&bytes).expect("parsing failed");
+        let paths: Vec<_> = warnings.iter().map(|w| w.path.as_slice()).collect();
+        assert_eq!(paths, vec![&[1][..]]);
+        assert_eq!(obj.get_path(paths[0]).map(|o| o.tag()), Some(Tag::Boolean));
+        // the warnings reported during parsing have the same paths
+        let options = ParseOptions::der().with_integer_policy(IntegerPolicy::Warn);
+        let (_, (obj, warnings)) =
+            parse_der_with_warnings(&hex!("30 06 30 04 02 02 00 01"), &options)
+                .expect("parsing failed");
+        assert_eq!(
+            warnings,
+            vec![Warning {
+                violation: DerViolation::NonMinimalInteger,
+                offset: 4,
+                path: vec![0, 0],
+            }]
+        );
+        assert_eq!(obj.get_path(&[0, 0]).map(|o| o.tag()), Some(Tag::Integer));
+    }
+
+    #[test]
+    fn test_set_order() {
+        // SET OF { INTEGER 1, INTEGER 0x0100, INTEGER 3, INTEGER 2 }
+        let bytes = hex!("31 0d 02 01 01 02 02 01 00 02 01 03 02 01 02");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert_eq!(check_der_set_of_order(&obj), Ok(vec![(1, 2), (2, 3)]));
+        // elements are sorted by encoding, not by value: SET OF { INTEGER -1, INTEGER 1 }
+        let bytes = hex!("31 06 02 01 ff 02 01 01");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert_eq!(check_der_set_of_order(&obj), Ok(vec![(0, 1)]));
+        // SET { INTEGER, [APPLICATION 0], [0], [0] }
+        let bytes = hex!("31 0b 02 01 01 40 00 80 00 80 02 05 00");
+        let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+        assert_eq!(check_der_set_order(&obj), Ok(vec![(2, 3)]));
+        // built objects have no encoding
+        let obj = BerObject::from_set(vec![BerObject::from_int_slice(b"\x01")]);
+        assert_eq!(check_der_set_order(&obj), Ok(vec![]));
+        assert_eq!(check_der_set_of_order(&obj), Err(BerError::BerValueError));
+        assert_eq!(
+            check_der_set_order(&BerObject::from_seq(vec![])),
+            Err(BerError::BerTypeError)
+        );
+    }
+
+    #[test]
+    fn test_der_tolerant() {
+        // SET { INTEGER 0x0001, INTEGER 1 }
+        let bytes = hex!("31 07 02 02 00 01 02 01 01");
+        let r = parse_der_tolerant(&bytes, &[DerViolation::UnsortedSet]);
+        assert_eq!(
+            r.unwrap_err(),
+            nom::Err::Error(BerError::DerConstraintFailed(
+                DerConstraint::IntegerLeadingZeroes
+            ))
+        );
+        let tolerated = [DerViolation::UnsortedSet, DerViolation::NonMinimalInteger];
+        let (_, (obj, warnings)) = parse_der_tolerant(&bytes, &tolerated).expect("parsing failed");
+        assert_eq!(obj[0].as_u32(), Ok(1));
+        assert_eq!(warnings.len(), 2);
+        // errors of the strict parser map to the same violations
+        for bytes in [
+            &hex!("01 01 01")[..],
+            &hex!("02 02 00 01"),
+            &hex!("03 02 01 01"),
+        ] {
+            let v = match parse_der(bytes).unwrap_err() {
+                nom::Err::Error(e) => DerViolation::from_error(&e),
+                _ => None,
+            };
+            assert_eq!(v, Some(violations(bytes)[0].1));
+        }
+    }
+}
@@ -54,13 +54,28 @@
 //! ```
 
 use crate::ber::{BerObject, BerObjectContent};
-pub use crate::ber::{Class, Header};
+pub use crate::ber::{
+    BitStringPolicy, BooleanPolicy, Class, ConstructedPolicy, EocPolicy, Header, IntegerPolicy,
+    ParseOptions, Span,
+};
 pub use asn1_rs::Tag;
 
+#[cfg(feature = "heapless")]
+mod bounded;
+mod content_info;
+mod lenient;
 mod multi;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod parser;
 mod tagged;
+#[cfg(feature = "heapless")]
+pub use crate::der::bounded::*;
+pub use crate::der::content_info::*;
+pub use crate::der::lenient::*;
 pub use crate::der::multi::*;
+#[cfg(feature = "rayon")]
+pub use crate::der::parallel::*;
 pub use crate::der::parser::*;
 pub use crate::der::tagged::*;
 
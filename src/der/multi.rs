@@ -1,4 +1,4 @@
-use crate::ber::Length;
+use crate::ber::{with_span, Length};
 use crate::der::*;
 use crate::error::*;
 use nom::bytes::complete::take;
@@ -32,11 +32,14 @@ use nom::{Err, IResult};
 /// # assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// let (rem, v) = parser(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_der_sequence_of<'a, F>(f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_der_sequence_of<'a, F, E>(
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>
 where
-    F: Fn(&'a [u8]) -> BerResult,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(parse_der_sequence_of_v(f), DerObject::from_seq)
+    with_span(map(parse_der_sequence_of_v(f), DerObject::from_seq))
 }
 
 /// Parse a SEQUENCE OF object (returning a vec)
@@ -155,14 +158,17 @@ where
 /// # assert_eq!(localparse_seq(&bytes), Ok((empty, expected)));
 /// let (rem, v) = localparse_seq(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_der_sequence_defined<'a, F>(mut f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_der_sequence_defined<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>
 where
-    F: FnMut(&'a [u8]) -> BerResult<Vec<DerObject>>,
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<DerObject<'a>>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(
+    with_span(map(
         parse_der_sequence_defined_g(move |data, _| f(data)),
         DerObject::from_seq,
-    )
+    ))
 }
 
 /// Parse a defined SEQUENCE object (generic function)
@@ -257,11 +263,14 @@ where
 /// # assert_eq!(parser(&bytes), Ok((empty, expected)));
 /// let (rem, v) = parser(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_der_set_of<'a, F>(f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_der_set_of<'a, F, E>(
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>
 where
-    F: Fn(&'a [u8]) -> BerResult,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(parse_der_set_of_v(f), DerObject::from_set)
+    with_span(map(parse_der_set_of_v(f), DerObject::from_set))
 }
 
 /// Parse a SET OF object (returning a vec)
@@ -378,14 +387,17 @@ where
 /// # assert_eq!(localparse_set(&bytes), Ok((empty, expected)));
 /// let (rem, v) = localparse_set(&bytes).expect("parsing failed");
 /// ```
-pub fn parse_der_set_defined<'a, F>(mut f: F) -> impl FnMut(&'a [u8]) -> BerResult
+pub fn parse_der_set_defined<'a, F, E>(
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], DerObject<'a>, E>
 where
-    F: FnMut(&'a [u8]) -> BerResult<Vec<DerObject>>,
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<DerObject<'a>>, E>,
+    E: ParseError<&'a [u8]> + From<BerError>,
 {
-    map(
+    with_span(map(
         parse_der_set_defined_g(move |data, _| f(data)),
         DerObject::from_set,
-    )
+    ))
 }
 
 /// Parse a defined SET object (generic version)
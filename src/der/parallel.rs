@@ -0,0 +1,67 @@
+use crate::ber::scan::Scan;
+use crate::der::{parse_der, DerObject};
+use crate::error::BerError;
+use alloc::vec::Vec;
+use nom::Err;
+use rayon::prelude::*;
+
+/// Parse all the concatenated DER objects of `i`, using multiple threads
+///
+/// The input is first scanned to locate the top-level objects (without decoding them), then the
+/// objects are parsed in parallel using the global `rayon` thread pool. The result contains the
+/// objects in the order of the input. This is useful for bulk processing of large inputs, for ex.
+/// certificate corpora.
+///
+/// If the input cannot be split, or if any object fails to parse, an error is returned. Trailing
+/// data is not allowed.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::der::parse_der_all_parallel;
+///
+/// // INTEGER 1, NULL, BOOLEAN TRUE
+/// let bytes = &[0x02, 0x01, 0x01, 0x05, 0x00, 0x01, 0x01, 0xff];
+/// let objects = parse_der_all_parallel(bytes).expect("parsing failed");
+/// assert_eq!(objects.len(), 3);
+/// assert_eq!(objects[0].as_u32(), Ok(1));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub fn parse_der_all_parallel(i: &[u8]) -> Result<Vec<DerObject<'_>>, BerError> {
+    let spans = Scan::flat(i)
+        .map(|tlv| tlv.map(|tlv| tlv.span().raw()))
+        .collect::<Result<Vec<_>, _>>()?;
+    spans
+        .into_par_iter()
+        .map(|raw| match parse_der(raw) {
+            Ok((_, obj)) => Ok(obj),
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(e),
+            Err(Err::Incomplete(n)) => Err(BerError::Incomplete(n)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_parse_der_all_parallel() {
+        // SEQUENCE { INTEGER 1 }, repeated
+        let bytes = hex!("30 03 02 01 01").repeat(100);
+        let objects = parse_der_all_parallel(&bytes).expect("parsing failed");
+        assert_eq!(objects.len(), 100);
+        assert!(objects.iter().all(|obj| obj[0].as_u32() == Ok(1)));
+
+        // indefinite length in the second object
+        let bytes = hex!("05 00 30 80 00 00");
+        assert!(parse_der_all_parallel(&bytes).is_err());
+        // truncated object
+        let bytes = hex!("05 00 30 03 02 01");
+        assert!(matches!(
+            parse_der_all_parallel(&bytes),
+            Err(BerError::Incomplete(_))
+        ));
+    }
+}
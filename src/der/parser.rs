@@ -1,8 +1,11 @@
 use crate::ber::*;
+use crate::der::lenient::{check_der, locate_warnings};
 use crate::der::*;
 use crate::der_constraint_fail_if;
 use crate::error::*;
+use crate::trace;
 use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
 use asn1_rs::{Any, FromDer};
 use nom::bytes::streaming::take;
 use nom::number::streaming::be_u8;
@@ -30,10 +33,76 @@ pub fn parse_der(i: &[u8]) -> DerResult {
     parse_der_recursive(i, MAX_RECURSION)
 }
 
+/// Parse DER object recursively, and return the location of the error if parsing failed
+///
+/// See [`LocatedError`] for details on how the error is located.
+pub fn parse_der_located<'a>(i: &'a [u8]) -> Result<(&'a [u8], DerObject<'a>), LocatedError> {
+    parse_der(i).map_err(|e| LocatedError::locate(i, e, parse_der))
+}
+
+/// Parse DER object recursively, using the provided options
+///
+/// This is the same as [`parse_der_with_warnings`], but warnings are ignored.
+#[inline]
+pub fn parse_der_with_options<'a>(i: &'a [u8], options: &ParseOptions) -> DerResult<'a> {
+    parse_der_with_warnings(i, options).map(|(rem, (obj, _))| (rem, obj))
+}
+
+/// Parse DER object recursively, using the provided options, and return the warnings
+///
+/// The object is parsed using the options (for ex. [`ParseOptions::der`]), and the DER rules are
+/// then checked on the object and all its descendants. The deviations accepted by the options
+/// (indefinite lengths if `allow_indefinite` is set, or values accepted by a `Warn` policy, for
+/// ex. [`IntegerPolicy::Warn`]) are returned as warnings, in the order of the objects. Other
+/// deviations raise an error (see [`DerViolation::to_error`]).
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::der::{parse_der_with_warnings, DerViolation, ParseOptions};
+///
+/// // SEQUENCE (indefinite length) { INTEGER 1 }
+/// let bytes = &[0x30, 0x80, 0x02, 0x01, 0x01, 0x00, 0x00];
+/// assert!(parse_der_with_warnings(bytes, &ParseOptions::der()).is_err());
+///
+/// let options = ParseOptions::der().with_allow_indefinite(true);
+/// let (_, (obj, warnings)) = parse_der_with_warnings(bytes, &options).expect("parsing failed");
+/// assert_eq!(obj[0].as_u32(), Ok(1));
+/// assert_eq!(warnings[0].violation, DerViolation::IndefiniteLengthForbidden);
+/// ```
+pub fn parse_der_with_warnings<'a>(
+    i: &'a [u8],
+    options: &ParseOptions,
+) -> BerResult<'a, (DerObject<'a>, Vec<Warning>)> {
+    let state = ParseState::new(i, options.max_depth, options);
+    let (rem, obj) = parse_ber_any_with_state_r(i, options.max_depth, options, &state)?;
+    let mut warnings = state.into_warnings();
+    let mut violations = Vec::new();
+    check_der(&obj, i, &mut violations);
+    for w in violations {
+        match w.violation {
+            DerViolation::IndefiniteLengthForbidden if options.allow_indefinite => warnings.push(w),
+            // already checked, according to the policies
+            DerViolation::InvalidBoolean
+            | DerViolation::NonMinimalInteger
+            | DerViolation::NonZeroUnusedBits
+            | DerViolation::UnexpectedEndOfContent
+            | DerViolation::InvalidEndOfContent => (),
+            violation => return Err(Err::Error(violation.to_error())),
+        }
+    }
+    locate_warnings(&obj, i, &mut warnings);
+    warnings.sort_by_key(|w| w.offset);
+    Ok((rem, (obj, warnings)))
+}
+
 /// Parse DER object recursively, specifying the maximum recursion depth
 ///
 /// Return a tuple containing the remaining (unparsed) bytes and the DER Object, or an error.
 ///
+/// `max_depth` is the remaining depth budget, as for
+/// [`parse_ber_recursive`](crate::ber::parse_ber_recursive).
+///
 /// ### Example
 ///
 /// ```
@@ -45,16 +114,23 @@ pub fn parse_der(i: &[u8]) -> DerResult {
 /// assert_eq!(obj.header.tag(), Tag::Integer);
 /// ```
 pub fn parse_der_recursive(i: &[u8], max_depth: usize) -> DerResult {
-    let (i, hdr) = der_read_element_header(i)?;
+    let (data, hdr) = der_read_element_header(i)?;
+    trace::header(0, MAX_RECURSION.saturating_sub(max_depth), &hdr);
     // safety check: length cannot be more than 2^32 bytes
     if let Length::Definite(l) = hdr.length() {
-        custom_check!(i, l > MAX_OBJECT_SIZE, BerError::InvalidLength)?;
+        custom_check!(data, l > MAX_OBJECT_SIZE, BerError::InvalidLength)?;
     }
-    der_read_element_content_recursive(i, hdr, max_depth)
+    let length = hdr.length();
+    let (rem, obj) = der_read_element_content_recursive(data, hdr, max_depth)?;
+    let span = Span::from_input(i, data, rem, length);
+    Ok((rem, obj.with_span(span)))
 }
 
 /// Parse a DER object, expecting a value with specified tag
 ///
+/// The class is not checked. If the tag does not match, an `UnexpectedTag` error is returned,
+/// with the expected and actual tags.
+///
 /// The object is parsed recursively, with a maximum depth of `MAX_RECURSION`.
 ///
 /// ### Example
@@ -68,17 +144,40 @@ pub fn parse_der_recursive(i: &[u8], max_depth: usize) -> DerResult {
 /// assert_eq!(obj.header.tag(), Tag::Integer);
 /// ```
 pub fn parse_der_with_tag<T: Into<Tag>>(i: &[u8], tag: T) -> DerResult {
+    parse_der_with_tag_r(i, tag, MAX_RECURSION)
+}
+
+/// Parse a DER object, expecting a value with specified tag, specifying the maximum recursion
+/// depth
+///
+/// This is the same as [`parse_der_with_tag`], with the remaining depth passed explicitly (see
+/// [`parse_der_recursive`]). Raise a `BerMaxDepth` error if `max_depth` is 0.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::der::{parse_der_with_tag_r, Tag};
+///
+/// // SEQUENCE { INTEGER 1 }
+/// let bytes = &[0x30, 0x03, 0x02, 0x01, 0x01];
+/// assert!(parse_der_with_tag_r(bytes, Tag::Sequence, 2).is_ok());
+/// assert!(parse_der_with_tag_r(bytes, Tag::Sequence, 1).is_err());
+/// ```
+pub fn parse_der_with_tag_r<T: Into<Tag>>(i: &[u8], tag: T, max_depth: usize) -> DerResult {
+    custom_check!(i, max_depth == 0, BerError::BerMaxDepth)?;
     let tag = tag.into();
-    let (i, hdr) = der_read_element_header(i)?;
+    let (data, hdr) = der_read_element_header(i)?;
     hdr.assert_tag(tag)?;
-    let (i, content) = der_read_element_content_as(
-        i,
+    let (rem, content) = der_read_element_content_as(
+        data,
         hdr.tag(),
         hdr.length(),
         hdr.is_constructed(),
-        MAX_RECURSION,
+        max_depth,
     )?;
-    Ok((i, DerObject::from_header_and_content(hdr, content)))
+    let span = Span::from_input(i, data, rem, hdr.length());
+    let obj = DerObject::from_header_and_content(hdr, content).with_span(span);
+    Ok((rem, obj))
 }
 
 /// Read end of content marker
@@ -298,6 +397,12 @@ pub fn parse_der_universalstring(i: &[u8]) -> DerResult {
     parse_der_with_tag(i, Tag::UniversalString)
 }
 
+/// Read an (unrestricted) CHARACTER STRING value
+#[inline]
+pub fn parse_der_characterstring(i: &[u8]) -> DerResult {
+    parse_der_with_tag(i, Tag::CharacterString)
+}
+
 /// Parse an optional tagged object, applying function to get content
 ///
 /// This function returns a `DerObject`, trying to read content as generic DER objects.
@@ -370,7 +475,7 @@ where
 /// integer type.
 #[inline]
 pub fn parse_der_i32(i: &[u8]) -> BerResult<i32> {
-    <i32>::from_der(i)
+    refine_unexpected_tag(i, Tag::Integer, <i32>::from_der(i))
 }
 
 /// Parse DER object and try to decode it as a 64-bits signed integer
@@ -379,7 +484,7 @@ pub fn parse_der_i32(i: &[u8]) -> BerResult<i32> {
 /// integer type.
 #[inline]
 pub fn parse_der_i64(i: &[u8]) -> BerResult<i64> {
-    <i64>::from_der(i)
+    refine_unexpected_tag(i, Tag::Integer, <i64>::from_der(i))
 }
 
 /// Parse DER object and try to decode it as a 32-bits unsigned integer
@@ -387,7 +492,7 @@ pub fn parse_der_i64(i: &[u8]) -> BerResult<i64> {
 /// Return `IntegerTooLarge` if object is an integer, but can not be represented in the target
 /// integer type.
 pub fn parse_der_u32(i: &[u8]) -> BerResult<u32> {
-    <u32>::from_der(i)
+    refine_unexpected_tag(i, Tag::Integer, <u32>::from_der(i))
 }
 
 /// Parse DER object and try to decode it as a 64-bits unsigned integer
@@ -395,7 +500,7 @@ pub fn parse_der_u32(i: &[u8]) -> BerResult<u32> {
 /// Return `IntegerTooLarge` if object is an integer, but can not be represented in the target
 /// integer type.
 pub fn parse_der_u64(i: &[u8]) -> BerResult<u64> {
-    <u64>::from_der(i)
+    refine_unexpected_tag(i, Tag::Integer, <u64>::from_der(i))
 }
 
 /// Parse DER object and get content as slice
@@ -485,10 +590,58 @@ pub fn der_read_element_content_as(
     constructed: bool,
     max_depth: usize,
 ) -> BerResult<DerObjectContent> {
+    let r = der_read_checked_content_as(i, tag, length, constructed, max_depth);
+    trace::result(&r);
+    r
+}
+
+/// Parse `data` as the content of a universal type, selected at runtime by `tag`
+///
+/// This is the DER version of [`ber_read_content`]: DER constraints are verified, and all of
+/// `data` must be used by the content.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::der::{der_read_content, Tag};
+///
+/// let content = der_read_content(Tag::Integer, false, &[0x01, 0x00]).expect("parsing failed");
+/// assert_eq!(content.as_u32(), Ok(256));
+/// // leading zero is not allowed in DER
+/// assert!(der_read_content(Tag::Integer, false, &[0x00, 0x01]).is_err());
+/// ```
+pub fn der_read_content(
+    tag: Tag,
+    constructed: bool,
+    data: &[u8],
+) -> Result<DerObjectContent, BerError> {
+    let length = Length::Definite(data.len());
+    let (rem, content) = der_read_element_content_as(data, tag, length, constructed, MAX_RECURSION)
+        .map_err(|e| match e {
+            // the content is complete, so incomplete data means an invalid length
+            Err::Incomplete(_) => BerError::InvalidLength,
+            Err::Error(BerError::Incomplete(_)) | Err::Failure(BerError::Incomplete(_)) => {
+                BerError::InvalidLength
+            }
+            Err::Error(e) | Err::Failure(e) => e,
+        })?;
+    if !rem.is_empty() {
+        return Err(BerError::InvalidLength);
+    }
+    Ok(content)
+}
+
+fn der_read_checked_content_as<'a>(
+    i: &'a [u8],
+    tag: Tag,
+    length: Length,
+    constructed: bool,
+    max_depth: usize,
+) -> BerResult<'a, DerObjectContent<'a>> {
     // Indefinite lengths are not allowed in DER (X.690 section 10.1)
     let l = length.definite().map_err(BerError::from)?;
     if i.len() < l {
-        return Err(Err::Incomplete(Needed::new(l)));
+        return Err(Err::Incomplete(Needed::new(l - i.len())));
     }
     match tag {
         Tag::Boolean => {
@@ -554,7 +707,22 @@ pub fn der_read_element_content<'a>(i: &'a [u8], hdr: Header<'a>) -> DerResult<'
     der_read_element_content_recursive(i, hdr, MAX_RECURSION)
 }
 
-fn der_read_element_content_recursive<'a>(
+/// Parse DER object content recursively, specifying the maximum recursion depth
+///
+/// `hdr` is the header of the object, already read from the input. Objects which are not of
+/// class `Universal` (or of an unknown type) are returned as `Unknown`, with the raw content.
+///
+/// ### Example
+///
+/// ```
+/// use der_parser::der::{der_read_element_content_recursive, der_read_element_header};
+///
+/// let bytes = &[0x30, 0x03, 0x02, 0x01, 0x01];
+/// let (i, hdr) = der_read_element_header(bytes).expect("could not read header");
+/// let (_, obj) = der_read_element_content_recursive(i, hdr, 2).expect("parsing failed");
+/// assert_eq!(obj[0].as_u32(), Ok(1));
+/// ```
+pub fn der_read_element_content_recursive<'a>(
     i: &'a [u8],
     hdr: Header<'a>,
     max_depth: usize,
@@ -628,5 +796,7 @@ fn der_read_content_bitstring(i: &[u8], len: usize) -> BerResult<DerObjectConten
 /// Read an object header (DER)
 #[inline]
 pub fn der_read_element_header(i: &[u8]) -> BerResult<Header> {
-    Header::from_der(i)
+    let r = refine_length_overflow(i, Header::from_der(i));
+    trace::result(&r);
+    r
 }
@@ -0,0 +1,221 @@
+//! Differential testing against a reference implementation
+//!
+//! This module compares the acceptance of inputs by this crate and by another implementation,
+//! to find inputs where the strictness of the parsers diverge (for ex. an input rejected by the
+//! reference implementation, but accepted by [`parse_der`]).
+//!
+//! The reference implementation is any type implementing [`Reference`]. [`OpensslAsn1Parse`]
+//! runs `openssl asn1parse` as a subprocess, and closures can be used to wrap other crates.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use der_parser::difftest::{compare_corpus, Mode, OpensslAsn1Parse};
+//!
+//! let report = compare_corpus("assets/corpus", &OpensslAsn1Parse::new(), Mode::Ber)
+//!     .expect("could not read corpus");
+//! for d in &report.divergences {
+//!     println!("{}: {}", d.path.display(), d);
+//! }
+//! assert!(report.divergences.is_empty());
+//! ```
+//!
+//! Note that `openssl asn1parse` is a BER parser: it accepts some encodings rejected in DER mode.
+
+use crate::ber::{parse_ber, BerObject};
+use crate::der::parse_der;
+use crate::error::BerResult;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// A reference implementation, telling if an input is accepted
+pub trait Reference {
+    /// Name of the implementation (used in reports)
+    fn name(&self) -> &str;
+
+    /// Test if the reference implementation accepts `data`
+    ///
+    /// An error is returned if the implementation could not be run.
+    fn accepts(&self, data: &[u8]) -> io::Result<bool>;
+}
+
+impl<F> Reference for F
+where
+    F: Fn(&[u8]) -> bool,
+{
+    fn name(&self) -> &str {
+        "closure"
+    }
+
+    fn accepts(&self, data: &[u8]) -> io::Result<bool> {
+        Ok(self(data))
+    }
+}
+
+/// Reference implementation running `openssl asn1parse -inform DER`
+///
+/// The input is accepted if the command succeeds.
+#[derive(Clone, Debug)]
+pub struct OpensslAsn1Parse {
+    program: PathBuf,
+}
+
+impl OpensslAsn1Parse {
+    /// Use the `openssl` program from `PATH`
+    pub fn new() -> Self {
+        Self::with_program("openssl")
+    }
+
+    /// Use the `openssl` program at the given location
+    pub fn with_program<P: Into<PathBuf>>(program: P) -> Self {
+        OpensslAsn1Parse {
+            program: program.into(),
+        }
+    }
+
+    /// Test if the program can be run
+    pub fn is_available(&self) -> bool {
+        Command::new(&self.program)
+            .arg("version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+impl Default for OpensslAsn1Parse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reference for OpensslAsn1Parse {
+    fn name(&self) -> &str {
+        "openssl asn1parse"
+    }
+
+    fn accepts(&self, data: &[u8]) -> io::Result<bool> {
+        let mut child = Command::new(&self.program)
+            .args(["asn1parse", "-inform", "DER"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // the process may exit before reading all the input
+            let _ = stdin.write_all(data);
+        }
+        Ok(child.wait()?.success())
+    }
+}
+
+/// Encoding rules used by this crate when comparing inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Parse inputs using [`parse_ber`]
+    Ber,
+    /// Parse inputs using [`parse_der`]
+    Der,
+}
+
+/// An input accepted by one implementation, and rejected by the other
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The input file
+    pub path: PathBuf,
+    /// The error returned by this crate, or `None` if the input was accepted
+    pub error: Option<String>,
+    /// `true` if the reference implementation accepted the input
+    pub reference: bool,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.error {
+            Some(ref e) => write!(f, "rejected ({}), but accepted by reference", e),
+            None => write!(f, "accepted, but rejected by reference"),
+        }
+    }
+}
+
+/// Result of the comparison of a corpus
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Number of compared inputs
+    pub checked: usize,
+    /// Inputs where the implementations diverge, sorted by path
+    pub divergences: Vec<Divergence>,
+}
+
+/// Test if this crate accepts `data`
+///
+/// The input is accepted if it is a sequence of valid objects, without trailing bytes.
+pub fn accepts(data: &[u8], mode: Mode) -> Result<(), String> {
+    let parse: fn(&[u8]) -> BerResult<'_, BerObject<'_>> = match mode {
+        Mode::Ber => parse_ber,
+        Mode::Der => parse_der,
+    };
+    let mut i = data;
+    if i.is_empty() {
+        return Err("empty input".to_string());
+    }
+    while !i.is_empty() {
+        let (rem, _) = parse(i).map_err(|e| e.to_string())?;
+        i = rem;
+    }
+    Ok(())
+}
+
+/// Compare the acceptance of `data` by this crate and by a reference implementation
+///
+/// Returns `None` if both implementations agree. The `path` of the divergence is empty.
+pub fn compare<R>(data: &[u8], reference: &R, mode: Mode) -> io::Result<Option<Divergence>>
+where
+    R: Reference + ?Sized,
+{
+    let ours = accepts(data, mode);
+    let theirs = reference.accepts(data)?;
+    if ours.is_ok() == theirs {
+        return Ok(None);
+    }
+    Ok(Some(Divergence {
+        path: PathBuf::new(),
+        error: ours.err(),
+        reference: theirs,
+    }))
+}
+
+/// Compare the acceptance of all files in directory `dir` (not recursively)
+///
+/// An error is returned if the directory cannot be read, or if the reference implementation
+/// could not be run.
+pub fn compare_corpus<P, R>(dir: P, reference: &R, mode: Mode) -> io::Result<Report>
+where
+    P: AsRef<Path>,
+    R: Reference + ?Sized,
+{
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    let mut report = Report::default();
+    for path in paths {
+        let data = fs::read(&path)?;
+        report.checked += 1;
+        if let Some(divergence) = compare(&data, reference, mode)? {
+            report.divergences.push(Divergence { path, ..divergence });
+        }
+    }
+    Ok(report)
+}
@@ -1,13 +1,163 @@
 //! Error type for BER/DER parsers
 
-use crate::ber::BerObject;
+use crate::ber::{universal_tag_name, BerObject, Class, Header, Length, Tag, MAX_RECURSION};
 use crate::der::DerObject;
-use nom::IResult;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use asn1_rs::{Any, FromBer};
+use core::fmt;
+use core::fmt::Write;
+use nom::error::{ContextError, ErrorKind, ParseError};
+use nom::{Err, IResult};
 
 pub use asn1_rs::{DerConstraint, Error};
 
 pub type BerError = Error;
 
+const LIMIT_EXCEEDED_MSG: &str = "limit of the parsing options exceeded";
+
+/// Error returned when a limit of the parsing options is exceeded
+///
+/// This is an `InvalidValue` error with tag 0 and a fixed message, which can be compared to the
+/// result of this function, or identified by its code (21, see [`ErrorCode`]).
+///
+/// See [`ParseOptions::max_nodes`](crate::ber::ParseOptions::max_nodes) and
+/// [`ParseOptions::max_alloc`](crate::ber::ParseOptions::max_alloc).
+pub fn limit_exceeded() -> BerError {
+    BerError::invalid_value(Tag(0), String::from(LIMIT_EXCEEDED_MSG))
+}
+
+const LENGTH_OVERFLOW_MSG: &str = "declared length does not fit in a usize";
+
+/// Error returned when the declared length of an object does not fit in a `usize`
+///
+/// This happens for lengths of more than 4 GiB on 32-bit platforms. The objects can still be
+/// skipped using [`ber_skip_object_from_reader`](crate::ber::ber_skip_object_from_reader), which
+/// uses 64-bit lengths.
+///
+/// This is an `InvalidValue` error with tag 0 and a fixed message (code 22, see [`ErrorCode`]).
+pub fn length_overflow() -> BerError {
+    BerError::invalid_value(Tag(0), String::from(LENGTH_OVERFLOW_MSG))
+}
+
+const OBJECT_TOO_LARGE_MSG: &str = "declared length exceeds the limit of the parsing options";
+
+/// Error returned when the declared length of an object exceeds the limit of the parsing options
+///
+/// This is an `InvalidValue` error with tag 0 and a fixed message (code 23, see [`ErrorCode`]).
+///
+/// See [`ParseOptions::max_object_len`](crate::ber::ParseOptions::max_object_len).
+pub fn object_too_large() -> BerError {
+    BerError::invalid_value(Tag(0), String::from(OBJECT_TOO_LARGE_MSG))
+}
+
+/// Stable numeric codes for parsing errors
+///
+/// Codes do not change across versions of the crate, so they can be used when the error must
+/// cross a language boundary (for ex. FFI), or be stored:
+///
+/// | Code | Error |
+/// |------|-------|
+/// | 1 | `BerTypeError` |
+/// | 2 | `BerValueError` |
+/// | 3 | `InvalidLength` |
+/// | 4 | `InvalidValue` |
+/// | 5 | `InvalidTag` |
+/// | 6 | `UnknownTag` |
+/// | 7 | `UnexpectedTag` |
+/// | 8 | `UnexpectedClass` |
+/// | 9 | `IndefiniteLengthUnexpected` |
+/// | 10 | `ConstructExpected` |
+/// | 11 | `ConstructUnexpected` |
+/// | 12 | `IntegerTooLarge` |
+/// | 13 | `IntegerNegative` |
+/// | 14 | `BerMaxDepth` |
+/// | 15 | `StringInvalidCharset` |
+/// | 16 | `InvalidDateTime` |
+/// | 17 | `LifetimeError` |
+/// | 18 | `Unsupported` |
+/// | 19 | `Incomplete` |
+/// | 20 | `NomError` |
+/// | 21 | [`limit_exceeded`] |
+/// | 22 | [`length_overflow`] |
+/// | 23 | [`object_too_large`] |
+/// | 100 + n | `DerConstraintFailed`, see below |
+///
+/// For `DerConstraintFailed`, `n` identifies the constraint: `IndefiniteLength` (0),
+/// `Constructed` (1), `NotConstructed` (2), `MissingTimeZone` (3), `MissingSeconds` (4),
+/// `UnusedBitsNotZero` (5), `InvalidBoolean` (6), `IntegerEmpty` (7), `IntegerLeadingZeroes` (8),
+/// `IntegerLeadingFF` (9).
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::der::parse_der;
+/// use der_parser::error::ErrorCode;
+///
+/// // BOOLEAN 0x01
+/// let err = parse_der(&[0x01, 0x01, 0x01]).expect_err("not DER");
+/// assert_eq!(err.code(), 106);
+/// ```
+pub trait ErrorCode {
+    /// Return the numeric code of the error
+    fn code(&self) -> u32;
+}
+
+impl ErrorCode for BerError {
+    fn code(&self) -> u32 {
+        match self {
+            BerError::BerTypeError => 1,
+            BerError::BerValueError => 2,
+            BerError::InvalidLength => 3,
+            BerError::InvalidValue { msg, .. } if msg == LIMIT_EXCEEDED_MSG => 21,
+            BerError::InvalidValue { msg, .. } if msg == LENGTH_OVERFLOW_MSG => 22,
+            BerError::InvalidValue { msg, .. } if msg == OBJECT_TOO_LARGE_MSG => 23,
+            BerError::InvalidValue { .. } => 4,
+            BerError::InvalidTag => 5,
+            BerError::UnknownTag(_) => 6,
+            BerError::UnexpectedTag { .. } => 7,
+            BerError::UnexpectedClass { .. } => 8,
+            BerError::IndefiniteLengthUnexpected => 9,
+            BerError::ConstructExpected => 10,
+            BerError::ConstructUnexpected => 11,
+            BerError::IntegerTooLarge => 12,
+            BerError::IntegerNegative => 13,
+            BerError::BerMaxDepth => 14,
+            BerError::StringInvalidCharset => 15,
+            BerError::InvalidDateTime => 16,
+            BerError::LifetimeError => 17,
+            BerError::Unsupported => 18,
+            BerError::Incomplete(_) => 19,
+            BerError::NomError(_) => 20,
+            BerError::DerConstraintFailed(c) => {
+                100 + match c {
+                    DerConstraint::IndefiniteLength => 0,
+                    DerConstraint::Constructed => 1,
+                    DerConstraint::NotConstructed => 2,
+                    DerConstraint::MissingTimeZone => 3,
+                    DerConstraint::MissingSeconds => 4,
+                    DerConstraint::UnusedBitsNotZero => 5,
+                    DerConstraint::InvalidBoolean => 6,
+                    DerConstraint::IntegerEmpty => 7,
+                    DerConstraint::IntegerLeadingZeroes => 8,
+                    DerConstraint::IntegerLeadingFF => 9,
+                }
+            }
+        }
+    }
+}
+
+/// `Incomplete` has code 19, and other errors use the code of the inner error
+impl ErrorCode for Err<BerError> {
+    fn code(&self) -> u32 {
+        match self {
+            Err::Incomplete(_) => 19,
+            Err::Error(e) | Err::Failure(e) => e.code(),
+        }
+    }
+}
+
 // pub use asn1_rs::Result;
 
 /// Holds the result of parsing functions
@@ -24,6 +174,350 @@ pub type BerResult<'a, O = BerObject<'a>> = IResult<&'a [u8], O, BerError>;
 /// Note that this type is also a `Result`, so usual functions (`map`, `unwrap` etc.) are available.
 pub type DerResult<'a> = BerResult<'a, DerObject<'a>>;
 
+/// An error, with the location in the input of the object that could not be parsed
+///
+/// Parsing errors (`BerError`) do not contain their position in the input. A `LocatedError` is
+/// built after the failure, by reading the input again to find the innermost object that
+/// cannot be parsed.
+///
+/// See [`parse_ber_located`](crate::ber::parse_ber_located) and
+/// [`parse_der_located`](crate::der::parse_der_located).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocatedError {
+    /// The parsing error
+    pub error: BerError,
+    /// Offset of the object in the input
+    pub offset: usize,
+    /// Objects containing the error, from the outermost to the innermost one
+    pub path: Vec<ErrorFrame>,
+}
+
+/// An object containing an error (see [`LocatedError::path`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorFrame {
+    /// Class of the object
+    pub class: Class,
+    /// Tag of the object
+    pub tag: Tag,
+    /// Index of the child containing the error, or `None` if the error is in this object
+    pub index: Option<usize>,
+}
+
+impl LocatedError {
+    /// Build a located error from the result of parsing `input`
+    ///
+    /// `parser` is used to test the children of constructed objects: the innermost object that
+    /// cannot be parsed is considered as the location of the error. Usually, this is a generic
+    /// parser like [`parse_ber`](crate::ber::parse_ber) or [`parse_der`](crate::der::parse_der).
+    pub fn locate<'a, F, O>(input: &'a [u8], error: Err<BerError>, mut parser: F) -> Self
+    where
+        F: FnMut(&'a [u8]) -> BerResult<'a, O>,
+    {
+        let error = match error {
+            Err::Error(e) | Err::Failure(e) => e,
+            Err::Incomplete(n) => BerError::Incomplete(n),
+        };
+        let mut path = Vec::new();
+        let offset = locate_error(input, &mut parser, &mut path);
+        LocatedError {
+            error,
+            offset,
+            path,
+        }
+    }
+}
+
+/// Return the offset of the innermost object of `input` that cannot be parsed by `parser`, and
+/// add the objects containing it to `path`
+fn locate_error<'a, F, O>(input: &'a [u8], parser: &mut F, path: &mut Vec<ErrorFrame>) -> usize
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a, O>,
+{
+    let (data, hdr) = match Header::from_ber(input) {
+        Ok(res) => res,
+        Err(_) => return 0,
+    };
+    path.push(ErrorFrame {
+        class: hdr.class(),
+        tag: hdr.tag(),
+        index: None,
+    });
+    if !hdr.is_constructed() || path.len() > MAX_RECURSION {
+        return 0;
+    }
+    let content = match hdr.length() {
+        Length::Definite(l) if l <= data.len() => &data[..l],
+        Length::Definite(_) => return 0,
+        Length::Indefinite => data,
+    };
+    let header_len = input.len() - data.len();
+    let frame = path.len() - 1;
+    let mut i = content;
+    let mut index = 0;
+    while !i.is_empty() {
+        if hdr.length() == Length::Indefinite && i.starts_with(&[0, 0]) {
+            break;
+        }
+        let child_offset = header_len + content.len() - i.len();
+        let (child, valid) = match Any::from_ber(i) {
+            Ok((rem, _)) => (&i[..i.len() - rem.len()], true),
+            Err(_) => (i, false),
+        };
+        if !valid || parser(child).is_err() {
+            path[frame].index = Some(index);
+            return child_offset + locate_error(child, parser, path);
+        }
+        i = &i[child.len()..];
+        index += 1;
+    }
+    0
+}
+
+impl LocatedError {
+    /// Get a report of the error, showing the bytes of the object in `input`
+    ///
+    /// `input` must be the buffer used to build the error. The report contains the error message,
+    /// then an hexadecimal dump of the input around the error, with the bytes of the object
+    /// underlined.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use der_parser::ber::parse_ber_located;
+    ///
+    /// let bytes = &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x02, 0x01];
+    /// let err = parse_ber_located(bytes).expect_err("parsing should fail");
+    /// println!("{}", err.report(bytes));
+    /// // error at offset 0x5 in SEQUENCE[1] → INTEGER: incomplete data, missing: Size(1)
+    /// // 00000000  30 06 02 01 01 02 02 01
+    /// //                          ^^ ^^ ^^
+    /// ```
+    pub fn report<'a>(&'a self, input: &'a [u8]) -> ErrorReport<'a> {
+        ErrorReport { error: self, input }
+    }
+}
+
+/// A report of a [`LocatedError`], including an hexadecimal dump of the input
+///
+/// This object is created by [`LocatedError::report`], and is used with its `Display`
+/// implementation.
+#[derive(Debug)]
+pub struct ErrorReport<'a> {
+    error: &'a LocatedError,
+    input: &'a [u8],
+}
+
+impl<'a> ErrorReport<'a> {
+    /// Number of bytes in each line of the dump
+    const WIDTH: usize = 16;
+    /// Maximum number of lines containing the object
+    const MAX_LINES: usize = 4;
+}
+
+impl<'a> fmt::Display for ErrorReport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const WIDTH: usize = ErrorReport::WIDTH;
+        writeln!(f, "{}", self.error)?;
+        let start = self.error.offset.min(self.input.len());
+        // underline the object, or at least one byte (possibly after the end of the input)
+        let len = match Any::from_ber(&self.input[start..]) {
+            Ok((rem, _)) => self.input.len() - start - rem.len(),
+            Err(_) => self.input.len() - start,
+        };
+        let end = start + len.max(1);
+        let first_line = (start / WIDTH).saturating_sub(1);
+        let last_line = ((end - 1) / WIDTH).min(start / WIDTH + ErrorReport::MAX_LINES - 1);
+        for line in first_line..=last_line {
+            let bytes = self.input.iter().skip(line * WIDTH).take(WIDTH);
+            write!(f, "{:08x} ", line * WIDTH)?;
+            for b in bytes {
+                write!(f, " {:02x}", b)?;
+            }
+            writeln!(f)?;
+            let marked = (0..WIDTH)
+                .map(|col| line * WIDTH + col)
+                .take_while(|pos| *pos < end)
+                .collect::<Vec<_>>();
+            if marked.last().map_or(false, |pos| *pos >= start) {
+                write!(f, "{:9}", "")?;
+                for pos in marked {
+                    let mark = if pos >= start { "^^" } else { "  " };
+                    write!(f, " {}", mark)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ErrorFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.class {
+            Class::Universal => match universal_tag_name(self.tag) {
+                Some(name) => f.write_str(name)?,
+                None => write!(f, "Universal({})", self.tag.0)?,
+            },
+            class => write!(f, "{:?}({})", class, self.tag.0)?,
+        }
+        match self.index {
+            Some(index) => write!(f, "[{}]", index),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for LocatedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error at offset {:#x}", self.offset)?;
+        for (idx, frame) in self.path.iter().enumerate() {
+            let sep = if idx == 0 { " in " } else { " → " };
+            write!(f, "{}{}", sep, frame)?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LocatedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The kind of a frame of a [`VerboseBerError`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerboseBerErrorKind {
+    /// A BER/DER parsing error
+    Ber(BerError),
+    /// An error raised by a nom combinator
+    Nom(ErrorKind),
+    /// A context, added by [`nom::error::context`]
+    Context(&'static str),
+}
+
+impl fmt::Display for VerboseBerErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerboseBerErrorKind::Ber(e) => write!(f, "{}", e),
+            VerboseBerErrorKind::Nom(kind) => write!(f, "error in {}", kind.description()),
+            VerboseBerErrorKind::Context(ctx) => write!(f, "in {}", ctx),
+        }
+    }
+}
+
+/// An error accumulating all the parser frames that failed, similar to nom's `VerboseError`
+///
+/// This error type is intended for debugging: it is larger and slower than `BerError`, but it
+/// records the position and kind of every failure, and all the branches tried by `alt` (for ex.
+/// when parsing a CHOICE). Frames are stored from the innermost to the outermost.
+///
+/// Parsers returning a `BerError` can be used with [`verbose`], which records their errors with
+/// the position in the input.
+///
+/// ### Example
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber_integer, parse_ber_octetstring, parse_ber_tagged_explicit};
+/// use der_parser::error::{verbose, VerboseBerError};
+/// use nom::branch::alt;
+/// use nom::error::context;
+///
+/// // CHOICE { a [0] INTEGER, b [1] OCTET STRING }
+/// let mut parse_choice = context(
+///     "Choice",
+///     alt((
+///         context("a", parse_ber_tagged_explicit(0, verbose(parse_ber_integer))),
+///         context("b", parse_ber_tagged_explicit(1, verbose(parse_ber_octetstring))),
+///     )),
+/// );
+///
+/// let bytes = &[0xa1, 0x03, 0x02, 0x01, 0x01];
+/// let err: nom::Err<VerboseBerError> = parse_choice(bytes).expect_err("parsing should fail");
+/// if let nom::Err::Error(e) = err {
+///     // prints the errors of both branches
+///     println!("{}", e.describe(bytes));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerboseBerError<'a> {
+    /// The frames of the error, with the input at the position of the failure
+    pub errors: Vec<(&'a [u8], VerboseBerErrorKind)>,
+}
+
+impl<'a> VerboseBerError<'a> {
+    /// Describe all the frames of the error, one per line, with their offset in `input`
+    ///
+    /// The offset is not displayed if the position of the frame is not known (for ex. if the
+    /// error was converted from a `BerError`).
+    pub fn describe(&self, input: &[u8]) -> String {
+        let mut s = String::new();
+        for (i, kind) in &self.errors {
+            if !s.is_empty() {
+                s.push('\n');
+            }
+            let offset = i.as_ptr() as usize;
+            let start = input.as_ptr() as usize;
+            if !i.is_empty() && offset >= start && offset <= start + input.len() {
+                let _ = write!(s, "at offset {:#x}: {}", offset - start, kind);
+            } else {
+                let _ = write!(s, "{}", kind);
+            }
+        }
+        s
+    }
+}
+
+impl<'a> ParseError<&'a [u8]> for VerboseBerError<'a> {
+    fn from_error_kind(input: &'a [u8], kind: ErrorKind) -> Self {
+        let errors = vec![(input, VerboseBerErrorKind::Nom(kind))];
+        VerboseBerError { errors }
+    }
+
+    fn append(input: &'a [u8], kind: ErrorKind, mut other: Self) -> Self {
+        other.errors.push((input, VerboseBerErrorKind::Nom(kind)));
+        other
+    }
+
+    /// Keep the frames of both branches
+    fn or(mut self, other: Self) -> Self {
+        self.errors.extend(other.errors);
+        self
+    }
+}
+
+impl<'a> ContextError<&'a [u8]> for VerboseBerError<'a> {
+    fn add_context(input: &'a [u8], ctx: &'static str, mut other: Self) -> Self {
+        other
+            .errors
+            .push((input, VerboseBerErrorKind::Context(ctx)));
+        other
+    }
+}
+
+/// Note that the position of the error is lost: use [`verbose`] to wrap parsers instead.
+impl<'a> From<BerError> for VerboseBerError<'a> {
+    fn from(e: BerError) -> Self {
+        let errors = vec![(&[][..], VerboseBerErrorKind::Ber(e))];
+        VerboseBerError { errors }
+    }
+}
+
+/// Wrap a parser returning a `BerError`, so errors are converted to [`VerboseBerError`] with
+/// their position in the input
+pub fn verbose<'a, O, F>(f: F) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], O, VerboseBerError<'a>>
+where
+    F: Fn(&'a [u8]) -> BerResult<'a, O>,
+{
+    move |i: &'a [u8]| {
+        f(i).map_err(|e| {
+            e.map(|e| VerboseBerError {
+                errors: vec![(i, VerboseBerErrorKind::Ber(e))],
+            })
+        })
+    }
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
@@ -36,4 +530,148 @@ mod tests {
         // println!("{}", e);
         let _: Result<(), Box<dyn Error>> = Err(Box::new(e));
     }
+
+    #[test]
+    fn test_located_error() {
+        use crate::ber::{parse_ber, parse_ber_located, parse_ber_tagged_explicit};
+        use crate::der::parse_der_located;
+        use hex_literal::hex;
+
+        // SEQUENCE { INTEGER 1, SET { NULL, BOOLEAN (invalid length) } }
+        let bytes = hex!("30 0b 02 01 01 31 06 05 00 01 02 ff ff");
+        let err = parse_ber_located(&bytes).expect_err("parsing should fail");
+        assert_eq!(err.offset, 9);
+        assert_eq!(err.error, BerError::InvalidLength);
+        let path = err.path.iter().map(|f| f.index).collect::<Vec<_>>();
+        assert_eq!(path, [Some(1), Some(1), None]);
+        assert_eq!(err.path[2].tag, Tag::Boolean);
+        assert_eq!(
+            std::format!("{}", err),
+            std::format!(
+                "error at offset 0x9 in SEQUENCE[1] → SET[1] → BOOLEAN: {}",
+                BerError::InvalidLength
+            )
+        );
+
+        // BER boolean is accepted, but not in DER
+        let bytes = hex!("01 01 01");
+        assert!(parse_ber_located(&bytes).is_ok());
+        let err = parse_der_located(&bytes).expect_err("parsing should fail");
+        assert_eq!(err.offset, 0);
+        assert!(matches!(err.error, BerError::DerConstraintFailed(_)));
+
+        // truncated input
+        let err =
+            parse_ber_located(&hex!("30 06 02 01 01 30 03")).expect_err("parsing should fail");
+        assert_eq!(err.offset, 0);
+        let err =
+            parse_ber_located(&hex!("30 80 02 01 01 30 03 02")).expect_err("parsing should fail");
+        assert_eq!(err.offset, 5);
+        assert_eq!(err.path.len(), 2);
+
+        // [0] { [APPLICATION 1] (truncated) }, with a specific parser
+        let bytes = hex!("a0 04 61 05 02 01");
+        let err = parse_ber_tagged_explicit(0, parse_ber)(&bytes).expect_err("parsing should fail");
+        let err = LocatedError::locate(&bytes, err, parse_ber);
+        assert_eq!(err.offset, 2);
+        let path = err
+            .path
+            .iter()
+            .map(|f| std::format!("{}", f))
+            .collect::<Vec<_>>();
+        assert_eq!(path, ["ContextSpecific(0)[0]", "Application(1)"]);
+    }
+
+    #[test]
+    fn test_located_error_report() {
+        use crate::ber::parse_ber_located;
+        use hex_literal::hex;
+
+        // SEQUENCE { OCTET STRING, INTEGER, OCTET STRING (invalid length) }
+        let bytes =
+            hex!("30 18 04 0f 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 02 02 01 02 04 05 01");
+        let err = parse_ber_located(&bytes).expect_err("parsing should fail");
+        assert_eq!(err.offset, 0x17);
+        let report = std::format!("{}", err.report(&bytes));
+        let expected = [
+            std::format!("{}", err),
+            "00000000  30 18 04 0f 00 01 02 03 04 05 06 07 08 09 0a 0b".into(),
+            "00000010  0c 0d 0e 02 02 01 02 04 05 01".into(),
+            "                               ^^ ^^ ^^".into(),
+        ];
+        assert_eq!(report.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        use alloc::string::{String, ToString};
+        let errors = [
+            BerError::InvalidLength,
+            BerError::invalid_value(Tag::Integer, String::new()),
+            BerError::NomError(ErrorKind::Tag),
+            limit_exceeded(),
+            length_overflow(),
+            object_too_large(),
+            BerError::DerConstraintFailed(DerConstraint::IndefiniteLength),
+            BerError::DerConstraintFailed(DerConstraint::IntegerLeadingFF),
+        ];
+        let codes = errors.iter().map(ErrorCode::code).collect::<Vec<_>>();
+        assert_eq!(codes, [3, 4, 20, 21, 22, 23, 100, 109]);
+        // nom errors are not confused with limits
+        assert_eq!(BerError::NomError(ErrorKind::TooLarge).code(), 20);
+        assert_eq!(BerError::NomError(ErrorKind::LengthValue).code(), 20);
+        assert_eq!(BerError::NomError(ErrorKind::LengthValueFn).code(), 20);
+        assert_eq!(
+            limit_exceeded().to_string(),
+            "Invalid Value when parsing object with tag Tag(0) limit of the parsing options exceeded"
+        );
+        assert_eq!(Err::<BerError>::Incomplete(nom::Needed::Unknown).code(), 19);
+        assert_eq!(Err::Failure(BerError::BerMaxDepth).code(), 14);
+    }
+
+    #[test]
+    fn test_verbose_error() {
+        use crate::ber::{parse_ber_integer, parse_ber_null, parse_ber_tagged_explicit};
+        use hex_literal::hex;
+        use nom::branch::alt;
+        use nom::error::context;
+
+        let bytes = hex!("a1 03 02 01 01");
+        let mut parser = context(
+            "Choice",
+            alt((
+                context(
+                    "a",
+                    parse_ber_tagged_explicit(0, verbose(parse_ber_integer)),
+                ),
+                context("b", parse_ber_tagged_explicit(1, verbose(parse_ber_null))),
+            )),
+        );
+        let err = match parser(&bytes) {
+            Err(Err::Error(e)) => e,
+            _ => panic!("parsing should fail"),
+        };
+        let kinds = err
+            .errors
+            .iter()
+            .map(|(_, k)| k.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                VerboseBerErrorKind::Ber(BerError::unexpected_tag(Some(Tag(0)), Tag(1))),
+                VerboseBerErrorKind::Context("a"),
+                VerboseBerErrorKind::Ber(BerError::unexpected_tag(Some(Tag::Null), Tag::Integer)),
+                VerboseBerErrorKind::Context("b"),
+                VerboseBerErrorKind::Nom(ErrorKind::Alt),
+                VerboseBerErrorKind::Context("Choice"),
+            ]
+        );
+        let description = err.describe(&bytes);
+        assert!(description
+            .lines()
+            .nth(2)
+            .unwrap()
+            .starts_with("at offset 0x2: "));
+    }
 }
@@ -3,7 +3,7 @@
 //! [![docs.rs](https://docs.rs/der-parser/badge.svg)](https://docs.rs/der-parser)
 //! [![crates.io](https://img.shields.io/crates/v/der-parser.svg)](https://crates.io/crates/der-parser)
 //! [![Download numbers](https://img.shields.io/crates/d/der-parser.svg)](https://crates.io/crates/der-parser)
-//! [![dependency status](https://deps.rs/crate/der-parser/9.0.0/status.svg)](https://deps.rs/crate/der-parser/9.0.0)
+//! [![dependency status](https://deps.rs/crate/der-parser/10.0.0/status.svg)](https://deps.rs/crate/der-parser/10.0.0)
 //! [![Github CI](https://github.com/rusticata/der-parser/actions/workflows/rust.yml/badge.svg)](https://github.com/rusticata/der-parser/actions/workflows/rust.yml)
 //! [![Minimum rustc version](https://img.shields.io/badge/rustc-1.63.0+-lightgray.svg)](#rust-version-requirements)
 //!
@@ -140,6 +140,10 @@
 //! All functions return a [`BerResult`](error/type.BerResult.html) object: the parsed
 //! [`BerObject`](ber/struct.BerObject.html), an `Incomplete` value, or an error.
 //!
+//! When the input is truncated, the `Incomplete` value contains the number of missing bytes,
+//! computed from the declared lengths. If an object has an indefinite length, this is only a lower
+//! bound (at least the end-of-content octets are missing).
+//!
 //! Note that this type is also a `Result`, so usual functions (`map`, `unwrap` etc.) are available.
 //!
 //! # Notes
@@ -187,16 +191,42 @@
 //! - macros: these are generally previous (historic) versions of parsers, kept for compatibility.
 //!   They can sometime reduce the amount of code to write, but are hard to debug.
 //!   Parsers should be preferred when possible.
+//! - [`der_sequence!`] and [`ber_sequence!`] define a struct and its parser from a declaration close to
+//!   the ASN.1 definition of a SEQUENCE, with named fields, tags, `OPTIONAL` and `DEFAULT` values.
 //!
 //! ## Misc Notes
 //!
 //! - The DER constraints are verified if using `parse_der`.
 //! - `BerObject` and `DerObject` are the same objects (type alias). The only difference is the
 //!   verification of constraints *during parsing*.
+//! - The crate is `no_std` when the `std` feature is disabled, but always requires `alloc`: headers
+//!   (from `asn1-rs`) and trees of objects are allocated. Use the [`ber::scan`] module to read
+//!   objects without allocating.
+//! - The [`schema`] module validates parsed objects against a description of their ASN.1 types
+//!   (SEQUENCE fields, OPTIONAL, CHOICE, tags), and reports errors with the path of each field.
+//! - The [`constraint`] module checks subtype constraints (SIZE, value ranges, permitted alphabets)
+//!   on parsed objects, in parsers (using the `constrained` combinator) or in a `Schema`.
+//! - With the `heapless` feature, `parse_ber_sequence_of_bounded` (and similar functions) collect
+//!   the elements of a SEQUENCE OF or SET OF in a `heapless::Vec`, with a fixed capacity.
+//! - With the `zeroize` feature, owned objects (`BerObjectOwned`) implement `Zeroize`, so values
+//!   like private keys can be erased from memory. Use `zeroize::Zeroizing<BerObjectOwned>` to erase
+//!   them when dropped.
+//! - With the `digest` feature, `BerObject::hash_into` feeds the original encoding of a parsed object
+//!   into a hasher (for ex. to verify a signature), without copying it.
+//! - With the `time` feature, `BerTime::time_duration_since` returns the (signed) duration between
+//!   two parsed times as a `time::Duration`.
+//! - With the `allocator-api2` feature, `BerArenaIn` stores the nodes of parsed trees using a custom
+//!   allocator (for ex. a per-request bump allocator).
+//! - A C API (`der_parse` and accessors, with a C header) is provided by the `der-parser-capi`
+//!   crate, in the `capi` directory of the repository.
+//! - JavaScript bindings (parsing to JSON or to a text dump), built with `wasm-bindgen`, are provided
+//!   by the `der-parser-wasm` crate, in the `wasm` directory.
+//! - The `der-parser-codegen` crate (in the `codegen` directory) generates Rust types, parsers and
+//!   encoders from ASN.1 modules, for use in build scripts.
 //!
 //! ## Rust version requirements
 //!
-//! The 9.0 series of `der-parser` requires **Rustc version 1.63 or greater**, based on `asn1-rs`
+//! The 10.0 series of `der-parser` requires **Rustc version 1.63 or greater**, based on `asn1-rs`
 //! and `nom` 7 dependencies.
 //!
 //! # Serialization
@@ -207,6 +237,22 @@
 //! See the `ber_encode_*` functions in the [`ber`](ber/index.html) module, and
 //! [`BerObject::to_vec`](ber/struct.BerObject.html#method.to_vec)
 //!
+//! With the `serde` feature, objects implement `serde::Serialize`, so a parsed tree can be dumped
+//! using any serde data format (for ex. JSON).
+//!
+//! # Instrumentation
+//!
+//! The `trace` feature emits [`tracing`](https://docs.rs/tracing) events when parsing: one event
+//! (level `TRACE`) for each header, with its offset, depth, tag and length, and one event (level
+//! `DEBUG`) for each DER constraint violation.
+//!
+//! The `defmt` feature implements `defmt::Format` for `BerObject` (in a compact form, without the
+//! children) and, using the `ber::Defmt` wrapper, for headers, tags and errors, so parse results
+//! can be logged on embedded targets.
+//!
+//! The `rayon` feature adds `parse_der_all_parallel`, to parse concatenated objects (for ex.
+//! certificate corpora) using multiple threads.
+//!
 //! # References
 //!
 //! - [[X.680]] Abstract Syntax Notation One (ASN.1): Specification of basic notation.
@@ -245,22 +291,44 @@ extern crate std;
 
 extern crate alloc;
 
+#[macro_use]
+mod macros;
+
 #[allow(clippy::module_inception)]
 pub mod ber;
+pub mod constraint;
+pub mod corpus;
 pub mod der;
+#[cfg(feature = "difftest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "difftest")))]
+pub mod difftest;
 pub mod error;
 pub mod oid;
+pub mod schema;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_der;
+mod trace;
 
 // compatibility: re-export at crate root
 pub use ber::parse_ber;
 pub use der::parse_der;
 
+#[doc(hidden)]
+pub use macros::__optional_field;
+
 pub use asn1_rs;
 pub use nom;
 #[cfg(feature = "bigint")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bigint")))]
 pub use num_bigint;
 pub use rusticata_macros;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use serde;
+#[cfg(feature = "uuid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+pub use uuid;
 
 // re-exports nom macros, so this crate's macros can be used without importing nom
 pub use nom::IResult;
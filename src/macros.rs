@@ -0,0 +1,288 @@
+//! Declarative macros to define SEQUENCE types, and to match headers
+
+use crate::error::BerResult;
+use nom::combinator::opt;
+
+/// Parse an OPTIONAL field (used by the sequence macros)
+///
+/// The field is absent if the parser fails, or at the end of the SEQUENCE.
+#[doc(hidden)]
+pub fn __optional_field<'a, O, F>(f: F) -> impl FnMut(&'a [u8]) -> BerResult<'a, Option<O>>
+where
+    F: FnMut(&'a [u8]) -> BerResult<'a, O>,
+{
+    let mut parser = opt(f);
+    move |i| {
+        if i.is_empty() {
+            Ok((i, None))
+        } else {
+            parser(i)
+        }
+    }
+}
+
+/// Define a struct for a DER SEQUENCE, and its parsing function
+///
+/// Each field is declared using a syntax close to the ASN.1 definition:
+///
+/// ```text
+/// name [CLASS tag] MODE OPTIONAL|DEFAULT(value): Type = parser,
+/// ```
+///
+/// where the tag, the tagging mode (`EXPLICIT` or `IMPLICIT`), and `OPTIONAL` or `DEFAULT(value)`
+/// are optional. The class of the tag is `APPLICATION`, `PRIVATE`, or context-specific if
+/// absent.
+///
+/// - untagged and `EXPLICIT` fields are parsed by `parser` (a function returning a
+///   `BerResult<Type>`, like `parse_der_u32` or `parse_der_integer`)
+/// - `IMPLICIT` fields are parsed by a content parser, receiving the content, the header and the
+///   maximum recursion depth (like `parse_der_content2(Tag::Integer)`)
+/// - `OPTIONAL` fields have type `Option<Type>`, and are `None` if the field is absent or cannot be
+///   parsed
+/// - `DEFAULT(value)` fields are set to `value` if absent. The value is not checked: in DER, a
+///   field equal to its default value should be absent.
+///
+/// The macro defines the struct, and a function `from_der` parsing a SEQUENCE and its fields, in
+/// order. As for [`parse_der_sequence_defined_g`](der/fn.parse_der_sequence_defined_g.html),
+/// trailing bytes in the SEQUENCE are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::der::*;
+/// use der_parser::der_sequence;
+///
+/// // Example ::= SEQUENCE {
+/// //     version  [0] EXPLICIT INTEGER DEFAULT 0,
+/// //     serial   INTEGER,
+/// //     uid      [1] IMPLICIT BIT STRING OPTIONAL,
+/// //     name     UTF8String OPTIONAL }
+/// der_sequence! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Example<'a> {
+///         pub version [0] EXPLICIT DEFAULT(0): u32 = parse_der_u32,
+///         pub serial: DerObject<'a> = parse_der_integer,
+///         pub uid [1] IMPLICIT OPTIONAL: DerObjectContent<'a> = parse_der_content2(Tag::BitString),
+///         pub name OPTIONAL: DerObject<'a> = parse_der_utf8string,
+///     }
+/// }
+///
+/// let bytes = &[0x30, 0x07, 0x02, 0x01, 0x05, 0x81, 0x02, 0x00, 0xff];
+/// let (_, example) = Example::from_der(bytes).expect("parsing failed");
+/// assert_eq!(example.version, 0);
+/// assert_eq!(example.serial.as_u32(), Ok(5));
+/// assert!(example.uid.is_some());
+/// assert!(example.name.is_none());
+/// ```
+#[macro_export]
+macro_rules! der_sequence {
+    ($($body:tt)*) => {
+        $crate::__sequence_def! {
+            [from_der, der, parse_der_sequence_defined_g, parse_der_tagged_explicit_class_g, parse_der_tagged_implicit_class_g]
+            $($body)*
+        }
+    };
+}
+
+/// Define a struct for a BER SEQUENCE, and its parsing function
+///
+/// See [`der_sequence!`] for the syntax. The parsing function is named `from_ber`, and BER
+/// parsers should be used for the fields (like `parse_ber_u32`).
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::*;
+/// use der_parser::ber_sequence;
+///
+/// ber_sequence! {
+///     pub struct Point {
+///         pub x: u32 = parse_ber_u32,
+///         pub y: u32 = parse_ber_u32,
+///         pub z [0] EXPLICIT DEFAULT(0): u32 = parse_ber_u32,
+///     }
+/// }
+///
+/// let (_, point) = Point::from_ber(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02]).unwrap();
+/// assert_eq!((point.x, point.y, point.z), (1, 2, 0));
+/// ```
+#[macro_export]
+macro_rules! ber_sequence {
+    ($($body:tt)*) => {
+        $crate::__sequence_def! {
+            [from_ber, ber, parse_ber_sequence_defined_g, parse_ber_tagged_explicit_class_g, parse_ber_tagged_implicit_class_g]
+            $($body)*
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __sequence_def {
+    // struct with a lifetime
+    (
+        [$parse:ident, $module:ident, $seq:ident, $explicit:ident, $implicit:ident]
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident <$lt:lifetime> {
+            $(
+                $(#[$fmeta:meta])*
+                $fvis:vis $fname:ident $([$($class:ident)? $tag:literal] $mode:ident)? $($kw:ident $(($def:expr))?)?
+                    : $fty:ty = $parser:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name<$lt> {
+            $(
+                $(#[$fmeta])*
+                $fvis $fname: $crate::__sequence_def!(@type [$($kw)?] $fty),
+            )*
+        }
+
+        impl<$lt> $name<$lt> {
+            #[doc = concat!("Parse a `", stringify!($name), "` SEQUENCE")]
+            pub fn $parse(i: &$lt [u8]) -> $crate::error::BerResult<$lt, Self> {
+                $crate::__sequence_def!(
+                    @body [$module, $seq, $explicit, $implicit] i, $name,
+                    $($fname [$([$($class)? $tag] $mode)?] [$($kw $(($def))?)?] ($parser)),*
+                )
+            }
+        }
+    };
+    // struct without lifetime
+    (
+        [$parse:ident, $module:ident, $seq:ident, $explicit:ident, $implicit:ident]
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$fmeta:meta])*
+                $fvis:vis $fname:ident $([$($class:ident)? $tag:literal] $mode:ident)? $($kw:ident $(($def:expr))?)?
+                    : $fty:ty = $parser:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$fmeta])*
+                $fvis $fname: $crate::__sequence_def!(@type [$($kw)?] $fty),
+            )*
+        }
+
+        impl $name {
+            #[doc = concat!("Parse a `", stringify!($name), "` SEQUENCE")]
+            pub fn $parse(i: &[u8]) -> $crate::error::BerResult<Self> {
+                $crate::__sequence_def!(
+                    @body [$module, $seq, $explicit, $implicit] i, $name,
+                    $($fname [$([$($class)? $tag] $mode)?] [$($kw $(($def))?)?] ($parser)),*
+                )
+            }
+        }
+    };
+
+    // type of a field
+    (@type [OPTIONAL] $fty:ty) => { Option<$fty> };
+    (@type [DEFAULT] $fty:ty) => { $fty };
+    (@type [] $fty:ty) => { $fty };
+
+    (@body [$module:ident, $seq:ident, $explicit:ident, $implicit:ident] $i:ident, $name:ident,
+        $($fname:ident [$($tagging:tt)*] [$($kw:tt)*] ($parser:expr)),*
+    ) => {
+        $crate::$module::$seq(|i, _| {
+            $(
+                let (i, $fname) = $crate::__sequence_def!(
+                    @presence [$($kw)*] $crate::__sequence_def!(@tagged [$module, $explicit, $implicit] [$($tagging)*] $parser)
+                )(i)?;
+            )*
+            Ok((i, $name { $($fname),* }))
+        })($i)
+    };
+
+    // tagging of a field
+    (@tagged [$module:ident, $explicit:ident, $implicit:ident] [] $parser:expr) => { $parser };
+    (@tagged [$module:ident, $explicit:ident, $implicit:ident]
+        [[$($class:ident)? $tag:literal] EXPLICIT] $parser:expr
+    ) => {
+        $crate::$module::$explicit(
+            $crate::__sequence_def!(@class $($class)?),
+            $crate::ber::Tag($tag),
+            |content, _| ($parser)(content),
+        )
+    };
+    (@tagged [$module:ident, $explicit:ident, $implicit:ident]
+        [[$($class:ident)? $tag:literal] IMPLICIT] $parser:expr
+    ) => {
+        $crate::$module::$implicit(
+            $crate::__sequence_def!(@class $($class)?),
+            $crate::ber::Tag($tag),
+            |content, hdr, depth| ($parser)(content, hdr, depth),
+        )
+    };
+
+    (@class) => { $crate::ber::Class::ContextSpecific };
+    (@class APPLICATION) => { $crate::ber::Class::Application };
+    (@class PRIVATE) => { $crate::ber::Class::Private };
+
+    // OPTIONAL or DEFAULT
+    (@presence [] $parser:expr) => { $parser };
+    (@presence [OPTIONAL] $parser:expr) => { $crate::__optional_field($parser) };
+    (@presence [DEFAULT ($def:expr)] $parser:expr) => {
+        $crate::nom::combinator::map($crate::__sequence_def!(@presence [OPTIONAL] $parser), |v| {
+            v.unwrap_or($def)
+        })
+    };
+}
+
+/// Build a `(Class, Tag)` pair, usable as a pattern or as an expression
+///
+/// The argument is one of:
+///
+/// - `univ(Name)`: universal class, with the tag [`Tag::Name`](crate::ber::Tag) (for ex.
+///   `univ(Sequence)`)
+/// - `app(n)`, `ctx(n)` or `priv(n)`: application, context-specific or private class, with the tag
+///   number `n`
+///
+/// In patterns, `_` can be used instead of the tag (for ex. `ctx(_)`).
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::ber::{parse_ber, Class, Tag};
+/// use der_parser::tag_pattern;
+///
+/// // SEQUENCE { [0] { INTEGER 2 }, [APPLICATION 5] { }, NULL }
+/// let bytes = &[0x30, 0x09, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x65, 0x00, 0x05, 0x00];
+/// let (_, obj) = parse_ber(bytes).expect("parsing failed");
+/// let names: Vec<_> = obj
+///     .ref_iter()
+///     .map(|o| match (o.class(), o.tag()) {
+///         tag_pattern!(ctx(0)) => "version",
+///         tag_pattern!(app(5)) => "extension",
+///         tag_pattern!(ctx(_)) => "other field",
+///         tag_pattern!(univ(Null)) => "null",
+///         _ => "unknown",
+///     })
+///     .collect();
+/// assert_eq!(names, ["version", "extension", "null"]);
+///
+/// // as an expression
+/// assert_eq!(tag_pattern!(ctx(0)), (Class::ContextSpecific, Tag(0)));
+/// ```
+#[macro_export]
+macro_rules! tag_pattern {
+    (univ(_)) => {
+        ($crate::ber::Class::Universal, _)
+    };
+    (univ($tag:ident)) => {
+        ($crate::ber::Class::Universal, $crate::ber::Tag::$tag)
+    };
+    (app($tag:tt)) => {
+        ($crate::ber::Class::Application, $crate::ber::Tag($tag))
+    };
+    (ctx($tag:tt)) => {
+        ($crate::ber::Class::ContextSpecific, $crate::ber::Tag($tag))
+    };
+    (priv($tag:tt)) => {
+        ($crate::ber::Class::Private, $crate::ber::Tag($tag))
+    };
+}
@@ -1 +1,98 @@
+//! Object identifiers
+//!
+//! [`Oid`] is re-exported from `asn1_rs`. [`OidCache`] keeps the dotted-decimal representation of
+//! OIDs, to avoid decoding the arcs again when the same OIDs are formatted many times (for ex. when
+//! logging the objects of a corpus).
+
 pub use asn1_rs::Oid;
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Cache of the dotted-decimal representation of OIDs
+///
+/// Entries are indexed by the encoded OID, and are never evicted: use [`OidCache::clear`] if the
+/// set of OIDs is not bounded.
+///
+/// # Examples
+///
+/// ```rust
+/// use der_parser::oid;
+/// use der_parser::oid::OidCache;
+///
+/// let mut cache = OidCache::new();
+/// assert_eq!(cache.to_id_string(&oid!(1.2.840.113549)), "1.2.840.113549");
+/// assert_eq!(cache.to_id_string(&oid!(1.2.840.113549)), "1.2.840.113549");
+/// assert_eq!(cache.to_id_string(&oid!(rel 42.1)), "42.1");
+/// assert_eq!(cache.len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OidCache {
+    absolute: BTreeMap<Vec<u8>, String>,
+    relative: BTreeMap<Vec<u8>, String>,
+}
+
+impl OidCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the dotted-decimal representation of `oid`, formatting it only if it is not in the
+    /// cache
+    pub fn to_id_string(&mut self, oid: &Oid) -> &str {
+        let bytes = oid.as_bytes();
+        let map = if is_relative(oid) {
+            &mut self.relative
+        } else {
+            &mut self.absolute
+        };
+        if !map.contains_key(bytes) {
+            map.insert(bytes.to_vec(), oid.to_id_string());
+        }
+        &map[bytes]
+    }
+
+    /// Number of OIDs in the cache
+    pub fn len(&self) -> usize {
+        self.absolute.len() + self.relative.len()
+    }
+
+    /// Test if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all OIDs from the cache
+    pub fn clear(&mut self) {
+        self.absolute.clear();
+        self.relative.clear();
+    }
+}
+
+/// Test if `oid` is a relative OID (the flag is not exposed by `Oid`)
+fn is_relative(oid: &Oid) -> bool {
+    *oid != Oid::new(Cow::Borrowed(oid.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use alloc::vec;
+
+    #[test]
+    fn test_oid_cache() {
+        let mut cache = OidCache::new();
+        // same encoding (0x2a 0x03), different representations
+        assert_eq!(cache.to_id_string(&oid!(1.2.3)), "1.2.3");
+        assert_eq!(cache.to_id_string(&oid!(rel 42.3)), "42.3");
+        let oid = Oid::new(Cow::Owned(vec![0x2a, 0x03]));
+        assert_eq!(cache.to_id_string(&oid), "1.2.3");
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,569 @@
+//! Description of ASN.1 types, and validation of parsed objects
+//!
+//! A [`Schema`] describes the expected structure of an object: universal types, SEQUENCE and SET
+//! with named fields (which can be OPTIONAL), SEQUENCE OF, SET OF, CHOICE, tagged types, and
+//! [constraints](crate::constraint) (SIZE, value ranges, permitted alphabets). It is built
+//! programmatically, and used to validate a tree of objects parsed by the generic parsers ([`parse_ber`](crate::ber::parse_ber) or [`parse_der`](crate::der::parse_der)).
+//!
+//! Validation does not stop at the first error: all the errors are reported, with the path of
+//! the field (for ex. `"signature.algorithm"`, or `"extensions[2]"` for an element of a SEQUENCE
+//! OF).
+//!
+//! Tagged objects are parsed as unknown objects by the generic parsers. When validating, their
+//! content is decoded according to the schema (as an object for EXPLICIT tags, or as the content
+//! of the underlying type for IMPLICIT tags), using the BER rules.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use der_parser::der::parse_der;
+//! use der_parser::schema::{Field, Schema, SchemaErrorKind};
+//! use der_parser::ber::Tag;
+//!
+//! // AlgorithmIdentifier ::= SEQUENCE {
+//! //     algorithm   OBJECT IDENTIFIER,
+//! //     parameters  ANY OPTIONAL }
+//! let schema = Schema::Sequence(vec![
+//!     Field::new("algorithm", Schema::Primitive(Tag::Oid)),
+//!     Field::new("parameters", Schema::Any).optional(),
+//! ]);
+//!
+//! // SEQUENCE { OID 1.2.840.113549.1.1.11, NULL }
+//! let bytes = &[
+//!     0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b, 0x05, 0x00,
+//! ];
+//! let (_, obj) = parse_der(bytes).expect("parsing failed");
+//! assert!(schema.validate(&obj).is_empty());
+//!
+//! // SEQUENCE { INTEGER 1 }
+//! let (_, obj) = parse_der(&[0x30, 0x03, 0x02, 0x01, 0x01]).expect("parsing failed");
+//! let errors = schema.validate(&obj);
+//! assert_eq!(errors[0].path, "algorithm");
+//! assert!(matches!(errors[0].kind, SchemaErrorKind::UnexpectedTag { .. }));
+//! ```
+
+use crate::ber::{parse_ber, BerObject, BerObjectContent, Class, Header, Length, Tag};
+use crate::constraint::{Constraint, ConstraintError};
+use crate::error::BerError;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use asn1_rs::Any;
+use core::convert::TryFrom;
+
+/// Tagging mode of a tagged type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagMode {
+    /// The tag is added before the encoding of the underlying type
+    Explicit,
+    /// The tag replaces the tag of the underlying type
+    Implicit,
+}
+
+/// Expected type of an object
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schema {
+    /// Any object (ASN.1 `ANY`)
+    Any,
+    /// A universal type without fields, identified by its tag (BOOLEAN, INTEGER, OBJECT
+    /// IDENTIFIER, string types, etc.)
+    Primitive(Tag),
+    /// SEQUENCE: fields, in order
+    Sequence(Vec<Field>),
+    /// SET: fields, in any order
+    Set(Vec<Field>),
+    /// SEQUENCE OF: type of the elements
+    SequenceOf(Box<Schema>),
+    /// SET OF: type of the elements
+    SetOf(Box<Schema>),
+    /// CHOICE: alternatives
+    Choice(Vec<Field>),
+    /// Tagged type: class, tag, mode and underlying type
+    Tagged(Class, Tag, TagMode, Box<Schema>),
+    /// Type with subtype constraints (SIZE, ranges, permitted alphabet)
+    Constrained(Box<Schema>, Vec<Constraint>),
+}
+
+/// A named component of a SEQUENCE, SET or CHOICE
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub schema: Schema,
+    /// True if the field is OPTIONAL (or has a DEFAULT value)
+    pub optional: bool,
+}
+
+impl Field {
+    /// Create a new (required) field
+    pub fn new<S: Into<String>>(name: S, schema: Schema) -> Self {
+        Field {
+            name: name.into(),
+            schema,
+            optional: false,
+        }
+    }
+
+    /// Mark the field as OPTIONAL
+    pub fn optional(self) -> Self {
+        Field {
+            optional: true,
+            ..self
+        }
+    }
+}
+
+/// Kind of a [`SchemaError`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaErrorKind {
+    /// The object does not have the expected class and tag
+    UnexpectedTag {
+        expected: (Class, Tag),
+        found: (Class, Tag),
+    },
+    /// A required field is absent
+    Missing,
+    /// The object does not correspond to any field of the SEQUENCE or SET
+    Unexpected,
+    /// The object does not match any alternative of the CHOICE
+    NoAlternative,
+    /// The content of the object could not be decoded as the expected type
+    InvalidContent(BerError),
+    /// The object does not satisfy a constraint
+    Constraint(ConstraintError),
+}
+
+/// An object not matching its schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaError {
+    /// Path of the field: names of the fields from the root object, separated by `.`, and
+    /// `[index]` for elements of SEQUENCE OF and SET OF and for unexpected objects
+    pub path: String,
+    pub kind: SchemaErrorKind,
+}
+
+impl Schema {
+    /// Shortcut for a context-specific EXPLICIT tag
+    pub fn explicit(tag: u32, schema: Schema) -> Self {
+        Schema::Tagged(
+            Class::ContextSpecific,
+            Tag(tag),
+            TagMode::Explicit,
+            Box::new(schema),
+        )
+    }
+
+    /// Shortcut for a context-specific IMPLICIT tag
+    pub fn implicit(tag: u32, schema: Schema) -> Self {
+        Schema::Tagged(
+            Class::ContextSpecific,
+            Tag(tag),
+            TagMode::Implicit,
+            Box::new(schema),
+        )
+    }
+
+    /// Add a constraint to this type
+    pub fn constrained(self, constraint: Constraint) -> Self {
+        match self {
+            Schema::Constrained(inner, mut constraints) => {
+                constraints.push(constraint);
+                Schema::Constrained(inner, constraints)
+            }
+            schema => Schema::Constrained(Box::new(schema), alloc::vec![constraint]),
+        }
+    }
+
+    /// Return the class and tag of objects of this type, or `None` for `ANY` and `CHOICE`
+    pub fn tag(&self) -> Option<(Class, Tag)> {
+        match self {
+            Schema::Any | Schema::Choice(_) => None,
+            Schema::Primitive(tag) => Some((Class::Universal, *tag)),
+            Schema::Sequence(_) | Schema::SequenceOf(_) => Some((Class::Universal, Tag::Sequence)),
+            Schema::Set(_) | Schema::SetOf(_) => Some((Class::Universal, Tag::Set)),
+            Schema::Tagged(class, tag, _, _) => Some((*class, *tag)),
+            Schema::Constrained(inner, _) => inner.tag(),
+        }
+    }
+
+    /// Test if the class and tag of `obj` are accepted by this type (the content is not checked)
+    pub fn accepts(&self, obj: &BerObject) -> bool {
+        match self {
+            Schema::Any => true,
+            Schema::Choice(alternatives) => alternatives.iter().any(|f| f.schema.accepts(obj)),
+            Schema::Constrained(inner, _) => inner.accepts(obj),
+            _ => self.tag() == Some((obj.class(), obj.tag())),
+        }
+    }
+
+    /// Validate an object (and its children), and return the list of errors
+    ///
+    /// The object is valid if the list is empty.
+    pub fn validate(&self, obj: &BerObject) -> Vec<SchemaError> {
+        let mut errors = Vec::new();
+        let mut path = String::new();
+        validate_rec(self, obj, &mut path, &mut errors);
+        errors
+    }
+}
+
+fn push_error(errors: &mut Vec<SchemaError>, path: &str, kind: SchemaErrorKind) {
+    errors.push(SchemaError {
+        path: path.into(),
+        kind,
+    });
+}
+
+/// Run `f` with `component` appended to `path`
+fn with_component<F: FnOnce(&mut String)>(path: &mut String, component: &str, f: F) {
+    let len = path.len();
+    if len > 0 && !component.starts_with('[') {
+        path.push('.');
+    }
+    path.push_str(component);
+    f(path);
+    path.truncate(len);
+}
+
+/// Decode the content of an object with an IMPLICIT tag, as the content of `schema`
+fn decode_implicit<'a>(obj: &BerObject<'a>, schema: &Schema) -> Result<BerObject<'a>, BerError> {
+    let (class, tag) = match schema.tag() {
+        Some(t) => t,
+        // an IMPLICIT tag cannot be applied to ANY or CHOICE types
+        None => return Err(BerError::Unsupported),
+    };
+    let any = Any::try_from(obj)?;
+    let length = Length::Definite(any.data.len());
+    let header = Header::new(class, obj.header.is_constructed(), tag, length);
+    BerObject::try_from(Any::new(header, any.data))
+}
+
+/// Decode the content of an object with an EXPLICIT tag
+fn decode_explicit<'a, 'b>(obj: &'b BerObject<'a>) -> Result<Cow<'b, BerObject<'a>>, BerError> {
+    match &obj.content {
+        BerObjectContent::Tagged(_, _, inner) => Ok(Cow::Borrowed(inner)),
+        BerObjectContent::Unknown(any) if obj.header.is_constructed() => {
+            let (rem, inner) = parse_ber(any.data).map_err(BerError::from)?;
+            if !rem.is_empty() {
+                return Err(BerError::InvalidLength);
+            }
+            Ok(Cow::Owned(inner))
+        }
+        _ => Err(BerError::ConstructExpected),
+    }
+}
+
+/// Decode the content of a tagged object, according to the tagging mode
+pub(crate) fn decode_tagged<'a, 'b>(
+    obj: &'b BerObject<'a>,
+    mode: TagMode,
+    inner: &Schema,
+) -> Result<Cow<'b, BerObject<'a>>, BerError> {
+    match mode {
+        TagMode::Explicit => decode_explicit(obj),
+        TagMode::Implicit => decode_implicit(obj, inner).map(Cow::Owned),
+    }
+}
+
+/// Decode the value of an object, removing the tags (the object must be valid)
+fn decode_value<'a, 'b>(
+    schema: &Schema,
+    obj: &'b BerObject<'a>,
+) -> Result<Cow<'b, BerObject<'a>>, BerError> {
+    match schema {
+        Schema::Tagged(_, _, mode, inner) => match decode_tagged(obj, *mode, inner)? {
+            Cow::Borrowed(obj) => decode_value(inner, obj),
+            Cow::Owned(obj) => Ok(Cow::Owned(decode_value(inner, &obj)?.into_owned())),
+        },
+        Schema::Constrained(inner, _) => decode_value(inner, obj),
+        Schema::Choice(alternatives) => match alternatives.iter().find(|f| f.schema.accepts(obj)) {
+            Some(f) => decode_value(&f.schema, obj),
+            None => Ok(Cow::Borrowed(obj)),
+        },
+        _ => Ok(Cow::Borrowed(obj)),
+    }
+}
+
+/// Return the field of the SEQUENCE matching each object (`None` for unexpected objects)
+pub(crate) fn sequence_fields<'s>(
+    fields: &'s [Field],
+    children: &[BerObject],
+) -> Vec<Option<&'s Field>> {
+    let mut fields = fields.iter();
+    children
+        .iter()
+        .map(|child| fields.by_ref().find(|f| f.schema.accepts(child)))
+        .collect()
+}
+
+/// Return the field of the SET matching each object (`None` for unexpected objects)
+pub(crate) fn set_fields<'s>(
+    fields: &'s [Field],
+    children: &[BerObject],
+) -> Vec<Option<&'s Field>> {
+    let mut used = alloc::vec![false; fields.len()];
+    children
+        .iter()
+        .map(|child| {
+            let (idx, field) = fields
+                .iter()
+                .enumerate()
+                .find(|(idx, f)| !used[*idx] && f.schema.accepts(child))?;
+            used[idx] = true;
+            Some(field)
+        })
+        .collect()
+}
+
+fn validate_rec(
+    schema: &Schema,
+    obj: &BerObject,
+    path: &mut String,
+    errors: &mut Vec<SchemaError>,
+) {
+    if let Schema::Constrained(inner, constraints) = schema {
+        let count = errors.len();
+        validate_rec(inner, obj, path, errors);
+        // constraints are checked on the decoded value, if the object itself is valid
+        if errors[count..].iter().all(|e| e.path != *path) {
+            let obj = match decode_value(inner, obj) {
+                Ok(obj) => obj,
+                Err(e) => return push_error(errors, path, SchemaErrorKind::InvalidContent(e)),
+            };
+            for c in constraints {
+                if let Err(e) = c.check(&obj) {
+                    push_error(errors, path, SchemaErrorKind::Constraint(e));
+                }
+            }
+        }
+        return;
+    }
+    if let Schema::Choice(alternatives) = schema {
+        match alternatives.iter().find(|f| f.schema.accepts(obj)) {
+            Some(f) => with_component(path, &f.name, |path| {
+                validate_rec(&f.schema, obj, path, errors)
+            }),
+            None => push_error(errors, path, SchemaErrorKind::NoAlternative),
+        }
+        return;
+    }
+    if let Some(expected) = schema.tag() {
+        let found = (obj.class(), obj.tag());
+        if expected != found {
+            let kind = SchemaErrorKind::UnexpectedTag { expected, found };
+            push_error(errors, path, kind);
+            return;
+        }
+    }
+    let children = match &obj.content {
+        BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => &v[..],
+        _ => &[],
+    };
+    match schema {
+        Schema::Any | Schema::Primitive(_) | Schema::Choice(_) | Schema::Constrained(..) => (),
+        Schema::Sequence(fields) => validate_sequence(fields, children, path, errors),
+        Schema::Set(fields) => validate_set(fields, children, path, errors),
+        Schema::SequenceOf(element) | Schema::SetOf(element) => {
+            for (idx, child) in children.iter().enumerate() {
+                with_component(path, &format!("[{}]", idx), |path| {
+                    validate_rec(element, child, path, errors)
+                });
+            }
+        }
+        Schema::Tagged(_, _, mode, inner) => match decode_tagged(obj, *mode, inner) {
+            Ok(inner_obj) => validate_rec(inner, &inner_obj, path, errors),
+            Err(e) => push_error(errors, path, SchemaErrorKind::InvalidContent(e)),
+        },
+    }
+}
+
+fn validate_sequence(
+    fields: &[Field],
+    children: &[BerObject],
+    path: &mut String,
+    errors: &mut Vec<SchemaError>,
+) {
+    let mut idx = 0;
+    for field in fields {
+        match children.get(idx) {
+            Some(child) if field.schema.accepts(child) => {
+                with_component(path, &field.name, |path| {
+                    validate_rec(&field.schema, child, path, errors)
+                });
+                idx += 1;
+            }
+            _ if field.optional => (),
+            Some(child) => {
+                // report the tag error on the field, and try the next field with the same object
+                with_component(path, &field.name, |path| {
+                    validate_rec(&field.schema, child, path, errors)
+                });
+            }
+            None => with_component(path, &field.name, |path| {
+                push_error(errors, path, SchemaErrorKind::Missing)
+            }),
+        }
+    }
+    for idx in idx..children.len() {
+        with_component(path, &format!("[{}]", idx), |path| {
+            push_error(errors, path, SchemaErrorKind::Unexpected)
+        });
+    }
+}
+
+fn validate_set(
+    fields: &[Field],
+    children: &[BerObject],
+    path: &mut String,
+    errors: &mut Vec<SchemaError>,
+) {
+    let mut used = alloc::vec![false; children.len()];
+    for field in fields {
+        let found = children
+            .iter()
+            .enumerate()
+            .find(|(idx, child)| !used[*idx] && field.schema.accepts(child));
+        with_component(path, &field.name, |path| match found {
+            Some((idx, child)) => {
+                used[idx] = true;
+                validate_rec(&field.schema, child, path, errors)
+            }
+            None if field.optional => (),
+            None => push_error(errors, path, SchemaErrorKind::Missing),
+        });
+    }
+    for (idx, _) in used.iter().enumerate().filter(|(_, used)| !**used) {
+        with_component(path, &format!("[{}]", idx), |path| {
+            push_error(errors, path, SchemaErrorKind::Unexpected)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::der::parse_der;
+    use alloc::vec;
+    use hex_literal::hex;
+
+    fn errors(schema: &Schema, bytes: &[u8]) -> Vec<(String, SchemaErrorKind)> {
+        let (_, obj) = parse_der(bytes).expect("parsing failed");
+        schema
+            .validate(&obj)
+            .into_iter()
+            .map(|e| (e.path, e.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_validate() {
+        // Example ::= SEQUENCE {
+        //     version  [0] EXPLICIT INTEGER OPTIONAL,
+        //     serial   INTEGER,
+        //     names    SEQUENCE OF CHOICE { dns [2] IMPLICIT IA5String, ip [7] IMPLICIT OCTET STRING },
+        //     flags    SET { a [1] IMPLICIT BOOLEAN, b BOOLEAN } }
+        let name = Schema::Choice(vec![
+            Field::new(
+                "dns",
+                Schema::implicit(2, Schema::Primitive(Tag::Ia5String)),
+            ),
+            Field::new(
+                "ip",
+                Schema::implicit(7, Schema::Primitive(Tag::OctetString)),
+            ),
+        ]);
+        let schema = Schema::Sequence(vec![
+            Field::new(
+                "version",
+                Schema::explicit(0, Schema::Primitive(Tag::Integer)),
+            )
+            .optional(),
+            Field::new("serial", Schema::Primitive(Tag::Integer)),
+            Field::new("names", Schema::SequenceOf(Box::new(name))),
+            Field::new(
+                "flags",
+                Schema::Set(vec![
+                    Field::new("a", Schema::implicit(1, Schema::Primitive(Tag::Boolean))),
+                    Field::new("b", Schema::Primitive(Tag::Boolean)),
+                ]),
+            ),
+        ]);
+        let valid = hex!(
+            "30 1a a0 03 02 01 02 02 01 01 30 08 82 01 61 87 03 7f 00 01 31 06 01 01 ff 81 01 00"
+        );
+        assert_eq!(errors(&schema, &valid), vec![]);
+        // no version, and the SET elements in another order
+        let valid = hex!("30 15 02 01 01 30 08 82 01 61 87 03 7f 00 01 31 06 81 01 00 01 01 ff");
+        assert_eq!(errors(&schema, &valid), vec![]);
+
+        // version is not an integer, unknown name, invalid boolean, missing flag, extra object
+        let invalid =
+            hex!("30 18 a0 03 04 01 02 02 01 01 30 06 82 01 61 83 01 00 31 04 81 02 00 00 05 00");
+        let expected = vec![
+            (
+                "version".into(),
+                SchemaErrorKind::UnexpectedTag {
+                    expected: (Class::Universal, Tag::Integer),
+                    found: (Class::Universal, Tag::OctetString),
+                },
+            ),
+            ("names[1]".into(), SchemaErrorKind::NoAlternative),
+            (
+                "flags.a".into(),
+                SchemaErrorKind::InvalidContent(BerError::InvalidLength),
+            ),
+            ("flags.b".into(), SchemaErrorKind::Missing),
+            ("[4]".into(), SchemaErrorKind::Unexpected),
+        ];
+        assert_eq!(errors(&schema, &invalid), expected);
+
+        // missing serial
+        let invalid = hex!("30 02 30 00");
+        let kinds = errors(&schema, &invalid);
+        assert_eq!(kinds[0].0, "serial");
+        assert_eq!(kinds.last().map(|e| e.0.as_str()), Some("flags"));
+    }
+
+    #[test]
+    fn test_schema_constraints() {
+        use crate::constraint::{Constraint, ConstraintErrorKind};
+
+        // SEQUENCE {
+        //     id    [0] IMPLICIT INTEGER (0..10),
+        //     names SEQUENCE SIZE (1..2) OF PrintableString (FROM ("a".."z")) }
+        let name = Schema::Primitive(Tag::PrintableString)
+            .constrained(Constraint::alphabet_range('a', 'z'));
+        let schema = Schema::Sequence(vec![
+            Field::new(
+                "id",
+                Schema::implicit(0, Schema::Primitive(Tag::Integer))
+                    .constrained(Constraint::range(0, 10)),
+            ),
+            Field::new(
+                "names",
+                Schema::SequenceOf(Box::new(name)).constrained(Constraint::size(1, 2)),
+            ),
+        ]);
+        let valid = hex!("30 08 80 01 05 30 03 13 01 61");
+        assert_eq!(errors(&schema, &valid), vec![]);
+
+        let invalid = hex!("30 0e 80 01 0b 30 09 13 01 61 13 01 41 13 01 62");
+        let kinds: Vec<_> = errors(&schema, &invalid)
+            .into_iter()
+            .map(|(path, kind)| match kind {
+                SchemaErrorKind::Constraint(e) => (path, e.kind),
+                kind => panic!("unexpected error {:?}", kind),
+            })
+            .collect();
+        let expected = vec![
+            ("id".into(), ConstraintErrorKind::ValueOutOfRange(Some(11))),
+            (
+                "names[1]".into(),
+                ConstraintErrorKind::CharNotPermitted('A'),
+            ),
+            ("names".into(), ConstraintErrorKind::SizeOutOfRange(3)),
+        ];
+        assert_eq!(kinds, expected);
+    }
+}
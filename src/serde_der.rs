@@ -0,0 +1,623 @@
+//! DER as a serde data format
+//!
+//! This module implements a `serde` [`Deserializer`] and [`Serializer`] for DER, so simple Rust
+//! types can be decoded from (and encoded to) DER without writing a parser. Structures and tuples
+//! are mapped to SEQUENCE objects, and their fields are read **in order**: field names are not
+//! used.
+//!
+//! | Rust type                   | ASN.1 type                                        |
+//! |-----------------------------|---------------------------------------------------|
+//! | `bool`                      | BOOLEAN                                           |
+//! | integers (up to 64 bits)    | INTEGER                                           |
+//! | `&str`, `String`, `char`    | UTF8String (any string type when decoding)        |
+//! | bytes (`serde_bytes`)       | OCTET STRING (or BIT STRING content when decoding) |
+//! | `()`, unit structs, `None`  | NULL                                              |
+//! | `Some(v)`, newtype structs  | encoding of `v`                                   |
+//! | unit enum variants          | ENUMERATED (variant index)                        |
+//! | structs, tuples, sequences  | SEQUENCE (SET is also accepted when decoding)     |
+//!
+//! Other types (floating-point numbers, maps, enum variants with data) are not supported. When
+//! decoding, OIDs and times can also be read as strings, and `deserialize_any` maps objects to
+//! the closest serde type.
+//!
+//! This is intended for simple schemas: optional fields must be encoded as NULL (absent fields
+//! are not supported), and tagged objects are not decoded.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use der_parser::serde_der::{from_der, to_der};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Entry<'a> {
+//!     id: u32,
+//!     name: &'a str,
+//!     enabled: bool,
+//! }
+//!
+//! let entry = Entry { id: 1, name: "test", enabled: true };
+//! let bytes = to_der(&entry).expect("serialization failed");
+//! assert_eq!(&bytes[..2], &[0x30, 0x0c]);
+//! let decoded: Entry = from_der(&bytes).expect("deserialization failed");
+//! assert_eq!(decoded, entry);
+//! ```
+//!
+//! [`Deserializer`]: serde::de::Deserializer
+//! [`Serializer`]: serde::ser::Serializer
+
+use crate::ber::{BerObject, BerObjectContent, Tag};
+use crate::der::parse_der;
+use crate::error::BerError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+
+/// Error returned by DER (de)serialization
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The input could not be parsed
+    Ber(BerError),
+    /// Input was not entirely read (after the object, or in a sequence)
+    TrailingData,
+    /// A type is not supported by this data format
+    Unsupported(&'static str),
+    /// Error reported by the serialized value
+    Custom(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Ber(e) => write!(f, "{}", e),
+            Error::TrailingData => f.write_str("trailing data"),
+            Error::Unsupported(ty) => write!(f, "unsupported type: {}", ty),
+            Error::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl de::StdError for Error {}
+
+impl From<BerError> for Error {
+    fn from(e: BerError) -> Self {
+        Error::Ber(e)
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+/// Decode a value of type `T` from a DER object
+///
+/// The input must contain exactly one object.
+pub fn from_der<'a, T: de::Deserialize<'a>>(i: &'a [u8]) -> Result<T, Error> {
+    let (rem, obj) = parse_der(i).map_err(|e| match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => Error::Ber(e),
+        nom::Err::Incomplete(n) => Error::Ber(BerError::Incomplete(n)),
+    })?;
+    if !rem.is_empty() {
+        return Err(Error::TrailingData);
+    }
+    T::deserialize(Deserializer::new(&obj))
+}
+
+/// Encode `value` as a DER object
+pub fn to_der<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    value.serialize(Serializer)
+}
+
+/// A serde `Deserializer` reading a parsed object
+#[derive(Debug)]
+pub struct Deserializer<'a, 'b> {
+    obj: &'b BerObject<'a>,
+}
+
+impl<'a, 'b> Deserializer<'a, 'b> {
+    /// Build a deserializer reading `obj`
+    pub fn new(obj: &'b BerObject<'a>) -> Self {
+        Deserializer { obj }
+    }
+
+    fn children(&self) -> Result<&'b [BerObject<'a>], Error> {
+        match &self.obj.content {
+            BerObjectContent::Sequence(v) | BerObjectContent::Set(v) => Ok(v),
+            _ => Err(Error::Ber(BerError::BerTypeError)),
+        }
+    }
+}
+
+macro_rules! deserialize_int {
+    ($method:ident, $visit:ident, $ty:ty, $as:ident) => {
+        fn $method<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+            let v = self.obj.$as()?;
+            let v = <$ty>::try_from(v).map_err(|_| Error::Ber(BerError::IntegerTooLarge))?;
+            visitor.$visit(v)
+        }
+    };
+}
+
+impl<'a, 'b> de::Deserializer<'a> for Deserializer<'a, 'b> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.obj.content {
+            BerObjectContent::Boolean(b) => visitor.visit_bool(*b),
+            BerObjectContent::Integer(_) => match self.obj.as_i64() {
+                Ok(v) => visitor.visit_i64(v),
+                Err(_) => visitor.visit_u64(self.obj.as_u64()?),
+            },
+            BerObjectContent::Enum(v) => visitor.visit_u64(*v),
+            BerObjectContent::Null => visitor.visit_unit(),
+            BerObjectContent::Sequence(_) | BerObjectContent::Set(_) => {
+                self.deserialize_seq(visitor)
+            }
+            BerObjectContent::OID(oid) | BerObjectContent::RelativeOID(oid) => {
+                visitor.visit_string(oid.to_id_string())
+            }
+            BerObjectContent::UTCTime(t) | BerObjectContent::GeneralizedTime(t) => {
+                visitor.visit_string(t.to_string())
+            }
+            _ => match self.obj.as_str() {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(_) => visitor.visit_borrowed_bytes(self.obj.as_slice()?),
+            },
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.obj.as_bool()?)
+    }
+
+    deserialize_int!(deserialize_i8, visit_i8, i8, as_i64);
+    deserialize_int!(deserialize_i16, visit_i16, i16, as_i64);
+    deserialize_int!(deserialize_i32, visit_i32, i32, as_i64);
+    deserialize_int!(deserialize_i64, visit_i64, i64, as_i64);
+    deserialize_int!(deserialize_u8, visit_u8, u8, as_u64);
+    deserialize_int!(deserialize_u16, visit_u16, u16, as_u64);
+    deserialize_int!(deserialize_u32, visit_u32, u32, as_u64);
+    deserialize_int!(deserialize_u64, visit_u64, u64, as_u64);
+
+    fn deserialize_f32<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut chars = self.obj.as_str()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::Ber(BerError::BerValueError)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.obj.content {
+            BerObjectContent::OID(_)
+            | BerObjectContent::RelativeOID(_)
+            | BerObjectContent::UTCTime(_)
+            | BerObjectContent::GeneralizedTime(_) => self.deserialize_any(visitor),
+            _ => visitor.visit_borrowed_str(self.obj.as_str()?),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.obj.as_slice()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj.content {
+            BerObjectContent::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj.content {
+            BerObjectContent::Null => visitor.visit_unit(),
+            _ => Err(Error::Ber(BerError::BerTypeError)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut seq = SeqAccess {
+            iter: self.children()?.iter(),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.iter.len() != 0 {
+            return Err(Error::TrailingData);
+        }
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'a>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("map"))
+    }
+
+    fn deserialize_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.obj.content {
+            BerObjectContent::Enum(idx) => {
+                let idx = u32::try_from(idx).map_err(|_| Error::Ber(BerError::IntegerTooLarge))?;
+                visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(idx))
+            }
+            _ => Err(Error::Ber(BerError::BerTypeError)),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+struct SeqAccess<'a, 'b> {
+    iter: core::slice::Iter<'b, BerObject<'a>>,
+}
+
+impl<'a, 'b> de::SeqAccess<'a> for SeqAccess<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'a>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.iter
+            .next()
+            .map(|obj| seed.deserialize(Deserializer::new(obj)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// A serde `Serializer` encoding values as DER objects
+///
+/// Each value is returned as a new buffer, containing the encoded object.
+#[derive(Clone, Copy, Debug)]
+pub struct Serializer;
+
+fn encode_tlv(tag: Tag, constructed: bool, content: &[u8]) -> Vec<u8> {
+    // only universal tags with a low number are used
+    let mut v = Vec::with_capacity(content.len() + 10);
+    v.push(tag.0 as u8 | if constructed { 0x20 } else { 0 });
+    let len = content.len();
+    if len < 0x80 {
+        v.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        v.push(0x80 | (bytes.len() - skip) as u8);
+        v.extend_from_slice(&bytes[skip..]);
+    }
+    v.extend_from_slice(content);
+    v
+}
+
+/// Encode an integer, given its two's complement big-endian representation
+fn encode_integer(tag: Tag, bytes: &[u8]) -> Vec<u8> {
+    // remove redundant leading octets (X.690 8.3.2)
+    let mut skip = 0;
+    while skip + 1 < bytes.len()
+        && ((bytes[skip] == 0 && bytes[skip + 1] & 0x80 == 0)
+            || (bytes[skip] == 0xff && bytes[skip + 1] & 0x80 != 0))
+    {
+        skip += 1;
+    }
+    encode_tlv(tag, false, &bytes[skip..])
+}
+
+fn encode_unsigned(v: u64) -> Vec<u8> {
+    let mut bytes = [0; 9];
+    bytes[1..].copy_from_slice(&v.to_be_bytes());
+    encode_integer(Tag::Integer, &bytes)
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = Impossible<Vec<u8>, Error>;
+    type SerializeMap = Impossible<Vec<u8>, Error>;
+    type SerializeStruct = SeqSerializer;
+    type SerializeStructVariant = Impossible<Vec<u8>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Vec<u8>, Error> {
+        Ok(encode_tlv(Tag::Boolean, false, &[if v { 0xff } else { 0 }]))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Vec<u8>, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Vec<u8>, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Vec<u8>, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Vec<u8>, Error> {
+        Ok(encode_integer(Tag::Integer, &v.to_be_bytes()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Vec<u8>, Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Vec<u8>, Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Vec<u8>, Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Vec<u8>, Error> {
+        Ok(encode_unsigned(v))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Vec<u8>, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Vec<u8>, Error> {
+        Ok(encode_tlv(Tag::Utf8String, false, v.as_bytes()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(encode_tlv(Tag::OctetString, false, v))
+    }
+
+    fn serialize_none(self) -> Result<Vec<u8>, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Vec<u8>, Error> {
+        Ok(encode_tlv(Tag::Null, false, &[]))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Vec<u8>, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Vec<u8>, Error> {
+        let mut v = encode_unsigned(u64::from(variant_index));
+        v[0] = Tag::Enumerated.0 as u8;
+        Ok(v)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported("newtype variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::default())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::default())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::default())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::Unsupported("tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::Unsupported("map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::Unsupported("struct variant"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializer for the elements of a SEQUENCE
+#[derive(Debug, Default)]
+pub struct SeqSerializer {
+    content: Vec<u8>,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let v = value.serialize(Serializer)?;
+        self.content.extend_from_slice(&v);
+        Ok(())
+    }
+
+    fn finish(self) -> Vec<u8> {
+        encode_tlv(Tag::Sequence, true, &self.content)
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SeqSerializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Ok(self.finish())
+    }
+}
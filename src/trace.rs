@@ -0,0 +1,46 @@
+//! Instrumentation of the parsers, using `tracing` (enabled by the `trace` feature)
+//!
+//! Without the feature, these functions do nothing and are optimized out.
+
+use crate::ber::Header;
+use crate::error::{BerError, BerResult};
+use nom::Err;
+
+/// Emit an event for a parsed header
+///
+/// `offset` is the position of the header in the input of the parsing function, and `depth` the
+/// nesting level (0 for the top-level object).
+#[inline]
+pub(crate) fn header(offset: usize, depth: usize, header: &Header) {
+    #[cfg(feature = "trace")]
+    tracing::trace!(
+        offset,
+        depth,
+        class = ?header.class(),
+        tag = header.tag().0,
+        constructed = header.is_constructed(),
+        length = ?header.length(),
+        "header"
+    );
+    #[cfg(not(feature = "trace"))]
+    let _ = (offset, depth, header);
+}
+
+/// Emit an event if the error is a DER constraint violation
+#[inline]
+pub(crate) fn violation(e: &BerError) {
+    #[cfg(feature = "trace")]
+    if let BerError::DerConstraintFailed(constraint) = e {
+        tracing::debug!(?constraint, "DER constraint violation");
+    }
+    #[cfg(not(feature = "trace"))]
+    let _ = e;
+}
+
+/// Emit an event if parsing failed with a DER constraint violation
+#[inline]
+pub(crate) fn result<T>(r: &BerResult<T>) {
+    if let Err(Err::Error(e)) | Err(Err::Failure(e)) = r {
+        violation(e);
+    }
+}
@@ -2,6 +2,7 @@
 #![allow(clippy::unused_unit)]
 
 use der_parser::ber::*;
+use der_parser::der::DerViolation;
 use der_parser::error::*;
 use der_parser::oid::*;
 use hex_literal::hex;
@@ -44,6 +45,165 @@ fn test_ber_bool() {
     );
 }
 
+#[test_case(&hex!("01 01 01"), BooleanPolicy::Lenient, Ok(true) ; "lenient non ff")]
+#[test_case(&hex!("01 01 01"), BooleanPolicy::Strict, Err(BerError::DerConstraintFailed(DerConstraint::InvalidBoolean)) ; "strict non ff")]
+#[test_case(&hex!("01 01 ff"), BooleanPolicy::Strict, Ok(true) ; "strict ff")]
+#[test_case(&hex!("30 03 01 01 01"), BooleanPolicy::Strict, Err(BerError::DerConstraintFailed(DerConstraint::InvalidBoolean)) ; "strict nested")]
+fn tc_ber_bool_policy(i: &[u8], policy: BooleanPolicy, out: Result<bool, BerError>) {
+    let options = ParseOptions::ber().with_boolean_policy(policy);
+    let res = parse_ber_with_options(i, &options).map(|(_, obj)| match obj.content {
+        BerObjectContent::Sequence(ref v) => v[0].as_bool().unwrap(),
+        _ => obj.as_bool().unwrap(),
+    });
+    assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test_case(&hex!("02 02 00 01"), IntegerPolicy::Lenient, Ok(vec![]) ; "lenient leading zero")]
+#[test_case(&hex!("02 02 00 01"), IntegerPolicy::Warn, Ok(vec![0]) ; "warn leading zero")]
+#[test_case(&hex!("02 02 00 01"), IntegerPolicy::Strict, Err(BerError::DerConstraintFailed(DerConstraint::IntegerLeadingZeroes)) ; "strict leading zero")]
+#[test_case(&hex!("02 02 ff 80"), IntegerPolicy::Strict, Err(BerError::DerConstraintFailed(DerConstraint::IntegerLeadingFF)) ; "strict leading ff")]
+#[test_case(&hex!("02 02 00 80"), IntegerPolicy::Strict, Ok(vec![]) ; "strict minimal")]
+#[test_case(&hex!("30 0a 0a 01 01 0a 01 00 0a 02 00 01"), IntegerPolicy::Warn, Ok(vec![8]) ; "warn nested enumerated")]
+fn tc_ber_integer_policy(i: &[u8], policy: IntegerPolicy, out: Result<Vec<usize>, BerError>) {
+    let options = ParseOptions::ber().with_integer_policy(policy);
+    let res = parse_ber_with_warnings(i, &options).map(|(_, (_, warnings))| {
+        assert!(warnings
+            .iter()
+            .all(|w| w.violation == DerViolation::NonMinimalInteger));
+        warnings.iter().map(|w| w.offset).collect::<Vec<_>>()
+    });
+    assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test_case(&hex!("03 02 07 80"), BitStringPolicy::Strict, Ok(vec![]) ; "strict zero padding")]
+#[test_case(&hex!("03 02 07 81"), BitStringPolicy::Lenient, Ok(vec![]) ; "lenient padding")]
+#[test_case(&hex!("03 02 07 81"), BitStringPolicy::Warn, Ok(vec![0]) ; "warn padding")]
+#[test_case(&hex!("30 06 05 00 03 02 04 ff"), BitStringPolicy::Warn, Ok(vec![4]) ; "warn nested padding")]
+#[test_case(&hex!("03 02 07 81"), BitStringPolicy::Strict, Err(BerError::DerConstraintFailed(DerConstraint::UnusedBitsNotZero)) ; "strict padding")]
+fn tc_ber_bitstring_policy(i: &[u8], policy: BitStringPolicy, out: Result<Vec<usize>, BerError>) {
+    let options = ParseOptions::ber().with_bitstring_policy(policy);
+    let res = parse_ber_with_warnings(i, &options).map(|(_, (_, warnings))| {
+        assert!(warnings
+            .iter()
+            .all(|w| w.violation == DerViolation::NonZeroUnusedBits));
+        warnings.iter().map(|w| w.offset).collect::<Vec<_>>()
+    });
+    assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test_case(&hex!("22 01 01"), ConstructedPolicy::Strict, Err(BerError::ConstructUnexpected) ; "strict constructed integer")]
+#[test_case(&hex!("30 02 25 00"), ConstructedPolicy::Strict, Err(BerError::ConstructUnexpected) ; "strict nested constructed null")]
+#[test_case(&hex!("08 00"), ConstructedPolicy::Strict, Err(BerError::ConstructExpected) ; "strict primitive external")]
+#[test_case(&hex!("24 03 04 01 61"), ConstructedPolicy::Strict, Ok(Tag::OctetString) ; "strict constructed octet string")]
+#[test_case(&hex!("22 01 01"), ConstructedPolicy::Lenient, Ok(Tag::Integer) ; "lenient constructed integer")]
+#[test_case(&hex!("08 00"), ConstructedPolicy::Lenient, Ok(Tag::External) ; "lenient primitive external")]
+#[test_case(&hex!("10 00"), ConstructedPolicy::Lenient, Err(BerError::ConstructExpected) ; "lenient primitive sequence")]
+fn tc_ber_constructed_policy(i: &[u8], policy: ConstructedPolicy, out: Result<Tag, BerError>) {
+    let options = ParseOptions::ber().with_constructed_policy(policy);
+    let res = parse_ber_with_options(i, &options).map(|(_, obj)| obj.tag());
+    assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test_case(&hex!("30 80 05 00 00 00"), EocPolicy::Strict, Ok(vec![]) ; "strict terminating eoc")]
+#[test_case(&hex!("30 06 05 00 00 00 00 00"), EocPolicy::Lenient, Ok(vec![]) ; "lenient padding")]
+#[test_case(&hex!("30 06 05 00 00 00 00 00"), EocPolicy::Warn, Ok(vec![(4, DerViolation::UnexpectedEndOfContent), (6, DerViolation::UnexpectedEndOfContent)]) ; "warn padding")]
+#[test_case(&hex!("30 06 05 00 00 00 00 00"), EocPolicy::Strict, Err(BerError::UnexpectedTag { expected: None, actual: Tag::EndOfContent }) ; "strict padding")]
+#[test_case(&hex!("30 80 00 02 ab cd 00 00"), EocPolicy::Warn, Ok(vec![(2, DerViolation::UnexpectedEndOfContent), (2, DerViolation::InvalidEndOfContent)]) ; "warn eoc with content")]
+#[test_case(&hex!("00 02 ab cd"), EocPolicy::Strict, Err(BerError::InvalidLength) ; "strict eoc with content")]
+fn tc_ber_eoc_policy(
+    i: &[u8],
+    policy: EocPolicy,
+    out: Result<Vec<(usize, DerViolation)>, BerError>,
+) {
+    let options = ParseOptions::ber().with_eoc_policy(policy);
+    let res = parse_ber_with_warnings(i, &options).map(|(_, (_, warnings))| {
+        warnings
+            .iter()
+            .map(|w| (w.offset, w.violation))
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test]
+fn test_parse_limits() {
+    // SEQUENCE { 1000 empty SEQUENCEs }
+    let mut data = vec![0x30, 0x82, 0x07, 0xd0];
+    data.extend([0x30, 0x00].repeat(1000));
+    let r = parse_ber_with_options(&data, &ParseOptions::ber().with_max_nodes(1001));
+    assert!(r.is_ok());
+    let r = parse_ber_with_options(&data, &ParseOptions::ber().with_max_nodes(1000));
+    assert_eq!(r.unwrap_err(), Err::Error(limit_exceeded()));
+    let max_alloc = 100 * core::mem::size_of::<BerObject>();
+    let r = parse_ber_with_options(&data, &ParseOptions::ber().with_max_alloc(max_alloc));
+    assert_eq!(r.unwrap_err(), Err::Error(limit_exceeded()));
+}
+
+#[test]
+fn test_max_object_len() {
+    let options = ParseOptions::ber().with_max_object_len(4);
+    // SEQUENCE { OCTET STRING "abc" }: the SEQUENCE is too large
+    let bytes = hex!("30 05 04 03 61 62 63");
+    assert_eq!(
+        parse_ber_with_options(&bytes, &options).map(|_| ()),
+        Err(Err::Error(object_too_large()))
+    );
+    let bytes = hex!("30 04 04 02 61 62");
+    assert!(parse_ber_with_options(&bytes, &options).is_ok());
+    // nested object too large, in an indefinite-length sequence
+    let bytes = hex!("30 80 04 05 61 62 63 64 65 00 00");
+    assert_eq!(
+        parse_ber_with_options(&bytes, &options).map(|_| ()),
+        Err(Err::Error(object_too_large()))
+    );
+    // the length is checked before reading the content (1 GiB, not present)
+    let bytes = hex!("04 84 40 00 00 00 61");
+    assert_eq!(
+        parse_ber_with_options(&bytes, &options).map(|_| ()),
+        Err(Err::Error(object_too_large()))
+    );
+    let options = ParseOptions::der().with_max_object_len(4);
+    assert_eq!(
+        der_parser::der::parse_der_with_options(&bytes, &options).map(|_| ()),
+        Err(Err::Error(object_too_large()))
+    );
+}
+
+#[test]
+fn test_lazy_strings() {
+    // SEQUENCE { PrintableString "ab", IA5String (invalid charset) }
+    let data = hex!("30 08 13 02 61 62 16 02 61 ff");
+    assert!(parse_ber(&data).is_err());
+    let options = ParseOptions::ber().with_lazy_strings(true);
+    let (rem, obj) = parse_ber_with_options(&data, &options).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert!(matches!(obj[0].content, BerObjectContent::Unknown(_)));
+    assert_eq!(obj[0].as_str(), Ok("ab"));
+    assert_eq!(obj[1].as_str(), Err(BerError::StringInvalidCharset));
+    assert_eq!(obj[1].as_slice(), Ok(&b"a\xff"[..]));
+}
+
+#[test]
+fn test_decode_primitives() {
+    // SEQUENCE { BOOLEAN (2 bytes), UTF8String "ab", SET { NULL } }
+    let data = hex!("30 0c 01 02 00 00 0c 02 61 62 31 02 05 00");
+    assert!(parse_ber(&data).is_err());
+    let options = ParseOptions::ber().with_decode_primitives(false);
+    let (rem, obj) = parse_ber_with_options(&data, &options).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        obj[0].content,
+        BerObjectContent::Primitive(Tag::Boolean, &[0, 0])
+    );
+    assert_eq!(obj[0].tag(), Tag::Boolean);
+    assert_eq!(obj[1].as_str(), Ok("ab"));
+    // constructed objects are still parsed
+    assert_eq!(
+        obj[2][0].content,
+        BerObjectContent::Primitive(Tag::Null, &[])
+    );
+}
+
 #[test]
 fn test_seq_indefinite_length() {
     let data = hex!("30 80 04 03 56 78 90 00 00 02 01 01");
@@ -554,3 +714,60 @@ fn parse_ber_private() {
     assert!(rem.is_empty());
     assert!(matches!(res.content, BerObjectContent::Unknown(_)));
 }
+
+#[test]
+fn parse_ber_tag_overflow() {
+    // [PRIVATE 2^32] 0xff (tag number does not fit in 32 bits)
+    let bytes = &hex!("df 90 80 80 80 00 01 ff");
+    let (rem, obj) = parse_ber(bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.header.class(), Class::Private);
+    assert_eq!(obj.header.tag(), TAG_OVERFLOW);
+    assert_eq!(obj.header.raw_tag(), Some(&bytes[..6]));
+    assert_eq!(
+        obj.content,
+        BerObjectContent::TagOverflow(&bytes[..6], &bytes[7..])
+    );
+    assert_eq!(obj.as_slice(), Ok(&[0xff][..]));
+    // in a SEQUENCE, with indefinite length
+    let bytes = &hex!("30 0f 7f 81 80 80 80 80 00 80 05 00 00 00 02 01 01");
+    let (rem, obj) = parse_ber(bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    let seq = obj.as_sequence().expect("not a sequence");
+    assert_eq!(
+        seq[0].content,
+        BerObjectContent::TagOverflow(&bytes[2..9], &hex!("05 00"))
+    );
+    assert_eq!(seq[0].raw_bytes(), Some(&bytes[2..14]));
+    assert_eq!(seq[1].as_u32(), Ok(1));
+    // the largest tag number is not an overflow
+    let bytes = &hex!("df 8f ff ff ff 7f 00");
+    let (_, obj) = parse_ber(bytes).expect("parsing failed");
+    assert!(matches!(obj.content, BerObjectContent::Unknown(_)));
+}
+
+#[test_case(Tag::Boolean, false, &hex!("ff"), Ok(BerObjectContent::Boolean(true)) ; "boolean")]
+#[test_case(Tag::Integer, false, &hex!("00 80"), Ok(BerObjectContent::Integer(&hex!("00 80"))) ; "integer")]
+#[test_case(Tag::Utf8String, false, b"abc", Ok(BerObjectContent::UTF8String("abc")) ; "utf8string")]
+#[test_case(Tag::Boolean, false, &hex!("ff 00"), Err(BerError::InvalidLength) ; "boolean invalid length")]
+#[test_case(Tag::Sequence, true, &hex!("05 00"), Ok(BerObjectContent::Sequence(vec![BerObject::from_obj(BerObjectContent::Null)])) ; "sequence")]
+#[test_case(Tag::Sequence, true, &hex!("05 00 01"), Err(BerError::InvalidLength) ; "sequence trailing bytes")]
+fn tc_ber_read_content(
+    tag: Tag,
+    constructed: bool,
+    data: &[u8],
+    expected: Result<BerObjectContent, BerError>,
+) {
+    let res = ber_read_content(tag, constructed, data);
+    match expected {
+        Ok(content) => {
+            let res = res.expect("parsing failed");
+            // compare objects, to ignore spans
+            assert_eq!(
+                BerObject::from_obj(res).content,
+                BerObject::from_obj(content).content
+            );
+        }
+        Err(e) => assert_eq!(res, Err(e)),
+    }
+}
@@ -57,7 +57,7 @@ fn parse_struct04(i: &[u8], tag: Tag) -> BerResult<MyStruct> {
 #[test_case(&hex!("30 07 02 03 01 00 01 02 03 01"), Err(Err::Error(BerError::NomError(ErrorKind::Eof))) ; "incomplete")]
 #[test_case(&hex!("31 0a 02 03 01 00 01 02 03 01 00 00"), Err(Err::Error(BerError::unexpected_tag(Some(Tag::Sequence), Tag::Set))) ; "invalid tag")]
 #[test_case(&hex!("30 80 02 03 01 00 01 00 00"), Ok(&[0x10001]) ; "indefinite seq ok")]
-#[test_case(&hex!("30 80"), Err(Err::Incomplete(Needed::new(1))) ; "indefinite incomplete")]
+#[test_case(&hex!("30 80"), Err(Err::Incomplete(Needed::new(2))) ; "indefinite incomplete")]
 fn tc_ber_seq_of(i: &[u8], out: Result<&[u32], Err<BerError>>) {
     fn parser(i: &[u8]) -> BerResult {
         parse_ber_sequence_of(parse_ber_integer)(i)
@@ -118,7 +118,7 @@ fn tc_ber_seq_defined(i: &[u8], out: Result<&[u32], Err<BerError>>) {
 #[test_case(&hex!("31 07 02 03 01 00 01 02 03 01"), Err(Err::Error(BerError::NomError(ErrorKind::Eof))) ; "incomplete")]
 #[test_case(&hex!("30 0a 02 03 01 00 01 02 03 01 00 00"), Err(Err::Error(BerError::unexpected_tag(Some(Tag::Set), Tag::Sequence))) ; "invalid tag")]
 #[test_case(&hex!("31 80 02 03 01 00 01 00 00"), Ok(&[0x10001]) ; "indefinite set ok")]
-#[test_case(&hex!("31 80"), Err(Err::Incomplete(Needed::new(1))) ; "indefinite incomplete")]
+#[test_case(&hex!("31 80"), Err(Err::Incomplete(Needed::new(2))) ; "indefinite incomplete")]
 fn tc_ber_set_of(i: &[u8], out: Result<&[u32], Err<BerError>>) {
     fn parser(i: &[u8]) -> BerResult {
         parse_ber_set_of(parse_ber_integer)(i)
@@ -1,7 +1,10 @@
 //! This test file ensures the functions to parse containers like sequences and sets
 //! work correctly with custom errors.
 
-use der_parser::ber::{parse_ber_sequence_of_v, parse_ber_u32};
+use der_parser::ber::{
+    parse_ber_integer, parse_ber_optional, parse_ber_sequence_of, parse_ber_sequence_of_v,
+    parse_ber_tagged_explicit, parse_ber_u32, BerObject,
+};
 use der_parser::error::BerError;
 use nom::error::{ErrorKind, ParseError};
 use nom::{Err, IResult};
@@ -51,3 +54,28 @@ fn parse_sequence_of_v_custom_errors() {
     assert!(rem.is_empty());
     assert_eq!(&v, &[65537, 65536]);
 }
+
+#[test]
+fn parse_tagged_and_sequence_custom_errors() {
+    fn parse_element(i: &[u8]) -> IResult<&[u8], BerObject, MyError> {
+        match parse_ber_integer(i) {
+            Ok(x) => Ok(x),
+            Err(Err::Incomplete(e)) => Err(Err::Incomplete(e)),
+            _ => Err(Err::Error(MyError::Variant2)),
+        }
+    }
+
+    // SEQUENCE OF INTEGER
+    let bytes = [0x30, 0x03, 0x02, 0x01, 0x01];
+    let (rem, obj) = parse_ber_sequence_of(parse_element)(&bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj.as_sequence().map(|v| v.len()), Ok(1));
+
+    // [0] EXPLICIT INTEGER, where the custom error of the inner parser is returned
+    let bytes = [0xa0, 0x03, 0x01, 0x01, 0xff];
+    let res = parse_ber_tagged_explicit(0, parse_element)(&bytes);
+    assert!(matches!(res, Err(Err::Error(MyError::Variant2))));
+
+    let res = parse_ber_optional(parse_ber_tagged_explicit(0, parse_element))(&bytes);
+    assert!(matches!(res, Ok((_, ref obj)) if obj.as_optional() == Ok(None)));
+}
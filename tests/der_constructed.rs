@@ -175,3 +175,82 @@ fn tc_der_set_defined(i: &[u8], out: Result<&[u32], Err<BerError>>) {
         }
     }
 }
+
+der_parser::der_sequence! {
+    #[derive(Debug, PartialEq)]
+    struct Example<'a> {
+        version [0] EXPLICIT DEFAULT(1): u32 = parse_der_u32,
+        serial: u32 = parse_der_u32,
+        uid [1] IMPLICIT OPTIONAL: DerObjectContent<'a> = parse_der_content2(Tag::OctetString),
+        name OPTIONAL: DerObject<'a> = parse_der_utf8string,
+    }
+}
+
+/// Fields of `Example`: version, serial, uid and name
+type ExampleFields<'a> = (u32, u32, Option<&'a [u8]>, Option<&'a str>);
+
+#[test_case(&hex!("30 03 02 01 05"), Ok((1, 5, None, None)) ; "defaults")]
+#[test_case(&hex!("30 0d a0 03 02 01 02 02 01 05 81 00 0c 01 61"), Ok((2, 5, Some(&[][..]), Some("a"))) ; "all fields")]
+#[test_case(&hex!("30 06 02 01 05 0c 01 61"), Ok((1, 5, None, Some("a"))) ; "no uid")]
+#[test_case(&hex!("30 05 a0 03 02 01 02"), Err(Err::Incomplete(Needed::new(1))) ; "missing serial")]
+#[test_case(&hex!("31 03 02 01 05"), Err(Err::Error(BerError::unexpected_tag(Some(Tag::Sequence), Tag::Set))) ; "invalid tag")]
+fn tc_der_sequence_macro(i: &[u8], out: Result<ExampleFields, Err<BerError>>) {
+    let res = Example::from_der(i).map(|(_, e)| {
+        let uid = e.uid.map(|c| match c {
+            DerObjectContent::OctetString(s) => s,
+            _ => panic!("wrong type for uid"),
+        });
+        (
+            e.version,
+            e.serial,
+            uid,
+            e.name.map(|n| n.as_str().unwrap()),
+        )
+    });
+    pretty_assertions::assert_eq!(res, out);
+}
+
+der_parser::der_sequence! {
+    struct KerberosLike {
+        app [APPLICATION 1] EXPLICIT: u32 = parse_der_u32,
+        private [PRIVATE 2] IMPLICIT OPTIONAL: u32 = |i, hdr, depth| {
+            let (rem, obj) = parse_der_content2(Tag::Integer)(i, hdr, depth)?;
+            Ok((rem, DerObject::from_obj(obj).as_u32()?))
+        },
+    }
+}
+
+#[test_case(&hex!("30 08 61 03 02 01 05 c2 01 07"), Ok((5, Some(7))) ; "application and private")]
+#[test_case(&hex!("30 05 61 03 02 01 05"), Ok((5, None)) ; "no private")]
+#[test_case(&hex!("30 05 a1 03 02 01 05"), Err(Err::Error(BerError::unexpected_class(Some(Class::Application), Class::ContextSpecific))) ; "wrong class")]
+fn tc_der_sequence_macro_class(i: &[u8], out: Result<(u32, Option<u32>), Err<BerError>>) {
+    let res = KerberosLike::from_der(i).map(|(_, v)| (v.app, v.private));
+    pretty_assertions::assert_eq!(res, out);
+}
+
+#[test_case(&hex!("c2 01 07"), Ok(7) ; "private ok")]
+#[test_case(&hex!("82 01 07"), Err(BerError::unexpected_class(Some(Class::Private), Class::ContextSpecific)) ; "context-specific")]
+#[test_case(&hex!("c3 01 07"), Err(BerError::unexpected_tag(Some(Tag(2)), Tag(3))) ; "invalid tag")]
+fn tc_der_private_implicit(i: &[u8], out: Result<u32, BerError>) {
+    let res = parse_der_private_implicit(2, parse_der_content(Tag::Integer))(i)
+        .map(|(_, obj)| obj.as_u32().unwrap());
+    pretty_assertions::assert_eq!(res, out.map_err(Err::Error));
+}
+
+#[test_case(&hex!("c2 01 07"), "private 2" ; "private")]
+#[test_case(&hex!("a0 00"), "context 0" ; "context-specific")]
+#[test_case(&hex!("a1 00"), "context" ; "other context-specific")]
+#[test_case(&hex!("30 00"), "sequence" ; "universal")]
+#[test_case(&hex!("05 00"), "universal" ; "other universal")]
+fn tc_tag_pattern(i: &[u8], out: &str) {
+    let (_, obj) = parse_der(i).expect("parsing failed");
+    let res = match (obj.class(), obj.tag()) {
+        der_parser::tag_pattern!(priv(2)) => "private 2",
+        der_parser::tag_pattern!(ctx(0)) => "context 0",
+        der_parser::tag_pattern!(ctx(_)) => "context",
+        der_parser::tag_pattern!(univ(Sequence)) => "sequence",
+        der_parser::tag_pattern!(univ(_)) => "universal",
+        _ => "other",
+    };
+    assert_eq!(res, out);
+}
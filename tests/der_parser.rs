@@ -350,6 +350,7 @@ fn test_der_contextspecific() {
     let expected = DerObject {
         header,
         content: BerObjectContent::Unknown(any),
+        span: None,
     };
     assert_eq!(parse_der(&bytes), Ok((empty, expected)));
 }
@@ -370,6 +371,7 @@ fn test_der_explicit_optional() {
                 Box::new(DerObject::from_int_slice(b"\x02")),
             ),
         )))),
+        span: None,
     };
     assert_eq!(
         parse_der_explicit_optional(&bytes, Tag(0), parse_der_integer),
@@ -390,6 +392,7 @@ fn test_der_implicit() {
         header: Header::new(Class::ContextSpecific, false, Tag(1), 4.into())
             .with_raw_tag(Some(Cow::Borrowed(&[0x81]))),
         content: BerObjectContent::IA5String("pass"),
+        span: None,
     };
     fn der_read_ia5string_content<'a>(
         i: &'a [u8],
@@ -416,6 +419,7 @@ fn test_der_implicit_long_tag() {
         header: Header::new(Class::Application, false, Tag(0x52), 4.into())
             .with_raw_tag(Some(Cow::Borrowed(&[0x5f, 0x52]))),
         content: BerObjectContent::IA5String("pass"),
+        span: None,
     };
     fn der_read_ia5string_content<'a>(
         i: &'a [u8],
@@ -626,3 +630,120 @@ fn tc_der_slice(i: &[u8], out: Result<&[u8], BerError>) {
         }
     }
 }
+
+#[test]
+fn test_recursive_depth() {
+    // SEQUENCE { SET { INTEGER 1 } }
+    let bytes = &hex!("30 05 31 03 02 01 01");
+    let max_depth = Err::Error(BerError::BerMaxDepth);
+    // BER and DER parsers use the same depth budget
+    for depth in 0..4 {
+        let ok = depth >= 3;
+        assert_eq!(parse_ber_recursive(bytes, depth).is_ok(), ok);
+        assert_eq!(parse_der_recursive(bytes, depth).is_ok(), ok);
+        assert_eq!(
+            parse_ber_with_tag_r(bytes, Tag::Sequence, depth).is_ok(),
+            ok
+        );
+        assert_eq!(
+            parse_der_with_tag_r(bytes, Tag::Sequence, depth).is_ok(),
+            ok
+        );
+    }
+    assert_eq!(
+        parse_ber_with_tag_r(bytes, Tag::Sequence, 0),
+        Err(max_depth.clone())
+    );
+    assert_eq!(
+        parse_der_with_tag_r(bytes, Tag::Sequence, 0),
+        Err(max_depth)
+    );
+    let (i, hdr) = der_read_element_header(bytes).expect("could not read header");
+    assert!(der_read_element_content_recursive(i, hdr.clone(), 2).is_err());
+    assert!(der_read_element_content_recursive(i, hdr, 3).is_ok());
+}
+
+#[test]
+fn test_unexpected_tag() {
+    // OCTET STRING, parsed as INTEGER
+    let bytes = &hex!("04 01 01");
+    let expected = Err(Err::Error(BerError::UnexpectedTag {
+        expected: Some(Tag::Integer),
+        actual: Tag::OctetString,
+    }));
+    assert_eq!(parse_der_integer(bytes).map(|_| ()), expected);
+    assert_eq!(parse_der_u32(bytes).map(|_| ()), expected);
+    assert_eq!(parse_ber_u64(bytes).map(|_| ()), expected);
+    // [0] { INTEGER 1 }, parsed as INTEGER (tag is checked before the primitive bit)
+    let bytes = &hex!("a0 03 02 01 01");
+    assert_eq!(
+        parse_der_i32(bytes).map(|_| ()),
+        Err(Err::Error(BerError::UnexpectedTag {
+            expected: Some(Tag::Integer),
+            actual: Tag(0),
+        }))
+    );
+    // the class is not checked: [PRIVATE 2], parsed as INTEGER
+    let bytes = &hex!("c2 01 01");
+    assert_eq!(parse_der_integer(bytes).map(|(_, o)| o.as_u32()), Ok(Ok(1)));
+    assert_eq!(parse_der_u64(bytes), Ok((&[][..], 1)));
+    assert_eq!(
+        parse_ber_with_tag(&[0x82, 1, 1], Tag(2)).map(|_| ()),
+        Ok(())
+    );
+    assert_eq!(parse_ber_u32(&[0x82, 1, 1]), Ok((&[][..], 1)));
+}
+
+#[test]
+fn test_der_bitstring_policy() {
+    // BIT STRING, with a non-zero unused bit
+    let bytes = &hex!("03 02 07 81");
+    assert_eq!(
+        parse_der_with_options(bytes, &ParseOptions::der()).map(|_| ()),
+        Err(Err::Error(BerError::DerConstraintFailed(
+            DerConstraint::UnusedBitsNotZero
+        )))
+    );
+    let options = ParseOptions::der().with_bitstring_policy(BitStringPolicy::Warn);
+    let (_, (obj, warnings)) = parse_der_with_warnings(bytes, &options).expect("parsing failed");
+    let warnings: Vec<_> = warnings.iter().map(|w| w.violation).collect();
+    assert_eq!(warnings, vec![DerViolation::NonZeroUnusedBits]);
+    let bits = obj.as_bitstring().expect("not a bit string");
+    assert_eq!(bits.nonzero_unused_bits(7), Some(0x81));
+}
+
+#[test]
+fn test_der_allow_indefinite() {
+    // SEQUENCE (indefinite) { OCTET STRING "a", SEQUENCE (indefinite) { } }
+    let bytes = &hex!("30 80 04 01 61 30 80 00 00 00 00");
+    let strict = ParseOptions::der();
+    assert_eq!(
+        parse_der_with_options(bytes, &strict).map(|_| ()),
+        Err(Err::Error(BerError::DerConstraintFailed(
+            DerConstraint::IndefiniteLength
+        )))
+    );
+    let options = ParseOptions::der().with_allow_indefinite(true);
+    let (rem, (obj, warnings)) = parse_der_with_warnings(bytes, &options).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(obj[0].as_slice(), Ok(&b"a"[..]));
+    let warnings: Vec<_> = warnings.iter().map(|w| (w.offset, w.violation)).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            (0, DerViolation::IndefiniteLengthForbidden),
+            (5, DerViolation::IndefiniteLengthForbidden)
+        ]
+    );
+    // other DER rules are still checked (BOOLEAN 0x01)
+    let bytes = &hex!("30 80 01 01 01 00 00");
+    assert!(parse_der_with_options(bytes, &options).is_err());
+    // constructed strings are still rejected
+    let bytes = &hex!("24 80 04 01 61 00 00");
+    assert_eq!(
+        parse_der_with_options(bytes, &options).map(|_| ()),
+        Err(Err::Error(BerError::DerConstraintFailed(
+            DerConstraint::Constructed
+        )))
+    );
+}
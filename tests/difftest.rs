@@ -0,0 +1,53 @@
+#![cfg(feature = "difftest")]
+
+use der_parser::difftest::*;
+use hex_literal::hex;
+use std::fs;
+
+#[test]
+fn difftest_closure() {
+    // reference accepting only definite lengths
+    let reference = |data: &[u8]| data.get(1) != Some(&0x80);
+
+    assert_eq!(
+        compare(&hex!("02 01 05"), &reference, Mode::Der).unwrap(),
+        None
+    );
+    // indefinite length: rejected in DER mode, by both implementations
+    let indefinite = hex!("30 80 02 01 05 00 00");
+    assert_eq!(compare(&indefinite, &reference, Mode::Der).unwrap(), None);
+    // accepted in BER mode only
+    let d = compare(&indefinite, &reference, Mode::Ber)
+        .unwrap()
+        .expect("no divergence");
+    assert_eq!((d.error, d.reference), (None, false));
+
+    // corpus
+    let dir = std::env::temp_dir().join(format!("der-parser-difftest-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.der"), hex!("02 01 05")).unwrap();
+    fs::write(dir.join("b.ber"), indefinite).unwrap();
+    let report = compare_corpus(&dir, &reference, Mode::Ber);
+    fs::remove_dir_all(&dir).unwrap();
+    let report = report.unwrap();
+    assert_eq!(report.checked, 2);
+    assert_eq!(report.divergences.len(), 1);
+    assert_eq!(report.divergences[0].path.file_name().unwrap(), "b.ber");
+}
+
+#[test]
+fn difftest_openssl() {
+    let openssl = OpensslAsn1Parse::new();
+    if !openssl.is_available() {
+        return;
+    }
+    assert_eq!(
+        compare(&hex!("02 01 05"), &openssl, Mode::Der).unwrap(),
+        None
+    );
+    // truncated object
+    assert_eq!(
+        compare(&hex!("30 05 02 01"), &openssl, Mode::Der).unwrap(),
+        None
+    );
+}
@@ -87,13 +87,13 @@ fn test_oid_iter_length_check() {
 
 #[test]
 fn test_unknown_tag() {
-    let bytes = hex!("1d 01 00");
+    let bytes = hex!("0f 01 00");
     let res = parse_ber(&bytes).expect("parsing failed");
     assert!(res.0.is_empty());
     assert_eq!(
         res.1,
         BerObject::from_obj(BerObjectContent::Unknown(Any::from_tag_and_data(
-            Tag(0x1d),
+            Tag(0x0f),
             &bytes[2..]
         )))
     );
@@ -102,7 +102,7 @@ fn test_unknown_tag() {
     assert_eq!(
         res.1,
         BerObject::from_obj(BerObjectContent::Unknown(Any::from_tag_and_data(
-            Tag(0x1d),
+            Tag(0x0f),
             &bytes[2..]
         )))
     );
@@ -121,6 +121,7 @@ fn test_unknown_context_specific() {
             content: BerObjectContent::Unknown(
                 Any::from_tag_and_data(Tag(0x0), &bytes[2..]).with_class(Class::ContextSpecific)
             ),
+            span: None,
         }
     );
 }
@@ -138,6 +139,7 @@ fn test_unknown_long_tag() {
             content: BerObjectContent::Unknown(
                 Any::from_tag_and_data(Tag(0x22), &bytes[3..]).with_class(Class::ContextSpecific)
             ),
+            span: None,
         }
     );
 }
@@ -155,6 +157,7 @@ fn test_unknown_longer_tag() {
             content: BerObjectContent::Unknown(
                 Any::from_tag_and_data(Tag(0x1122), &bytes[4..]).with_class(Class::ContextSpecific)
             ),
+            span: None,
         }
     );
 }
@@ -169,7 +172,12 @@ fn test_incomplete_tag() {
 #[test]
 fn test_overflow_tag() {
     let bytes = hex!("9f a2 a2 a2 a2 a2 22 01 00");
-    let res = parse_ber(&bytes);
+    let (_, obj) = parse_ber(&bytes).expect("parsing failed");
+    assert_eq!(
+        obj.content,
+        BerObjectContent::TagOverflow(&bytes[..7], &bytes[8..])
+    );
+    let res = parse_der(&bytes);
     assert!(res.is_err());
 }
 
@@ -193,6 +201,22 @@ fn test_incomplete_length() {
     assert_eq!(res, Err::Incomplete(Needed::new(4)));
 }
 
+#[test]
+fn test_incomplete_needed() {
+    // the number of missing bytes is computed from the declared length
+    for bytes in [&hex!("04 05 01")[..], &hex!("30 06 02 04 01")] {
+        let needed = bytes[1] as usize + 2 - bytes.len();
+        let res = parse_ber(bytes).expect_err("expected error");
+        assert_eq!(res, Err::Incomplete(Needed::new(needed)));
+        let res = parse_der(bytes).expect_err("expected error");
+        assert_eq!(res, Err::Incomplete(Needed::new(needed)));
+    }
+    // indefinite length: at least the end-of-content octets are missing
+    let bytes = hex!("30 80 02 01 01");
+    let res = parse_ber(&bytes).expect_err("expected error");
+    assert_eq!(res, Err::Incomplete(Needed::new(2)));
+}
+
 #[test]
 fn test_invalid_length() {
     let bytes = hex!("02 ff 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f 10");
@@ -201,7 +225,7 @@ fn test_invalid_length() {
     //
     let bytes = hex!("02 8a ff ff ff ff ff ff ff ff ff ff 00");
     let res = parse_ber(&bytes).expect_err("parsing should have returned error");
-    assert_eq!(Err::Error(BerError::InvalidLength), res);
+    assert_eq!(Err::Error(length_overflow()), res);
     //
     let bytes = hex!("02 ff 00");
     let res = parse_ber(&bytes).expect_err("parsing should have returned error");
@@ -209,7 +233,7 @@ fn test_invalid_length() {
     //
     let bytes = hex!("02 02 00");
     let res = parse_der(&bytes).expect_err("expected error");
-    assert_eq!(res, Err::Incomplete(Needed::new(2)));
+    assert_eq!(res, Err::Incomplete(Needed::new(1)));
 }
 
 #[test]
@@ -0,0 +1,97 @@
+#![cfg(feature = "serde")]
+
+use der_parser::ber::{BerObject, BerObjectContent};
+use der_parser::error::BerError;
+use der_parser::serde_der::*;
+use hex_literal::hex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum Version {
+    V1,
+    V2,
+    V3,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Inner(i32, Option<u8>);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Record {
+    version: Version,
+    serial: u64,
+    names: Vec<String>,
+    inner: Inner,
+    extra: Option<bool>,
+}
+
+#[test]
+fn test_serde_der_roundtrip() {
+    let record = Record {
+        version: Version::V3,
+        serial: 0x8000_0000_0000_0000,
+        names: vec!["a".to_string(), "bc".to_string()],
+        inner: Inner(-129, None),
+        extra: Some(false),
+    };
+    let bytes = to_der(&record).expect("serialization failed");
+    let expected = hex!(
+        "30 22 0a 01 02 02 09 00 80 00 00 00 00 00 00 00 30 07 0c 01 61 0c 02 62 63"
+        "30 06 02 02 ff 7f 05 00 01 01 00"
+    );
+    assert_eq!(bytes, expected);
+    let decoded: Record = from_der(&bytes).expect("deserialization failed");
+    assert_eq!(decoded, record);
+    // the output is valid DER
+    let (_, obj) = der_parser::parse_der(&bytes).expect("invalid DER");
+    assert_eq!(obj.as_sequence().map(Vec::len), Ok(5));
+}
+
+#[test]
+fn test_serde_der_decode() {
+    // SEQUENCE { PrintableString "ab", OCTET STRING 01 02, OID 1.2.3 }
+    let bytes = hex!("30 0d 13 02 61 62 04 02 01 02 06 03 2a 03 04");
+    #[derive(Debug, Deserialize)]
+    struct S<'a> {
+        name: &'a str,
+        #[serde(with = "bytes")]
+        data: &'a [u8],
+        oid: String,
+    }
+    mod bytes {
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<&'de [u8], D::Error> {
+            serde::Deserialize::deserialize(d)
+        }
+    }
+    let s: S = from_der(&bytes).expect("deserialization failed");
+    assert_eq!(s.name, "ab");
+    assert_eq!(s.data, &[1, 2]);
+    assert_eq!(s.oid, "1.2.3.4");
+
+    // any value can be read using deserialize_any
+    let v: (bool, (), i64) = from_der(&hex!("30 08 01 01 ff 05 00 02 01 fe")).unwrap();
+    assert_eq!(v, (true, (), -2));
+}
+
+#[test]
+fn test_serde_der_errors() {
+    // trailing data
+    let r = from_der::<u8>(&hex!("02 01 01 00"));
+    assert_eq!(r, Err(Error::TrailingData));
+    // value too large
+    let r = from_der::<u8>(&hex!("02 02 01 00"));
+    assert_eq!(r, Err(Error::Ber(BerError::IntegerTooLarge)));
+    // too many elements
+    let r = from_der::<(u8,)>(&hex!("30 06 02 01 01 02 01 02"));
+    assert_eq!(r, Err(Error::TrailingData));
+    // missing element
+    let r = from_der::<(u8, u8)>(&hex!("30 03 02 01 01"));
+    assert!(matches!(r, Err(Error::Custom(_))));
+    // unsupported type
+    let r = to_der(&1.5f32);
+    assert_eq!(r, Err(Error::Unsupported("f32")));
+    // objects can also be deserialized from a tree
+    let obj = BerObject::from_obj(BerObjectContent::Boolean(true));
+    let b = bool::deserialize(Deserializer::new(&obj));
+    assert_eq!(b, Ok(true));
+}
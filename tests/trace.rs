@@ -0,0 +1,59 @@
+#![cfg(feature = "trace")]
+
+use der_parser::ber::parse_ber;
+use der_parser::der::parse_der;
+use hex_literal::hex;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Collect the fields of all events, as strings
+#[derive(Default)]
+struct Collector(Arc<Mutex<Vec<String>>>);
+
+struct FieldsVisitor(String);
+
+impl Visit for FieldsVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0 += &format!("{}={:?} ", field.name(), value);
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut v = FieldsVisitor(String::new());
+        event.record(&mut v);
+        self.0.lock().unwrap().push(v.0.trim_end().to_string());
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_trace_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let collector = Collector(events.clone());
+    tracing::subscriber::with_default(collector, || {
+        // SEQUENCE { INTEGER 1, NULL }
+        let _ = parse_ber(&hex!("30 05 02 01 01 05 00")).expect("parsing failed");
+        // BOOLEAN 0x01 (not DER)
+        let _ = parse_der(&hex!("01 01 01")).expect_err("parsing should fail");
+    });
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 5);
+    assert!(events[0].starts_with("message=header offset=0 depth=0 class=Universal tag=16"));
+    assert!(events[2].starts_with("message=header offset=5 depth=1 class=Universal tag=5"));
+    assert_eq!(
+        events[4],
+        "message=DER constraint violation constraint=InvalidBoolean"
+    );
+}
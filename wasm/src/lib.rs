@@ -0,0 +1,105 @@
+//! JavaScript bindings for `der-parser`
+//!
+//! This crate exports functions to parse BER/DER data from JavaScript (for ex. to inspect
+//! certificates in a browser, without a server). Build it with `wasm-pack`:
+//!
+//! ```shell
+//! wasm-pack build --target web
+//! ```
+//!
+//! All functions take a `Uint8Array`, and throw an exception if the data cannot be parsed.
+//!
+//! ```js
+//! import init, { parseDer, prettyDer } from "./pkg/der_parser_wasm.js";
+//!
+//! await init();
+//! const tree = JSON.parse(parseDer(new Uint8Array([0x02, 0x01, 0x05])));
+//! console.log(prettyDer(new Uint8Array([0x02, 0x01, 0x05])));
+//! ```
+
+#![deny(missing_debug_implementations, unreachable_pub)]
+#![warn(rust_2018_idioms)]
+
+use der_parser::ber::{parse_ber, BerObject};
+use der_parser::der::parse_der;
+use der_parser::error::BerResult;
+use wasm_bindgen::prelude::*;
+
+fn parse_all<'a>(
+    data: &'a [u8],
+    parser: fn(&'a [u8]) -> BerResult<'a>,
+) -> Result<Vec<BerObject<'a>>, String> {
+    let mut objects = Vec::new();
+    let mut i = data;
+    while !i.is_empty() {
+        let offset = data.len() - i.len();
+        match parser(i) {
+            Ok((rem, obj)) => {
+                objects.push(obj);
+                i = rem;
+            }
+            Err(e) => return Err(format!("at offset {}: {}", offset, e)),
+        }
+    }
+    Ok(objects)
+}
+
+fn to_json(objects: &[BerObject<'_>]) -> Result<String, String> {
+    serde_json::to_string(objects).map_err(|e| e.to_string())
+}
+
+fn to_pretty(objects: &[BerObject<'_>]) -> String {
+    objects
+        .iter()
+        .map(|obj| format!("{:?}\n", obj.as_pretty(0, 2)))
+        .collect()
+}
+
+/// Parse all the DER objects of `data`, and return them as a JSON array
+///
+/// See the `serde` support of `der-parser` for the format of objects.
+#[wasm_bindgen(js_name = parseDer)]
+pub fn parse_der_json(data: &[u8]) -> Result<String, JsError> {
+    let objects = parse_all(data, parse_der).map_err(|e| JsError::new(&e))?;
+    to_json(&objects).map_err(|e| JsError::new(&e))
+}
+
+/// Parse all the BER objects of `data`, and return them as a JSON array
+#[wasm_bindgen(js_name = parseBer)]
+pub fn parse_ber_json(data: &[u8]) -> Result<String, JsError> {
+    let objects = parse_all(data, parse_ber).map_err(|e| JsError::new(&e))?;
+    to_json(&objects).map_err(|e| JsError::new(&e))
+}
+
+/// Parse all the DER objects of `data`, and return them as an indented text dump
+#[wasm_bindgen(js_name = prettyDer)]
+pub fn pretty_der(data: &[u8]) -> Result<String, JsError> {
+    let objects = parse_all(data, parse_der).map_err(|e| JsError::new(&e))?;
+    Ok(to_pretty(&objects))
+}
+
+/// Parse all the BER objects of `data`, and return them as an indented text dump
+#[wasm_bindgen(js_name = prettyBer)]
+pub fn pretty_ber(data: &[u8]) -> Result<String, JsError> {
+    let objects = parse_all(data, parse_ber).map_err(|e| JsError::new(&e))?;
+    Ok(to_pretty(&objects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `JsError` can only be built on wasm targets, so the tests use the inner functions
+    #[test]
+    fn test_parse_all() {
+        let bytes = [0x02, 0x01, 0x05, 0x05, 0x00];
+        let objects = parse_all(&bytes, parse_der).expect("parsing failed");
+        let json = to_json(&objects).expect("serialization failed");
+        assert!(json.starts_with(r#"[{"class":"Universal","tag":2,"#));
+        let pretty = to_pretty(&objects);
+        assert_eq!(pretty.lines().count(), 2);
+
+        let r = parse_all(&[0x05, 0x00, 0x02, 0x05], parse_der);
+        assert!(r.unwrap_err().starts_with("at offset 2:"));
+    }
+}